@@ -0,0 +1,111 @@
+use std::env;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use axum::{
+    extract::Request,
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::CookieJar;
+use serde::Deserialize;
+use tower::{Layer, Service};
+
+// The permissions carried by the auth token, as reported by the auth service
+#[derive(Clone, Debug, Deserialize)]
+pub struct Grants {
+    pub permissions: Vec<String>,
+}
+
+// Middleware checking the `jwt` cookie with the auth service, which keeps the grants of the user
+// in the request extensions for the `RequirePermission` guards behind it
+pub async fn authenticate(jar: CookieJar, mut request: Request, next: Next) -> Response {
+    let jwt_cookie = match jar.get("jwt") {
+        Some(cookie) => cookie,
+        None => {
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+    };
+
+    let api_client = reqwest::Client::builder().build().unwrap();
+
+    let verify_token_body = serde_json::json!({
+        "token": &jwt_cookie.value(),
+    });
+
+    let auth_hostname = env::var("AUTH_SERVICE_HOST_NAME").unwrap_or("0.0.0.0".to_owned());
+    let url = format!("http://{}:3000/verify_token", auth_hostname);
+
+    let response = match api_client.post(&url).json(&verify_token_body).send().await {
+        Ok(response) => response,
+        Err(_) => {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let grants = match response.status() {
+        reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::BAD_REQUEST => {
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+        reqwest::StatusCode::OK => match response.json::<Grants>().await {
+            Ok(grants) => grants,
+            Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        },
+        _ => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+
+    request.extensions_mut().insert(grants);
+
+    next.run(request).await
+}
+
+// Guard layer letting through only users granted the permission, such as `RequirePermission("content:read")`.
+// It has to run behind the `authenticate` middleware.
+#[derive(Clone, Copy, Debug)]
+pub struct RequirePermission(pub &'static str);
+
+impl<S> Layer<S> for RequirePermission {
+    type Service = RequirePermissionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequirePermissionService {
+            inner,
+            permission: self.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RequirePermissionService<S> {
+    inner: S,
+    permission: &'static str,
+}
+
+impl<S> Service<Request> for RequirePermissionService<S>
+where
+    S: Service<Request, Response = Response>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let is_granted = request
+            .extensions()
+            .get::<Grants>()
+            .is_some_and(|grants| grants.permissions.iter().any(|permission| permission == self.permission));
+
+        if !is_granted {
+            return Box::pin(async { Ok(StatusCode::FORBIDDEN.into_response()) });
+        }
+
+        Box::pin(self.inner.call(request))
+    }
+}
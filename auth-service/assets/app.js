@@ -30,6 +30,15 @@ signupLoginLink.addEventListener("click", (e) => {
     signupSection.style.display = "none";
 });
 
+// Invite links open the sign up form at /signup?invite=<token>
+const invite = new URLSearchParams(window.location.search).get("invite");
+
+if (window.location.pathname === "/signup") {
+    loginSection.style.display = "none";
+    twoFASection.style.display = "none";
+    signupSection.style.display = "block";
+}
+
 // -----------------------------------------------------
 
 const loginForm = document.getElementById("login-form");
@@ -97,7 +106,7 @@ signupButton.addEventListener("click", (e) => {
         headers: {
             'Content-Type': 'application/json',
         },
-        body: JSON.stringify({ email, password, requires2FA }),
+        body: JSON.stringify({ email, password, requires2FA, invite }),
     }).then(response => {
         if (response.ok) {
             signupForm.email.value = "";
@@ -0,0 +1,31 @@
+// Opened from the emailed invitation link, which carries the token in its query.
+// Nothing happens until the user chooses to join.
+const joinForm = document.getElementById("join-form");
+const joinButton = document.getElementById("join-form-submit");
+const joinErrAlert = document.getElementById("join-err-alert");
+const joinSuccessAlert = document.getElementById("join-success-alert");
+const token = new URLSearchParams(window.location.search).get("token");
+
+joinButton.addEventListener("click", (e) => {
+    e.preventDefault();
+
+    fetch('/organizations/join', {
+        method: 'POST',
+        headers: {
+            'Content-Type': 'application/json',
+        },
+        body: JSON.stringify({ token }),
+    }).then(response => {
+        response.json().then(data => {
+            if (response.ok) {
+                joinForm.style.display = "none";
+                joinErrAlert.style.display = "none";
+                joinSuccessAlert.innerHTML = data.message;
+                joinSuccessAlert.style.display = "block";
+            } else {
+                joinErrAlert.innerHTML = `<span><strong>Error: </strong>${data.error}</span>`;
+                joinErrAlert.style.display = "block";
+            }
+        });
+    });
+});
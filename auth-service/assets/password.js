@@ -0,0 +1,83 @@
+// Shows the error of a failed request in the alert of its form
+function showError(alert, response) {
+    response.json().then(data => {
+        let error_msg = data.error;
+        if (error_msg !== undefined && error_msg !== null && error_msg !== "") {
+            alert.innerHTML = `<span><strong>Error: </strong>${error_msg}</span>`;
+            alert.style.display = "block";
+        } else {
+            alert.style.display = "none";
+        }
+    });
+}
+
+// -----------------------------------------------------
+
+// Opened from the emailed reset link, which carries the token in its query
+const resetForm = document.getElementById("reset-form");
+
+if (resetForm !== null) {
+    const resetButton = document.getElementById("reset-form-submit");
+    const resetErrAlert = document.getElementById("reset-err-alert");
+    const resetSuccessAlert = document.getElementById("reset-success-alert");
+    const token = new URLSearchParams(window.location.search).get("token");
+
+    resetButton.addEventListener("click", (e) => {
+        e.preventDefault();
+
+        const password = resetForm.password.value;
+
+        fetch('/password/reset', {
+            method: 'POST',
+            headers: {
+                'Content-Type': 'application/json',
+            },
+            body: JSON.stringify({ token, password }),
+        }).then(response => {
+            if (response.ok) {
+                resetForm.style.display = "none";
+                resetErrAlert.style.display = "none";
+                resetSuccessAlert.innerHTML = "Your password was reset, you can now log in with it.";
+                resetSuccessAlert.style.display = "block";
+            } else {
+                showError(resetErrAlert, response);
+            }
+        });
+    });
+}
+
+// -----------------------------------------------------
+
+// Linked from the emails about a locked account or a changed password
+const forgotForm = document.getElementById("forgot-form");
+
+if (forgotForm !== null) {
+    const forgotButton = document.getElementById("forgot-form-submit");
+    const forgotErrAlert = document.getElementById("forgot-err-alert");
+    const forgotSuccessAlert = document.getElementById("forgot-success-alert");
+
+    forgotButton.addEventListener("click", (e) => {
+        e.preventDefault();
+
+        const email = forgotForm.email.value;
+
+        fetch('/password/forgot', {
+            method: 'POST',
+            headers: {
+                'Content-Type': 'application/json',
+            },
+            body: JSON.stringify({ email }),
+        }).then(response => {
+            if (response.ok) {
+                response.json().then(data => {
+                    forgotForm.style.display = "none";
+                    forgotErrAlert.style.display = "none";
+                    forgotSuccessAlert.innerHTML = data.message;
+                    forgotSuccessAlert.style.display = "block";
+                });
+            } else {
+                showError(forgotErrAlert, response);
+            }
+        });
+    });
+}
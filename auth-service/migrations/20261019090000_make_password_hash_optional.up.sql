@@ -0,0 +1,3 @@
+-- Add up migration script here
+ALTER TABLE users
+    ALTER COLUMN password_hash DROP NOT NULL;
\ No newline at end of file
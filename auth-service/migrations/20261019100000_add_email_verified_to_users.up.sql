@@ -0,0 +1,7 @@
+-- Add up migration script here
+-- Accounts created before email verification existed are considered verified
+ALTER TABLE users
+    ADD COLUMN email_verified BOOLEAN NOT NULL DEFAULT TRUE;
+
+ALTER TABLE users
+    ALTER COLUMN email_verified SET DEFAULT FALSE;
@@ -0,0 +1,7 @@
+-- Add down migration script here
+ALTER TABLE users
+    DROP COLUMN display_name,
+    DROP COLUMN locale,
+    DROP COLUMN timezone,
+    DROP COLUMN created_at,
+    DROP COLUMN last_login_at;
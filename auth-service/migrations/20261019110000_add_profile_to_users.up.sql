@@ -0,0 +1,7 @@
+-- Add up migration script here
+ALTER TABLE users
+    ADD COLUMN display_name TEXT,
+    ADD COLUMN locale TEXT,
+    ADD COLUMN timezone TEXT,
+    ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ADD COLUMN last_login_at TIMESTAMPTZ;
@@ -0,0 +1,33 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS roles
+(
+    name TEXT NOT NULL PRIMARY KEY
+);
+
+CREATE TABLE IF NOT EXISTS role_permissions
+(
+    role_name  TEXT NOT NULL REFERENCES roles (name) ON DELETE CASCADE,
+    permission TEXT NOT NULL,
+    PRIMARY KEY (role_name, permission)
+);
+
+CREATE TABLE IF NOT EXISTS user_roles
+(
+    user_email TEXT NOT NULL REFERENCES users (email) ON DELETE CASCADE ON UPDATE CASCADE,
+    role_name  TEXT NOT NULL,
+    PRIMARY KEY (user_email, role_name),
+    CONSTRAINT user_roles_role_name_fkey FOREIGN KEY (role_name) REFERENCES roles (name) ON DELETE CASCADE
+);
+
+-- Every user has the `user` role without it being assigned
+INSERT INTO roles (name)
+VALUES ('admin'),
+       ('user');
+
+INSERT INTO role_permissions (role_name, permission)
+VALUES ('admin', 'content:read'),
+       ('admin', 'roles:read'),
+       ('admin', 'roles:write'),
+       ('admin', 'users:read'),
+       ('admin', 'users:write'),
+       ('user', 'content:read');
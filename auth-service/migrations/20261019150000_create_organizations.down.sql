@@ -0,0 +1,11 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS organization_invitations;
+DROP TABLE IF EXISTS organization_member_roles;
+DROP TABLE IF EXISTS organization_members;
+DROP TABLE IF EXISTS organizations;
+
+DELETE FROM role_permissions
+WHERE role_name = 'admin' AND permission IN ('members:read', 'members:write', 'organizations:write');
+
+DELETE FROM roles
+WHERE name = 'org-admin';
@@ -0,0 +1,54 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS organizations
+(
+    id         UUID        NOT NULL PRIMARY KEY,
+    name       TEXT        NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS organization_members
+(
+    organization_id UUID        NOT NULL,
+    user_email      TEXT        NOT NULL REFERENCES users (email) ON DELETE CASCADE ON UPDATE CASCADE,
+    joined_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (organization_id, user_email),
+    CONSTRAINT organization_members_organization_id_fkey FOREIGN KEY (organization_id) REFERENCES organizations (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS organization_members_user_email_idx ON organization_members (user_email);
+
+CREATE TABLE IF NOT EXISTS organization_member_roles
+(
+    organization_id UUID NOT NULL,
+    user_email      TEXT NOT NULL,
+    role_name       TEXT NOT NULL,
+    PRIMARY KEY (organization_id, user_email, role_name),
+    FOREIGN KEY (organization_id, user_email) REFERENCES organization_members (organization_id, user_email)
+        ON DELETE CASCADE ON UPDATE CASCADE,
+    CONSTRAINT organization_member_roles_role_name_fkey FOREIGN KEY (role_name) REFERENCES roles (name) ON DELETE CASCADE
+);
+
+-- Roles offered to an existing user, until they accept them
+CREATE TABLE IF NOT EXISTS organization_invitations
+(
+    id              TEXT        NOT NULL PRIMARY KEY,
+    organization_id UUID        NOT NULL,
+    user_email      TEXT        NOT NULL REFERENCES users (email) ON DELETE CASCADE ON UPDATE CASCADE,
+    roles           TEXT[]      NOT NULL,
+    expires_at      TIMESTAMPTZ NOT NULL,
+    UNIQUE (organization_id, user_email),
+    CONSTRAINT organization_invitations_organization_id_fkey FOREIGN KEY (organization_id) REFERENCES organizations (id) ON DELETE CASCADE
+);
+
+-- Given to whoever creates an organization
+INSERT INTO roles (name)
+VALUES ('org-admin')
+ON CONFLICT DO NOTHING;
+
+INSERT INTO role_permissions (role_name, permission)
+VALUES ('admin', 'members:read'),
+       ('admin', 'members:write'),
+       ('admin', 'organizations:write'),
+       ('org-admin', 'members:read'),
+       ('org-admin', 'members:write')
+ON CONFLICT DO NOTHING;
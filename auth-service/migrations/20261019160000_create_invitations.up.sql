@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS invitations
+(
+    id              TEXT        NOT NULL PRIMARY KEY,
+    email           TEXT        NOT NULL,
+    role_name       TEXT REFERENCES roles (name) ON DELETE SET NULL,
+    organization_id UUID REFERENCES organizations (id) ON DELETE CASCADE,
+    invited_by      TEXT        NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at      TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS invitations_email_idx ON invitations (email);
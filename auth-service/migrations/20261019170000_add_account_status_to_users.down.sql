@@ -0,0 +1,6 @@
+-- Add down migration script here
+ALTER TABLE users
+    DROP COLUMN status,
+    DROP COLUMN status_reason,
+    DROP COLUMN status_changed_at,
+    DROP COLUMN suspended_until;
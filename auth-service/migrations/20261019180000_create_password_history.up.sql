@@ -0,0 +1,16 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS password_history
+(
+    id            BIGSERIAL   NOT NULL PRIMARY KEY,
+    user_email    TEXT        NOT NULL REFERENCES users (email) ON DELETE CASCADE ON UPDATE CASCADE,
+    password_hash TEXT        NOT NULL,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS password_history_user_email_idx ON password_history (user_email, created_at);
+
+-- The current passwords count as used
+INSERT INTO password_history (user_email, password_hash)
+SELECT email, password_hash
+FROM users
+WHERE password_hash IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DELETE FROM role_permissions
+WHERE role_name = 'admin' AND permission = 'metrics:read';
@@ -0,0 +1,4 @@
+-- Add up migration script here
+INSERT INTO role_permissions (role_name, permission)
+VALUES ('admin', 'metrics:read')
+ON CONFLICT DO NOTHING;
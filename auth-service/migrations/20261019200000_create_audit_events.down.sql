@@ -0,0 +1,5 @@
+-- Add down migration script here
+DELETE FROM role_permissions
+WHERE role_name = 'admin' AND permission = 'audit:read';
+
+DROP TABLE IF EXISTS audit_events;
@@ -0,0 +1,20 @@
+-- Add up migration script here
+-- No reference to users, events outlive the account they were about
+CREATE TABLE IF NOT EXISTS audit_events
+(
+    id          BIGSERIAL   NOT NULL PRIMARY KEY,
+    occurred_at TIMESTAMPTZ NOT NULL,
+    actor       TEXT,
+    ip          TEXT,
+    user_agent  TEXT,
+    event_type  TEXT        NOT NULL,
+    outcome     TEXT        NOT NULL,
+    reason      TEXT
+);
+
+CREATE INDEX IF NOT EXISTS audit_events_occurred_at_idx ON audit_events (occurred_at);
+CREATE INDEX IF NOT EXISTS audit_events_actor_idx ON audit_events (actor, occurred_at);
+
+INSERT INTO role_permissions (role_name, permission)
+VALUES ('admin', 'audit:read')
+ON CONFLICT DO NOTHING;
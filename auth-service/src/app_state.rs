@@ -1,5 +1,11 @@
-use crate::domain::data_stores::{BannedTokenStore, TwoFACodeStore, UserStore};
+use crate::domain::data_stores::{
+    AuditLog, BannedTokenStore, BreachedPasswordStore, DeviceHistoryStore, EmailChangeStore, InvitationStore, LoginFailureStore,
+    OneTimeTokenStore, OrganizationStore, PasswordHistoryStore, RateLimitStore, RoleStore, TrustedDeviceStore, TwoFACodeStore,
+    UserStore,
+};
 use crate::domain::email_client::EmailClient;
+use crate::domain::password_policy::PasswordPolicy;
+use crate::domain::rate_limit::RateLimitPolicy;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -8,27 +14,44 @@ pub type UserStoreType = Arc<RwLock<dyn UserStore>>;
 pub type BannedTokenStoreType = Arc<RwLock<dyn BannedTokenStore>>;
 pub type TwoFACodeStoreType = Arc<RwLock<dyn TwoFACodeStore>>;
 pub type EmailClientType = Arc<RwLock<dyn EmailClient>>;
+pub type OneTimeTokenStoreType = Arc<RwLock<dyn OneTimeTokenStore>>;
+pub type TrustedDeviceStoreType = Arc<RwLock<dyn TrustedDeviceStore>>;
+pub type EmailChangeStoreType = Arc<RwLock<dyn EmailChangeStore>>;
+pub type LoginFailureStoreType = Arc<RwLock<dyn LoginFailureStore>>;
+pub type RoleStoreType = Arc<RwLock<dyn RoleStore>>;
+pub type OrganizationStoreType = Arc<RwLock<dyn OrganizationStore>>;
+pub type InvitationStoreType = Arc<RwLock<dyn InvitationStore>>;
+pub type BreachedPasswordStoreType = Arc<RwLock<dyn BreachedPasswordStore>>;
+pub type PasswordHistoryStoreType = Arc<RwLock<dyn PasswordHistoryStore>>;
+pub type RateLimitStoreType = Arc<RwLock<dyn RateLimitStore>>;
+pub type AuditLogType = Arc<RwLock<dyn AuditLog>>;
+pub type DeviceHistoryStoreType = Arc<RwLock<dyn DeviceHistoryStore>>;
 
+// Built with a struct literal, there are too many stores for a readable constructor
 #[derive(Clone)]
 pub struct AppState {
     pub user_store: UserStoreType,
     pub banned_token_store: BannedTokenStoreType,
     pub two_fa_code_store: TwoFACodeStoreType,
     pub email_client: EmailClientType,
-}
-
-impl AppState {
-    pub fn new(
-        user_store: UserStoreType,
-        banned_token_store: BannedTokenStoreType,
-        two_fa_code_store: TwoFACodeStoreType,
-        email_client: EmailClientType,
-    ) -> Self {
-        Self {
-            user_store,
-            banned_token_store,
-            two_fa_code_store,
-            email_client,
-        }
-    }
+    pub one_time_token_store: OneTimeTokenStoreType,
+    pub trusted_device_store: TrustedDeviceStoreType,
+    pub email_change_store: EmailChangeStoreType,
+    pub login_failure_store: LoginFailureStoreType,
+    pub role_store: RoleStoreType,
+    pub organization_store: OrganizationStoreType,
+    pub invitation_store: InvitationStoreType,
+    // Uninvited signups are rejected
+    pub invite_only_signup: bool,
+    // Signups with a taken email get the same response as new ones, so that they don't tell which emails have an account
+    pub uniform_signup_response: bool,
+    pub breached_password_store: BreachedPasswordStoreType,
+    pub password_history_store: PasswordHistoryStoreType,
+    // Rules for new passwords, breached passwords are rejected on top of them
+    pub password_policy: PasswordPolicy,
+    pub rate_limit_store: RateLimitStoreType,
+    // Read when the routes are built, changing it afterwards has no effect
+    pub rate_limit_policy: RateLimitPolicy,
+    pub audit_log: AuditLogType,
+    pub device_history_store: DeviceHistoryStoreType,
 }
@@ -0,0 +1,157 @@
+use chrono::{DateTime, Utc};
+use validator::ValidationError;
+
+const MAX_STATUS_REASON_LENGTH: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountStatus {
+    Active,
+    // Waiting for an admin to approve the account
+    Pending,
+    // Temporarily blocked, until a given time or until an admin reactivates the account
+    Suspended,
+    // Blocked until an admin enables the account again
+    Disabled,
+}
+
+impl AccountStatus {
+    pub fn parse(value: &str) -> Result<AccountStatus, ValidationError> {
+        match value {
+            "active" => Ok(AccountStatus::Active),
+            "pending" => Ok(AccountStatus::Pending),
+            "suspended" => Ok(AccountStatus::Suspended),
+            "disabled" => Ok(AccountStatus::Disabled),
+            _ => Err(ValidationError::new("Invalid account status.")),
+        }
+    }
+}
+
+impl AsRef<str> for AccountStatus {
+    fn as_ref(&self) -> &str {
+        match self {
+            AccountStatus::Active => "active",
+            AccountStatus::Pending => "pending",
+            AccountStatus::Suspended => "suspended",
+            AccountStatus::Disabled => "disabled",
+        }
+    }
+}
+
+// Why an admin changed the status, shown to other admins
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusReason(String);
+
+impl StatusReason {
+    pub fn parse(value: &str) -> Result<StatusReason, ValidationError> {
+        let value = value.trim();
+
+        if value.is_empty() {
+            return Err(ValidationError::new("Status reason cannot be empty."));
+        }
+
+        if value.chars().count() > MAX_STATUS_REASON_LENGTH {
+            return Err(ValidationError::new("Status reason is too long."));
+        }
+
+        Ok(StatusReason(value.to_owned()))
+    }
+}
+
+impl AsRef<str> for StatusReason {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountState {
+    pub status: AccountStatus,
+    pub reason: Option<StatusReason>,
+    pub changed_at: DateTime<Utc>,
+    // When a suspension ends, `None` for the other statuses and for indefinite suspensions
+    pub suspended_until: Option<DateTime<Utc>>,
+}
+
+impl AccountState {
+    pub fn new(status: AccountStatus, reason: Option<StatusReason>) -> AccountState {
+        AccountState {
+            status,
+            reason,
+            changed_at: Utc::now(),
+            suspended_until: None,
+        }
+    }
+
+    pub fn suspended(reason: Option<StatusReason>, until: Option<DateTime<Utc>>) -> Result<AccountState, ValidationError> {
+        if until.is_some_and(|until| until <= Utc::now()) {
+            return Err(ValidationError::new("Suspension must end in the future."));
+        }
+
+        Ok(AccountState {
+            suspended_until: until,
+            ..AccountState::new(AccountStatus::Suspended, reason)
+        })
+    }
+
+    // The status that applies at `now`, a suspension that has ended leaves the account active
+    pub fn status_at(&self, now: DateTime<Utc>) -> AccountStatus {
+        match (self.status, self.suspended_until) {
+            (AccountStatus::Suspended, Some(until)) if until <= now => AccountStatus::Active,
+            (status, _) => status,
+        }
+    }
+}
+
+impl Default for AccountState {
+    fn default() -> Self {
+        AccountState::new(AccountStatus::Active, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn account_status_round_trips() {
+        for status in [
+            AccountStatus::Active,
+            AccountStatus::Pending,
+            AccountStatus::Suspended,
+            AccountStatus::Disabled,
+        ] {
+            assert_eq!(AccountStatus::parse(status.as_ref()).unwrap(), status);
+        }
+
+        assert!(AccountStatus::parse("deleted").is_err());
+    }
+
+    #[test]
+    fn status_reason_is_trimmed() {
+        assert_eq!(StatusReason::parse(" Chargeback ").unwrap().as_ref(), "Chargeback");
+        assert!(StatusReason::parse("  ").is_err());
+        assert!(StatusReason::parse(&"a".repeat(MAX_STATUS_REASON_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn suspension_ends_at_the_given_time() {
+        let until = Utc::now() + Duration::hours(1);
+        let state = AccountState::suspended(None, Some(until)).unwrap();
+
+        assert_eq!(state.status_at(Utc::now()), AccountStatus::Suspended);
+        assert_eq!(state.status_at(until), AccountStatus::Active);
+    }
+
+    #[test]
+    fn suspension_without_end_lasts() {
+        let state = AccountState::suspended(None, None).unwrap();
+
+        assert_eq!(state.status_at(Utc::now() + Duration::days(3650)), AccountStatus::Suspended);
+    }
+
+    #[test]
+    fn suspension_cannot_end_in_the_past() {
+        assert!(AccountState::suspended(None, Some(Utc::now() - Duration::seconds(1))).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use crate::domain::email::Email;
+use chrono::{DateTime, Utc};
+use std::net::IpAddr;
+use validator::ValidationError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditEventType {
+    Signup,
+    Login,
+    Verify2FA,
+    Logout,
+    VerifyToken,
+    MagicLink,
+}
+
+impl AuditEventType {
+    pub fn parse(value: &str) -> Result<AuditEventType, ValidationError> {
+        match value {
+            "signup" => Ok(AuditEventType::Signup),
+            "login" => Ok(AuditEventType::Login),
+            "verify_2fa" => Ok(AuditEventType::Verify2FA),
+            "logout" => Ok(AuditEventType::Logout),
+            "verify_token" => Ok(AuditEventType::VerifyToken),
+            "magic_link" => Ok(AuditEventType::MagicLink),
+            _ => Err(ValidationError::new("Invalid audit event type.")),
+        }
+    }
+}
+
+impl AsRef<str> for AuditEventType {
+    fn as_ref(&self) -> &str {
+        match self {
+            AuditEventType::Signup => "signup",
+            AuditEventType::Login => "login",
+            AuditEventType::Verify2FA => "verify_2fa",
+            AuditEventType::Logout => "logout",
+            AuditEventType::VerifyToken => "verify_token",
+            AuditEventType::MagicLink => "magic_link",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditOutcome {
+    Success,
+    // The request went through but more is needed, such as the 2FA code of a login
+    Challenged,
+    Failure,
+}
+
+impl AuditOutcome {
+    pub fn parse(value: &str) -> Result<AuditOutcome, ValidationError> {
+        match value {
+            "success" => Ok(AuditOutcome::Success),
+            "challenged" => Ok(AuditOutcome::Challenged),
+            "failure" => Ok(AuditOutcome::Failure),
+            _ => Err(ValidationError::new("Invalid audit outcome.")),
+        }
+    }
+}
+
+impl AsRef<str> for AuditOutcome {
+    fn as_ref(&self) -> &str {
+        match self {
+            AuditOutcome::Success => "success",
+            AuditOutcome::Challenged => "challenged",
+            AuditOutcome::Failure => "failure",
+        }
+    }
+}
+
+// An authentication request and how it ended
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEvent {
+    pub occurred_at: DateTime<Utc>,
+    // The user the request was about, such as the email a login was tried with, even when it has no account
+    pub actor: Option<Email>,
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+    pub event_type: AuditEventType,
+    pub outcome: AuditOutcome,
+    // Why the request failed, the error it was answered with
+    pub reason: Option<String>,
+}
+
+// Events matching every filter that is set, most recent first
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditSearch {
+    pub actor: Option<Email>,
+    // Any type when empty
+    pub event_types: Vec<AuditEventType>,
+    pub offset: u64,
+    pub limit: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditPage {
+    pub events: Vec<AuditEvent>,
+    // Number of matching events across all pages
+    pub total: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_types_round_trip() {
+        for event_type in [
+            AuditEventType::Signup,
+            AuditEventType::Login,
+            AuditEventType::Verify2FA,
+            AuditEventType::Logout,
+            AuditEventType::VerifyToken,
+            AuditEventType::MagicLink,
+        ] {
+            assert_eq!(AuditEventType::parse(event_type.as_ref()).unwrap(), event_type);
+        }
+
+        assert!(AuditEventType::parse("password_reset").is_err());
+    }
+
+    #[test]
+    fn outcomes_round_trip() {
+        for outcome in [AuditOutcome::Success, AuditOutcome::Challenged, AuditOutcome::Failure] {
+            assert_eq!(AuditOutcome::parse(outcome.as_ref()).unwrap(), outcome);
+        }
+
+        assert!(AuditOutcome::parse("").is_err());
+    }
+}
@@ -1,9 +1,31 @@
+use crate::domain::account_status::AccountState;
+use crate::domain::audit::{AuditEvent, AuditPage, AuditSearch};
+use crate::domain::device_history::{DeviceFingerprint, DeviceRecord, DeviceSighting};
 use crate::domain::email::Email;
-use crate::domain::user::User;
-use color_eyre::eyre::{eyre, Report, Result};
+use crate::domain::email_change::EmailChange;
+use crate::domain::hashed_password::HashedPassword;
+use crate::domain::invitation::Invitation;
+use crate::domain::login_failures::LoginFailures;
+use crate::domain::organization::{Member, MemberInvitation, Membership, Organization, OrganizationId};
+use crate::domain::profile::Profile;
+use crate::domain::rate_limit::{RateLimit, RateLimitDecision};
+use crate::domain::rbac::{Role, RoleName};
+use crate::domain::trusted_device::TrustedDevice;
+use crate::domain::user::{User, UserPage, UserSearch};
+use crate::utils::auth::{
+    EMAIL_CHANGE_TTL_SECONDS, EMAIL_VERIFICATION_TTL_SECONDS, INVITATION_TTL_SECONDS, MAGIC_LINK_TTL_SECONDS,
+    PASSWORD_RESET_TTL_SECONDS, UNRECOGNIZED_LOGIN_TTL_SECONDS,
+};
+use crate::utils::constants::TWO_FA_CODE_SECRET;
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{eyre, Context, Report, Result};
+use hmac::{Hmac, Mac};
 use rand::{rng, Rng};
 use secrecy::{ExposeSecret, SecretString};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -47,26 +69,466 @@ impl PartialEq for BannedTokenStoreError {
 pub trait UserStore: Send + Sync {
     async fn add_user(&mut self, user: User) -> Result<(), UserStoreError>;
     async fn get_user(&self, email: &Email) -> Result<User, UserStoreError>;
+    // Users are ordered by creation date
+    async fn list_users(&self, search: &UserSearch) -> Result<UserPage, UserStoreError>;
     async fn validate_user(&self, email: &Email, password: &str) -> Result<(), UserStoreError>;
+    async fn mark_email_verified(&mut self, email: &Email) -> Result<(), UserStoreError>;
+    async fn update_password(&mut self, email: &Email, password: HashedPassword) -> Result<(), UserStoreError>;
+    // Swaps the hash for a new one of the same password, unless the password changed since `old_password` was read
+    async fn upgrade_password_hash(
+        &mut self,
+        email: &Email,
+        old_password: &HashedPassword,
+        new_password: HashedPassword,
+    ) -> Result<(), UserStoreError>;
+    // Fails with `UserAlreadyExists` if `new_email` is taken. The user's rows in other tables move with it
+    async fn update_email(&mut self, old_email: &Email, new_email: &Email) -> Result<(), UserStoreError>;
+    async fn delete_user(&mut self, email: &Email) -> Result<(), UserStoreError>;
+    async fn update_profile(&mut self, email: &Email, profile: Profile) -> Result<(), UserStoreError>;
+    // Turns the account into a passwordless one until a new password is set through the reset flow
+    async fn clear_password(&mut self, email: &Email) -> Result<(), UserStoreError>;
+    async fn set_account_state(&mut self, email: &Email, account_state: AccountState) -> Result<(), UserStoreError>;
+    async fn set_requires_2fa(&mut self, email: &Email, requires_2fa: bool) -> Result<(), UserStoreError>;
+    async fn record_login(&mut self, email: &Email, logged_in_at: DateTime<Utc>) -> Result<(), UserStoreError>;
 }
 
 #[async_trait::async_trait]
 pub trait BannedTokenStore: Send + Sync {
     async fn add_token(&mut self, token: SecretString) -> Result<(), BannedTokenStoreError>;
     async fn contains_token(&self, token: &str) -> Result<bool, BannedTokenStoreError>;
+    // Bans every token issued to the user up to now, except `keep_token`, e.g. after a password reset
+    async fn revoke_user_tokens(&mut self, email: &Email, keep_token: Option<&str>) -> Result<(), BannedTokenStoreError>;
+    // `issued_at_micros` is when the token was issued, in microseconds
+    async fn is_user_token_revoked(
+        &self,
+        email: &Email,
+        token: &str,
+        issued_at_micros: i64,
+    ) -> Result<bool, BannedTokenStoreError>;
+}
+
+// Revocation of all the tokens a user was issued up to `revoked_at_micros`. The precision keeps a login made right
+// after the revocation from being caught by it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenRevocation {
+    pub revoked_at_micros: i64,
+    pub kept_token: Option<String>,
+}
+
+impl TokenRevocation {
+    pub fn new(keep_token: Option<&str>) -> Self {
+        Self {
+            revoked_at_micros: Utc::now().timestamp_micros(),
+            kept_token: keep_token.map(str::to_owned),
+        }
+    }
+
+    pub fn revokes(&self, token: &str, issued_at_micros: i64) -> bool {
+        if self.kept_token.as_deref() == Some(token) {
+            return false;
+        }
+
+        issued_at_micros <= self.revoked_at_micros
+    }
 }
 
 // This trait represents the interface all concrete 2FA code stores should implement
 #[async_trait::async_trait]
 pub trait TwoFACodeStore: Send + Sync {
+    // Only the keyed hash of a code is ever stored, so read access to a store doesn't reveal live codes
     async fn add_code(
         &mut self,
         email: Email,
         login_attempt_id: LoginAttemptId,
-        code: TwoFACode,
+        code_hash: TwoFACodeHash,
     ) -> Result<(), TwoFACodeStoreError>;
     async fn remove_code(&mut self, email: &Email) -> Result<(), TwoFACodeStoreError>;
-    async fn get_code(&self, email: &Email) -> Result<(LoginAttemptId, TwoFACode), TwoFACodeStoreError>;
+    async fn get_code(&self, email: &Email) -> Result<(LoginAttemptId, TwoFACodeHash), TwoFACodeStoreError>;
+}
+
+// This trait represents the interface all concrete single-use link token stores should implement
+#[async_trait::async_trait]
+pub trait OneTimeTokenStore: Send + Sync {
+    async fn add_token(&mut self, purpose: TokenPurpose, token_id: &str, email: Email) -> Result<(), OneTimeTokenStoreError>;
+    // Removes the token so that it can never be used again
+    async fn consume_token(&mut self, purpose: TokenPurpose, token_id: &str) -> Result<Email, OneTimeTokenStoreError>;
+    // Removes every token issued to the user, whatever its purpose
+    async fn remove_tokens(&mut self, email: &Email) -> Result<(), OneTimeTokenStoreError>;
+}
+
+#[derive(Debug, Error)]
+pub enum OneTimeTokenStoreError {
+    #[error("Token not found")]
+    TokenNotFound,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+impl PartialEq for OneTimeTokenStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::TokenNotFound, Self::TokenNotFound) | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+#[async_trait::async_trait]
+pub trait TrustedDeviceStore: Send + Sync {
+    async fn add_device(&mut self, email: Email, device: TrustedDevice) -> Result<(), TrustedDeviceStoreError>;
+    // Expired devices are never returned
+    async fn get_device(&self, email: &Email, device_id: &str) -> Result<TrustedDevice, TrustedDeviceStoreError>;
+    async fn get_devices(&self, email: &Email) -> Result<Vec<TrustedDevice>, TrustedDeviceStoreError>;
+    async fn remove_device(&mut self, email: &Email, device_id: &str) -> Result<(), TrustedDeviceStoreError>;
+    // Follows a change of the user's email address
+    async fn move_devices(&mut self, old_email: &Email, new_email: &Email) -> Result<(), TrustedDeviceStoreError>;
+    async fn remove_devices(&mut self, email: &Email) -> Result<(), TrustedDeviceStoreError>;
+}
+
+// Keeps at most one pending change per user, keyed by the current address
+#[async_trait::async_trait]
+pub trait EmailChangeStore: Send + Sync {
+    // Replaces any pending change of the same user
+    async fn add_change(&mut self, change: EmailChange) -> Result<(), EmailChangeStoreError>;
+    // Records the confirmation of the address the token was sent to and returns the updated change
+    async fn confirm_change(&mut self, old_email: &Email, token_id: &str) -> Result<EmailChange, EmailChangeStoreError>;
+    async fn get_change(&self, old_email: &Email) -> Result<EmailChange, EmailChangeStoreError>;
+    async fn remove_change(&mut self, old_email: &Email) -> Result<(), EmailChangeStoreError>;
+}
+
+#[derive(Debug, Error)]
+pub enum EmailChangeStoreError {
+    #[error("Email change not found")]
+    ChangeNotFound,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+impl PartialEq for EmailChangeStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::ChangeNotFound, Self::ChangeNotFound) | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TrustedDeviceStoreError {
+    #[error("Device not found")]
+    DeviceNotFound,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+impl PartialEq for TrustedDeviceStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::DeviceNotFound, Self::DeviceNotFound) | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+// Keeps track of failed logins per email to lock out password guessing
+#[async_trait::async_trait]
+pub trait LoginFailureStore: Send + Sync {
+    // Returns an empty record if the user never failed to log in
+    async fn get_failures(&self, email: &Email) -> Result<LoginFailures, LoginFailureStoreError>;
+    async fn set_failures(&mut self, email: &Email, failures: LoginFailures) -> Result<(), LoginFailureStoreError>;
+    async fn clear_failures(&mut self, email: &Email) -> Result<(), LoginFailureStoreError>;
+    // The failures follow the user to their new address, so that changing it doesn't end a lockout.
+    // Safe to repeat, a second call finds nothing left to move
+    async fn move_failures(&mut self, old_email: &Email, new_email: &Email) -> Result<(), LoginFailureStoreError>;
+}
+
+#[derive(Debug, Error)]
+pub enum LoginFailureStoreError {
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+impl PartialEq for LoginFailureStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!((self, other), (Self::UnexpectedError(_), Self::UnexpectedError(_)))
+    }
+}
+
+// Passwords known from data breaches, looked up by their SHA-1 hash like in the Have I Been Pwned dumps
+#[async_trait::async_trait]
+pub trait BreachedPasswordStore: Send + Sync {
+    // How many times the password appeared in breaches, 0 if it never did
+    async fn breach_count(&self, password: &SecretString) -> Result<u64, BreachedPasswordStoreError>;
+}
+
+#[derive(Debug, Error)]
+pub enum BreachedPasswordStoreError {
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+impl PartialEq for BreachedPasswordStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!((self, other), (Self::UnexpectedError(_), Self::UnexpectedError(_)))
+    }
+}
+
+// Upper case hex SHA-1 of the password, the format of the Have I Been Pwned dumps
+pub fn breached_password_hash(password: &SecretString) -> String {
+    hex::encode_upper(Sha1::digest(password.expose_secret().as_bytes()))
+}
+
+#[async_trait::async_trait]
+pub trait RoleStore: Send + Sync {
+    async fn list_roles(&self) -> Result<Vec<Role>, RoleStoreError>;
+    // Creates the role, or replaces the permissions of an existing one
+    async fn set_role(&mut self, role: Role) -> Result<(), RoleStoreError>;
+    // Also takes the role away from every user it was assigned to
+    async fn delete_role(&mut self, name: &RoleName) -> Result<(), RoleStoreError>;
+    async fn assign_role(&mut self, email: &Email, name: &RoleName) -> Result<(), RoleStoreError>;
+    async fn unassign_role(&mut self, email: &Email, name: &RoleName) -> Result<(), RoleStoreError>;
+    async fn get_user_roles(&self, email: &Email) -> Result<Vec<RoleName>, RoleStoreError>;
+    async fn move_user_roles(&mut self, old_email: &Email, new_email: &Email) -> Result<(), RoleStoreError>;
+    async fn remove_user_roles(&mut self, email: &Email) -> Result<(), RoleStoreError>;
+}
+
+#[derive(Debug, Error)]
+pub enum RoleStoreError {
+    #[error("Role not found")]
+    RoleNotFound,
+    #[error("Role not assigned")]
+    RoleNotAssigned,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+impl PartialEq for RoleStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::RoleNotFound, Self::RoleNotFound)
+                | (Self::RoleNotAssigned, Self::RoleNotAssigned)
+                | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+#[async_trait::async_trait]
+pub trait OrganizationStore: Send + Sync {
+    async fn add_organization(&mut self, organization: Organization) -> Result<(), OrganizationStoreError>;
+    async fn get_organization(&self, id: &OrganizationId) -> Result<Organization, OrganizationStoreError>;
+    // Adds the user to the organization, or replaces the roles they hold in it
+    async fn set_member(&mut self, id: &OrganizationId, email: &Email, roles: &[RoleName]) -> Result<(), OrganizationStoreError>;
+    async fn remove_member(&mut self, id: &OrganizationId, email: &Email) -> Result<(), OrganizationStoreError>;
+    async fn get_membership(&self, id: &OrganizationId, email: &Email) -> Result<Membership, OrganizationStoreError>;
+    // Ordered by the time the user joined, oldest first
+    async fn list_memberships(&self, email: &Email) -> Result<Vec<Membership>, OrganizationStoreError>;
+    async fn list_members(&self, id: &OrganizationId) -> Result<Vec<Member>, OrganizationStoreError>;
+    // Replaces an earlier invitation of the user to the same organization
+    async fn add_member_invitation(&mut self, invitation: MemberInvitation) -> Result<(), OrganizationStoreError>;
+    // Removes the invitation, so that its link can only be used once
+    async fn take_member_invitation(&mut self, id: &str) -> Result<MemberInvitation, OrganizationStoreError>;
+    // Memberships and pending invitations both follow the user
+    async fn move_memberships(&mut self, old_email: &Email, new_email: &Email) -> Result<(), OrganizationStoreError>;
+    async fn remove_memberships(&mut self, email: &Email) -> Result<(), OrganizationStoreError>;
+}
+
+#[derive(Debug, Error)]
+pub enum OrganizationStoreError {
+    #[error("Organization not found")]
+    OrganizationNotFound,
+    #[error("Member not found")]
+    MemberNotFound,
+    #[error("Invitation not found")]
+    InvitationNotFound,
+    #[error("Role not found")]
+    RoleNotFound,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+impl PartialEq for OrganizationStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::OrganizationNotFound, Self::OrganizationNotFound)
+                | (Self::MemberNotFound, Self::MemberNotFound)
+                | (Self::InvitationNotFound, Self::InvitationNotFound)
+                | (Self::RoleNotFound, Self::RoleNotFound)
+                | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+#[async_trait::async_trait]
+pub trait InvitationStore: Send + Sync {
+    async fn add_invitation(&mut self, invitation: Invitation) -> Result<(), InvitationStoreError>;
+    // Expired invitations are never returned
+    async fn get_invitation(&self, id: &str) -> Result<Invitation, InvitationStoreError>;
+    // Pending invitations, oldest first
+    async fn list_invitations(&self) -> Result<Vec<Invitation>, InvitationStoreError>;
+    async fn remove_invitation(&mut self, id: &str) -> Result<(), InvitationStoreError>;
+    // Once the user signed up, every invitation sent to them is used up
+    async fn remove_invitations(&mut self, email: &Email) -> Result<(), InvitationStoreError>;
+}
+
+#[derive(Debug, Error)]
+pub enum InvitationStoreError {
+    #[error("Invitation not found")]
+    InvitationNotFound,
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+impl PartialEq for InvitationStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::InvitationNotFound, Self::InvitationNotFound) | (Self::UnexpectedError(_), Self::UnexpectedError(_))
+        )
+    }
+}
+
+// Hashes of the passwords users had, kept to stop them from going back to one of them
+#[async_trait::async_trait]
+pub trait PasswordHistoryStore: Send + Sync {
+    // Most recent first, including the current password
+    async fn get_passwords(&self, email: &Email, limit: usize) -> Result<Vec<HashedPassword>, PasswordHistoryStoreError>;
+    // Forgets all but the `keep` most recent passwords of the user
+    async fn add_password(
+        &mut self,
+        email: &Email,
+        password: HashedPassword,
+        keep: usize,
+    ) -> Result<(), PasswordHistoryStoreError>;
+    async fn move_passwords(&mut self, old_email: &Email, new_email: &Email) -> Result<(), PasswordHistoryStoreError>;
+    async fn remove_passwords(&mut self, email: &Email) -> Result<(), PasswordHistoryStoreError>;
+}
+
+#[derive(Debug, Error)]
+pub enum PasswordHistoryStoreError {
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+impl PartialEq for PasswordHistoryStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!((self, other), (Self::UnexpectedError(_), Self::UnexpectedError(_)))
+    }
+}
+
+// Requests made recently under a key, such as the IP of a client on a route, to rate limit them over a sliding window
+#[async_trait::async_trait]
+pub trait RateLimitStore: Send + Sync {
+    // Records the request made at `now` unless the limit was already reached, rejected requests are not recorded
+    async fn record_request(
+        &mut self,
+        key: &str,
+        limit: &RateLimit,
+        now: DateTime<Utc>,
+    ) -> Result<RateLimitDecision, RateLimitStoreError>;
+}
+
+#[derive(Debug, Error)]
+pub enum RateLimitStoreError {
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+impl PartialEq for RateLimitStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!((self, other), (Self::UnexpectedError(_), Self::UnexpectedError(_)))
+    }
+}
+
+// Record of the authentication requests, kept for security reviews. Events are only ever added, though their actor
+// follows the user when they change their address and is forgotten when they delete their account.
+#[async_trait::async_trait]
+pub trait AuditLog: Send + Sync {
+    async fn record_event(&mut self, event: AuditEvent) -> Result<(), AuditLogError>;
+    async fn list_events(&self, search: &AuditSearch) -> Result<AuditPage, AuditLogError>;
+    async fn move_actor(&mut self, old_email: &Email, new_email: &Email) -> Result<(), AuditLogError>;
+    // Strips the actor, IP and user agent from the user's events, keeping only what happened
+    async fn forget_actor(&mut self, email: &Email) -> Result<(), AuditLogError>;
+}
+
+#[derive(Debug, Error)]
+pub enum AuditLogError {
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+impl PartialEq for AuditLogError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!((self, other), (Self::UnexpectedError(_), Self::UnexpectedError(_)))
+    }
+}
+
+// Devices each user logged in from, to tell them about logins from new ones
+#[async_trait::async_trait]
+pub trait DeviceHistoryStore: Send + Sync {
+    // Remembers the device as one the user logged in from, and tells whether they had before
+    async fn record_device(
+        &mut self,
+        email: &Email,
+        fingerprint: &DeviceFingerprint,
+        seen_at: DateTime<Utc>,
+    ) -> Result<DeviceSighting, DeviceHistoryStoreError>;
+    // Most recently seen first
+    async fn get_devices(&self, email: &Email) -> Result<Vec<DeviceRecord>, DeviceHistoryStoreError>;
+    async fn move_devices(&mut self, old_email: &Email, new_email: &Email) -> Result<(), DeviceHistoryStoreError>;
+    async fn remove_devices(&mut self, email: &Email) -> Result<(), DeviceHistoryStoreError>;
+}
+
+#[derive(Debug, Error)]
+pub enum DeviceHistoryStoreError {
+    #[error("Unexpected error")]
+    UnexpectedError(#[source] Report),
+}
+
+impl PartialEq for DeviceHistoryStoreError {
+    fn eq(&self, other: &Self) -> bool {
+        matches!((self, other), (Self::UnexpectedError(_), Self::UnexpectedError(_)))
+    }
+}
+
+// What a one-time token sent by email can be used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenPurpose {
+    MagicLink,
+    EmailVerification,
+    PasswordReset,
+    EmailChange,
+    Invitation,
+    // Joining an organization as an existing user
+    OrganizationInvitation,
+    // Reporting a login the user didn't make
+    UnrecognizedLogin,
+}
+
+impl TokenPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenPurpose::MagicLink => "magic_link",
+            TokenPurpose::EmailVerification => "email_verification",
+            TokenPurpose::PasswordReset => "password_reset",
+            TokenPurpose::EmailChange => "email_change",
+            TokenPurpose::Invitation => "invitation",
+            TokenPurpose::OrganizationInvitation => "organization_invitation",
+            TokenPurpose::UnrecognizedLogin => "unrecognized_login",
+        }
+    }
+
+    pub fn ttl_seconds(&self) -> i64 {
+        match self {
+            TokenPurpose::MagicLink => MAGIC_LINK_TTL_SECONDS,
+            TokenPurpose::EmailVerification => EMAIL_VERIFICATION_TTL_SECONDS,
+            TokenPurpose::PasswordReset => PASSWORD_RESET_TTL_SECONDS,
+            TokenPurpose::EmailChange => EMAIL_CHANGE_TTL_SECONDS,
+            TokenPurpose::Invitation | TokenPurpose::OrganizationInvitation => INVITATION_TTL_SECONDS,
+            TokenPurpose::UnrecognizedLogin => UNRECOGNIZED_LOGIN_TTL_SECONDS,
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -90,7 +552,7 @@ pub struct LoginAttemptId(pub SecretString);
 
 impl PartialEq for LoginAttemptId {
     fn eq(&self, other: &Self) -> bool {
-        self.0.expose_secret() == other.0.expose_secret()
+        constant_time_eq(&self.0, &other.0)
     }
 }
 
@@ -122,7 +584,7 @@ pub struct TwoFACode(pub SecretString);
 
 impl PartialEq for TwoFACode {
     fn eq(&self, other: &Self) -> bool {
-        self.0.expose_secret() == other.0.expose_secret()
+        constant_time_eq(&self.0, &other.0)
     }
 }
 
@@ -133,6 +595,12 @@ impl TwoFACode {
             _ => Err(eyre!("Code is invalid")),
         }
     }
+
+    // The hash is bound to the login attempt, so equal codes never produce equal hashes
+    pub fn hash(&self, login_attempt_id: &LoginAttemptId) -> Result<TwoFACodeHash> {
+        let mac = two_fa_code_mac(login_attempt_id, self)?;
+        Ok(TwoFACodeHash(hex::encode(mac.finalize().into_bytes()).into()))
+    }
 }
 
 impl Default for TwoFACode {
@@ -146,3 +614,94 @@ impl AsRef<SecretString> for TwoFACode {
         &self.0
     }
 }
+
+// HMAC-SHA256 of a 2FA code, keyed with a server-side secret
+#[derive(Clone, Debug)]
+pub struct TwoFACodeHash(SecretString);
+
+impl PartialEq for TwoFACodeHash {
+    fn eq(&self, other: &Self) -> bool {
+        constant_time_eq(&self.0, &other.0)
+    }
+}
+
+impl TwoFACodeHash {
+    pub fn parse(hash: String) -> Result<Self> {
+        let bytes = hex::decode(&hash).wrap_err("2FA code hash is not valid hex")?;
+
+        match bytes.len() {
+            32 => Ok(TwoFACodeHash(hash.into())),
+            _ => Err(eyre!("2FA code hash has an invalid length")),
+        }
+    }
+
+    // Compares in constant time, so response timing doesn't reveal how much of a guess was right
+    pub fn verify(&self, login_attempt_id: &LoginAttemptId, code: &TwoFACode) -> bool {
+        let Ok(expected) = hex::decode(self.0.expose_secret()) else {
+            return false;
+        };
+
+        two_fa_code_mac(login_attempt_id, code)
+            .map(|mac| mac.verify_slice(&expected).is_ok())
+            .unwrap_or(false)
+    }
+}
+
+impl AsRef<SecretString> for TwoFACodeHash {
+    fn as_ref(&self) -> &SecretString {
+        &self.0
+    }
+}
+
+fn two_fa_code_mac(login_attempt_id: &LoginAttemptId, code: &TwoFACode) -> Result<Hmac<Sha256>> {
+    let secret = TWO_FA_CODE_SECRET
+        .as_ref()
+        .ok_or_else(|| eyre!("TWO_FA_CODE_SECRET is not set"))?;
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.expose_secret().as_bytes()).map_err(|_| eyre!("Invalid 2FA code secret"))?;
+    mac.update(login_attempt_id.0.expose_secret().as_bytes());
+    mac.update(b":");
+    mac.update(code.0.expose_secret().as_bytes());
+    Ok(mac)
+}
+
+fn constant_time_eq(a: &SecretString, b: &SecretString) -> bool {
+    a.expose_secret().as_bytes().ct_eq(b.expose_secret().as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_verifies_matching_code() {
+        let login_attempt_id = LoginAttemptId::default();
+        let code = TwoFACode::parse("123456".to_owned()).unwrap();
+
+        let hash = code.hash(&login_attempt_id).unwrap();
+
+        assert!(hash.verify(&login_attempt_id, &code));
+    }
+
+    #[test]
+    fn hash_rejects_other_code_or_login_attempt() {
+        let login_attempt_id = LoginAttemptId::default();
+        let code = TwoFACode::parse("123456".to_owned()).unwrap();
+
+        let hash = code.hash(&login_attempt_id).unwrap();
+
+        assert!(!hash.verify(&login_attempt_id, &TwoFACode::parse("654321".to_owned()).unwrap()));
+        assert!(!hash.verify(&LoginAttemptId::default(), &code));
+    }
+
+    #[test]
+    fn hash_does_not_contain_the_code() {
+        let login_attempt_id = LoginAttemptId::default();
+        let code = TwoFACode::parse("123456".to_owned()).unwrap();
+
+        let hash = code.hash(&login_attempt_id).unwrap();
+
+        assert!(!hash.as_ref().expose_secret().contains("123456"));
+        assert!(TwoFACodeHash::parse(hash.as_ref().expose_secret().to_owned()).is_ok());
+    }
+}
@@ -0,0 +1,109 @@
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use validator::ValidationError;
+
+// Identifies the device a user logs in from by its user agent and the network it connects from. Only the network
+// prefix is used, so that a device moving around its network, or getting a new address from its provider, stays the
+// same device.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeviceFingerprint(String);
+
+impl DeviceFingerprint {
+    pub fn new(user_agent: Option<&str>, ip: Option<IpAddr>) -> DeviceFingerprint {
+        let network = ip.map(network_prefix).unwrap_or_default();
+        let digest = Sha256::digest(format!("{}\n{}", user_agent.unwrap_or_default().trim(), network));
+
+        DeviceFingerprint(hex::encode(digest))
+    }
+
+    // A fingerprint read back from a store
+    pub fn parse(value: String) -> Result<DeviceFingerprint, ValidationError> {
+        match value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit()) {
+            true => Ok(DeviceFingerprint(value)),
+            false => Err(ValidationError::new("Invalid device fingerprint.")),
+        }
+    }
+}
+
+impl AsRef<str> for DeviceFingerprint {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// The /24 of IPv4 addresses and the /48 of IPv6 ones, usually what a single site is handed
+fn network_prefix(ip: IpAddr) -> String {
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            format!("{}.{}.{}.0/24", a, b, c)
+        }
+        IpAddr::V6(ip) => {
+            let prefix: Vec<String> = ip.segments().iter().take(3).map(|segment| format!("{:x}", segment)).collect();
+            format!("{}::/48", prefix.join(":"))
+        }
+    }
+}
+
+// How a login device compares with the ones the user logged in from before
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceSighting {
+    // The user had no devices recorded yet, such as on their first login
+    First,
+    Known,
+    New,
+}
+
+// A device the user logged in from
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceRecord {
+    pub fingerprint: DeviceFingerprint,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIREFOX: &str = "Mozilla/5.0 (X11; Linux x86_64) Firefox/128.0";
+
+    #[test]
+    fn addresses_are_reduced_to_their_network() {
+        assert_eq!(network_prefix("203.0.113.7".parse().unwrap()), "203.0.113.0/24");
+        assert_eq!(network_prefix("::ffff:203.0.113.7".parse().unwrap()), "203.0.113.0/24");
+        assert_eq!(network_prefix("2001:db8:1:2::1".parse().unwrap()), "2001:db8:1::/48");
+    }
+
+    #[test]
+    fn same_browser_on_same_network_is_same_device() {
+        assert_eq!(
+            DeviceFingerprint::new(Some(FIREFOX), Some("203.0.113.7".parse().unwrap())),
+            DeviceFingerprint::new(Some(FIREFOX), Some("203.0.113.200".parse().unwrap()))
+        );
+    }
+
+    #[test]
+    fn other_browser_or_network_is_other_device() {
+        let device = DeviceFingerprint::new(Some(FIREFOX), Some("203.0.113.7".parse().unwrap()));
+
+        assert_ne!(
+            device,
+            DeviceFingerprint::new(Some("curl/8.5.0"), Some("203.0.113.7".parse().unwrap()))
+        );
+        assert_ne!(
+            device,
+            DeviceFingerprint::new(Some(FIREFOX), Some("198.51.100.7".parse().unwrap()))
+        );
+        assert_ne!(device, DeviceFingerprint::new(Some(FIREFOX), None));
+    }
+
+    #[test]
+    fn fingerprints_parse_back() {
+        let device = DeviceFingerprint::new(Some(FIREFOX), None);
+
+        assert_eq!(DeviceFingerprint::parse(device.as_ref().to_owned()).unwrap(), device);
+        assert!(DeviceFingerprint::parse("not a fingerprint".to_owned()).is_err());
+    }
+}
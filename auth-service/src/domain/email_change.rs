@@ -0,0 +1,76 @@
+use crate::domain::email::Email;
+
+// A requested change of address, applied once both the old and the new address confirmed it
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmailChange {
+    pub old_email: Email,
+    pub new_email: Email,
+    pub old_email_token_id: String,
+    pub new_email_token_id: String,
+    pub old_email_confirmed: bool,
+    pub new_email_confirmed: bool,
+}
+
+impl EmailChange {
+    pub fn new(old_email: Email, new_email: Email, old_email_token_id: String, new_email_token_id: String) -> Self {
+        Self {
+            old_email,
+            new_email,
+            old_email_token_id,
+            new_email_token_id,
+            old_email_confirmed: false,
+            new_email_confirmed: false,
+        }
+    }
+
+    // Marks the address the token was sent to as confirmed. Returns false for an unknown token.
+    pub fn confirm(&mut self, token_id: &str) -> bool {
+        if self.old_email_token_id == token_id {
+            self.old_email_confirmed = true;
+        } else if self.new_email_token_id == token_id {
+            self.new_email_confirmed = true;
+        } else {
+            return false;
+        }
+
+        true
+    }
+
+    pub fn is_confirmed(&self) -> bool {
+        self.old_email_confirmed && self.new_email_confirmed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email_change() -> EmailChange {
+        EmailChange::new(
+            Email::parse("old@example.com".into()).unwrap(),
+            Email::parse("new@example.com".into()).unwrap(),
+            "old-token".to_owned(),
+            "new-token".to_owned(),
+        )
+    }
+
+    #[test]
+    fn test_confirmed_once_both_addresses_confirm() {
+        let mut change = email_change();
+
+        assert!(change.confirm("old-token"));
+        assert!(!change.is_confirmed());
+
+        assert!(change.confirm("new-token"));
+        assert!(change.is_confirmed());
+    }
+
+    #[test]
+    fn test_unknown_token_confirms_nothing() {
+        let mut change = email_change();
+
+        assert!(!change.confirm("other-token"));
+        assert!(!change.old_email_confirmed);
+        assert!(!change.new_email_confirmed);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::domain::password_policy::PasswordViolation;
+use axum::http::header::RETRY_AFTER;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum::Json;
@@ -6,6 +8,9 @@ use serde::{Deserialize, Serialize};
 use std::error::Error;
 use thiserror::Error;
 
+// Hashing a password takes a fraction of a second, so the queue is likely to have drained by then
+const SERVICE_OVERLOADED_RETRY_AFTER_SECONDS: u64 = 1;
+
 #[derive(Debug, Error)]
 pub enum AuthAPIError {
     #[error("User already exists")]
@@ -18,6 +23,54 @@ pub enum AuthAPIError {
     MissingToken,
     #[error("Invalid token")]
     InvalidToken,
+    #[error("Device not found")]
+    DeviceNotFound,
+    #[error("User not found")]
+    UserNotFound,
+    #[error("Role not found")]
+    RoleNotFound,
+    #[error("Invalid role")]
+    InvalidRole,
+    #[error("Email not verified")]
+    EmailNotVerified,
+    #[error("Invalid profile")]
+    InvalidProfile,
+    #[error("Account locked")]
+    AccountLocked,
+    #[error("Account disabled")]
+    AccountDisabled,
+    #[error("Account suspended")]
+    AccountSuspended,
+    #[error("Account pending")]
+    AccountPending,
+    #[error("Invalid account status")]
+    InvalidAccountStatus,
+    #[error("Forbidden")]
+    Forbidden,
+    #[error("Organization not found")]
+    OrganizationNotFound,
+    #[error("Invalid organization")]
+    InvalidOrganization,
+    #[error("No active organization")]
+    NoActiveOrganization,
+    #[error("Invitation required")]
+    InvitationRequired,
+    #[error("Invitation not found")]
+    InvitationNotFound,
+    #[error("Invalid audit event type")]
+    InvalidAuditEventType,
+    // Every rule of the password policy the password broke
+    #[error("Weak password")]
+    WeakPassword(Vec<PasswordViolation>),
+    #[error("Password was used recently")]
+    PasswordReused,
+    #[error("Too many users")]
+    TooManyUsers,
+    // Too many passwords are waiting to be hashed, the request can be retried shortly
+    #[error("Service overloaded")]
+    ServiceOverloaded,
+    #[error("Too many requests")]
+    TooManyRequests { retry_after_seconds: u64 },
     #[error("Unexpected error")]
     UnexpectedError(#[source] Report),
 }
@@ -25,6 +78,15 @@ pub enum AuthAPIError {
 #[derive(Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
+    // What exactly was wrong, for errors with several possible causes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<ErrorDetail>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ErrorDetail {
+    pub rule: String,
+    pub message: String,
 }
 
 impl IntoResponse for AuthAPIError {
@@ -36,13 +98,59 @@ impl IntoResponse for AuthAPIError {
             AuthAPIError::InvalidCredentials => StatusCode::BAD_REQUEST,
             AuthAPIError::MissingToken => StatusCode::BAD_REQUEST,
             AuthAPIError::InvalidToken => StatusCode::UNAUTHORIZED,
+            AuthAPIError::DeviceNotFound => StatusCode::NOT_FOUND,
+            AuthAPIError::UserNotFound => StatusCode::NOT_FOUND,
+            AuthAPIError::RoleNotFound => StatusCode::NOT_FOUND,
+            AuthAPIError::InvalidRole => StatusCode::BAD_REQUEST,
+            AuthAPIError::EmailNotVerified => StatusCode::FORBIDDEN,
+            AuthAPIError::InvalidProfile => StatusCode::BAD_REQUEST,
+            AuthAPIError::AccountLocked => StatusCode::LOCKED,
+            AuthAPIError::AccountDisabled => StatusCode::FORBIDDEN,
+            AuthAPIError::AccountSuspended => StatusCode::FORBIDDEN,
+            AuthAPIError::AccountPending => StatusCode::FORBIDDEN,
+            AuthAPIError::InvalidAccountStatus => StatusCode::BAD_REQUEST,
+            AuthAPIError::Forbidden => StatusCode::FORBIDDEN,
+            AuthAPIError::OrganizationNotFound => StatusCode::NOT_FOUND,
+            AuthAPIError::InvalidOrganization => StatusCode::BAD_REQUEST,
+            AuthAPIError::NoActiveOrganization => StatusCode::BAD_REQUEST,
+            AuthAPIError::InvitationRequired => StatusCode::FORBIDDEN,
+            AuthAPIError::InvitationNotFound => StatusCode::NOT_FOUND,
+            AuthAPIError::InvalidAuditEventType => StatusCode::BAD_REQUEST,
+            AuthAPIError::WeakPassword(_) => StatusCode::BAD_REQUEST,
+            AuthAPIError::PasswordReused => StatusCode::BAD_REQUEST,
+            AuthAPIError::TooManyUsers => StatusCode::PAYLOAD_TOO_LARGE,
+            AuthAPIError::ServiceOverloaded => StatusCode::SERVICE_UNAVAILABLE,
+            AuthAPIError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
             AuthAPIError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AuthAPIError::IncorrectCredentials => StatusCode::UNAUTHORIZED,
         };
 
-        let body = Json(ErrorResponse { error: self.to_string() });
+        let details = match &self {
+            AuthAPIError::WeakPassword(violations) => violations
+                .iter()
+                .map(|violation| ErrorDetail {
+                    rule: violation.rule().to_owned(),
+                    message: violation.to_string(),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let body = Json(ErrorResponse {
+            error: self.to_string(),
+            details,
+        });
+
+        let retry_after_seconds = match &self {
+            AuthAPIError::ServiceOverloaded => Some(SERVICE_OVERLOADED_RETRY_AFTER_SECONDS),
+            AuthAPIError::TooManyRequests { retry_after_seconds } => Some(*retry_after_seconds),
+            _ => None,
+        };
 
-        (status, body).into_response()
+        match retry_after_seconds {
+            Some(seconds) => (status, [(RETRY_AFTER, seconds.to_string())], body).into_response(),
+            None => (status, body).into_response(),
+        }
     }
 }
 
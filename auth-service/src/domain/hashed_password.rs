@@ -1,9 +1,147 @@
 use argon2::{
     password_hash::{rand_core::OsRng, SaltString},
-    Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version,
+    Algorithm, Argon2, KeyId, Params, ParamsBuilder, PasswordHash, PasswordHasher, PasswordVerifier, Version,
 };
 use color_eyre::eyre::{eyre, Result};
+use pbkdf2::Pbkdf2;
+use scrypt::Scrypt;
 use secrecy::{ExposeSecret, SecretString};
+
+use crate::utils::constants::{
+    ARGON2_MEMORY_COST_KIB, ARGON2_PARALLELISM, ARGON2_TIME_COST, MIN_PASSWORD_LENGTH, PASSWORD_PEPPER, PASSWORD_PEPPER_ID,
+    PASSWORD_RETIRED_PEPPERS,
+};
+use crate::utils::hashing_executor::{HashingOverloaded, HASHING_EXECUTOR};
+use tokio::sync::OnceCell;
+
+// Stands in for the hash of users that don't exist or have no password, made with the current parameters and pepper
+static DUMMY_PASSWORD_HASH: OnceCell<HashedPassword> = OnceCell::const_new();
+
+// Argon2id cost of new hashes, hashes made with other parameters are upgraded when their user logs in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashingParams {
+    pub memory_cost_kib: u32,
+    pub time_cost: u32,
+    pub parallelism: u32,
+}
+
+impl Default for HashingParams {
+    fn default() -> Self {
+        HashingParams {
+            memory_cost_kib: *ARGON2_MEMORY_COST_KIB,
+            time_cost: *ARGON2_TIME_COST,
+            parallelism: *ARGON2_PARALLELISM,
+        }
+    }
+}
+
+impl HashingParams {
+    fn argon2<'a>(&self, pepper: Option<&'a Pepper>) -> Result<Argon2<'a>> {
+        let mut builder = ParamsBuilder::new();
+        builder
+            .m_cost(self.memory_cost_kib)
+            .t_cost(self.time_cost)
+            .p_cost(self.parallelism);
+
+        let Some(pepper) = pepper else {
+            return Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, builder.build()?));
+        };
+
+        builder.keyid(KeyId::new(pepper.id.as_bytes())?);
+
+        Ok(Argon2::new_with_secret(
+            pepper.secret.expose_secret().as_bytes(),
+            Algorithm::Argon2id,
+            Version::V0x13,
+            builder.build()?,
+        )?)
+    }
+
+    // Whether the hash was made by Argon2id with these parameters and the pepper
+    fn produced(&self, hash: &PasswordHash, pepper: Option<&Pepper>) -> bool {
+        if hash.algorithm != Algorithm::Argon2id.ident() || hash.version != Some(Version::V0x13.into()) {
+            return false;
+        }
+
+        let pepper_id = pepper.map(|pepper| pepper.id.as_bytes()).unwrap_or_default();
+
+        Params::try_from(hash).is_ok_and(|params| {
+            params.m_cost() == self.memory_cost_kib
+                && params.t_cost() == self.time_cost
+                && params.p_cost() == self.parallelism
+                && params.keyid() == pepper_id
+        })
+    }
+}
+
+// Secret mixed into the hashes and kept out of the database, so that a dump alone isn't enough to brute-force them.
+// Hashes record the id of their pepper as the Argon2 `keyid`.
+#[derive(Debug, Clone)]
+pub struct Pepper {
+    pub id: String,
+    pub secret: SecretString,
+}
+
+// The pepper of new hashes, and the retired ones still needed to verify hashes made before a rotation
+#[derive(Debug, Clone)]
+pub struct Peppers {
+    pub current: Option<Pepper>,
+    pub retired: Vec<Pepper>,
+}
+
+impl Default for Peppers {
+    fn default() -> Self {
+        Peppers {
+            current: PASSWORD_PEPPER.as_ref().map(|secret| Pepper {
+                id: PASSWORD_PEPPER_ID.clone(),
+                secret: secret.clone(),
+            }),
+            retired: PASSWORD_RETIRED_PEPPERS
+                .iter()
+                .map(|(id, secret)| Pepper {
+                    id: id.clone(),
+                    secret: secret.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Peppers {
+    fn find(&self, id: &[u8]) -> Option<&Pepper> {
+        self.current
+            .iter()
+            .chain(&self.retired)
+            .find(|pepper| pepper.id.as_bytes() == id)
+    }
+}
+
+// Hashes that can be verified. New hashes are always Argon2id, the others come from users imported from older systems
+// and are upgraded when their user logs in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HashAlgorithm {
+    Argon2,
+    Bcrypt,
+    Scrypt,
+    Pbkdf2Sha256,
+}
+
+impl HashAlgorithm {
+    fn of(hash: &str) -> Option<HashAlgorithm> {
+        // bcrypt uses its own format rather than a PHC string
+        if ["$2a$", "$2b$", "$2y$"].iter().any(|prefix| hash.starts_with(prefix)) {
+            return Some(HashAlgorithm::Bcrypt);
+        }
+
+        match PasswordHash::new(hash).ok()?.algorithm.as_str() {
+            "argon2id" | "argon2i" | "argon2d" => Some(HashAlgorithm::Argon2),
+            "scrypt" => Some(HashAlgorithm::Scrypt),
+            "pbkdf2-sha256" => Some(HashAlgorithm::Pbkdf2Sha256),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct HashedPassword(pub(crate) SecretString);
 
@@ -15,59 +153,143 @@ impl PartialEq for HashedPassword {
 
 impl HashedPassword {
     pub async fn parse(s: SecretString) -> Result<Self> {
-        if s.expose_secret().len() < 8 {
+        Self::parse_with_params(s, HashingParams::default()).await
+    }
+
+    pub async fn parse_with_params(s: SecretString, params: HashingParams) -> Result<Self> {
+        Self::parse_with_peppers(s, params, Peppers::default()).await
+    }
+
+    async fn parse_with_peppers(s: SecretString, params: HashingParams, peppers: Peppers) -> Result<Self> {
+        if s.expose_secret().chars().count() < MIN_PASSWORD_LENGTH {
             return Err(eyre!("Password is to short"));
         }
 
-        match compute_password_hash(s).await {
+        match compute_password_hash(s, params, peppers.current).await {
             Ok(hashed_password) => Ok(Self(hashed_password)),
             Err(e) => Err(e),
         }
     }
 
     pub fn parse_password_hash(hash: SecretString) -> Result<HashedPassword, String> {
-        if let Ok(hashed_string) = PasswordHash::new(hash.expose_secret().as_ref()) {
-            Ok(Self(SecretString::from(hashed_string.to_string())))
-        } else {
-            Err(String::from("twoja stara"))
+        match HashAlgorithm::of(hash.expose_secret()) {
+            Some(HashAlgorithm::Bcrypt) => Ok(Self(hash)),
+            Some(_) => match PasswordHash::new(hash.expose_secret()) {
+                Ok(hashed_string) => Ok(Self(SecretString::from(hashed_string.to_string()))),
+                Err(e) => Err(e.to_string()),
+            },
+            None => Err(String::from("Unsupported password hash")),
         }
     }
 
-    #[tracing::instrument(name = "Verify raw password", skip_all)]
     pub async fn verify_raw_password(&self, password_candidate: &str) -> Result<()> {
+        self.verify_with_peppers(password_candidate, Peppers::default()).await
+    }
+
+    #[tracing::instrument(name = "Verify raw password", skip_all)]
+    async fn verify_with_peppers(&self, password_candidate: &str, peppers: Peppers) -> Result<()> {
         let current_span = tracing::Span::current();
 
         let password_hash = self.as_ref().to_owned();
         let password_candidate = password_candidate.to_owned();
 
-        tokio::task::spawn_blocking(move || {
-            current_span.in_scope(|| {
-                let expected_password_hash: PasswordHash<'_> = PasswordHash::new(&password_hash.expose_secret())?;
-                return Argon2::default()
-                    .verify_password(password_candidate.as_bytes(), &expected_password_hash)
-                    .map_err(|e| e.into());
+        HASHING_EXECUTOR
+            .run(move || {
+                current_span.in_scope(|| {
+                    let password_hash = password_hash.expose_secret();
+
+                    match HashAlgorithm::of(password_hash) {
+                        Some(HashAlgorithm::Argon2) => verify_argon2(password_hash, &password_candidate, &peppers),
+                        Some(HashAlgorithm::Bcrypt) => match bcrypt::verify(password_candidate.as_bytes(), password_hash)? {
+                            true => Ok(()),
+                            false => Err(eyre!("Invalid password")),
+                        },
+                        Some(HashAlgorithm::Scrypt) => {
+                            Ok(Scrypt.verify_password(password_candidate.as_bytes(), &PasswordHash::new(password_hash)?)?)
+                        }
+                        Some(HashAlgorithm::Pbkdf2Sha256) => {
+                            Ok(Pbkdf2.verify_password(password_candidate.as_bytes(), &PasswordHash::new(password_hash)?)?)
+                        }
+                        None => Err(eyre!("Unsupported password hash")),
+                    }
+                })
             })
-        })
-        .await?
+            .await
+    }
+
+    // Verifies the candidate against a hash that no password matches, so that a login for an unknown user takes as long
+    // as one with a wrong password. Only fails when hashing is overloaded, which must be reported the same way too.
+    // Known limitation: the dummy is an Argon2id hash, so a wrong password for a user still on an imported bcrypt,
+    // scrypt or PBKDF2 hash can take a different time. The gap closes as these users log in and get an Argon2id hash.
+    pub async fn verify_dummy_password(password_candidate: &str) -> Result<()> {
+        let dummy = DUMMY_PASSWORD_HASH
+            .get_or_try_init(|| HashedPassword::parse(SecretString::from(uuid::Uuid::new_v4().to_string())))
+            .await?;
+
+        match dummy.verify_raw_password(password_candidate).await {
+            Err(e) if HashingOverloaded::caused(&e) => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    // Whether the hash should be replaced by one made with the current parameters and pepper
+    pub fn needs_rehash(&self, params: &HashingParams) -> bool {
+        self.needs_rehash_with_peppers(params, &Peppers::default())
+    }
+
+    fn needs_rehash_with_peppers(&self, params: &HashingParams, peppers: &Peppers) -> bool {
+        match HashAlgorithm::of(self.0.expose_secret()) {
+            Some(HashAlgorithm::Argon2) => {
+                PasswordHash::new(self.0.expose_secret()).is_ok_and(|hash| !params.produced(&hash, peppers.current.as_ref()))
+            }
+            Some(_) => true,
+            None => false,
+        }
     }
 }
 
+fn verify_argon2(password_hash: &str, password_candidate: &str, peppers: &Peppers) -> Result<()> {
+    let expected_password_hash = PasswordHash::new(password_hash)?;
+
+    // Hashes made before peppering was turned on have no key id
+    let pepper_id = Params::try_from(&expected_password_hash)?.keyid().to_vec();
+    let argon2 = match pepper_id.is_empty() {
+        true => Argon2::default(),
+        false => {
+            let pepper = peppers
+                .find(&pepper_id)
+                .ok_or_else(|| eyre!("Password hash was made with an unknown pepper"))?;
+
+            Argon2::new_with_secret(
+                pepper.secret.expose_secret().as_bytes(),
+                Algorithm::default(),
+                Version::default(),
+                Params::default(),
+            )?
+        }
+    };
+
+    Ok(argon2.verify_password(password_candidate.as_bytes(), &expected_password_hash)?)
+}
+
 #[tracing::instrument(name = "Computing password hash", skip_all)]
-async fn compute_password_hash(password: SecretString) -> Result<SecretString> {
+async fn compute_password_hash(password: SecretString, params: HashingParams, pepper: Option<Pepper>) -> Result<SecretString> {
     let current_span = tracing::Span::current();
 
-    tokio::task::spawn_blocking(move || {
-        current_span.in_scope(|| {
-            let salt: SaltString = SaltString::generate(&mut OsRng);
-            let password_hash = Argon2::new(Algorithm::Argon2id, Version::V0x13, Params::new(15000, 2, 1, None)?)
-                .hash_password(password.expose_secret().as_bytes(), &salt)?
-                .to_string();
+    HASHING_EXECUTOR
+        .run(move || {
+            current_span.in_scope(|| {
+                let salt: SaltString = SaltString::generate(&mut OsRng);
+                let password_hash = params
+                    .argon2(pepper.as_ref())?
+                    .hash_password(password.expose_secret().as_bytes(), &salt)?
+                    .to_string();
 
-            let bs = password_hash.into_boxed_str();
-            Ok(SecretString::new(bs))
+                let bs = password_hash.into_boxed_str();
+                Ok(SecretString::new(bs))
+            })
         })
-    })
-    .await?
+        .await
 }
 
 impl AsRef<SecretString> for HashedPassword {
@@ -78,7 +300,7 @@ impl AsRef<SecretString> for HashedPassword {
 
 #[cfg(test)]
 mod tests {
-    use super::HashedPassword;
+    use super::{HashedPassword, HashingParams, Pepper, Peppers};
 
     use argon2::{
         // new
@@ -145,6 +367,179 @@ mod tests {
         assert_eq!(result.unwrap(), ())
     }
 
+    const PARAMS: HashingParams = HashingParams {
+        memory_cost_kib: 8192,
+        time_cost: 1,
+        parallelism: 1,
+    };
+
+    #[tokio::test]
+    async fn hash_made_with_current_params_is_kept() {
+        let hash_password = HashedPassword::parse_with_params("TestPassword123".into(), PARAMS)
+            .await
+            .unwrap();
+
+        assert!(hash_password.0.expose_secret().contains("m=8192,t=1,p=1"));
+        assert!(!hash_password.needs_rehash(&PARAMS));
+    }
+
+    #[tokio::test]
+    async fn hash_made_with_other_params_needs_rehash() {
+        let hash_password = HashedPassword::parse_with_params("TestPassword123".into(), PARAMS)
+            .await
+            .unwrap();
+
+        for params in [
+            HashingParams {
+                memory_cost_kib: 16384,
+                ..PARAMS
+            },
+            HashingParams { time_cost: 2, ..PARAMS },
+            HashingParams {
+                parallelism: 2,
+                ..PARAMS
+            },
+        ] {
+            assert!(hash_password.needs_rehash(&params), "{:?}", params);
+        }
+    }
+
+    #[test]
+    fn hash_made_by_other_algorithm_needs_rehash() {
+        let salt = SaltString::generate(&mut OsRng);
+        let argon2 = Argon2::new(
+            Algorithm::Argon2i,
+            Version::V0x13,
+            Params::new(PARAMS.memory_cost_kib, PARAMS.time_cost, PARAMS.parallelism, None).unwrap(),
+        );
+        let hash_string = argon2.hash_password(b"TestPassword123", &salt).unwrap().to_string();
+
+        let hash_password = HashedPassword::parse_password_hash(SecretString::from(hash_string)).unwrap();
+
+        assert!(hash_password.needs_rehash(&PARAMS));
+    }
+
+    fn pepper(id: &str, secret: &str) -> Pepper {
+        Pepper {
+            id: id.to_owned(),
+            secret: secret.into(),
+        }
+    }
+
+    fn peppers(current: Option<Pepper>, retired: Vec<Pepper>) -> Peppers {
+        Peppers { current, retired }
+    }
+
+    #[tokio::test]
+    async fn peppered_hash_needs_the_pepper() {
+        let current = peppers(Some(pepper("v1", "first-pepper")), vec![]);
+        let hash_password = HashedPassword::parse_with_peppers("TestPassword123".into(), PARAMS, current.clone())
+            .await
+            .unwrap();
+
+        // `djE` is `v1` in B64
+        assert!(hash_password.0.expose_secret().contains("keyid=djE"));
+        assert!(hash_password.verify_with_peppers("TestPassword123", current).await.is_ok());
+        assert!(hash_password
+            .verify_with_peppers("TestPassword123", peppers(None, vec![]))
+            .await
+            .is_err());
+        assert!(hash_password
+            .verify_with_peppers("TestPassword123", peppers(Some(pepper("v1", "other-pepper")), vec![]))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn hash_made_with_retired_pepper_verifies_and_needs_rehash() {
+        let old = peppers(Some(pepper("v1", "first-pepper")), vec![]);
+        let rotated = peppers(Some(pepper("v2", "second-pepper")), vec![pepper("v1", "first-pepper")]);
+
+        let hash_password = HashedPassword::parse_with_peppers("TestPassword123".into(), PARAMS, old.clone())
+            .await
+            .unwrap();
+
+        assert!(!hash_password.needs_rehash_with_peppers(&PARAMS, &old));
+        assert!(hash_password.needs_rehash_with_peppers(&PARAMS, &rotated));
+        assert!(hash_password.verify_with_peppers("TestPassword123", rotated).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn hash_made_without_pepper_verifies_and_needs_rehash() {
+        let current = peppers(Some(pepper("v1", "first-pepper")), vec![]);
+        let hash_password = HashedPassword::parse_with_peppers("TestPassword123".into(), PARAMS, peppers(None, vec![]))
+            .await
+            .unwrap();
+
+        assert!(!hash_password.0.expose_secret().contains("keyid"));
+        assert!(hash_password.needs_rehash_with_peppers(&PARAMS, &current));
+        assert!(hash_password.verify_with_peppers("TestPassword123", current).await.is_ok());
+    }
+
+    // Hashes of the password as an older system would have stored them, with cheap parameters
+    fn legacy_hashes(password: &str) -> Vec<String> {
+        let salt = SaltString::generate(&mut OsRng);
+
+        let scrypt_hash = scrypt::Scrypt
+            .hash_password_customized(
+                password.as_bytes(),
+                None,
+                None,
+                scrypt::Params::new(10, 8, 1, 32).unwrap(),
+                &salt,
+            )
+            .unwrap();
+
+        let pbkdf2_hash = pbkdf2::Pbkdf2
+            .hash_password_customized(
+                password.as_bytes(),
+                Some(pbkdf2::Algorithm::Pbkdf2Sha256.ident()),
+                None,
+                pbkdf2::Params {
+                    rounds: 1000,
+                    output_length: 32,
+                },
+                &salt,
+            )
+            .unwrap();
+
+        vec![
+            bcrypt::hash(password, 4).unwrap(),
+            scrypt_hash.to_string(),
+            pbkdf2_hash.to_string(),
+        ]
+    }
+
+    #[tokio::test]
+    async fn legacy_hashes_verify_and_need_rehash() {
+        for hash in legacy_hashes("TestPassword123") {
+            let hash_password = HashedPassword::parse_password_hash(SecretString::from(hash.clone())).unwrap();
+
+            assert!(hash_password.verify_raw_password("TestPassword123").await.is_ok(), "{}", hash);
+            assert!(
+                hash_password.verify_raw_password("WrongPassword123").await.is_err(),
+                "{}",
+                hash
+            );
+            assert!(hash_password.needs_rehash(&PARAMS), "{}", hash);
+        }
+    }
+
+    #[test]
+    fn unsupported_hashes_are_rejected() {
+        for hash in [
+            "TestPassword123",
+            "$1$saltsalt$qjXMvbEw8oaL.CzflDugX/",
+            "$pbkdf2-sha512$i=1000,l=32$c2FsdHNhbHQ$YmVtR0kxbGRCTzY4TEpvQ3JkT2lsSlRDbWNaVnBUSHo",
+        ] {
+            assert!(
+                HashedPassword::parse_password_hash(SecretString::from(hash)).is_err(),
+                "{}",
+                hash
+            );
+        }
+    }
+
     #[derive(Debug, Clone)]
     struct ValidPasswordFixture(pub String);
 
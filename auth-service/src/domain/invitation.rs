@@ -0,0 +1,23 @@
+use crate::domain::email::Email;
+use crate::domain::organization::OrganizationId;
+use crate::domain::rbac::RoleName;
+use chrono::{DateTime, Utc};
+
+// An invitation to sign up with `email`, whose id is the one of the token sent in the invite link.
+// The role is given in the organization when there is one, otherwise it is assigned to the user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invitation {
+    pub id: String,
+    pub email: Email,
+    pub role: Option<RoleName>,
+    pub organization_id: Option<OrganizationId>,
+    pub invited_by: Email,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Invitation {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
@@ -0,0 +1,131 @@
+use crate::utils::constants::{
+    LOGIN_FAILURE_WINDOW_SECONDS, LOGIN_LOCKOUT_BASE_SECONDS, LOGIN_LOCKOUT_MAX_SECONDS, LOGIN_LOCKOUT_THRESHOLD,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+// How long an account is locked for after repeated failed logins
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LockoutPolicy {
+    // Failures in a row that lock the account for the first time
+    pub threshold: u32,
+    pub base_lockout_seconds: i64,
+    pub max_lockout_seconds: i64,
+    // Failures older than this are forgotten
+    pub failure_window_seconds: i64,
+}
+
+impl Default for LockoutPolicy {
+    fn default() -> Self {
+        LockoutPolicy {
+            threshold: *LOGIN_LOCKOUT_THRESHOLD,
+            base_lockout_seconds: *LOGIN_LOCKOUT_BASE_SECONDS,
+            max_lockout_seconds: *LOGIN_LOCKOUT_MAX_SECONDS,
+            failure_window_seconds: LOGIN_FAILURE_WINDOW_SECONDS,
+        }
+    }
+}
+
+impl LockoutPolicy {
+    // The lockout doubles with every failure past the threshold, up to the maximum
+    pub fn lockout_duration(&self, failures: u32) -> Option<Duration> {
+        let exponent = failures.checked_sub(self.threshold)?;
+
+        let seconds = 2_i64
+            .checked_pow(exponent)
+            .and_then(|factor| factor.checked_mul(self.base_lockout_seconds))
+            .map_or(self.max_lockout_seconds, |seconds| seconds.min(self.max_lockout_seconds));
+
+        Duration::try_seconds(seconds)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoginFailures {
+    pub count: u32,
+    pub last_failed_at: Option<DateTime<Utc>>,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl LoginFailures {
+    pub fn is_locked(&self, now: DateTime<Utc>) -> bool {
+        self.locked_until.is_some_and(|locked_until| locked_until > now)
+    }
+
+    // Counts a failed login and returns the end of the lockout it started, if any
+    pub fn record_failure(&mut self, now: DateTime<Utc>, policy: &LockoutPolicy) -> Option<DateTime<Utc>> {
+        let is_stale = self.last_failed_at.is_some_and(|last_failed_at| {
+            Duration::try_seconds(policy.failure_window_seconds)
+                .and_then(|window| last_failed_at.checked_add_signed(window))
+                .is_some_and(|forgotten_at| forgotten_at <= now)
+        });
+
+        if is_stale {
+            *self = LoginFailures::default();
+        }
+
+        self.count = self.count.saturating_add(1);
+        self.last_failed_at = Some(now);
+        self.locked_until = policy
+            .lockout_duration(self.count)
+            .and_then(|duration| now.checked_add_signed(duration));
+
+        self.locked_until
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> LockoutPolicy {
+        LockoutPolicy {
+            threshold: 3,
+            base_lockout_seconds: 60,
+            max_lockout_seconds: 600,
+            failure_window_seconds: 3600,
+        }
+    }
+
+    #[test]
+    fn lockout_grows_exponentially_up_to_the_maximum() {
+        let policy = policy();
+
+        assert_eq!(policy.lockout_duration(2), None);
+        assert_eq!(policy.lockout_duration(3), Duration::try_seconds(60));
+        assert_eq!(policy.lockout_duration(4), Duration::try_seconds(120));
+        assert_eq!(policy.lockout_duration(5), Duration::try_seconds(240));
+        assert_eq!(policy.lockout_duration(7), Duration::try_seconds(600));
+        assert_eq!(policy.lockout_duration(u32::MAX), Duration::try_seconds(600));
+    }
+
+    #[test]
+    fn account_is_locked_once_threshold_is_reached() {
+        let policy = policy();
+        let now = Utc::now();
+        let mut failures = LoginFailures::default();
+
+        assert_eq!(failures.record_failure(now, &policy), None);
+        assert_eq!(failures.record_failure(now, &policy), None);
+        assert!(!failures.is_locked(now));
+
+        assert_eq!(failures.record_failure(now, &policy), Some(now + Duration::seconds(60)));
+        assert!(failures.is_locked(now));
+        assert!(!failures.is_locked(now + Duration::seconds(60)));
+    }
+
+    #[test]
+    fn failures_outside_of_window_are_forgotten() {
+        let policy = policy();
+        let now = Utc::now();
+        let mut failures = LoginFailures::default();
+
+        failures.record_failure(now, &policy);
+        failures.record_failure(now, &policy);
+
+        let later = now + Duration::seconds(policy.failure_window_seconds);
+        assert_eq!(failures.record_failure(later, &policy), None);
+        assert_eq!(failures.count, 1);
+    }
+}
@@ -1,6 +1,19 @@
+pub mod account_status;
+pub mod audit;
 pub mod data_stores;
+pub mod device_history;
 pub mod email;
+pub mod email_change;
 pub mod email_client;
 pub mod error;
 pub mod hashed_password;
+pub mod invitation;
+pub mod login_failures;
+pub mod organization;
+pub mod password_policy;
+pub mod password_strength;
+pub mod profile;
+pub mod rate_limit;
+pub mod rbac;
+pub mod trusted_device;
 pub mod user;
@@ -0,0 +1,152 @@
+use crate::domain::email::Email;
+use crate::domain::rbac::RoleName;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::ValidationError;
+
+// Given to whoever creates an organization, so that they can manage its members
+pub const ORGANIZATION_ADMIN_ROLE: &str = "org-admin";
+
+const MAX_ORGANIZATION_NAME_LENGTH: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct OrganizationId(Uuid);
+
+impl OrganizationId {
+    pub fn new() -> Self {
+        OrganizationId(Uuid::new_v4())
+    }
+
+    pub fn parse(value: &str) -> Result<OrganizationId, ValidationError> {
+        Uuid::parse_str(value)
+            .map(OrganizationId)
+            .map_err(|_| ValidationError::new("Invalid organization id."))
+    }
+
+    pub fn as_uuid(&self) -> &Uuid {
+        &self.0
+    }
+}
+
+impl Default for OrganizationId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Uuid> for OrganizationId {
+    fn from(uuid: Uuid) -> Self {
+        OrganizationId(uuid)
+    }
+}
+
+impl std::fmt::Display for OrganizationId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrganizationName(String);
+
+impl OrganizationName {
+    pub fn parse(value: &str) -> Result<OrganizationName, ValidationError> {
+        let value = value.trim();
+
+        if value.is_empty() {
+            return Err(ValidationError::new("Organization name cannot be empty."));
+        }
+
+        if value.chars().count() > MAX_ORGANIZATION_NAME_LENGTH {
+            return Err(ValidationError::new("Organization name is too long."));
+        }
+
+        if value.chars().any(char::is_control) {
+            return Err(ValidationError::new("Organization name cannot contain control characters."));
+        }
+
+        Ok(OrganizationName(value.to_owned()))
+    }
+}
+
+impl AsRef<str> for OrganizationName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Organization {
+    pub id: OrganizationId,
+    pub name: OrganizationName,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Organization {
+    pub fn new(name: OrganizationName) -> Organization {
+        Organization {
+            id: OrganizationId::new(),
+            name,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+// An organization seen from one of its members, with the roles they hold in it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Membership {
+    pub organization: Organization,
+    pub roles: Vec<RoleName>,
+    pub joined_at: DateTime<Utc>,
+}
+
+// A member seen from the organization
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub email: Email,
+    pub roles: Vec<RoleName>,
+    pub joined_at: DateTime<Utc>,
+}
+
+// Roles offered to a user in an organization, held until they accept them. Its id is the one of the token sent
+// in the invite link.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberInvitation {
+    pub id: String,
+    pub organization_id: OrganizationId,
+    pub email: Email,
+    pub roles: Vec<RoleName>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl MemberInvitation {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn organization_id_round_trips() {
+        let id = OrganizationId::new();
+
+        assert_eq!(OrganizationId::parse(&id.to_string()).unwrap(), id);
+        assert!(OrganizationId::parse("not-an-id").is_err());
+    }
+
+    #[test]
+    fn organization_name_is_trimmed() {
+        assert_eq!(OrganizationName::parse("  Acme Inc. ").unwrap().as_ref(), "Acme Inc.");
+    }
+
+    #[test]
+    fn invalid_organization_names_are_rejected() {
+        assert!(OrganizationName::parse("  ").is_err());
+        assert!(OrganizationName::parse("Acme\tInc.").is_err());
+        assert!(OrganizationName::parse(&"a".repeat(MAX_ORGANIZATION_NAME_LENGTH + 1)).is_err());
+    }
+}
@@ -0,0 +1,187 @@
+use crate::domain::email::Email;
+use crate::domain::password_strength;
+use crate::utils::constants::{PASSWORD_HISTORY_SIZE, PASSWORD_MAX_LENGTH, PASSWORD_MIN_LENGTH, PASSWORD_MIN_STRENGTH};
+use secrecy::{ExposeSecret, SecretString};
+use thiserror::Error;
+
+// Parts of the email shorter than this are too common to be worth rejecting, e.g. `jo`
+const MIN_EMAIL_PART_LENGTH: usize = 4;
+
+// Rules new passwords have to follow, lengths are counted in characters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    // From 0, which accepts any password, to 4
+    pub min_strength: u8,
+    // How many of their last passwords, the current one included, users cannot pick again
+    pub history_size: usize,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        PasswordPolicy {
+            min_length: *PASSWORD_MIN_LENGTH,
+            max_length: *PASSWORD_MAX_LENGTH,
+            min_strength: *PASSWORD_MIN_STRENGTH,
+            history_size: *PASSWORD_HISTORY_SIZE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum PasswordViolation {
+    #[error("Password must be at least {0} characters long")]
+    TooShort(usize),
+    #[error("Password must be at most {0} characters long")]
+    TooLong(usize),
+    #[error("Password is too easy to guess, it scored {score} out of 4 where at least {min} is required")]
+    TooWeak { score: u8, min: u8 },
+    #[error("Password cannot contain the email address")]
+    ContainsEmail,
+    #[error("Password appeared in a data breach")]
+    Breached,
+}
+
+impl PasswordViolation {
+    // Name of the broken rule, for clients to show their own message
+    pub fn rule(&self) -> &'static str {
+        match self {
+            PasswordViolation::TooShort(_) => "minLength",
+            PasswordViolation::TooLong(_) => "maxLength",
+            PasswordViolation::TooWeak { .. } => "strength",
+            PasswordViolation::ContainsEmail => "containsEmail",
+            PasswordViolation::Breached => "breached",
+        }
+    }
+}
+
+impl PasswordPolicy {
+    // Every rule the password breaks, except the breach and history checks which need their stores
+    pub fn check(&self, password: &SecretString, email: &Email) -> Vec<PasswordViolation> {
+        let password = password.expose_secret();
+        let length = password.chars().count();
+        let mut violations = Vec::new();
+
+        if length < self.min_length {
+            violations.push(PasswordViolation::TooShort(self.min_length));
+        }
+
+        if length > self.max_length {
+            violations.push(PasswordViolation::TooLong(self.max_length));
+            // Too long to be worth scoring
+            return violations;
+        }
+
+        let email = email.as_ref().expose_secret().to_lowercase();
+        let email_parts = email_parts(&email);
+
+        let lowercase_password = password.to_lowercase();
+        if email_parts
+            .iter()
+            .any(|part| part.chars().count() >= MIN_EMAIL_PART_LENGTH && lowercase_password.contains(part))
+        {
+            violations.push(PasswordViolation::ContainsEmail);
+        }
+
+        let score = password_strength::score(password, &email_parts);
+        if score < self.min_strength {
+            violations.push(PasswordViolation::TooWeak {
+                score,
+                min: self.min_strength,
+            });
+        }
+
+        violations
+    }
+}
+
+// The whole address, the part before the `@`, and the words it is made of, e.g. `john` and `doe` in `john.doe@`
+fn email_parts(email: &str) -> Vec<&str> {
+    let local_part = email.split('@').next().unwrap_or_default();
+
+    let mut parts = vec![email, local_part];
+    parts.extend(
+        local_part
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty() && *word != local_part),
+    );
+
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> PasswordPolicy {
+        PasswordPolicy {
+            min_length: 8,
+            max_length: 16,
+            min_strength: 3,
+            history_size: 5,
+        }
+    }
+
+    fn email() -> Email {
+        Email::parse("john.kowalski@example.com".into()).unwrap()
+    }
+
+    #[test]
+    fn strong_password_is_accepted() {
+        assert!(policy().check(&"g7#Kq9!vLm2@".into(), &email()).is_empty());
+    }
+
+    #[test]
+    fn length_is_counted_in_characters() {
+        // 4 characters, but 8 bytes
+        assert_eq!(
+            policy().check(&"żółć".into(), &email()).first(),
+            Some(&PasswordViolation::TooShort(8))
+        );
+        // 16 characters, but 32 bytes
+        assert!(!policy()
+            .check(&"ąęśćżźółĄĘŚĆŻŹÓŁ".into(), &email())
+            .contains(&PasswordViolation::TooLong(16)));
+    }
+
+    #[test]
+    fn too_long_password_is_rejected() {
+        assert_eq!(
+            policy().check(&"g7#Kq9!vLm2@g7#Kq".into(), &email()),
+            vec![PasswordViolation::TooLong(16)]
+        );
+    }
+
+    #[test]
+    fn password_containing_email_is_rejected() {
+        for password in ["JOHN.KOWALSKI!!", "Kowalski#2024!", "#jOhN.KoWaLsKi9"] {
+            assert!(
+                policy()
+                    .check(&password.into(), &email())
+                    .contains(&PasswordViolation::ContainsEmail),
+                "{}",
+                password
+            );
+        }
+    }
+
+    #[test]
+    fn weak_password_is_rejected() {
+        assert_eq!(
+            policy().check(&"password123".into(), &email()),
+            vec![PasswordViolation::TooWeak { score: 1, min: 3 }]
+        );
+    }
+
+    #[test]
+    fn every_broken_rule_is_reported() {
+        let rules: Vec<_> = policy()
+            .check(&"john".into(), &email())
+            .iter()
+            .map(PasswordViolation::rule)
+            .collect();
+
+        assert_eq!(rules, vec!["minLength", "containsEmail", "strength"]);
+    }
+}
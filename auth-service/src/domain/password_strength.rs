@@ -0,0 +1,289 @@
+// A small take on zxcvbn: the password is split into dictionary words, keyboard or alphabet runs and
+// random characters, and the bits needed to guess each part are added up.
+
+// Passwords and words people pick the most, matched case-insensitively and through common substitutions
+const COMMON_WORDS: &[&str] = &[
+    "password",
+    "passwort",
+    "123456",
+    "qwerty",
+    "letmein",
+    "welcome",
+    "admin",
+    "administrator",
+    "iloveyou",
+    "monkey",
+    "dragon",
+    "football",
+    "baseball",
+    "soccer",
+    "hockey",
+    "sunshine",
+    "princess",
+    "master",
+    "shadow",
+    "superman",
+    "batman",
+    "trustno1",
+    "starwars",
+    "whatever",
+    "freedom",
+    "hello",
+    "login",
+    "secret",
+    "changeme",
+    "access",
+    "michael",
+    "jennifer",
+    "charlie",
+    "computer",
+    "internet",
+    "summer",
+    "winter",
+    "spring",
+    "autumn",
+    "flower",
+    "cookie",
+    "cheese",
+    "pepper",
+    "ginger",
+    "orange",
+    "banana",
+    "purple",
+    "killer",
+    "hunter",
+    "ranger",
+    "buster",
+    "tigger",
+    "jordan",
+    "harley",
+    "thomas",
+    "robert",
+    "daniel",
+    "matthew",
+    "andrew",
+    "joshua",
+    "love",
+    "lovely",
+    "angel",
+    "baby",
+    "family",
+    "mustang",
+    "ferrari",
+    "corvette",
+    "mercedes",
+    "default",
+    "guest",
+    "root",
+    "test",
+    "temp",
+    "user",
+    "pass",
+    "abc",
+    "god",
+    "money",
+    "dollar",
+    "euro",
+    "qazwsx",
+    "zaq12wsx",
+    "google",
+    "facebook",
+    "linkedin",
+    "twitter",
+    "apple",
+    "samsung",
+    "microsoft",
+    "company",
+    "office",
+];
+
+// The lists are short, so a matched word is worth little more than picking it from them
+const COMMON_WORD_BITS: f64 = 7.0;
+const USER_INPUT_BITS: f64 = 2.0;
+// Upper case or substituted characters in a word
+const WORD_VARIATION_BITS: f64 = 1.0;
+const MIN_WORD_LENGTH: usize = 3;
+const MIN_RUN_LENGTH: usize = 3;
+
+const KEYBOARD_ROWS: &[&str] = &["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+// Guesses, as bits, from which each score is reached, the same thresholds as zxcvbn
+const SCORE_THRESHOLDS: [f64; 4] = [10.0, 20.0, 26.6, 33.2];
+
+pub const MAX_SCORE: u8 = 4;
+
+// From 0, guessable within a thousand tries, to 4, out of reach of an offline attack on a slow hash.
+// `user_inputs` are words specific to the user, such as parts of their email.
+pub fn score(password: &str, user_inputs: &[&str]) -> u8 {
+    let bits = estimate_bits(password, user_inputs);
+
+    let passed = SCORE_THRESHOLDS.iter().filter(|threshold| bits >= **threshold).count();
+
+    u8::try_from(passed).map_or(MAX_SCORE, |score| score.min(MAX_SCORE))
+}
+
+pub fn estimate_bits(password: &str, user_inputs: &[&str]) -> f64 {
+    let original: Vec<char> = password.chars().collect();
+    let normalized: Vec<char> = original.iter().map(|c| unleet(c.to_ascii_lowercase())).collect();
+    let mut covered = vec![false; normalized.len()];
+    let mut bits = 0.0;
+
+    let mut words: Vec<(Vec<char>, f64)> = COMMON_WORDS
+        .iter()
+        .map(|word| (word.chars().map(unleet).collect(), COMMON_WORD_BITS))
+        .chain(
+            user_inputs
+                .iter()
+                .map(|word| (word.to_lowercase().chars().map(unleet).collect(), USER_INPUT_BITS)),
+        )
+        .filter(|(word, _): &(Vec<char>, f64)| word.len() >= MIN_WORD_LENGTH)
+        .collect();
+    // Longer words first, so that `password` wins over `pass`
+    words.sort_by_key(|(word, _)| std::cmp::Reverse(word.len()));
+
+    for (word, word_bits) in &words {
+        let mut start: usize = 0;
+
+        while let Some(end) = start.checked_add(word.len()).filter(|end| *end <= normalized.len()) {
+            let is_match = normalized.get(start..end) == Some(word.as_slice())
+                && covered
+                    .get(start..end)
+                    .is_some_and(|span| span.iter().all(|covered| !covered));
+
+            if !is_match {
+                start = start.saturating_add(1);
+                continue;
+            }
+
+            let is_varied = original.get(start..end) != normalized.get(start..end);
+            bits += word_bits + if is_varied { WORD_VARIATION_BITS } else { 0.0 };
+
+            covered
+                .iter_mut()
+                .skip(start)
+                .take(word.len())
+                .for_each(|covered| *covered = true);
+            start = end;
+        }
+    }
+
+    // The characters left are split into runs such as `aaaa`, `abcd` or `asdf`, and random characters
+    let mut run: Vec<char> = Vec::new();
+    for (c, covered) in original.iter().zip(covered) {
+        let continues_run = run.last().is_some_and(|last| is_patterned(*last, *c));
+
+        if covered || !continues_run {
+            bits += run_bits(&run);
+            run.clear();
+        }
+
+        if !covered {
+            run.push(*c);
+        }
+    }
+
+    bits + run_bits(&run)
+}
+
+fn run_bits(run: &[char]) -> f64 {
+    match run.first() {
+        None => 0.0,
+        Some(first) if run.len() >= MIN_RUN_LENGTH => {
+            char_bits(*first) + u32::try_from(run.len()).map_or(0.0, |length| f64::from(length).log2())
+        }
+        Some(_) => run.iter().map(|c| char_bits(*c)).sum(),
+    }
+}
+
+// Bits to guess a character picked at random among the characters of its kind
+fn char_bits(c: char) -> f64 {
+    let cardinality: u32 = match c {
+        '0'..='9' => 10,
+        'a'..='z' => 26,
+        'A'..='Z' => 26,
+        c if c.is_ascii() => 33,
+        // Anything outside ASCII is harder to guess, but rarely typed
+        _ => 100,
+    };
+
+    f64::from(cardinality).log2()
+}
+
+// Repeated characters, neighbours in the alphabet, or neighbours on the keyboard
+fn is_patterned(previous: char, next: char) -> bool {
+    let previous = previous.to_ascii_lowercase();
+    let next = next.to_ascii_lowercase();
+
+    if previous == next {
+        return true;
+    }
+
+    let is_alphabet_neighbour =
+        previous.is_ascii_alphanumeric() && next.is_ascii_alphanumeric() && u32::from(previous).abs_diff(u32::from(next)) == 1;
+
+    let is_keyboard_neighbour = KEYBOARD_ROWS.iter().any(|row| {
+        row.chars()
+            .zip(row.chars().skip(1))
+            .any(|(a, b)| (a, b) == (previous, next) || (b, a) == (previous, next))
+    });
+
+    is_alphabet_neighbour || is_keyboard_neighbour
+}
+
+fn unleet(c: char) -> char {
+    match c {
+        '0' => 'o',
+        '1' | '!' => 'i',
+        '3' => 'e',
+        '4' | '@' => 'a',
+        '5' | '$' => 's',
+        '7' => 't',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn common_passwords_score_low() {
+        for password in [
+            "password",
+            "Password1",
+            "p@ssw0rd123",
+            "qwerty123",
+            "letmein!",
+            "11111111",
+            "abcdefgh",
+        ] {
+            assert!(score(password, &[]) <= 1, "{} scored {}", password, score(password, &[]));
+        }
+    }
+
+    #[test]
+    fn keyboard_runs_score_low() {
+        assert!(score("qwertyuiop", &[]) <= 1);
+        assert!(score("asdfghjkl;", &[]) <= 1);
+    }
+
+    #[test]
+    fn random_passwords_score_high() {
+        for password in ["Tr0ub4dor&3x", "correct horse battery staple", "g7#Kq9!vLm2@", "xkcdzqvbnwpl"] {
+            assert_eq!(score(password, &[]), MAX_SCORE, "{}", password);
+        }
+    }
+
+    #[test]
+    fn user_inputs_lower_the_score() {
+        let password = "jdoe-kowalski";
+
+        assert!(score(password, &["jdoe", "kowalski"]) < score(password, &[]));
+    }
+
+    #[test]
+    fn empty_password_scores_zero() {
+        assert_eq!(score("", &[]), 0);
+        assert_eq!(estimate_bits("", &[]), 0.0);
+    }
+}
@@ -0,0 +1,145 @@
+use validator::ValidationError;
+
+const MAX_DISPLAY_NAME_LENGTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayName(String);
+
+impl DisplayName {
+    pub fn parse(value: &str) -> Result<DisplayName, ValidationError> {
+        let value = value.trim();
+
+        if value.is_empty() {
+            return Err(ValidationError::new("Display name cannot be empty."));
+        }
+
+        if value.chars().count() > MAX_DISPLAY_NAME_LENGTH {
+            return Err(ValidationError::new("Display name is too long."));
+        }
+
+        if value.chars().any(char::is_control) {
+            return Err(ValidationError::new("Display name cannot contain control characters."));
+        }
+
+        Ok(DisplayName(value.to_owned()))
+    }
+}
+
+impl AsRef<str> for DisplayName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// A language tag such as `en` or `pt-BR`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Locale(String);
+
+impl Locale {
+    pub fn parse(value: &str) -> Result<Locale, ValidationError> {
+        let mut parts = value.split('-');
+
+        let is_valid_language = parts
+            .next()
+            .is_some_and(|language| (2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_lowercase()));
+
+        let is_valid_region = match parts.next() {
+            None => true,
+            Some(region) => {
+                (region.len() == 2 && region.chars().all(|c| c.is_ascii_uppercase()))
+                    || (region.len() == 3 && region.chars().all(|c| c.is_ascii_digit()))
+            }
+        };
+
+        if !is_valid_language || !is_valid_region || parts.next().is_some() {
+            return Err(ValidationError::new("Invalid locale format."));
+        }
+
+        Ok(Locale(value.to_owned()))
+    }
+}
+
+impl AsRef<str> for Locale {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// An IANA time zone name such as `UTC` or `Europe/Warsaw`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timezone(String);
+
+impl Timezone {
+    pub fn parse(value: &str) -> Result<Timezone, ValidationError> {
+        let is_valid = value.split('/').all(|part| {
+            part.starts_with(|c: char| c.is_ascii_uppercase())
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+'))
+        });
+
+        if !is_valid {
+            return Err(ValidationError::new("Invalid timezone format."));
+        }
+
+        Ok(Timezone(value.to_owned()))
+    }
+}
+
+impl AsRef<str> for Timezone {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Profile {
+    pub display_name: Option<DisplayName>,
+    pub locale: Option<Locale>,
+    pub timezone: Option<Timezone>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_name_is_trimmed() {
+        assert_eq!(DisplayName::parse("  Jane Doe ").unwrap().as_ref(), "Jane Doe");
+    }
+
+    #[test]
+    fn invalid_display_names_are_rejected() {
+        assert!(DisplayName::parse("   ").is_err());
+        assert!(DisplayName::parse("Jane\nDoe").is_err());
+        assert!(DisplayName::parse(&"a".repeat(MAX_DISPLAY_NAME_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn valid_locales_are_accepted() {
+        for locale in ["en", "pl-PL", "es-419", "fil"] {
+            assert!(Locale::parse(locale).is_ok(), "{locale}");
+        }
+    }
+
+    #[test]
+    fn invalid_locales_are_rejected() {
+        for locale in ["", "EN", "en_US", "en-us", "en-US-x", "english"] {
+            assert!(Locale::parse(locale).is_err(), "{locale}");
+        }
+    }
+
+    #[test]
+    fn valid_timezones_are_accepted() {
+        for timezone in ["UTC", "Europe/Warsaw", "America/Argentina/Buenos_Aires", "Etc/GMT+2"] {
+            assert!(Timezone::parse(timezone).is_ok(), "{timezone}");
+        }
+    }
+
+    #[test]
+    fn invalid_timezones_are_rejected() {
+        for timezone in ["", "europe/warsaw", "Europe/", "/Warsaw", "Europe Warsaw"] {
+            assert!(Timezone::parse(timezone).is_err(), "{timezone}");
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::utils::constants::{
+    RATE_LIMIT_FORGOT_PASSWORD_PER_EMAIL, RATE_LIMIT_FORGOT_PASSWORD_PER_IP, RATE_LIMIT_LOGIN_PER_EMAIL, RATE_LIMIT_LOGIN_PER_IP,
+    RATE_LIMIT_MAGIC_LINK_PER_EMAIL, RATE_LIMIT_MAGIC_LINK_PER_IP, RATE_LIMIT_RESEND_VERIFICATION_PER_EMAIL,
+    RATE_LIMIT_RESEND_VERIFICATION_PER_IP, RATE_LIMIT_SIGNUP_PER_EMAIL, RATE_LIMIT_SIGNUP_PER_IP,
+    RATE_LIMIT_VERIFY_2FA_PER_EMAIL, RATE_LIMIT_VERIFY_2FA_PER_IP,
+};
+use chrono::{DateTime, Duration, Utc};
+
+// At most `max_requests` within any `window_seconds` long period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub max_requests: u32,
+    pub window_seconds: u32,
+}
+
+impl RateLimit {
+    // Parses `max_requests/window_seconds`, such as `10/60`. `off` turns the limit off.
+    pub fn parse(value: &str) -> Result<Option<RateLimit>, String> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("off") {
+            return Ok(None);
+        }
+
+        let (max_requests, window_seconds) = value
+            .split_once('/')
+            .ok_or_else(|| format!("Rate limit `{}` is not of the form `requests/seconds`", value))?;
+
+        let max_requests = max_requests.trim().parse::<u32>().map_err(|e| e.to_string())?;
+        let window_seconds = window_seconds.trim().parse::<u32>().map_err(|e| e.to_string())?;
+
+        if max_requests == 0 || window_seconds == 0 {
+            return Err(format!("Rate limit `{}` must allow requests within a window", value));
+        }
+
+        Ok(Some(RateLimit {
+            max_requests,
+            window_seconds,
+        }))
+    }
+
+    pub fn window(&self) -> Duration {
+        Duration::seconds(i64::from(self.window_seconds))
+    }
+
+    // Whether one more request is let through, given the times of the requests let through so far, oldest first
+    pub fn decide(&self, requests: &[DateTime<Utc>], now: DateTime<Utc>) -> RateLimitDecision {
+        let window_start = now.checked_sub_signed(self.window()).unwrap_or(DateTime::<Utc>::MIN_UTC);
+        let recent: Vec<&DateTime<Utc>> = requests.iter().filter(|at| **at > window_start).collect();
+
+        if recent.len() < usize::try_from(self.max_requests).unwrap_or(usize::MAX) {
+            return RateLimitDecision::Allowed;
+        }
+
+        // A request is let through again once the oldest one in the window leaves it
+        let retry_after_seconds = recent
+            .first()
+            .and_then(|oldest| oldest.checked_add_signed(self.window()))
+            .map_or(u64::from(self.window_seconds), |freed_at| {
+                u64::try_from(freed_at.signed_duration_since(now).num_milliseconds())
+                    .unwrap_or_default()
+                    .div_ceil(1000)
+                    .max(1)
+            });
+
+        RateLimitDecision::Limited { retry_after_seconds }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitDecision {
+    Allowed,
+    Limited { retry_after_seconds: u64 },
+}
+
+// Limits of one route, by the IP of the client and by the email the request is about. `None` leaves it unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RouteRateLimits {
+    pub per_ip: Option<RateLimit>,
+    pub per_email: Option<RateLimit>,
+}
+
+// Limits of the routes open to password and 2FA code guessing, to mass signups, or to flooding an inbox with emails
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitPolicy {
+    pub login: RouteRateLimits,
+    pub signup: RouteRateLimits,
+    pub verify_2fa: RouteRateLimits,
+    pub magic_link: RouteRateLimits,
+    pub forgot_password: RouteRateLimits,
+    pub resend_verification: RouteRateLimits,
+}
+
+impl Default for RateLimitPolicy {
+    fn default() -> Self {
+        RateLimitPolicy {
+            login: RouteRateLimits {
+                per_ip: *RATE_LIMIT_LOGIN_PER_IP,
+                per_email: *RATE_LIMIT_LOGIN_PER_EMAIL,
+            },
+            signup: RouteRateLimits {
+                per_ip: *RATE_LIMIT_SIGNUP_PER_IP,
+                per_email: *RATE_LIMIT_SIGNUP_PER_EMAIL,
+            },
+            verify_2fa: RouteRateLimits {
+                per_ip: *RATE_LIMIT_VERIFY_2FA_PER_IP,
+                per_email: *RATE_LIMIT_VERIFY_2FA_PER_EMAIL,
+            },
+            magic_link: RouteRateLimits {
+                per_ip: *RATE_LIMIT_MAGIC_LINK_PER_IP,
+                per_email: *RATE_LIMIT_MAGIC_LINK_PER_EMAIL,
+            },
+            forgot_password: RouteRateLimits {
+                per_ip: *RATE_LIMIT_FORGOT_PASSWORD_PER_IP,
+                per_email: *RATE_LIMIT_FORGOT_PASSWORD_PER_EMAIL,
+            },
+            resend_verification: RouteRateLimits {
+                per_ip: *RATE_LIMIT_RESEND_VERIFICATION_PER_IP,
+                per_email: *RATE_LIMIT_RESEND_VERIFICATION_PER_EMAIL,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit() -> RateLimit {
+        RateLimit {
+            max_requests: 2,
+            window_seconds: 60,
+        }
+    }
+
+    #[test]
+    fn rate_limit_is_parsed() {
+        assert_eq!(
+            RateLimit::parse(" 10 / 60 ").unwrap(),
+            Some(RateLimit {
+                max_requests: 10,
+                window_seconds: 60
+            })
+        );
+        assert_eq!(RateLimit::parse("OFF").unwrap(), None);
+
+        for value in ["", "10", "10/", "/60", "0/60", "10/0", "-1/60", "ten/60"] {
+            assert!(RateLimit::parse(value).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn requests_below_the_limit_are_allowed() {
+        let now = Utc::now();
+
+        assert_eq!(limit().decide(&[], now), RateLimitDecision::Allowed);
+        assert_eq!(limit().decide(&[now - Duration::seconds(1)], now), RateLimitDecision::Allowed);
+    }
+
+    #[test]
+    fn requests_over_the_limit_wait_for_the_oldest_to_leave_the_window() {
+        let now = Utc::now();
+        let requests = [now - Duration::seconds(45), now - Duration::seconds(10)];
+
+        assert_eq!(
+            limit().decide(&requests, now),
+            RateLimitDecision::Limited { retry_after_seconds: 15 }
+        );
+    }
+
+    #[test]
+    fn window_slides() {
+        let now = Utc::now();
+        let requests = [now - Duration::seconds(60), now - Duration::seconds(10)];
+
+        assert_eq!(limit().decide(&requests, now), RateLimitDecision::Allowed);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::domain::organization::OrganizationId;
+use serde::{Deserialize, Serialize};
+use validator::ValidationError;
+
+// Every user has the default role without it being assigned, the admin role is only used to seed the first admins
+pub const DEFAULT_ROLE: &str = "user";
+pub const ADMIN_ROLE: &str = "admin";
+
+const MAX_ROLE_NAME_LENGTH: usize = 32;
+
+// A role name such as `admin` or `support-agent`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RoleName(String);
+
+impl RoleName {
+    pub fn parse(value: &str) -> Result<RoleName, ValidationError> {
+        let is_valid = value.starts_with(|c: char| c.is_ascii_lowercase())
+            && value.len() <= MAX_ROLE_NAME_LENGTH
+            && value
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-'));
+
+        if !is_valid {
+            return Err(ValidationError::new("Invalid role name."));
+        }
+
+        Ok(RoleName(value.to_owned()))
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.0 == DEFAULT_ROLE
+    }
+}
+
+impl AsRef<str> for RoleName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// A permission such as `users:write`, naming a resource and an action on it
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Permission(String);
+
+impl Permission {
+    pub fn parse(value: &str) -> Result<Permission, ValidationError> {
+        let is_valid_part = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_lowercase() || c == '_');
+
+        let is_valid = value
+            .split_once(':')
+            .is_some_and(|(resource, action)| is_valid_part(resource) && is_valid_part(action));
+
+        if !is_valid {
+            return Err(ValidationError::new("Invalid permission format."));
+        }
+
+        Ok(Permission(value.to_owned()))
+    }
+}
+
+impl AsRef<str> for Permission {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Role {
+    pub name: RoleName,
+    pub permissions: Vec<Permission>,
+}
+
+// The roles of a user and the permissions they grant, embedded into the auth token when it is issued.
+// Inside an organization, they include the roles the user holds in it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Grants {
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    #[serde(default, rename = "org", skip_serializing_if = "Option::is_none")]
+    pub organization_id: Option<OrganizationId>,
+}
+
+impl Grants {
+    // `assigned` are the roles given to the user, to which the default role is added
+    pub fn new(roles: &[Role], assigned: &[RoleName]) -> Grants {
+        let mut granted: Vec<&Role> = roles
+            .iter()
+            .filter(|role| role.name.is_default() || assigned.contains(&role.name))
+            .collect();
+        granted.sort_by_key(|role| &role.name);
+
+        let mut permissions: Vec<String> = granted
+            .iter()
+            .flat_map(|role| role.permissions.iter().map(|permission| permission.as_ref().to_owned()))
+            .collect();
+        permissions.sort();
+        permissions.dedup();
+
+        Grants {
+            roles: granted.iter().map(|role| role.name.as_ref().to_owned()).collect(),
+            permissions,
+            organization_id: None,
+        }
+    }
+
+    pub fn in_organization(self, organization_id: OrganizationId) -> Grants {
+        Grants {
+            organization_id: Some(organization_id),
+            ..self
+        }
+    }
+
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|granted| granted == permission)
+    }
+
+    // Whether every permission of the role is granted, so that handing the role out doesn't escalate privileges
+    pub fn covers(&self, role: &Role) -> bool {
+        role.permissions
+            .iter()
+            .all(|permission| self.has_permission(permission.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn role(name: &str, permissions: &[&str]) -> Role {
+        Role {
+            name: RoleName::parse(name).unwrap(),
+            permissions: permissions.iter().map(|p| Permission::parse(p).unwrap()).collect(),
+        }
+    }
+
+    #[test]
+    fn valid_role_names_are_accepted() {
+        for name in ["admin", "support-agent", "tier_2"] {
+            assert!(RoleName::parse(name).is_ok(), "{name}");
+        }
+    }
+
+    #[test]
+    fn invalid_role_names_are_rejected() {
+        for name in ["", "Admin", "2fa", "support agent", &"a".repeat(MAX_ROLE_NAME_LENGTH + 1)] {
+            assert!(RoleName::parse(name).is_err(), "{name}");
+        }
+    }
+
+    #[test]
+    fn valid_permissions_are_accepted() {
+        for permission in ["users:write", "trusted_devices:read"] {
+            assert!(Permission::parse(permission).is_ok(), "{permission}");
+        }
+    }
+
+    #[test]
+    fn invalid_permissions_are_rejected() {
+        for permission in ["", "users", "users:", ":write", "users:write:all", "Users:write"] {
+            assert!(Permission::parse(permission).is_err(), "{permission}");
+        }
+    }
+
+    #[test]
+    fn grants_include_default_role_and_assigned_roles_only() {
+        let roles = [
+            role(ADMIN_ROLE, &["users:write", "content:read"]),
+            role("support", &["users:read"]),
+            role(DEFAULT_ROLE, &["content:read"]),
+        ];
+
+        let grants = Grants::new(&roles, &[RoleName::parse(ADMIN_ROLE).unwrap()]);
+
+        assert_eq!(grants.roles, vec!["admin", "user"]);
+        assert_eq!(grants.permissions, vec!["content:read", "users:write"]);
+        assert!(grants.has_permission("users:write"));
+        assert!(!grants.has_permission("users:read"));
+    }
+
+    #[test]
+    fn grants_cover_roles_with_granted_permissions_only() {
+        let roles = [
+            role("support", &["users:read", "content:read"]),
+            role(DEFAULT_ROLE, &["content:read"]),
+        ];
+
+        let grants = Grants::new(&roles, &[RoleName::parse("support").unwrap()]);
+
+        assert!(grants.covers(&role("reader", &["content:read"])));
+        assert!(grants.covers(&role("empty", &[])));
+        assert!(!grants.covers(&role("editor", &["content:read", "content:write"])));
+    }
+
+    #[test]
+    fn assigned_roles_that_no_longer_exist_are_ignored() {
+        let roles = [role(DEFAULT_ROLE, &["content:read"])];
+
+        let grants = Grants::new(&roles, &[RoleName::parse("deleted").unwrap()]);
+
+        assert_eq!(grants.roles, vec!["user"]);
+    }
+}
@@ -1,19 +1,50 @@
+use crate::domain::account_status::AccountState;
 use crate::domain::email::Email;
 use crate::domain::hashed_password::HashedPassword;
+use crate::domain::profile::Profile;
+use chrono::{DateTime, Utc};
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct User {
     pub email: Email,
-    pub password: HashedPassword,
+    // `None` for passwordless accounts, which can only sign in through a magic link
+    pub password: Option<HashedPassword>,
     pub requires_2fa: bool,
+    // New accounts can't log in until the address is confirmed through the link sent at signup
+    pub email_verified: bool,
+    // Only active accounts can log in
+    pub account_state: AccountState,
+    pub profile: Profile,
+    pub created_at: DateTime<Utc>,
+    pub last_login_at: Option<DateTime<Utc>>,
 }
 
 impl User {
-    pub fn new(email: Email, password: HashedPassword, requires_2fa: bool) -> User {
+    pub fn new(email: Email, password: Option<HashedPassword>, requires_2fa: bool) -> User {
         User {
             email,
             password,
             requires_2fa,
+            email_verified: false,
+            account_state: AccountState::default(),
+            profile: Profile::default(),
+            created_at: Utc::now(),
+            last_login_at: None,
         }
     }
 }
+
+// A page of the users matching an optional search on their email or display name
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserSearch {
+    pub query: Option<String>,
+    pub offset: u64,
+    pub limit: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserPage {
+    pub users: Vec<User>,
+    // Number of matching users across all pages
+    pub total: u64,
+}
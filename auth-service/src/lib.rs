@@ -1,6 +1,11 @@
 use crate::app_state::AppState;
+use crate::utils::auth::{authenticate, RequirePermission};
+use crate::utils::rate_limit::RateLimitLayer;
 use crate::utils::tracing::{make_span_with_request_id, on_request, on_response};
-use axum::routing::post;
+use axum::extract::connect_info::IntoMakeServiceWithConnectInfo;
+use axum::extract::ConnectInfo;
+use axum::middleware::{from_fn_with_state, AddExtension};
+use axum::routing::{delete, get, get_service, post, put};
 use axum::serve::Serve;
 use axum::Router;
 use dotenv::dotenv;
@@ -10,9 +15,10 @@ use secrecy::{ExposeSecret, SecretString};
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
 use std::error::Error;
+use std::net::SocketAddr;
 use tokio::net::TcpListener;
 use tower_http::cors::CorsLayer;
-use tower_http::services::ServeDir;
+use tower_http::services::{ServeDir, ServeFile};
 use tower_http::trace::TraceLayer;
 
 pub mod app_state;
@@ -22,7 +28,7 @@ pub mod services;
 pub mod utils;
 
 pub struct Application {
-    server: Serve<TcpListener, Router, Router>,
+    server: Serve<TcpListener, IntoMakeServiceWithConnectInfo<Router, SocketAddr>, AddExtension<Router, ConnectInfo<SocketAddr>>>,
     pub address: String,
 }
 
@@ -31,20 +37,145 @@ impl Application {
         dotenv().ok();
 
         let assets_dir = ServeDir::new("assets");
+        // Pages opened from the links in emails, which submit their form to the route of the same path
+        let page = |file: &str| get_service(ServeFile::new(format!("assets/{}", file)));
 
         let allowed_origins = ["http://localhost:8000".parse()?, "http://167.71.36.159:7000".parse()?];
 
         let cors_layer = CorsLayer::new()
-            .allow_methods([Method::GET, Method::POST])
+            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE])
             .allow_origin(allowed_origins)
             .allow_credentials(true);
 
+        let read_users = RequirePermission("users:read");
+        let write_users = RequirePermission("users:write");
+        let read_roles = RequirePermission("roles:read");
+        let write_roles = RequirePermission("roles:write");
+        let write_organizations = RequirePermission("organizations:write");
+        let read_members = RequirePermission("members:read");
+        let write_members = RequirePermission("members:write");
+        let read_metrics = RequirePermission("metrics:read");
+        let read_audit = RequirePermission("audit:read");
+
+        // Every admin route requires a permission granted by the roles in the auth token
+        let admin_router = Router::new()
+            .route("/audit-events", get(routes::list_audit_events).route_layer(read_audit))
+            .route(
+                "/invitations",
+                get(routes::list_invitations)
+                    .route_layer(read_users)
+                    .merge(post(routes::create_invitation).route_layer(write_users)),
+            )
+            .route(
+                "/invitations/{id}",
+                delete(routes::revoke_invitation).route_layer(write_users),
+            )
+            .route("/lockouts/{email}", delete(routes::clear_lockout).route_layer(write_users))
+            .route("/metrics/hashing", get(routes::get_hashing_metrics).route_layer(read_metrics))
+            .route("/roles", get(routes::list_roles).route_layer(read_roles))
+            .route(
+                "/roles/{role}",
+                put(routes::set_role).delete(routes::delete_role).route_layer(write_roles),
+            )
+            .route("/users", get(routes::list_users).route_layer(read_users))
+            .route("/users/import", post(routes::import_users).route_layer(write_users))
+            .route("/users/{email}", get(routes::get_user).route_layer(read_users))
+            .route("/users/{email}/disable", post(routes::disable_user).route_layer(write_users))
+            .route("/users/{email}/enable", post(routes::enable_user).route_layer(write_users))
+            .route("/users/{email}/force-2fa", post(routes::force_2fa).route_layer(write_users))
+            .route(
+                "/users/{email}/reset-password",
+                post(routes::admin_reset_password).route_layer(write_users),
+            )
+            .route(
+                "/users/{email}/revoke-sessions",
+                post(routes::revoke_user_sessions).route_layer(write_users),
+            )
+            .route(
+                "/users/{email}/roles/{role}",
+                put(routes::assign_user_role)
+                    .delete(routes::remove_user_role)
+                    .route_layer(write_roles),
+            )
+            .route(
+                "/users/{email}/status",
+                put(routes::set_account_status).route_layer(write_users),
+            )
+            .route_layer(from_fn_with_state(app_state.clone(), authenticate));
+
+        // Members are managed within the organization the user is signed into
+        let organization_router = Router::new()
+            .route(
+                "/organizations",
+                get(routes::list_organizations).merge(post(routes::create_organization).route_layer(write_organizations)),
+            )
+            .route("/organizations/switch", post(routes::switch_organization))
+            .route("/organization/members", get(routes::list_members).route_layer(read_members))
+            .route(
+                "/organization/members/{email}",
+                put(routes::set_member)
+                    .delete(routes::remove_member)
+                    .route_layer(write_members),
+            )
+            .route_layer(from_fn_with_state(app_state.clone(), authenticate));
+
+        let rate_limits = app_state.rate_limit_policy;
+        let rate_limit = |route, limits| RateLimitLayer::new(app_state.rate_limit_store.clone(), route, limits);
+
         let router = Router::new()
             .fallback_service(assets_dir)
-            .route("/signup", post(routes::signup))
-            .route("/login", post(routes::login))
+            .route(
+                "/signup",
+                page("index.html").merge(post(routes::signup).route_layer(rate_limit("signup", rate_limits.signup))),
+            )
+            .route("/account", delete(routes::delete_account))
+            .route("/account/export", get(routes::export_account))
+            .nest("/admin", admin_router)
+            .route(
+                "/login",
+                page("index.html").merge(post(routes::login).route_layer(rate_limit("login", rate_limits.login))),
+            )
+            .route(
+                "/login/magic-link",
+                post(routes::request_magic_link).route_layer(rate_limit("magic_link", rate_limits.magic_link)),
+            )
+            .route(
+                "/login/magic-link/verify",
+                get(routes::confirm_magic_link).post(routes::verify_magic_link),
+            )
+            .route(
+                "/login/not-me",
+                get(routes::confirm_unrecognized_login_report).post(routes::report_unrecognized_login),
+            )
+            .route("/email/change", post(routes::request_email_change))
+            .route("/email/change/confirm", get(routes::confirm_email_change))
             .route("/logout", post(routes::logout))
-            .route("/verify-2fa", post(routes::verify_2fa))
+            .route("/me", get(routes::get_me).patch(routes::update_me))
+            .route("/me/login-history", get(routes::get_login_history))
+            .merge(organization_router)
+            .route(
+                "/organizations/join",
+                page("organization-join.html").post(routes::join_organization),
+            )
+            .route("/password/change", post(routes::change_password))
+            .route(
+                "/password/forgot",
+                page("password-forgot.html")
+                    .merge(post(routes::forgot_password).route_layer(rate_limit("forgot_password", rate_limits.forgot_password))),
+            )
+            .route("/password/reset", page("password-reset.html").post(routes::reset_password))
+            .route("/trusted-devices", get(routes::list_trusted_devices))
+            .route("/trusted-devices/{device_id}", delete(routes::revoke_trusted_device))
+            .route(
+                "/verify-2fa",
+                post(routes::verify_2fa).route_layer(rate_limit("verify_2fa", rate_limits.verify_2fa)),
+            )
+            .route("/verify-email", get(routes::verify_email))
+            .route(
+                "/verify-email/resend",
+                post(routes::resend_verification_email)
+                    .route_layer(rate_limit("resend_verification", rate_limits.resend_verification)),
+            )
             .route("/verify_token", post(routes::verify_token))
             .with_state(app_state)
             .layer(cors_layer)
@@ -59,7 +190,8 @@ impl Application {
 
         let address = listener.local_addr()?.to_string();
 
-        let server = axum::serve(listener, router);
+        // The client address is needed to rate limit by IP
+        let server = axum::serve(listener, router.into_make_service_with_connect_info::<SocketAddr>());
 
         Ok(Application { server, address })
     }
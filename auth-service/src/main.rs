@@ -1,11 +1,29 @@
-use auth_service::app_state::AppState;
+use auth_service::app_state::{AppState, BreachedPasswordStoreType};
 use auth_service::domain::email::Email;
+use auth_service::domain::password_policy::PasswordPolicy;
+use auth_service::domain::rate_limit::RateLimitPolicy;
+use auth_service::services::data_stores::file_breached_password_store::FileBreachedPasswordStore;
+use auth_service::services::data_stores::hashmap_breached_password_store::HashmapBreachedPasswordStore;
+use auth_service::services::data_stores::postgres_audit_log::PostgresAuditLog;
+use auth_service::services::data_stores::postgres_device_history_store::PostgresDeviceHistoryStore;
+use auth_service::services::data_stores::postgres_invitation_store::PostgresInvitationStore;
+use auth_service::services::data_stores::postgres_organization_store::PostgresOrganizationStore;
+use auth_service::services::data_stores::postgres_password_history_store::PostgresPasswordHistoryStore;
+use auth_service::services::data_stores::postgres_role_store::PostgresRoleStore;
 use auth_service::services::data_stores::postgres_user_store::PostgresUserStore;
 use auth_service::services::data_stores::redis_banned_token_store::RedisBannedTokenStore;
+use auth_service::services::data_stores::redis_email_change_store::RedisEmailChangeStore;
+use auth_service::services::data_stores::redis_login_failure_store::RedisLoginFailureStore;
+use auth_service::services::data_stores::redis_one_time_token_store::RedisOneTimeTokenStore;
+use auth_service::services::data_stores::redis_rate_limit_store::RedisRateLimitStore;
+use auth_service::services::data_stores::redis_trusted_device_store::RedisTrustedDeviceStore;
 use auth_service::services::data_stores::redis_two_fa_code_store::RedisTwoFACodeStore;
 use auth_service::services::postmark_email_client::PostmarkEmailClient;
 use auth_service::utils::constants::env::DATABASE_URL_NAME;
-use auth_service::utils::constants::{prod, POSTMARK_AUTH_TOKEN, REDIS_HOST_NAME};
+use auth_service::utils::constants::{
+    prod, BREACHED_PASSWORDS_FILE, INVITE_ONLY_SIGNUP, PASSWORD_PEPPER, POSTMARK_AUTH_TOKEN, REDIS_HOST_NAME, TWO_FA_CODE_SECRET,
+    UNIFORM_SIGNUP_RESPONSE,
+};
 use auth_service::utils::tracing::init_tracing;
 use auth_service::{get_postgres_pool, get_redis_client, Application};
 use reqwest::Client;
@@ -17,6 +35,14 @@ async fn main() {
     init_tracing().expect("Failed to initialize tracing");
     color_eyre::install().expect("Failed to install color_eyre");
 
+    TWO_FA_CODE_SECRET
+        .as_ref()
+        .expect("TWO_FA_CODE_SECRET must be set and cannot be empty");
+
+    if PASSWORD_PEPPER.is_none() {
+        tracing::warn!("PASSWORD_PEPPER is not set, password hashes are not peppered");
+    }
+
     let redis_connection = get_redis_client(REDIS_HOST_NAME.to_owned()).expect("Couldn't get Redis connection");
     let banned_token_store = Arc::new(RwLock::new(RedisBannedTokenStore::new(
         redis_connection.get_connection().unwrap(),
@@ -26,12 +52,45 @@ async fn main() {
         .await
         .expect("Failed to create Postgres poll");
 
-    let user_store = Arc::new(RwLock::new(PostgresUserStore::new(poll)));
+    let user_store = Arc::new(RwLock::new(PostgresUserStore::new(poll.clone())));
+    let role_store = Arc::new(RwLock::new(PostgresRoleStore::new(poll.clone())));
+    let organization_store = Arc::new(RwLock::new(PostgresOrganizationStore::new(poll.clone())));
+    let invitation_store = Arc::new(RwLock::new(PostgresInvitationStore::new(poll.clone())));
+    let password_history_store = Arc::new(RwLock::new(PostgresPasswordHistoryStore::new(poll.clone())));
+    let audit_log = Arc::new(RwLock::new(PostgresAuditLog::new(poll.clone())));
+    let device_history_store = Arc::new(RwLock::new(PostgresDeviceHistoryStore::new(poll)));
     // let two_fa_code_store = Arc::new(RwLock::new(HashmapTwoFACodeStore::default()));
     let two_fa_code_store = Arc::new(RwLock::new(RedisTwoFACodeStore::new(
         redis_connection.get_connection().unwrap(),
     )));
 
+    let one_time_token_store = Arc::new(RwLock::new(RedisOneTimeTokenStore::new(
+        redis_connection.get_connection().unwrap(),
+    )));
+
+    let trusted_device_store = Arc::new(RwLock::new(RedisTrustedDeviceStore::new(
+        redis_connection.get_connection().unwrap(),
+    )));
+
+    let email_change_store = Arc::new(RwLock::new(RedisEmailChangeStore::new(
+        redis_connection.get_connection().unwrap(),
+    )));
+
+    let login_failure_store = Arc::new(RwLock::new(RedisLoginFailureStore::new(
+        redis_connection.get_connection().unwrap(),
+    )));
+
+    // Shared by every instance, so that spreading requests over them doesn't get around the limits
+    let rate_limit_store = Arc::new(RwLock::new(RedisRateLimitStore::new(
+        redis_connection.get_connection().unwrap(),
+    )));
+
+    // Without a dump of breached passwords, the store stays empty and the check is skipped
+    let breached_password_store: BreachedPasswordStoreType = match BREACHED_PASSWORDS_FILE.as_deref() {
+        Some(path) => Arc::new(RwLock::new(FileBreachedPasswordStore::new(path))),
+        None => Arc::new(RwLock::new(HashmapBreachedPasswordStore::default())),
+    };
+
     let http_client = Client::builder()
         .timeout(prod::email_client::TIMEOUT)
         .build()
@@ -44,7 +103,28 @@ async fn main() {
         http_client,
     )));
 
-    let app_state = AppState::new(user_store, banned_token_store, two_fa_code_store, email_client);
+    let app_state = AppState {
+        user_store,
+        banned_token_store,
+        two_fa_code_store,
+        email_client,
+        one_time_token_store,
+        trusted_device_store,
+        email_change_store,
+        login_failure_store,
+        role_store,
+        organization_store,
+        invitation_store,
+        invite_only_signup: *INVITE_ONLY_SIGNUP,
+        uniform_signup_response: *UNIFORM_SIGNUP_RESPONSE,
+        breached_password_store,
+        password_history_store,
+        password_policy: PasswordPolicy::default(),
+        rate_limit_store,
+        rate_limit_policy: RateLimitPolicy::default(),
+        audit_log,
+        device_history_store,
+    };
 
     let app = Application::build(app_state, prod::APP_ADDRESS)
         .await
@@ -0,0 +1,323 @@
+use crate::app_state::AppState;
+use crate::domain::audit::AuditSearch;
+use crate::domain::data_stores::{EmailChangeStoreError, UserStoreError};
+use crate::domain::device_history::DeviceRecord;
+use crate::domain::email::Email;
+use crate::domain::error::AuthAPIError;
+use crate::domain::organization::{Membership, OrganizationId};
+use crate::domain::trusted_device::TrustedDevice;
+use crate::routes::{AccountStatusResponse, AuditEventResponse};
+use crate::utils::auth::{hashing_error, revoke_sessions, AuthenticatedUser};
+use crate::utils::constants::env::{JWT_COOKIE_NAME, TRUSTED_DEVICE_COOKIE_NAME};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use axum_extra::extract::{cookie, CookieJar};
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::eyre;
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct DeleteAccountRequest {
+    pub password: SecretString,
+}
+
+#[tracing::instrument(name = "Delete account", skip_all)]
+pub async fn delete_account(
+    jar: CookieJar,
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(request): Json<DeleteAccountRequest>,
+) -> (CookieJar, Result<impl IntoResponse, AuthAPIError>) {
+    if let Err(e) = reauthenticate(&state, &user.email, &request.password).await {
+        return (jar, Err(e));
+    }
+
+    if let Err(e) = purge_user(&state, &user.email).await {
+        return (jar, Err(e));
+    }
+
+    let jar = jar
+        .remove(cookie::Cookie::from(JWT_COOKIE_NAME))
+        .remove(cookie::Cookie::from(TRUSTED_DEVICE_COOKIE_NAME));
+
+    (jar, Ok(StatusCode::NO_CONTENT))
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct AccountExport {
+    pub user: UserExport,
+    #[serde(rename = "trustedDevices")]
+    pub trusted_devices: Vec<TrustedDevice>,
+    #[serde(rename = "pendingEmailChange")]
+    pub pending_email_change: Option<EmailChangeExport>,
+    pub organizations: Vec<OrganizationExport>,
+    // Devices the user logged in from, most recently seen first
+    #[serde(rename = "knownDevices")]
+    pub known_devices: Vec<KnownDeviceExport>,
+    // Every audit event recorded about the user, most recent first
+    #[serde(rename = "auditEvents")]
+    pub audit_events: Vec<AuditEventResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct UserExport {
+    pub email: String,
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    #[serde(rename = "requires2FA")]
+    pub requires_2fa: bool,
+    #[serde(rename = "emailVerified")]
+    pub email_verified: bool,
+    // The password hash itself is never exported
+    #[serde(rename = "hasPassword")]
+    pub has_password: bool,
+    pub roles: Vec<String>,
+    pub status: AccountStatusResponse,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "lastLoginAt")]
+    pub last_login_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct KnownDeviceExport {
+    pub fingerprint: String,
+    #[serde(rename = "firstSeenAt")]
+    pub first_seen_at: DateTime<Utc>,
+    #[serde(rename = "lastSeenAt")]
+    pub last_seen_at: DateTime<Utc>,
+}
+
+impl From<DeviceRecord> for KnownDeviceExport {
+    fn from(device: DeviceRecord) -> Self {
+        KnownDeviceExport {
+            fingerprint: device.fingerprint.as_ref().to_owned(),
+            first_seen_at: device.first_seen_at,
+            last_seen_at: device.last_seen_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct EmailChangeExport {
+    #[serde(rename = "newEmail")]
+    pub new_email: String,
+    #[serde(rename = "oldEmailConfirmed")]
+    pub old_email_confirmed: bool,
+    #[serde(rename = "newEmailConfirmed")]
+    pub new_email_confirmed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct OrganizationExport {
+    pub id: OrganizationId,
+    pub name: String,
+    pub roles: Vec<String>,
+    #[serde(rename = "joinedAt")]
+    pub joined_at: DateTime<Utc>,
+}
+
+impl From<Membership> for OrganizationExport {
+    fn from(membership: Membership) -> Self {
+        OrganizationExport {
+            id: membership.organization.id,
+            name: membership.organization.name.as_ref().to_owned(),
+            roles: membership.roles.iter().map(|role| role.as_ref().to_owned()).collect(),
+            joined_at: membership.joined_at,
+        }
+    }
+}
+
+#[tracing::instrument(name = "Export account", skip_all)]
+pub async fn export_account(State(state): State<AppState>, user: AuthenticatedUser) -> Result<impl IntoResponse, AuthAPIError> {
+    let stored_user = state
+        .user_store
+        .read()
+        .await
+        .get_user(&user.email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let trusted_devices = state
+        .trusted_device_store
+        .read()
+        .await
+        .get_devices(&user.email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let pending_email_change = match state.email_change_store.read().await.get_change(&user.email).await {
+        Ok(change) => Some(EmailChangeExport {
+            new_email: change.new_email.0.expose_secret().to_owned(),
+            old_email_confirmed: change.old_email_confirmed,
+            new_email_confirmed: change.new_email_confirmed,
+        }),
+        Err(EmailChangeStoreError::ChangeNotFound) => None,
+        Err(e) => return Err(AuthAPIError::UnexpectedError(eyre!(e))),
+    };
+
+    let memberships = state
+        .organization_store
+        .read()
+        .await
+        .list_memberships(&user.email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let roles = state
+        .role_store
+        .read()
+        .await
+        .get_user_roles(&user.email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let known_devices = state
+        .device_history_store
+        .read()
+        .await
+        .get_devices(&user.email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let audit_events = state
+        .audit_log
+        .read()
+        .await
+        .list_events(&AuditSearch {
+            actor: Some(user.email.clone()),
+            event_types: Vec::new(),
+            offset: 0,
+            // As many as the stores can page through
+            limit: i64::MAX.unsigned_abs(),
+        })
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?
+        .events;
+
+    let response = Json(AccountExport {
+        user: UserExport {
+            email: stored_user.email.0.expose_secret().to_owned(),
+            display_name: stored_user.profile.display_name.map(|name| name.as_ref().to_owned()),
+            locale: stored_user.profile.locale.map(|locale| locale.as_ref().to_owned()),
+            timezone: stored_user.profile.timezone.map(|timezone| timezone.as_ref().to_owned()),
+            requires_2fa: stored_user.requires_2fa,
+            email_verified: stored_user.email_verified,
+            has_password: stored_user.password.is_some(),
+            roles: roles.iter().map(|role| role.as_ref().to_owned()).collect(),
+            status: AccountStatusResponse::from(stored_user.account_state),
+            created_at: stored_user.created_at,
+            last_login_at: stored_user.last_login_at,
+        },
+        trusted_devices,
+        pending_email_change,
+        organizations: memberships.into_iter().map(OrganizationExport::from).collect(),
+        known_devices: known_devices.into_iter().map(KnownDeviceExport::from).collect(),
+        audit_events: audit_events.into_iter().map(AuditEventResponse::from).collect(),
+    });
+
+    Ok((StatusCode::OK, response))
+}
+
+async fn reauthenticate(state: &AppState, email: &Email, password: &SecretString) -> Result<(), AuthAPIError> {
+    state
+        .user_store
+        .read()
+        .await
+        .get_user(email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?
+        .password
+        // Passwordless accounts have to set a password through the reset flow first
+        .ok_or(AuthAPIError::IncorrectCredentials)?
+        .verify_raw_password(password.expose_secret())
+        .await
+        .map_err(|e| hashing_error(e, AuthAPIError::IncorrectCredentials))
+}
+
+// Removes the user and everything keyed by their email from every store. The account itself goes last, so that the
+// user can sign in and delete it again should a step fail, and every step is a no-op once done.
+async fn purge_user(state: &AppState, email: &Email) -> Result<(), AuthAPIError> {
+    // Signed out everywhere first, so that no session outlives the account should a later step fail
+    revoke_sessions(state, email).await?;
+
+    match state.email_change_store.write().await.remove_change(email).await {
+        Ok(()) | Err(EmailChangeStoreError::ChangeNotFound) => {}
+        Err(e) => return Err(AuthAPIError::UnexpectedError(eyre!(e))),
+    }
+
+    state
+        .one_time_token_store
+        .write()
+        .await
+        .remove_tokens(email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    state
+        .role_store
+        .write()
+        .await
+        .remove_user_roles(email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    state
+        .organization_store
+        .write()
+        .await
+        .remove_memberships(email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    state
+        .invitation_store
+        .write()
+        .await
+        .remove_invitations(email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    state
+        .login_failure_store
+        .write()
+        .await
+        .clear_failures(email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    state
+        .password_history_store
+        .write()
+        .await
+        .remove_passwords(email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    state
+        .device_history_store
+        .write()
+        .await
+        .remove_devices(email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    // The events stay for security reviews, but no longer tell who made them
+    state
+        .audit_log
+        .write()
+        .await
+        .forget_actor(email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    match state.user_store.write().await.delete_user(email).await {
+        Ok(()) | Err(UserStoreError::UserNotFound) => Ok(()),
+        Err(e) => Err(AuthAPIError::UnexpectedError(eyre!(e))),
+    }
+}
@@ -0,0 +1,478 @@
+use crate::app_state::AppState;
+use crate::domain::account_status::{AccountState, AccountStatus, StatusReason};
+use crate::domain::data_stores::{RoleStoreError, UserStoreError};
+use crate::domain::email::Email;
+use crate::domain::error::AuthAPIError;
+use crate::domain::hashed_password::HashedPassword;
+use crate::domain::rbac::RoleName;
+use crate::domain::user::{User, UserSearch};
+use crate::routes::send_password_reset_link;
+use crate::utils::auth::{record_password, revoke_sessions};
+use crate::utils::hashing_executor::HASHING_EXECUTOR;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::eyre;
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_USERS_PER_PAGE: u64 = 20;
+const MAX_USERS_PER_PAGE: u64 = 100;
+const MAX_IMPORTED_USERS: usize = 1000;
+
+#[derive(Deserialize)]
+pub struct ListUsersQuery {
+    search: Option<String>,
+    // Pages are numbered from 1
+    page: Option<u64>,
+    #[serde(rename = "perPage")]
+    per_page: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct AdminUserSummary {
+    pub email: String,
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    #[serde(rename = "requires2FA")]
+    pub requires_2fa: bool,
+    #[serde(rename = "emailVerified")]
+    pub email_verified: bool,
+    // Assigned roles, without the default role every user has
+    pub roles: Vec<String>,
+    pub status: AccountStatusResponse,
+    #[serde(rename = "hasPassword")]
+    pub has_password: bool,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "lastLoginAt")]
+    pub last_login_at: Option<DateTime<Utc>>,
+}
+
+impl AdminUserSummary {
+    fn new(user: User, roles: Vec<RoleName>) -> Self {
+        AdminUserSummary {
+            email: user.email.0.expose_secret().to_owned(),
+            display_name: user.profile.display_name.map(|name| name.as_ref().to_owned()),
+            locale: user.profile.locale.map(|locale| locale.as_ref().to_owned()),
+            timezone: user.profile.timezone.map(|timezone| timezone.as_ref().to_owned()),
+            requires_2fa: user.requires_2fa,
+            email_verified: user.email_verified,
+            roles: roles.iter().map(|role| role.as_ref().to_owned()).collect(),
+            status: AccountStatusResponse::from(user.account_state),
+            has_password: user.password.is_some(),
+            created_at: user.created_at,
+            last_login_at: user.last_login_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct AccountStatusResponse {
+    pub status: String,
+    pub reason: Option<String>,
+    #[serde(rename = "changedAt")]
+    pub changed_at: DateTime<Utc>,
+    #[serde(rename = "suspendedUntil")]
+    pub suspended_until: Option<DateTime<Utc>>,
+}
+
+impl From<AccountState> for AccountStatusResponse {
+    fn from(account_state: AccountState) -> Self {
+        AccountStatusResponse {
+            status: account_state.status.as_ref().to_owned(),
+            reason: account_state.reason.map(|reason| reason.as_ref().to_owned()),
+            changed_at: account_state.changed_at,
+            suspended_until: account_state.suspended_until,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SetAccountStatusRequest {
+    status: String,
+    reason: Option<String>,
+    // Only for suspensions, which last until reactivation when omitted
+    #[serde(rename = "suspendedUntil")]
+    suspended_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct AdminUserList {
+    pub users: Vec<AdminUserSummary>,
+    pub page: u64,
+    #[serde(rename = "perPage")]
+    pub per_page: u64,
+    pub total: u64,
+}
+
+#[tracing::instrument(name = "List users", skip_all)]
+pub async fn list_users(
+    State(state): State<AppState>,
+    Query(query): Query<ListUsersQuery>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(DEFAULT_USERS_PER_PAGE).clamp(1, MAX_USERS_PER_PAGE);
+
+    let search = UserSearch {
+        query: query.search.filter(|search| !search.trim().is_empty()),
+        offset: page.saturating_sub(1).saturating_mul(per_page),
+        limit: per_page,
+    };
+
+    let result = state
+        .user_store
+        .read()
+        .await
+        .list_users(&search)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let mut users = Vec::with_capacity(result.users.len());
+    for user in result.users {
+        let roles = get_user_roles(&state, &user.email).await?;
+        users.push(AdminUserSummary::new(user, roles));
+    }
+
+    let response = Json(AdminUserList {
+        users,
+        page,
+        per_page,
+        total: result.total,
+    });
+
+    Ok((StatusCode::OK, response))
+}
+
+#[tracing::instrument(name = "Get user", skip_all)]
+pub async fn get_user(State(state): State<AppState>, Path(email): Path<String>) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = parse_email(email)?;
+
+    let user = state
+        .user_store
+        .read()
+        .await
+        .get_user(&email)
+        .await
+        .map_err(map_user_store_error)?;
+
+    let roles = get_user_roles(&state, &email).await?;
+
+    Ok((StatusCode::OK, Json(AdminUserSummary::new(user, roles))))
+}
+
+// Users who are no longer active are signed out everywhere and can't log in until reactivated
+#[tracing::instrument(name = "Set account status", skip_all)]
+pub async fn set_account_status(
+    State(state): State<AppState>,
+    Path(email): Path<String>,
+    Json(request): Json<SetAccountStatusRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = parse_email(email)?;
+
+    let status = AccountStatus::parse(&request.status).map_err(|_| AuthAPIError::InvalidAccountStatus)?;
+    let reason = request
+        .reason
+        .as_deref()
+        .map(StatusReason::parse)
+        .transpose()
+        .map_err(|_| AuthAPIError::InvalidAccountStatus)?;
+
+    let account_state = match (status, request.suspended_until) {
+        (AccountStatus::Suspended, until) => {
+            AccountState::suspended(reason, until).map_err(|_| AuthAPIError::InvalidAccountStatus)?
+        }
+        (_, Some(_)) => return Err(AuthAPIError::InvalidAccountStatus),
+        (status, None) => AccountState::new(status, reason),
+    };
+
+    change_account_state(&state, &email, account_state).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[tracing::instrument(name = "Disable user", skip_all)]
+pub async fn disable_user(State(state): State<AppState>, Path(email): Path<String>) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = parse_email(email)?;
+
+    change_account_state(&state, &email, AccountState::new(AccountStatus::Disabled, None)).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[tracing::instrument(name = "Enable user", skip_all)]
+pub async fn enable_user(State(state): State<AppState>, Path(email): Path<String>) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = parse_email(email)?;
+
+    change_account_state(&state, &email, AccountState::default()).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Takes effect from the next login, existing sessions are kept
+#[tracing::instrument(name = "Force 2FA", skip_all)]
+pub async fn force_2fa(State(state): State<AppState>, Path(email): Path<String>) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = parse_email(email)?;
+
+    state
+        .user_store
+        .write()
+        .await
+        .set_requires_2fa(&email, true)
+        .await
+        .map_err(map_user_store_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Drops the current password, so that the user has to choose a new one through the emailed link
+#[tracing::instrument(name = "Admin reset password", skip_all)]
+pub async fn admin_reset_password(
+    State(state): State<AppState>,
+    Path(email): Path<String>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = parse_email(email)?;
+
+    state
+        .user_store
+        .write()
+        .await
+        .clear_password(&email)
+        .await
+        .map_err(map_user_store_error)?;
+
+    revoke_sessions(&state, &email).await?;
+    send_password_reset_link(&state, &email).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Load of the password hashing queue, to tell whether it is sized right for the traffic
+#[tracing::instrument(name = "Get hashing metrics", skip_all)]
+pub async fn get_hashing_metrics() -> impl IntoResponse {
+    (StatusCode::OK, Json(HASHING_EXECUTOR.metrics()))
+}
+
+#[tracing::instrument(name = "Revoke user sessions", skip_all)]
+pub async fn revoke_user_sessions(
+    State(state): State<AppState>,
+    Path(email): Path<String>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = parse_email(email)?;
+
+    // Make sure the user exists, so that a typo in the address isn't reported as a success
+    state
+        .user_store
+        .read()
+        .await
+        .get_user(&email)
+        .await
+        .map_err(map_user_store_error)?;
+
+    revoke_sessions(&state, &email).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[tracing::instrument(name = "Assign user role", skip_all)]
+pub async fn assign_user_role(
+    State(state): State<AppState>,
+    Path((email, role)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = parse_email(email)?;
+    let role = RoleName::parse(&role).map_err(|_| AuthAPIError::InvalidRole)?;
+
+    state
+        .user_store
+        .read()
+        .await
+        .get_user(&email)
+        .await
+        .map_err(map_user_store_error)?;
+
+    state
+        .role_store
+        .write()
+        .await
+        .assign_role(&email, &role)
+        .await
+        .map_err(|e| match e {
+            RoleStoreError::RoleNotFound => AuthAPIError::RoleNotFound,
+            e => AuthAPIError::UnexpectedError(eyre!(e)),
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Signs the user out, so that the permissions of the role stop being granted right away
+#[tracing::instrument(name = "Remove user role", skip_all)]
+pub async fn remove_user_role(
+    State(state): State<AppState>,
+    Path((email, role)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = parse_email(email)?;
+    let role = RoleName::parse(&role).map_err(|_| AuthAPIError::InvalidRole)?;
+
+    state
+        .role_store
+        .write()
+        .await
+        .unassign_role(&email, &role)
+        .await
+        .map_err(|e| match e {
+            RoleStoreError::RoleNotAssigned => AuthAPIError::RoleNotFound,
+            e => AuthAPIError::UnexpectedError(eyre!(e)),
+        })?;
+
+    revoke_sessions(&state, &email).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Lets a locked out user log in again right away, forgetting their failed logins
+#[tracing::instrument(name = "Clear account lockout", skip_all)]
+pub async fn clear_lockout(State(state): State<AppState>, Path(email): Path<String>) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = parse_email(email)?;
+
+    state
+        .login_failure_store
+        .write()
+        .await
+        .clear_failures(&email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub struct ImportUsersRequest {
+    users: Vec<ImportedUser>,
+}
+
+#[derive(Deserialize)]
+pub struct ImportedUser {
+    email: SecretString,
+    // Argon2, bcrypt, scrypt or PBKDF2-SHA256 hash from the old system, without one the user signs in by magic link
+    #[serde(rename = "passwordHash")]
+    password_hash: Option<SecretString>,
+    #[serde(rename = "emailVerified", default)]
+    email_verified: bool,
+    #[serde(rename = "requires2FA", default)]
+    requires_2fa: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ImportUsersResponse {
+    pub imported: u64,
+    // Users left out, the others are imported regardless
+    pub failed: Vec<ImportFailure>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ImportFailure {
+    pub email: String,
+    pub error: String,
+}
+
+// Creates users moved from another system along with their password hashes, which are replaced by Argon2id ones
+// the first time each user logs in
+#[tracing::instrument(name = "Import users", skip_all)]
+pub async fn import_users(
+    State(state): State<AppState>,
+    Json(request): Json<ImportUsersRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    if request.users.len() > MAX_IMPORTED_USERS {
+        return Err(AuthAPIError::TooManyUsers);
+    }
+
+    let mut imported: u64 = 0;
+    let mut failed = Vec::new();
+
+    for imported_user in request.users {
+        let email_string = imported_user.email.expose_secret().to_owned();
+        let failure = |error: &str| ImportFailure {
+            email: email_string.clone(),
+            error: error.to_owned(),
+        };
+
+        let Ok(email) = Email::parse(imported_user.email) else {
+            failed.push(failure("Invalid email"));
+            continue;
+        };
+
+        let password = match imported_user
+            .password_hash
+            .map(HashedPassword::parse_password_hash)
+            .transpose()
+        {
+            Ok(password) => password,
+            Err(_) => {
+                failed.push(failure("Unsupported password hash"));
+                continue;
+            }
+        };
+
+        let mut user = User::new(email.clone(), password.clone(), imported_user.requires_2fa);
+        user.email_verified = imported_user.email_verified;
+
+        match state.user_store.write().await.add_user(user).await {
+            Ok(()) => {}
+            Err(UserStoreError::UserAlreadyExists) => {
+                failed.push(failure("User already exists"));
+                continue;
+            }
+            Err(e) => return Err(AuthAPIError::UnexpectedError(eyre!(e))),
+        }
+
+        if let Some(password) = password {
+            record_password(&state, &email, password).await?;
+        }
+
+        imported = imported.saturating_add(1);
+    }
+
+    Ok((StatusCode::OK, Json(ImportUsersResponse { imported, failed })))
+}
+
+fn parse_email(email: String) -> Result<Email, AuthAPIError> {
+    Email::parse(SecretString::from(email)).map_err(|_| AuthAPIError::InvalidCredentials)
+}
+
+async fn get_user_roles(state: &AppState, email: &Email) -> Result<Vec<RoleName>, AuthAPIError> {
+    state
+        .role_store
+        .read()
+        .await
+        .get_user_roles(email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))
+}
+
+fn map_user_store_error(e: UserStoreError) -> AuthAPIError {
+    match e {
+        UserStoreError::UserNotFound => AuthAPIError::UserNotFound,
+        e => AuthAPIError::UnexpectedError(eyre!(e)),
+    }
+}
+
+async fn change_account_state(state: &AppState, email: &Email, account_state: AccountState) -> Result<(), AuthAPIError> {
+    let is_active = account_state.status == AccountStatus::Active;
+
+    state
+        .user_store
+        .write()
+        .await
+        .set_account_state(email, account_state)
+        .await
+        .map_err(map_user_store_error)?;
+
+    match is_active {
+        true => Ok(()),
+        false => revoke_sessions(state, email).await,
+    }
+}
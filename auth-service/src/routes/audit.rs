@@ -0,0 +1,149 @@
+use crate::app_state::AppState;
+use crate::domain::audit::{AuditEvent, AuditEventType, AuditSearch};
+use crate::domain::email::Email;
+use crate::domain::error::AuthAPIError;
+use crate::utils::auth::AuthenticatedUser;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::eyre;
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_EVENTS_PER_PAGE: u64 = 20;
+const MAX_EVENTS_PER_PAGE: u64 = 100;
+
+// Events a user sees in their login history
+const LOGIN_EVENT_TYPES: [AuditEventType; 2] = [AuditEventType::Login, AuditEventType::Verify2FA];
+
+#[derive(Deserialize)]
+pub struct ListAuditEventsQuery {
+    actor: Option<String>,
+    #[serde(rename = "eventType")]
+    event_type: Option<String>,
+    // Pages are numbered from 1
+    page: Option<u64>,
+    #[serde(rename = "perPage")]
+    per_page: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct LoginHistoryQuery {
+    // Pages are numbered from 1
+    page: Option<u64>,
+    #[serde(rename = "perPage")]
+    per_page: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct AuditEventResponse {
+    #[serde(rename = "occurredAt")]
+    pub occurred_at: DateTime<Utc>,
+    pub actor: Option<String>,
+    pub ip: Option<String>,
+    #[serde(rename = "userAgent")]
+    pub user_agent: Option<String>,
+    #[serde(rename = "eventType")]
+    pub event_type: String,
+    pub outcome: String,
+    pub reason: Option<String>,
+}
+
+impl From<AuditEvent> for AuditEventResponse {
+    fn from(event: AuditEvent) -> Self {
+        AuditEventResponse {
+            occurred_at: event.occurred_at,
+            actor: event.actor.map(|actor| actor.0.expose_secret().to_owned()),
+            ip: event.ip.map(|ip| ip.to_string()),
+            user_agent: event.user_agent,
+            event_type: event.event_type.as_ref().to_owned(),
+            outcome: event.outcome.as_ref().to_owned(),
+            reason: event.reason,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct AuditEventList {
+    pub events: Vec<AuditEventResponse>,
+    pub page: u64,
+    #[serde(rename = "perPage")]
+    pub per_page: u64,
+    pub total: u64,
+}
+
+#[tracing::instrument(name = "List audit events", skip_all)]
+pub async fn list_audit_events(
+    State(state): State<AppState>,
+    Query(query): Query<ListAuditEventsQuery>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let actor = query
+        .actor
+        .map(|actor| Email::parse(SecretString::from(actor)))
+        .transpose()
+        .map_err(|_| AuthAPIError::InvalidCredentials)?;
+    let event_types = query
+        .event_type
+        .as_deref()
+        .map(AuditEventType::parse)
+        .transpose()
+        .map_err(|_| AuthAPIError::InvalidAuditEventType)?;
+
+    let response = list_events(&state, actor, event_types.into_iter().collect(), query.page, query.per_page).await?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+// The user's own logins, failed ones included, so that they can spot the ones that weren't them
+#[tracing::instrument(name = "Get login history", skip_all)]
+pub async fn get_login_history(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Query(query): Query<LoginHistoryQuery>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let response = list_events(
+        &state,
+        Some(user.email),
+        LOGIN_EVENT_TYPES.to_vec(),
+        query.page,
+        query.per_page,
+    )
+    .await?;
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+async fn list_events(
+    state: &AppState,
+    actor: Option<Email>,
+    event_types: Vec<AuditEventType>,
+    page: Option<u64>,
+    per_page: Option<u64>,
+) -> Result<AuditEventList, AuthAPIError> {
+    let page = page.unwrap_or(1).max(1);
+    let per_page = per_page.unwrap_or(DEFAULT_EVENTS_PER_PAGE).clamp(1, MAX_EVENTS_PER_PAGE);
+
+    let search = AuditSearch {
+        actor,
+        event_types,
+        offset: page.saturating_sub(1).saturating_mul(per_page),
+        limit: per_page,
+    };
+
+    let result = state
+        .audit_log
+        .read()
+        .await
+        .list_events(&search)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    Ok(AuditEventList {
+        events: result.events.into_iter().map(AuditEventResponse::from).collect(),
+        page,
+        per_page,
+        total: result.total,
+    })
+}
@@ -0,0 +1,265 @@
+use crate::app_state::AppState;
+use crate::domain::data_stores::{EmailChangeStoreError, TokenPurpose, TwoFACodeStoreError, UserStoreError};
+use crate::domain::email::Email;
+use crate::domain::email_change::EmailChange;
+use crate::domain::error::AuthAPIError;
+use crate::utils::auth::{generate_one_time_token, validate_one_time_token, AuthenticatedUser};
+use crate::utils::constants::AUTH_SERVICE_URL;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use color_eyre::eyre::eyre;
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct ChangeEmailRequest {
+    #[serde(rename = "newEmail")]
+    new_email: SecretString,
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmEmailChangeQuery {
+    token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ChangeEmailResponse {
+    pub message: String,
+}
+
+#[tracing::instrument(name = "Request email change", skip_all)]
+pub async fn request_email_change(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(request): Json<ChangeEmailRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let new_email = Email::parse(request.new_email.expose_secret().into()).map_err(|_| AuthAPIError::InvalidCredentials)?;
+
+    if new_email == user.email {
+        return Err(AuthAPIError::InvalidCredentials);
+    }
+
+    match state.user_store.read().await.get_user(&new_email).await {
+        Ok(_) => return Err(AuthAPIError::UserAlreadyExists),
+        Err(UserStoreError::UserNotFound) => {}
+        Err(e) => return Err(AuthAPIError::UnexpectedError(eyre!(e))),
+    }
+
+    // Both tokens identify the account by its current address
+    let (old_email_token, old_email_token_id) =
+        generate_one_time_token(&user.email, TokenPurpose::EmailChange).map_err(AuthAPIError::UnexpectedError)?;
+    let (new_email_token, new_email_token_id) =
+        generate_one_time_token(&user.email, TokenPurpose::EmailChange).map_err(AuthAPIError::UnexpectedError)?;
+
+    state
+        .email_change_store
+        .write()
+        .await
+        .add_change(EmailChange::new(
+            user.email.clone(),
+            new_email.clone(),
+            old_email_token_id,
+            new_email_token_id,
+        ))
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let email_client = state.email_client.read().await;
+
+    email_client
+        .send_email(
+            &user.email,
+            "Confirm your email address change",
+            format!(
+                "Confirm that your account should use {} from now on by following this link: {}",
+                new_email.0.expose_secret(),
+                confirmation_link(&old_email_token)
+            )
+            .as_str(),
+        )
+        .await
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    email_client
+        .send_email(
+            &new_email,
+            "Confirm your new email address",
+            format!(
+                "Confirm this address for your account by following this link: {}",
+                confirmation_link(&new_email_token)
+            )
+            .as_str(),
+        )
+        .await
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    let response = Json(ChangeEmailResponse {
+        message: "Confirmation links have been sent to both addresses".into(),
+    });
+
+    Ok((StatusCode::OK, response))
+}
+
+#[tracing::instrument(name = "Confirm email change", skip_all)]
+pub async fn confirm_email_change(
+    State(state): State<AppState>,
+    Query(query): Query<ConfirmEmailChangeQuery>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let claims = validate_one_time_token(&query.token, TokenPurpose::EmailChange).map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let old_email = Email::parse(SecretString::from(claims.sub)).map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let change = state
+        .email_change_store
+        .write()
+        .await
+        .confirm_change(&old_email, &claims.jti)
+        .await
+        .map_err(|e| match e {
+            EmailChangeStoreError::ChangeNotFound => AuthAPIError::InvalidToken,
+            e => AuthAPIError::UnexpectedError(eyre!(e)),
+        })?;
+
+    if !change.is_confirmed() {
+        let response = Json(ChangeEmailResponse {
+            message: "Address confirmed, waiting for the confirmation of the other address".into(),
+        });
+
+        return Ok((StatusCode::OK, response));
+    }
+
+    // The change stays pending until every step went through, so that following either link again
+    // finishes a swap that failed halfway
+    let updated = state
+        .user_store
+        .write()
+        .await
+        .update_email(&change.old_email, &change.new_email)
+        .await;
+
+    match updated {
+        Ok(()) => {}
+        // The new address was used to sign up while the change was pending
+        Err(UserStoreError::UserAlreadyExists) => {
+            remove_email_change(&state, &old_email).await?;
+            return Err(AuthAPIError::UserAlreadyExists);
+        }
+        // An earlier attempt already moved the account
+        Err(UserStoreError::UserNotFound) if user_exists(&state, &change.new_email).await? => {}
+        Err(UserStoreError::UserNotFound) => return Err(AuthAPIError::InvalidToken),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(eyre!(e))),
+    }
+
+    move_email_keyed_entries(&state, &change.old_email, &change.new_email).await?;
+
+    remove_email_change(&state, &old_email).await?;
+
+    let response = Json(ChangeEmailResponse {
+        message: "Email address changed, please log in again".into(),
+    });
+
+    Ok((StatusCode::OK, response))
+}
+
+// Sessions and links sent to the old address are bound to it, so they end with the change. Every step
+// is safe to repeat, in Postgres the user's rows have already moved with `update_email`
+#[tracing::instrument(name = "Move email-keyed entries", skip_all)]
+async fn move_email_keyed_entries(state: &AppState, old_email: &Email, new_email: &Email) -> Result<(), AuthAPIError> {
+    state
+        .banned_token_store
+        .write()
+        .await
+        .revoke_user_tokens(old_email, None)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    match state.two_fa_code_store.write().await.remove_code(old_email).await {
+        Ok(()) | Err(TwoFACodeStoreError::LoginAttemptIdNotFound) => {}
+        Err(e) => return Err(AuthAPIError::UnexpectedError(eyre!(e))),
+    }
+
+    state
+        .one_time_token_store
+        .write()
+        .await
+        .remove_tokens(old_email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    state
+        .login_failure_store
+        .write()
+        .await
+        .move_failures(old_email, new_email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    state
+        .password_history_store
+        .write()
+        .await
+        .move_passwords(old_email, new_email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    state
+        .device_history_store
+        .write()
+        .await
+        .move_devices(old_email, new_email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    state
+        .audit_log
+        .write()
+        .await
+        .move_actor(old_email, new_email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    state
+        .trusted_device_store
+        .write()
+        .await
+        .move_devices(old_email, new_email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    state
+        .role_store
+        .write()
+        .await
+        .move_user_roles(old_email, new_email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    state
+        .organization_store
+        .write()
+        .await
+        .move_memberships(old_email, new_email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))
+}
+
+async fn user_exists(state: &AppState, email: &Email) -> Result<bool, AuthAPIError> {
+    match state.user_store.read().await.get_user(email).await {
+        Ok(_) => Ok(true),
+        Err(UserStoreError::UserNotFound) => Ok(false),
+        Err(e) => Err(AuthAPIError::UnexpectedError(eyre!(e))),
+    }
+}
+
+async fn remove_email_change(state: &AppState, old_email: &Email) -> Result<(), AuthAPIError> {
+    match state.email_change_store.write().await.remove_change(old_email).await {
+        Ok(()) | Err(EmailChangeStoreError::ChangeNotFound) => Ok(()),
+        Err(e) => Err(AuthAPIError::UnexpectedError(eyre!(e))),
+    }
+}
+
+fn confirmation_link(token: &str) -> String {
+    format!("{}/email/change/confirm?token={}", AUTH_SERVICE_URL.as_str(), token)
+}
@@ -0,0 +1,95 @@
+use crate::app_state::AppState;
+use crate::domain::error::AuthAPIError;
+use crate::utils::auth::{ensure_password_not_reused, hash_new_password, hashing_error, record_password, AuthenticatedUser};
+use crate::utils::constants::AUTH_SERVICE_URL;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use color_eyre::eyre::eyre;
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct ChangePasswordRequest {
+    #[serde(rename = "currentPassword")]
+    current_password: SecretString,
+    #[serde(rename = "newPassword")]
+    new_password: SecretString,
+    // Sign out every other session, keeping the one making the request
+    #[serde(rename = "revokeOtherSessions", default)]
+    revoke_other_sessions: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ChangePasswordResponse {
+    pub message: String,
+}
+
+#[tracing::instrument(name = "Change password", skip_all)]
+pub async fn change_password(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(request): Json<ChangePasswordRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let current_password = state
+        .user_store
+        .read()
+        .await
+        .get_user(&user.email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?
+        .password
+        // Passwordless accounts have to go through the reset flow to set a first password
+        .ok_or(AuthAPIError::IncorrectCredentials)?;
+
+    current_password
+        .verify_raw_password(request.current_password.expose_secret())
+        .await
+        .map_err(|e| hashing_error(e, AuthAPIError::IncorrectCredentials))?;
+
+    ensure_password_not_reused(&state, &user.email, &request.new_password).await?;
+    let new_password = hash_new_password(&state, &user.email, request.new_password).await?;
+
+    state
+        .user_store
+        .write()
+        .await
+        .update_password(&user.email, new_password.clone())
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    record_password(&state, &user.email, new_password).await?;
+
+    if request.revoke_other_sessions {
+        state
+            .banned_token_store
+            .write()
+            .await
+            .revoke_user_tokens(&user.email, Some(&user.token))
+            .await
+            .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+    }
+
+    state
+        .email_client
+        .read()
+        .await
+        .send_email(
+            &user.email,
+            "Your password was changed",
+            format!(
+                "The password of your account was just changed. If you didn't do it, reset your password right away: {}/password/forgot",
+                AUTH_SERVICE_URL.as_str()
+            )
+            .as_str(),
+        )
+        .await
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    let response = Json(ChangePasswordResponse {
+        message: "Password changed successfully".into(),
+    });
+
+    Ok((StatusCode::OK, response))
+}
@@ -0,0 +1,195 @@
+use crate::app_state::AppState;
+use crate::domain::data_stores::{InvitationStoreError, OrganizationStoreError, TokenPurpose};
+use crate::domain::email::Email;
+use crate::domain::error::AuthAPIError;
+use crate::domain::invitation::Invitation;
+use crate::domain::organization::OrganizationId;
+use crate::domain::rbac::RoleName;
+use crate::utils::auth::{generate_one_time_token, AuthenticatedUser};
+use crate::utils::constants::AUTH_SERVICE_URL;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use chrono::{DateTime, Duration, Utc};
+use color_eyre::eyre::{eyre, ContextCompat};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct CreateInvitationRequest {
+    email: SecretString,
+    role: Option<String>,
+    #[serde(rename = "organizationId")]
+    organization_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct InvitationResponse {
+    pub id: String,
+    pub email: String,
+    pub role: Option<String>,
+    #[serde(rename = "organizationId")]
+    pub organization_id: Option<OrganizationId>,
+    #[serde(rename = "invitedBy")]
+    pub invited_by: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+}
+
+impl From<Invitation> for InvitationResponse {
+    fn from(invitation: Invitation) -> Self {
+        InvitationResponse {
+            id: invitation.id,
+            email: invitation.email.0.expose_secret().to_owned(),
+            role: invitation.role.map(|role| role.as_ref().to_owned()),
+            organization_id: invitation.organization_id,
+            invited_by: invitation.invited_by.0.expose_secret().to_owned(),
+            created_at: invitation.created_at,
+            expires_at: invitation.expires_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct InvitationListResponse {
+    pub invitations: Vec<InvitationResponse>,
+}
+
+// Emails a signed signup link to the invited address. Only roles whose permissions the admin already has can be given.
+#[tracing::instrument(name = "Create invitation", skip_all)]
+pub async fn create_invitation(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(request): Json<CreateInvitationRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = Email::parse(request.email).map_err(|_| AuthAPIError::InvalidCredentials)?;
+
+    if state.user_store.read().await.get_user(&email).await.is_ok() {
+        return Err(AuthAPIError::UserAlreadyExists);
+    }
+
+    let role = match request.role {
+        Some(role) => Some(RoleName::parse(&role).map_err(|_| AuthAPIError::InvalidRole)?),
+        None => None,
+    };
+
+    if let Some(role) = &role {
+        let roles = state
+            .role_store
+            .read()
+            .await
+            .list_roles()
+            .await
+            .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+        let role = roles
+            .iter()
+            .find(|existing| &existing.name == role)
+            .ok_or(AuthAPIError::RoleNotFound)?;
+
+        if !user.grants.covers(role) {
+            return Err(AuthAPIError::Forbidden);
+        }
+    }
+
+    let organization_id = match request.organization_id {
+        Some(id) => Some(OrganizationId::parse(&id).map_err(|_| AuthAPIError::InvalidOrganization)?),
+        None => None,
+    };
+
+    if let Some(organization_id) = &organization_id {
+        state
+            .organization_store
+            .read()
+            .await
+            .get_organization(organization_id)
+            .await
+            .map_err(|e| match e {
+                OrganizationStoreError::OrganizationNotFound => AuthAPIError::OrganizationNotFound,
+                e => AuthAPIError::UnexpectedError(eyre!(e)),
+            })?;
+    }
+
+    let (token, token_id) = generate_one_time_token(&email, TokenPurpose::Invitation).map_err(AuthAPIError::UnexpectedError)?;
+
+    let created_at = Utc::now();
+    let expires_at = Duration::try_seconds(TokenPurpose::Invitation.ttl_seconds())
+        .and_then(|ttl| created_at.checked_add_signed(ttl))
+        .wrap_err("Failed to compute the invitation expiration time")
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    let invitation = Invitation {
+        id: token_id,
+        email: email.clone(),
+        role,
+        organization_id,
+        invited_by: user.email,
+        created_at,
+        expires_at,
+    };
+
+    state
+        .invitation_store
+        .write()
+        .await
+        .add_invitation(invitation.clone())
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let link = format!("{}/signup?invite={}", AUTH_SERVICE_URL.as_str(), token);
+
+    state
+        .email_client
+        .read()
+        .await
+        .send_email(
+            &email,
+            "You have been invited",
+            format!(
+                "You have been invited to create an account. Sign up by following this link: {}",
+                link
+            )
+            .as_str(),
+        )
+        .await
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    Ok((StatusCode::CREATED, Json(InvitationResponse::from(invitation))))
+}
+
+#[tracing::instrument(name = "List invitations", skip_all)]
+pub async fn list_invitations(State(state): State<AppState>) -> Result<impl IntoResponse, AuthAPIError> {
+    let invitations = state
+        .invitation_store
+        .read()
+        .await
+        .list_invitations()
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let response = Json(InvitationListResponse {
+        invitations: invitations.into_iter().map(InvitationResponse::from).collect(),
+    });
+
+    Ok((StatusCode::OK, response))
+}
+
+// The invite link stops working right away
+#[tracing::instrument(name = "Revoke invitation", skip_all)]
+pub async fn revoke_invitation(State(state): State<AppState>, Path(id): Path<String>) -> Result<impl IntoResponse, AuthAPIError> {
+    state
+        .invitation_store
+        .write()
+        .await
+        .remove_invitation(&id)
+        .await
+        .map_err(|e| match e {
+            InvitationStoreError::InvitationNotFound => AuthAPIError::InvitationNotFound,
+            e => AuthAPIError::UnexpectedError(eyre!(e)),
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
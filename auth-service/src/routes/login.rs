@@ -1,13 +1,23 @@
 use crate::app_state::AppState;
-use crate::domain::data_stores::{LoginAttemptId, TwoFACode};
+use crate::domain::audit::AuditEventType;
+use crate::domain::data_stores::{LoginAttemptId, TrustedDeviceStoreError, TwoFACode, UserStoreError};
 use crate::domain::email::Email;
 use crate::domain::error::AuthAPIError;
-use crate::utils::auth::generate_auth_cookie;
+use crate::domain::login_failures::LockoutPolicy;
+use crate::routes::notify_new_device;
+use crate::utils::audit::{record_audit_event, ClientInfo};
+use crate::utils::auth::{
+    ensure_account_active, generate_auth_cookie, get_user_grants, upgrade_password_hash, validate_trusted_device_token,
+};
+use crate::utils::constants::env::TRUSTED_DEVICE_COOKIE_NAME;
+use crate::utils::constants::AUTH_SERVICE_URL;
+use crate::utils::hashing_executor::HashingOverloaded;
 use axum::extract::State;
 use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::response::Response;
 use axum::Json;
 use axum_extra::extract::CookieJar;
+use chrono::Utc;
 use color_eyre::eyre::eyre;
 use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
@@ -38,21 +48,45 @@ pub struct TwoFactorAuthResponse {
 #[tracing::instrument(name = "Login", skip_all)]
 pub async fn login(
     State(state): State<AppState>,
+    client: ClientInfo,
     jar: CookieJar,
     Json(request): Json<LoginRequest>,
-) -> (CookieJar, Result<impl IntoResponse, AuthAPIError>) {
+) -> (CookieJar, Response) {
+    let actor = Email::parse(request.email.expose_secret().into()).ok();
+    let (jar, result) = attempt_login(&state, &client, jar, request).await;
+
+    (
+        jar,
+        record_audit_event(&state, client, AuditEventType::Login, actor, result).await,
+    )
+}
+
+async fn attempt_login(
+    state: &AppState,
+    client: &ClientInfo,
+    jar: CookieJar,
+    request: LoginRequest,
+) -> (CookieJar, Result<(StatusCode, Json<LoginResponse>), AuthAPIError>) {
     let email = match Email::parse(request.email.expose_secret().into()) {
         Ok(email) => email,
         Err(_) => return (jar, Err(AuthAPIError::InvalidCredentials)),
     };
 
+    if let Err(e) = ensure_not_locked(&email, state).await {
+        return (jar, Err(e));
+    }
+
     let user_store = state.user_store.read().await;
-    if user_store
-        .validate_user(&email, &request.password.expose_secret())
-        .await
-        .is_err()
-    {
-        return (jar, Err(AuthAPIError::IncorrectCredentials));
+    match user_store.validate_user(&email, &request.password.expose_secret()).await {
+        Ok(()) => {}
+        // The password couldn't be checked, which must not count towards a lockout
+        Err(UserStoreError::UnexpectedError(e)) if HashingOverloaded::caused(&e) => {
+            return (jar, Err(AuthAPIError::ServiceOverloaded));
+        }
+        Err(_) => {
+            drop(user_store);
+            return (jar, Err(record_failed_login(&email, state).await));
+        }
     }
 
     let user = match user_store.get_user(&email).await {
@@ -60,17 +94,120 @@ pub async fn login(
         Err(_) => return (jar, Err(AuthAPIError::InvalidCredentials)),
     };
 
-    let auth_cookie = match generate_auth_cookie(&email) {
-        Ok(cookie) => cookie,
-        Err(e) => return (jar, Err(AuthAPIError::UnexpectedError(eyre!(e)))),
-    };
+    // Release the lock before hitting the other stores
+    drop(user_store);
+
+    if let Some(password) = &user.password {
+        upgrade_password_hash(state, &email, password, request.password);
+    }
 
-    let jar = jar.add(auth_cookie);
+    if let Err(e) = state.login_failure_store.write().await.clear_failures(&email).await {
+        return (jar, Err(AuthAPIError::UnexpectedError(eyre!(e))));
+    }
+
+    if !user.email_verified {
+        return (jar, Err(AuthAPIError::EmailNotVerified));
+    }
+
+    if let Err(e) = ensure_account_active(&user) {
+        return (jar, Err(e));
+    }
 
     // Handle request based on user's 2FA configuration
-    match user.requires_2fa {
-        true => handle_2fa(&user.email, &state, jar).await,
-        false => handle_no_2fa(&user.email, jar).await,
+    if !user.requires_2fa {
+        return handle_no_2fa(&user.email, state, client, jar).await;
+    }
+
+    match is_trusted_device(&user.email, state, &jar).await {
+        Ok(true) => handle_no_2fa(&user.email, state, client, jar).await,
+        Ok(false) => handle_2fa(&user.email, state, jar).await,
+        Err(e) => (jar, Err(e)),
+    }
+}
+
+#[tracing::instrument(name = "Check account lockout", skip_all)]
+async fn ensure_not_locked(email: &Email, state: &AppState) -> Result<(), AuthAPIError> {
+    let failures = state
+        .login_failure_store
+        .read()
+        .await
+        .get_failures(email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    match failures.is_locked(Utc::now()) {
+        true => Err(AuthAPIError::AccountLocked),
+        false => Ok(()),
+    }
+}
+
+// Counts the failure and returns the error to respond with, notifying the user when it locks their account.
+// Unknown emails are counted too, so that a lockout doesn't tell whether an account exists
+#[tracing::instrument(name = "Record failed login", skip_all)]
+async fn record_failed_login(email: &Email, state: &AppState) -> AuthAPIError {
+    let mut login_failure_store = state.login_failure_store.write().await;
+
+    let mut failures = match login_failure_store.get_failures(email).await {
+        Ok(failures) => failures,
+        Err(e) => return AuthAPIError::UnexpectedError(eyre!(e)),
+    };
+
+    let locked_until = failures.record_failure(Utc::now(), &LockoutPolicy::default());
+
+    if let Err(e) = login_failure_store.set_failures(email, failures).await {
+        return AuthAPIError::UnexpectedError(eyre!(e));
+    }
+
+    drop(login_failure_store);
+
+    let Some(locked_until) = locked_until else {
+        return AuthAPIError::IncorrectCredentials;
+    };
+
+    match state.user_store.read().await.get_user(email).await {
+        Ok(_) => {}
+        Err(UserStoreError::UserNotFound) => return AuthAPIError::AccountLocked,
+        Err(e) => return AuthAPIError::UnexpectedError(eyre!(e)),
+    }
+
+    let content = format!(
+        "We locked your account until {} after too many failed login attempts. If it wasn't you, consider resetting your password: {}/password/forgot",
+        locked_until.to_rfc3339(),
+        AUTH_SERVICE_URL.as_str()
+    );
+
+    // The response must not depend on whether the account exists, so a failed email is only logged
+    if let Err(e) = state
+        .email_client
+        .read()
+        .await
+        .send_email(email, "Your account was locked", &content)
+        .await
+    {
+        tracing::error!(error = ?e, "Failed to send the account lock email");
+    }
+
+    AuthAPIError::AccountLocked
+}
+
+// A device is trusted if its cookie was issued for one of the user's devices that has not been revoked since
+#[tracing::instrument(name = "Check trusted device", skip_all)]
+async fn is_trusted_device(email: &Email, state: &AppState, jar: &CookieJar) -> Result<bool, AuthAPIError> {
+    let Some(cookie) = jar.get(TRUSTED_DEVICE_COOKIE_NAME) else {
+        return Ok(false);
+    };
+
+    // The subject isn't compared, the device must be among the user's own devices,
+    // which still holds once the devices followed a change of address
+    let claims = match validate_trusted_device_token(cookie.value()) {
+        Ok(claims) => claims,
+        Err(_) => return Ok(false),
+    };
+
+    match state.trusted_device_store.read().await.get_device(email, &claims.jti).await {
+        Ok(_) => Ok(true),
+        Err(TrustedDeviceStoreError::DeviceNotFound) => Ok(false),
+        Err(e) => Err(AuthAPIError::UnexpectedError(eyre!(e))),
     }
 }
 
@@ -83,11 +220,16 @@ async fn handle_2fa(
     let login_attempt_id = LoginAttemptId::default();
     let two_fa_code = TwoFACode::default();
 
+    let two_fa_code_hash = match two_fa_code.hash(&login_attempt_id) {
+        Ok(hash) => hash,
+        Err(e) => return (jar, Err(AuthAPIError::UnexpectedError(e))),
+    };
+
     if let Err(e) = state
         .two_fa_code_store
         .write()
         .await
-        .add_code(email.clone(), login_attempt_id.clone(), two_fa_code.clone())
+        .add_code(email.clone(), login_attempt_id.clone(), two_fa_code_hash)
         .await
     {
         return (jar, Err(AuthAPIError::UnexpectedError(eyre!(e))));
@@ -122,14 +264,44 @@ async fn handle_2fa(
 #[tracing::instrument(name = "Handle no 2FA flow", skip_all)]
 pub async fn handle_no_2fa(
     email: &Email,
+    state: &AppState,
+    client: &ClientInfo,
     jar: CookieJar,
 ) -> (CookieJar, Result<(StatusCode, Json<LoginResponse>), AuthAPIError>) {
-    let cookie = match generate_auth_cookie(&email) {
+    let mut user_store = state.user_store.write().await;
+
+    // Checked again, the account may have been blocked while the user was entering the 2FA code
+    match user_store.get_user(email).await {
+        Ok(user) => {
+            if let Err(e) = ensure_account_active(&user) {
+                return (jar, Err(e));
+            }
+        }
+        Err(e) => return (jar, Err(AuthAPIError::UnexpectedError(eyre!(e)))),
+    }
+
+    if let Err(e) = user_store.record_login(email, Utc::now()).await {
+        return (jar, Err(AuthAPIError::UnexpectedError(eyre!(e))));
+    }
+
+    drop(user_store);
+
+    let grants = match get_user_grants(state, email, None).await {
+        Ok(grants) => grants,
+        Err(e) => return (jar, Err(e)),
+    };
+
+    let cookie = match generate_auth_cookie(email, grants) {
         Ok(cookie) => cookie,
         Err(e) => return (jar, Err(AuthAPIError::UnexpectedError(eyre!(e)))),
     };
 
     let updated_jar = jar.add(cookie);
 
+    // The user is logged in either way, the email is only a warning
+    if let Err(e) = notify_new_device(state, email, client).await {
+        tracing::error!(error = ?e, "Failed to check the login device");
+    }
+
     (updated_jar, Ok((StatusCode::OK, Json(LoginResponse::RegularAuth))))
 }
@@ -1,15 +1,30 @@
 use crate::app_state::AppState;
+use crate::domain::audit::AuditEventType;
 use crate::domain::error::AuthAPIError;
+use crate::utils::audit::{record_audit_event, token_actor, ClientInfo};
 use crate::utils::auth::validate_token;
 use crate::utils::constants::env::JWT_COOKIE_NAME;
 use axum::extract::State;
 use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::response::{IntoResponse, Response};
 use axum_extra::extract::{cookie, CookieJar};
 use color_eyre::eyre::eyre;
 
 #[tracing::instrument(name = "Logout", skip_all)]
-pub async fn logout(jar: CookieJar, State(state): State<AppState>) -> (CookieJar, Result<impl IntoResponse, AuthAPIError>) {
+pub async fn logout(jar: CookieJar, State(state): State<AppState>, client: ClientInfo) -> (CookieJar, Response) {
+    let actor = match jar.get(JWT_COOKIE_NAME) {
+        Some(jwt) => token_actor(jwt.value()).await,
+        None => None,
+    };
+    let (jar, result) = revoke_session(&state, jar).await;
+
+    (
+        jar,
+        record_audit_event(&state, client, AuditEventType::Logout, actor, result).await,
+    )
+}
+
+async fn revoke_session(state: &AppState, jar: CookieJar) -> (CookieJar, Result<Response, AuthAPIError>) {
     let jwt = match jar.get("jwt") {
         None => return (jar, Err(AuthAPIError::InvalidCredentials)),
         Some(jwt) => jwt,
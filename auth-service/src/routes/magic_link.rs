@@ -0,0 +1,201 @@
+use crate::app_state::AppState;
+use crate::domain::audit::AuditEventType;
+use crate::domain::data_stores::{OneTimeTokenStoreError, TokenPurpose, UserStoreError};
+use crate::domain::email::Email;
+use crate::domain::error::AuthAPIError;
+use crate::routes::notify_new_device;
+use crate::utils::audit::{record_audit_event, ClientInfo};
+use crate::utils::auth::{
+    ensure_account_active, forget_unproven_password, generate_auth_cookie, generate_one_time_token, get_user_grants,
+    validate_one_time_token,
+};
+use crate::utils::constants::AUTH_SERVICE_URL;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use axum_extra::extract::CookieJar;
+use chrono::Utc;
+use color_eyre::eyre::eyre;
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct MagicLinkRequest {
+    email: SecretString,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct MagicLinkResponse {
+    pub message: String,
+}
+
+#[derive(Deserialize)]
+pub struct VerifyMagicLinkQuery {
+    token: String,
+}
+
+#[derive(Deserialize)]
+pub struct VerifyMagicLinkRequest {
+    token: String,
+}
+
+#[tracing::instrument(name = "Request magic link", skip_all)]
+pub async fn request_magic_link(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(request): Json<MagicLinkRequest>,
+) -> Response {
+    let actor = Email::parse(request.email.expose_secret().into()).ok();
+    let result = send_magic_link(&state, request).await;
+
+    record_audit_event(&state, client, AuditEventType::MagicLink, actor, result).await
+}
+
+async fn send_magic_link(state: &AppState, request: MagicLinkRequest) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = Email::parse(request.email.expose_secret().into()).map_err(|_| AuthAPIError::InvalidCredentials)?;
+
+    // Respond the same way whether the account exists or not, so the route can't be used to enumerate users
+    let response = Json(MagicLinkResponse {
+        message: "If an account exists for this email, a sign-in link has been sent".into(),
+    });
+
+    match state.user_store.read().await.get_user(&email).await {
+        Ok(_) => {}
+        Err(UserStoreError::UserNotFound) => return Ok((StatusCode::OK, response)),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(eyre!(e))),
+    }
+
+    let (token, token_id) = generate_one_time_token(&email, TokenPurpose::MagicLink).map_err(AuthAPIError::UnexpectedError)?;
+
+    state
+        .one_time_token_store
+        .write()
+        .await
+        .add_token(TokenPurpose::MagicLink, &token_id, email.clone())
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let link = format!("{}/login/magic-link/verify?token={}", AUTH_SERVICE_URL.as_str(), token);
+
+    state
+        .email_client
+        .read()
+        .await
+        .send_email(
+            &email,
+            "Your sign-in link",
+            format!("Sign in by following this link (it can be used only once): {}", link).as_str(),
+        )
+        .await
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    Ok((StatusCode::OK, response))
+}
+
+// Followed from the sign-in email. Only asks the user to confirm, as mail scanners open the links they come across and
+// would otherwise burn the link or sign in on the user's behalf.
+#[tracing::instrument(name = "Confirm magic link", skip_all)]
+pub async fn confirm_magic_link(Query(query): Query<VerifyMagicLinkQuery>) -> Result<impl IntoResponse, AuthAPIError> {
+    validate_one_time_token(&query.token, TokenPurpose::MagicLink).map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let response = Json(MagicLinkResponse {
+        message: "Confirm to sign in".into(),
+    });
+
+    Ok((StatusCode::OK, response))
+}
+
+// The 2FA code would be delivered to the same inbox as the link,
+// so a consumed magic link already proves possession of the second factor.
+#[tracing::instrument(name = "Verify magic link", skip_all)]
+pub async fn verify_magic_link(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    jar: CookieJar,
+    Json(request): Json<VerifyMagicLinkRequest>,
+) -> (CookieJar, Response) {
+    // Following the link signs the user in, so it is recorded like any other login
+    let actor = validate_one_time_token(&request.token, TokenPurpose::MagicLink)
+        .ok()
+        .and_then(|claims| Email::parse(SecretString::from(claims.sub)).ok());
+    let (jar, result) = login_with_magic_link(&state, &client, jar, request).await;
+
+    (
+        jar,
+        record_audit_event(&state, client, AuditEventType::Login, actor, result).await,
+    )
+}
+
+async fn login_with_magic_link(
+    state: &AppState,
+    client: &ClientInfo,
+    jar: CookieJar,
+    request: VerifyMagicLinkRequest,
+) -> (CookieJar, Result<StatusCode, AuthAPIError>) {
+    let claims = match validate_one_time_token(&request.token, TokenPurpose::MagicLink) {
+        Ok(claims) => claims,
+        Err(_) => return (jar, Err(AuthAPIError::InvalidToken)),
+    };
+
+    let email = match state
+        .one_time_token_store
+        .write()
+        .await
+        .consume_token(TokenPurpose::MagicLink, &claims.jti)
+        .await
+    {
+        Ok(email) => email,
+        Err(OneTimeTokenStoreError::TokenNotFound) => return (jar, Err(AuthAPIError::InvalidToken)),
+        Err(e) => return (jar, Err(AuthAPIError::UnexpectedError(eyre!(e)))),
+    };
+
+    if email.0.expose_secret() != claims.sub {
+        return (jar, Err(AuthAPIError::InvalidToken));
+    }
+
+    let user = match state.user_store.read().await.get_user(&email).await {
+        Ok(user) => user,
+        Err(UserStoreError::UserNotFound) => return (jar, Err(AuthAPIError::InvalidToken)),
+        Err(e) => return (jar, Err(AuthAPIError::UnexpectedError(eyre!(e)))),
+    };
+
+    if let Err(e) = ensure_account_active(&user) {
+        return (jar, Err(e));
+    }
+
+    // Following the link proves ownership of the address just like the confirmation link does, and drops the
+    // password whoever signed up with the address chose
+    if !user.email_verified {
+        if let Err(e) = state.user_store.write().await.mark_email_verified(&email).await {
+            return (jar, Err(AuthAPIError::UnexpectedError(eyre!(e))));
+        }
+
+        if let Err(e) = forget_unproven_password(state, &email).await {
+            return (jar, Err(e));
+        }
+    }
+
+    if let Err(e) = state.user_store.write().await.record_login(&email, Utc::now()).await {
+        return (jar, Err(AuthAPIError::UnexpectedError(eyre!(e))));
+    }
+
+    let grants = match get_user_grants(state, &email, None).await {
+        Ok(grants) => grants,
+        Err(e) => return (jar, Err(e)),
+    };
+
+    let auth_cookie = match generate_auth_cookie(&email, grants) {
+        Ok(cookie) => cookie,
+        Err(e) => return (jar, Err(AuthAPIError::UnexpectedError(e))),
+    };
+
+    let updated_jar = jar.add(auth_cookie);
+
+    // The user is logged in either way, the email is only a warning
+    if let Err(e) = notify_new_device(state, &email, client).await {
+        tracing::error!(error = ?e, "Failed to check the login device");
+    }
+
+    (updated_jar, Ok(StatusCode::OK))
+}
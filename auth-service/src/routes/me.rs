@@ -0,0 +1,136 @@
+use crate::app_state::AppState;
+use crate::domain::error::AuthAPIError;
+use crate::domain::organization::OrganizationId;
+use crate::domain::profile::{DisplayName, Locale, Timezone};
+use crate::domain::user::User;
+use crate::utils::auth::AuthenticatedUser;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::eyre;
+use secrecy::ExposeSecret;
+use serde::{Deserialize, Deserializer, Serialize};
+use validator::ValidationError;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct MeResponse {
+    pub email: String,
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    #[serde(rename = "requires2FA")]
+    pub requires_2fa: bool,
+    #[serde(rename = "emailVerified")]
+    pub email_verified: bool,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "lastLoginAt")]
+    pub last_login_at: Option<DateTime<Utc>>,
+    // The organization the user is signed into
+    #[serde(rename = "organizationId")]
+    pub organization_id: Option<OrganizationId>,
+}
+
+impl MeResponse {
+    fn new(user: User, organization_id: Option<OrganizationId>) -> Self {
+        MeResponse {
+            email: user.email.0.expose_secret().to_owned(),
+            display_name: user.profile.display_name.map(|name| name.as_ref().to_owned()),
+            locale: user.profile.locale.map(|locale| locale.as_ref().to_owned()),
+            timezone: user.profile.timezone.map(|timezone| timezone.as_ref().to_owned()),
+            requires_2fa: user.requires_2fa,
+            email_verified: user.email_verified,
+            created_at: user.created_at,
+            last_login_at: user.last_login_at,
+            organization_id,
+        }
+    }
+}
+
+// Fields left out of the request are kept as they are, while `null` clears them
+#[derive(Deserialize)]
+pub struct UpdateProfileRequest {
+    #[serde(rename = "displayName", default, deserialize_with = "deserialize_patch")]
+    display_name: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_patch")]
+    locale: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_patch")]
+    timezone: Option<Option<String>>,
+}
+
+#[tracing::instrument(name = "Get profile", skip_all)]
+pub async fn get_me(State(state): State<AppState>, user: AuthenticatedUser) -> Result<impl IntoResponse, AuthAPIError> {
+    let stored_user = state
+        .user_store
+        .read()
+        .await
+        .get_user(&user.email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    Ok((
+        StatusCode::OK,
+        Json(MeResponse::new(stored_user, user.grants.organization_id)),
+    ))
+}
+
+#[tracing::instrument(name = "Update profile", skip_all)]
+pub async fn update_me(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(request): Json<UpdateProfileRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let mut user_store = state.user_store.write().await;
+
+    let mut profile = user_store
+        .get_user(&user.email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?
+        .profile;
+
+    if let Some(display_name) = request.display_name {
+        profile.display_name = parse_field(display_name, DisplayName::parse)?;
+    }
+
+    if let Some(locale) = request.locale {
+        profile.locale = parse_field(locale, Locale::parse)?;
+    }
+
+    if let Some(timezone) = request.timezone {
+        profile.timezone = parse_field(timezone, Timezone::parse)?;
+    }
+
+    user_store
+        .update_profile(&user.email, profile)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let stored_user = user_store
+        .get_user(&user.email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    Ok((
+        StatusCode::OK,
+        Json(MeResponse::new(stored_user, user.grants.organization_id)),
+    ))
+}
+
+fn parse_field<T>(value: Option<String>, parse: fn(&str) -> Result<T, ValidationError>) -> Result<Option<T>, AuthAPIError> {
+    value
+        .map(|value| parse(&value))
+        .transpose()
+        .map_err(|_| AuthAPIError::InvalidProfile)
+}
+
+// Tells a field set to `null` apart from a missing one, which serde's `default` turns into `None`
+fn deserialize_patch<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
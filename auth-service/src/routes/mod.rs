@@ -1,11 +1,39 @@
-mod signup;
+mod account;
+mod admin;
+mod audit;
+mod change_email;
+mod change_password;
+mod invitations;
 mod login;
 mod logout;
+mod magic_link;
+mod me;
+mod organizations;
+mod password_reset;
+mod roles;
+mod signup;
+mod trusted_devices;
+mod unrecognized_login;
 mod verify_2fa;
+mod verify_email;
 mod verify_token;
 
+pub use account::*;
+pub use admin::*;
+pub use audit::*;
+pub use change_email::*;
+pub use change_password::*;
+pub use invitations::*;
 pub use login::*;
 pub use logout::*;
+pub use magic_link::*;
+pub use me::*;
+pub use organizations::*;
+pub use password_reset::*;
+pub use roles::*;
 pub use signup::*;
+pub use trusted_devices::*;
+pub use unrecognized_login::*;
 pub use verify_2fa::*;
+pub use verify_email::*;
 pub use verify_token::*;
@@ -0,0 +1,425 @@
+use crate::app_state::AppState;
+use crate::domain::data_stores::{OrganizationStoreError, TokenPurpose, UserStoreError};
+use crate::domain::email::Email;
+use crate::domain::error::AuthAPIError;
+use crate::domain::organization::{
+    Member, MemberInvitation, Membership, Organization, OrganizationId, OrganizationName, ORGANIZATION_ADMIN_ROLE,
+};
+use crate::domain::rbac::RoleName;
+use crate::utils::auth::{
+    generate_auth_cookie, generate_one_time_token, get_user_grants, validate_one_time_token, AuthenticatedUser,
+};
+use crate::utils::constants::AUTH_SERVICE_URL;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use axum_extra::extract::CookieJar;
+use chrono::{DateTime, Duration, Utc};
+use color_eyre::eyre::{eyre, ContextCompat};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct CreateOrganizationRequest {
+    name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct OrganizationResponse {
+    pub id: OrganizationId,
+    pub name: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Organization> for OrganizationResponse {
+    fn from(organization: Organization) -> Self {
+        OrganizationResponse {
+            id: organization.id,
+            name: organization.name.as_ref().to_owned(),
+            created_at: organization.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct MembershipResponse {
+    pub id: OrganizationId,
+    pub name: String,
+    pub roles: Vec<String>,
+    #[serde(rename = "joinedAt")]
+    pub joined_at: DateTime<Utc>,
+    // Whether the user is signed into this organization
+    pub active: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct MembershipListResponse {
+    pub organizations: Vec<MembershipResponse>,
+}
+
+#[derive(Deserialize)]
+pub struct SwitchOrganizationRequest {
+    #[serde(rename = "organizationId")]
+    organization_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct MemberResponse {
+    pub email: String,
+    pub roles: Vec<String>,
+    #[serde(rename = "joinedAt")]
+    pub joined_at: DateTime<Utc>,
+}
+
+impl From<Member> for MemberResponse {
+    fn from(member: Member) -> Self {
+        MemberResponse {
+            email: member.email.0.expose_secret().to_owned(),
+            roles: member.roles.iter().map(|role| role.as_ref().to_owned()).collect(),
+            joined_at: member.joined_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct MemberListResponse {
+    pub members: Vec<MemberResponse>,
+}
+
+#[derive(Deserialize)]
+pub struct SetMemberRequest {
+    roles: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct JoinOrganizationRequest {
+    token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct JoinOrganizationResponse {
+    pub message: String,
+}
+
+// The creator joins the organization as its admin, and has to switch to it to manage its members
+#[tracing::instrument(name = "Create organization", skip_all)]
+pub async fn create_organization(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(request): Json<CreateOrganizationRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let name = OrganizationName::parse(&request.name).map_err(|_| AuthAPIError::InvalidOrganization)?;
+    let organization = Organization::new(name);
+    let admin_role = RoleName::parse(ORGANIZATION_ADMIN_ROLE).map_err(|e| AuthAPIError::UnexpectedError(e.into()))?;
+
+    let mut organization_store = state.organization_store.write().await;
+
+    organization_store
+        .add_organization(organization.clone())
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    organization_store
+        .set_member(&organization.id, &user.email, &[admin_role])
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    Ok((StatusCode::CREATED, Json(OrganizationResponse::from(organization))))
+}
+
+#[tracing::instrument(name = "List organizations", skip_all)]
+pub async fn list_organizations(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let memberships = state
+        .organization_store
+        .read()
+        .await
+        .list_memberships(&user.email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let response = Json(MembershipListResponse {
+        organizations: memberships
+            .into_iter()
+            .map(|membership| membership_response(membership, user.grants.organization_id))
+            .collect(),
+    });
+
+    Ok((StatusCode::OK, response))
+}
+
+// Issues a new auth token carrying the organization and the roles the user holds in it, the old one is banned
+#[tracing::instrument(name = "Switch organization", skip_all)]
+pub async fn switch_organization(
+    jar: CookieJar,
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(request): Json<SwitchOrganizationRequest>,
+) -> (CookieJar, Result<impl IntoResponse, AuthAPIError>) {
+    let organization_id = match OrganizationId::parse(&request.organization_id) {
+        Ok(id) => id,
+        Err(_) => return (jar, Err(AuthAPIError::InvalidOrganization)),
+    };
+
+    let grants = match get_user_grants(&state, &user.email, Some(organization_id)).await {
+        Ok(grants) => grants,
+        Err(e) => return (jar, Err(e)),
+    };
+
+    let auth_cookie = match generate_auth_cookie(&user.email, grants) {
+        Ok(cookie) => cookie,
+        Err(e) => return (jar, Err(AuthAPIError::UnexpectedError(e))),
+    };
+
+    if let Err(e) = state.banned_token_store.write().await.add_token(user.token.into()).await {
+        return (jar, Err(AuthAPIError::UnexpectedError(eyre!(e))));
+    }
+
+    (jar.add(auth_cookie), Ok(StatusCode::OK))
+}
+
+#[tracing::instrument(name = "List organization members", skip_all)]
+pub async fn list_members(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let organization_id = active_organization(&user)?;
+
+    let members = state
+        .organization_store
+        .read()
+        .await
+        .list_members(&organization_id)
+        .await
+        .map_err(map_organization_store_error)?;
+
+    let response = Json(MemberListResponse {
+        members: members.into_iter().map(MemberResponse::from).collect(),
+    });
+
+    Ok((StatusCode::OK, response))
+}
+
+// Replaces the roles of a member of the active organization, and invites anyone else to join it with these roles.
+// Only roles whose permissions the caller already has can be given, and a member whose roles change is signed out.
+// The invitation is only sent if the email has an account, but the response doesn't tell whether it does.
+#[tracing::instrument(name = "Set organization member", skip_all)]
+pub async fn set_member(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(email): Path<String>,
+    Json(request): Json<SetMemberRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let organization_id = active_organization(&user)?;
+    let email = Email::parse(SecretString::from(email)).map_err(|_| AuthAPIError::InvalidCredentials)?;
+
+    let mut names = request
+        .roles
+        .iter()
+        .map(|role| RoleName::parse(role))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| AuthAPIError::InvalidRole)?;
+    names.sort();
+    names.dedup();
+
+    let roles = state
+        .role_store
+        .read()
+        .await
+        .list_roles()
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    for name in &names {
+        let role = roles
+            .iter()
+            .find(|role| &role.name == name)
+            .ok_or(AuthAPIError::RoleNotFound)?;
+
+        if !user.grants.covers(role) {
+            return Err(AuthAPIError::Forbidden);
+        }
+    }
+
+    let mut organization_store = state.organization_store.write().await;
+
+    match organization_store.get_membership(&organization_id, &email).await {
+        Ok(_) => {}
+        Err(OrganizationStoreError::MemberNotFound) => {
+            drop(organization_store);
+            invite_member(&state, organization_id, email, names).await?;
+
+            return Ok(StatusCode::ACCEPTED);
+        }
+        Err(e) => return Err(AuthAPIError::UnexpectedError(eyre!(e))),
+    }
+
+    organization_store
+        .set_member(&organization_id, &email, &names)
+        .await
+        .map_err(map_organization_store_error)?;
+
+    drop(organization_store);
+
+    revoke_member_tokens(&state, &email).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Adds the user to the organization they were invited to, with the roles they were offered
+#[tracing::instrument(name = "Join organization", skip_all)]
+pub async fn join_organization(
+    State(state): State<AppState>,
+    Json(request): Json<JoinOrganizationRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let claims =
+        validate_one_time_token(&request.token, TokenPurpose::OrganizationInvitation).map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let mut organization_store = state.organization_store.write().await;
+
+    let invitation = organization_store
+        .take_member_invitation(&claims.jti)
+        .await
+        .map_err(|e| match e {
+            OrganizationStoreError::InvitationNotFound => AuthAPIError::InvalidToken,
+            e => AuthAPIError::UnexpectedError(eyre!(e)),
+        })?;
+
+    if *invitation.email.0.expose_secret() != claims.sub || invitation.is_expired() {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    organization_store
+        .set_member(&invitation.organization_id, &invitation.email, &invitation.roles)
+        .await
+        .map_err(map_organization_store_error)?;
+
+    let response = Json(JoinOrganizationResponse {
+        message: "You joined the organization, switch to it to use your roles there".into(),
+    });
+
+    Ok((StatusCode::OK, response))
+}
+
+// The member is signed out, so that the roles they held in the organization stop being granted right away
+#[tracing::instrument(name = "Remove organization member", skip_all)]
+pub async fn remove_member(
+    State(state): State<AppState>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(email): Path<String>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let organization_id = active_organization(&user)?;
+    let email = Email::parse(SecretString::from(email)).map_err(|_| AuthAPIError::InvalidCredentials)?;
+
+    state
+        .organization_store
+        .write()
+        .await
+        .remove_member(&organization_id, &email)
+        .await
+        .map_err(map_organization_store_error)?;
+
+    revoke_member_tokens(&state, &email).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Emails a link to accept the roles, if the email has an account
+async fn invite_member(
+    state: &AppState,
+    organization_id: OrganizationId,
+    email: Email,
+    roles: Vec<RoleName>,
+) -> Result<(), AuthAPIError> {
+    match state.user_store.read().await.get_user(&email).await {
+        Ok(_) => {}
+        Err(UserStoreError::UserNotFound) => return Ok(()),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(eyre!(e))),
+    }
+
+    let organization = state
+        .organization_store
+        .read()
+        .await
+        .get_organization(&organization_id)
+        .await
+        .map_err(map_organization_store_error)?;
+
+    let (token, token_id) =
+        generate_one_time_token(&email, TokenPurpose::OrganizationInvitation).map_err(AuthAPIError::UnexpectedError)?;
+
+    let expires_at = Duration::try_seconds(TokenPurpose::OrganizationInvitation.ttl_seconds())
+        .and_then(|ttl| Utc::now().checked_add_signed(ttl))
+        .wrap_err("Failed to compute the invitation expiration time")
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    state
+        .organization_store
+        .write()
+        .await
+        .add_member_invitation(MemberInvitation {
+            id: token_id,
+            organization_id,
+            email: email.clone(),
+            roles,
+            expires_at,
+        })
+        .await
+        .map_err(map_organization_store_error)?;
+
+    let link = format!("{}/organizations/join?token={}", AUTH_SERVICE_URL.as_str(), token);
+
+    state
+        .email_client
+        .read()
+        .await
+        .send_email(
+            &email,
+            "You have been invited to an organization",
+            format!(
+                "You have been invited to join {}. Accept by following this link: {}",
+                organization.name.as_ref(),
+                link
+            )
+            .as_str(),
+        )
+        .await
+        .map_err(AuthAPIError::UnexpectedError)
+}
+
+fn active_organization(user: &AuthenticatedUser) -> Result<OrganizationId, AuthAPIError> {
+    user.grants.organization_id.ok_or(AuthAPIError::NoActiveOrganization)
+}
+
+fn membership_response(membership: Membership, active_organization_id: Option<OrganizationId>) -> MembershipResponse {
+    MembershipResponse {
+        id: membership.organization.id,
+        name: membership.organization.name.as_ref().to_owned(),
+        roles: membership.roles.iter().map(|role| role.as_ref().to_owned()).collect(),
+        joined_at: membership.joined_at,
+        active: active_organization_id == Some(membership.organization.id),
+    }
+}
+
+fn map_organization_store_error(e: OrganizationStoreError) -> AuthAPIError {
+    match e {
+        OrganizationStoreError::OrganizationNotFound => AuthAPIError::OrganizationNotFound,
+        OrganizationStoreError::MemberNotFound => AuthAPIError::UserNotFound,
+        OrganizationStoreError::RoleNotFound => AuthAPIError::RoleNotFound,
+        e => AuthAPIError::UnexpectedError(eyre!(e)),
+    }
+}
+
+async fn revoke_member_tokens(state: &AppState, email: &Email) -> Result<(), AuthAPIError> {
+    state
+        .banned_token_store
+        .write()
+        .await
+        .revoke_user_tokens(email, None)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))
+}
@@ -0,0 +1,147 @@
+use crate::app_state::AppState;
+use crate::domain::data_stores::{OneTimeTokenStoreError, TokenPurpose, UserStoreError};
+use crate::domain::email::Email;
+use crate::domain::error::AuthAPIError;
+use crate::utils::auth::{
+    ensure_password_not_reused, generate_one_time_token, hash_new_password, record_password, revoke_sessions,
+    validate_one_time_token,
+};
+use crate::utils::constants::AUTH_SERVICE_URL;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use color_eyre::eyre::eyre;
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct ForgotPasswordRequest {
+    email: SecretString,
+}
+
+#[derive(Deserialize)]
+pub struct ResetPasswordRequest {
+    token: String,
+    password: SecretString,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct PasswordResetResponse {
+    pub message: String,
+}
+
+#[tracing::instrument(name = "Forgot password", skip_all)]
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    Json(request): Json<ForgotPasswordRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = Email::parse(request.email.expose_secret().into()).map_err(|_| AuthAPIError::InvalidCredentials)?;
+
+    // Respond the same way whether the account exists or not, so the route can't be used to enumerate users
+    let response = Json(PasswordResetResponse {
+        message: "If an account exists for this email, a password reset link has been sent".into(),
+    });
+
+    match state.user_store.read().await.get_user(&email).await {
+        Ok(_) => {}
+        Err(UserStoreError::UserNotFound) => return Ok((StatusCode::OK, response)),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(eyre!(e))),
+    }
+
+    send_password_reset_link(&state, &email).await?;
+
+    Ok((StatusCode::OK, response))
+}
+
+// Emails a single-use link to the reset form, also used when an admin resets the password
+pub async fn send_password_reset_link(state: &AppState, email: &Email) -> Result<(), AuthAPIError> {
+    let (token, token_id) = generate_one_time_token(email, TokenPurpose::PasswordReset).map_err(AuthAPIError::UnexpectedError)?;
+
+    state
+        .one_time_token_store
+        .write()
+        .await
+        .add_token(TokenPurpose::PasswordReset, &token_id, email.clone())
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let link = format!("{}/password/reset?token={}", AUTH_SERVICE_URL.as_str(), token);
+
+    state
+        .email_client
+        .read()
+        .await
+        .send_email(
+            email,
+            "Reset your password",
+            format!(
+                "Reset your password by following this link (it can be used only once): {}",
+                link
+            )
+            .as_str(),
+        )
+        .await
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    Ok(())
+}
+
+#[tracing::instrument(name = "Reset password", skip_all)]
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Json(request): Json<ResetPasswordRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let claims = validate_one_time_token(&request.token, TokenPurpose::PasswordReset).map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let claimed_email = Email::parse(claims.sub.clone().into()).map_err(|_| AuthAPIError::InvalidToken)?;
+
+    // Hash before consuming the token, so that a rejected password doesn't burn the link
+    ensure_password_not_reused(&state, &claimed_email, &request.password).await?;
+    let password = hash_new_password(&state, &claimed_email, request.password).await?;
+
+    let email = state
+        .one_time_token_store
+        .write()
+        .await
+        .consume_token(TokenPurpose::PasswordReset, &claims.jti)
+        .await
+        .map_err(|e| match e {
+            OneTimeTokenStoreError::TokenNotFound => AuthAPIError::InvalidToken,
+            e => AuthAPIError::UnexpectedError(eyre!(e)),
+        })?;
+
+    if email.0.expose_secret() != claims.sub {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    {
+        let mut user_store = state.user_store.write().await;
+
+        user_store
+            .update_password(&email, password.clone())
+            .await
+            .map_err(|e| match e {
+                UserStoreError::UserNotFound => AuthAPIError::InvalidToken,
+                e => AuthAPIError::UnexpectedError(eyre!(e)),
+            })?;
+
+        // Following the link proves ownership of the address just like the confirmation link does
+        user_store
+            .mark_email_verified(&email)
+            .await
+            .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+    }
+
+    record_password(&state, &email, password).await?;
+
+    // Whoever knew the old password must not stay signed in, finish a pending 2FA login or skip 2FA on a device
+    // they trusted
+    revoke_sessions(&state, &email).await?;
+
+    let response = Json(PasswordResetResponse {
+        message: "Password reset successfully".into(),
+    });
+
+    Ok((StatusCode::OK, response))
+}
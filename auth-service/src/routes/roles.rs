@@ -0,0 +1,104 @@
+use crate::app_state::AppState;
+use crate::domain::data_stores::RoleStoreError;
+use crate::domain::error::AuthAPIError;
+use crate::domain::rbac::{Permission, Role, RoleName};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use color_eyre::eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct RoleResponse {
+    pub name: String,
+    pub permissions: Vec<String>,
+}
+
+impl From<Role> for RoleResponse {
+    fn from(role: Role) -> Self {
+        RoleResponse {
+            name: role.name.as_ref().to_owned(),
+            permissions: role
+                .permissions
+                .iter()
+                .map(|permission| permission.as_ref().to_owned())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct RoleListResponse {
+    pub roles: Vec<RoleResponse>,
+}
+
+#[derive(Deserialize)]
+pub struct SetRoleRequest {
+    permissions: Vec<String>,
+}
+
+#[tracing::instrument(name = "List roles", skip_all)]
+pub async fn list_roles(State(state): State<AppState>) -> Result<impl IntoResponse, AuthAPIError> {
+    let roles = state
+        .role_store
+        .read()
+        .await
+        .list_roles()
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let response = Json(RoleListResponse {
+        roles: roles.into_iter().map(RoleResponse::from).collect(),
+    });
+
+    Ok((StatusCode::OK, response))
+}
+
+// Creates the role, or replaces its permissions. Users holding it get the new permissions on their next login.
+#[tracing::instrument(name = "Set role", skip_all)]
+pub async fn set_role(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(request): Json<SetRoleRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let name = RoleName::parse(&name).map_err(|_| AuthAPIError::InvalidRole)?;
+
+    let mut permissions = request
+        .permissions
+        .iter()
+        .map(|permission| Permission::parse(permission))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| AuthAPIError::InvalidRole)?;
+    permissions.sort();
+    permissions.dedup();
+
+    let role = Role { name, permissions };
+
+    state
+        .role_store
+        .write()
+        .await
+        .set_role(role.clone())
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    Ok((StatusCode::OK, Json(RoleResponse::from(role))))
+}
+
+// The default role can't be deleted, as every user has it
+#[tracing::instrument(name = "Delete role", skip_all)]
+pub async fn delete_role(State(state): State<AppState>, Path(name): Path<String>) -> Result<impl IntoResponse, AuthAPIError> {
+    let name = RoleName::parse(&name).map_err(|_| AuthAPIError::InvalidRole)?;
+
+    if name.is_default() {
+        return Err(AuthAPIError::InvalidRole);
+    }
+
+    state.role_store.write().await.delete_role(&name).await.map_err(|e| match e {
+        RoleStoreError::RoleNotFound => AuthAPIError::RoleNotFound,
+        e => AuthAPIError::UnexpectedError(eyre!(e)),
+    })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
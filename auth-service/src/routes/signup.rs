@@ -1,52 +1,173 @@
 use crate::app_state::AppState;
-use crate::domain::data_stores::UserStoreError;
+use crate::domain::audit::AuditEventType;
+use crate::domain::data_stores::{InvitationStoreError, TokenPurpose, UserStoreError};
 use crate::domain::email::Email;
 use crate::domain::error::AuthAPIError;
-use crate::domain::hashed_password::HashedPassword;
+use crate::domain::invitation::Invitation;
 use crate::domain::user::User;
+use crate::routes::send_verification_email;
+use crate::utils::audit::{record_audit_event, ClientInfo};
+use crate::utils::auth::{hash_new_password, record_password, validate_one_time_token};
+use crate::utils::constants::AUTH_SERVICE_URL;
 use axum::extract::State;
 use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::response::Response;
 use axum::Json;
 use color_eyre::eyre::eyre;
 use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
 
 #[tracing::instrument(name = "Signup", skip_all)]
-pub async fn signup(
-    State(state): State<AppState>,
-    Json(request): Json<SignupRequest>,
-) -> Result<impl IntoResponse, AuthAPIError> {
+pub async fn signup(State(state): State<AppState>, client: ClientInfo, Json(request): Json<SignupRequest>) -> Response {
+    let actor = Email::parse(request.email.expose_secret().into()).ok();
+    let result = sign_up(&state, request).await;
+
+    record_audit_event(&state, client, AuditEventType::Signup, actor, result).await
+}
+
+async fn sign_up(state: &AppState, request: SignupRequest) -> Result<(StatusCode, Json<SignupResponse>), AuthAPIError> {
     let email = Email::parse(request.email.expose_secret().into()).map_err(|_| AuthAPIError::InvalidCredentials)?;
-    let password = HashedPassword::parse(request.password.into())
-        .await
-        .map_err(|_| AuthAPIError::InvalidCredentials)?;
+    // Accounts created without a password can only sign in through a magic link
+    let password = match request.password {
+        Some(password) => Some(hash_new_password(state, &email, password).await?),
+        None => None,
+    };
+
+    let invitation = match request.invite {
+        Some(token) => Some(get_invitation(state, &token, &email).await?),
+        None if state.invite_only_signup => return Err(AuthAPIError::InvitationRequired),
+        None => None,
+    };
 
     let mut user_store = state.user_store.write().await;
 
     if user_store.get_user(&email).await.is_ok() {
-        return Err(AuthAPIError::UserAlreadyExists);
+        drop(user_store);
+        return respond_to_taken_email(state, &email, invitation.is_some()).await;
+    }
+    let mut user = User::new(email.clone(), password.clone(), request.requires_2fa);
+    // The invite link was sent to the address, which proves the user owns it
+    user.email_verified = invitation.is_some();
+
+    match user_store.add_user(user).await {
+        Ok(()) => {}
+        // Someone else signed up with the email in the meantime
+        Err(UserStoreError::UserAlreadyExists) => {
+            drop(user_store);
+            return respond_to_taken_email(state, &email, invitation.is_some()).await;
+        }
+        Err(UserStoreError::InvalidCredentials) => return Err(AuthAPIError::InvalidCredentials),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(eyre!(e))),
+    }
+    drop(user_store);
+
+    if let Some(password) = password {
+        record_password(state, &email, password).await?;
     }
-    let user = User::new(email, password, request.requires_2fa);
 
-    user_store.add_user(user).await.map_err(|err| match err {
-        UserStoreError::UserAlreadyExists => AuthAPIError::UserAlreadyExists,
-        UserStoreError::InvalidCredentials => AuthAPIError::InvalidCredentials,
-        e => AuthAPIError::UnexpectedError(eyre!(e)),
-    })?;
+    match invitation {
+        Some(invitation) => accept_invitation(state, invitation).await?,
+        None => send_verification_email(state, &email).await?,
+    }
+
+    Ok(signed_up())
+}
+
+fn signed_up() -> (StatusCode, Json<SignupResponse>) {
     let response = Json(SignupResponse {
         message: "User signed up successfully".into(),
     });
 
-    Ok((StatusCode::CREATED, response))
+    (StatusCode::CREATED, response)
+}
+
+// Either rejects the signup, or answers it like a successful one and lets the owner of the account know by email, so
+// that the response doesn't tell whether the email has an account. The holder of an invite link already knows the
+// address is theirs, so they are told.
+async fn respond_to_taken_email(
+    state: &AppState,
+    email: &Email,
+    invited: bool,
+) -> Result<(StatusCode, Json<SignupResponse>), AuthAPIError> {
+    if !state.uniform_signup_response || invited {
+        return Err(AuthAPIError::UserAlreadyExists);
+    }
+
+    let content = format!(
+        "Someone tried to sign up with this email address, but you already have an account. Log in at {url}/login, or reset your password at {url}/password/forgot if you forgot it. If it wasn't you, you can ignore this email.",
+        url = AUTH_SERVICE_URL.as_str()
+    );
+
+    state
+        .email_client
+        .read()
+        .await
+        .send_email(email, "You already have an account", &content)
+        .await
+        .map_err(AuthAPIError::UnexpectedError)?;
+
+    Ok(signed_up())
+}
+
+// The invite token has to be issued for the email the user signs up with
+async fn get_invitation(state: &AppState, token: &str, email: &Email) -> Result<Invitation, AuthAPIError> {
+    let claims = validate_one_time_token(token, TokenPurpose::Invitation).map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let invitation = state
+        .invitation_store
+        .read()
+        .await
+        .get_invitation(&claims.jti)
+        .await
+        .map_err(|e| match e {
+            InvitationStoreError::InvitationNotFound => AuthAPIError::InvalidToken,
+            e => AuthAPIError::UnexpectedError(eyre!(e)),
+        })?;
+
+    if &invitation.email != email {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    Ok(invitation)
+}
+
+// Gives the user the role or organization membership they were invited with, and uses up their invitations
+async fn accept_invitation(state: &AppState, invitation: Invitation) -> Result<(), AuthAPIError> {
+    match (invitation.organization_id, invitation.role) {
+        (Some(organization_id), role) => state
+            .organization_store
+            .write()
+            .await
+            .set_member(&organization_id, &invitation.email, &role.into_iter().collect::<Vec<_>>())
+            .await
+            .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?,
+        (None, Some(role)) => state
+            .role_store
+            .write()
+            .await
+            .assign_role(&invitation.email, &role)
+            .await
+            .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?,
+        (None, None) => {}
+    }
+
+    state
+        .invitation_store
+        .write()
+        .await
+        .remove_invitations(&invitation.email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))
 }
 
 #[derive(Deserialize)]
 pub struct SignupRequest {
-    password: SecretString,
+    password: Option<SecretString>,
     email: SecretString,
     #[serde(rename = "requires2FA")]
     requires_2fa: bool,
+    // Token of an invite link
+    invite: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
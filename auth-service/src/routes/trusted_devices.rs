@@ -0,0 +1,45 @@
+use crate::app_state::AppState;
+use crate::domain::data_stores::TrustedDeviceStoreError;
+use crate::domain::error::AuthAPIError;
+use crate::domain::trusted_device::TrustedDevice;
+use crate::utils::auth::AuthenticatedUser;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use color_eyre::eyre::eyre;
+
+#[tracing::instrument(name = "List trusted devices", skip_all)]
+pub async fn list_trusted_devices(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<Json<Vec<TrustedDevice>>, AuthAPIError> {
+    let devices = state
+        .trusted_device_store
+        .read()
+        .await
+        .get_devices(&user.email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    Ok(Json(devices))
+}
+
+#[tracing::instrument(name = "Revoke trusted device", skip_all)]
+pub async fn revoke_trusted_device(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(device_id): Path<String>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    match state
+        .trusted_device_store
+        .write()
+        .await
+        .remove_device(&user.email, &device_id)
+        .await
+    {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(TrustedDeviceStoreError::DeviceNotFound) => Err(AuthAPIError::DeviceNotFound),
+        Err(e) => Err(AuthAPIError::UnexpectedError(eyre!(e))),
+    }
+}
@@ -0,0 +1,150 @@
+use crate::app_state::AppState;
+use crate::domain::data_stores::{OneTimeTokenStoreError, TokenPurpose, UserStoreError};
+use crate::domain::device_history::{DeviceFingerprint, DeviceSighting};
+use crate::domain::email::Email;
+use crate::domain::error::AuthAPIError;
+use crate::routes::send_password_reset_link;
+use crate::utils::audit::ClientInfo;
+use crate::utils::auth::{generate_one_time_token, revoke_sessions, validate_one_time_token};
+use crate::utils::constants::AUTH_SERVICE_URL;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use chrono::Utc;
+use color_eyre::eyre::eyre;
+use secrecy::ExposeSecret;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct ReportUnrecognizedLoginQuery {
+    token: String,
+}
+
+#[derive(Deserialize)]
+pub struct ReportUnrecognizedLoginRequest {
+    token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct UnrecognizedLoginResponse {
+    pub message: String,
+}
+
+// Remembers the device the user just logged in from and emails them when they never used it before. Nothing is sent
+// for the very first device, there is nothing to compare it with yet.
+#[tracing::instrument(name = "Notify new device", skip_all)]
+pub async fn notify_new_device(state: &AppState, email: &Email, client: &ClientInfo) -> Result<(), AuthAPIError> {
+    let fingerprint = DeviceFingerprint::new(client.user_agent.as_deref(), client.ip);
+    let now = Utc::now();
+
+    let sighting = state
+        .device_history_store
+        .write()
+        .await
+        .record_device(email, &fingerprint, now)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    if sighting != DeviceSighting::New {
+        return Ok(());
+    }
+
+    let (token, token_id) =
+        generate_one_time_token(email, TokenPurpose::UnrecognizedLogin).map_err(AuthAPIError::UnexpectedError)?;
+
+    state
+        .one_time_token_store
+        .write()
+        .await
+        .add_token(TokenPurpose::UnrecognizedLogin, &token_id, email.clone())
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let link = format!("{}/login/not-me?token={}", AUTH_SERVICE_URL.as_str(), token);
+    let content = format!(
+        "Your account was signed in to from a new device at {}.\nDevice: {}\nIP address: {}\n\nIf it wasn't you, follow this link to sign it out and reset your password: {}",
+        now.to_rfc3339(),
+        client.user_agent.as_deref().unwrap_or("unknown"),
+        client.ip.map(|ip| ip.to_string()).unwrap_or_else(|| "unknown".into()),
+        link
+    );
+
+    state
+        .email_client
+        .read()
+        .await
+        .send_email(email, "New sign-in to your account", &content)
+        .await
+        .map_err(AuthAPIError::UnexpectedError)
+}
+
+// Followed from the new sign-in email. Only asks the user to confirm, as mail scanners open the links they come across.
+#[tracing::instrument(name = "Confirm unrecognized login report", skip_all)]
+pub async fn confirm_unrecognized_login_report(
+    Query(query): Query<ReportUnrecognizedLoginQuery>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    validate_one_time_token(&query.token, TokenPurpose::UnrecognizedLogin).map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let response = Json(UnrecognizedLoginResponse {
+        message: "Confirm that you didn't sign in to sign your account out everywhere and reset your password".into(),
+    });
+
+    Ok((StatusCode::OK, response))
+}
+
+// Whoever logged in is signed out and the password can only be set again through the reset link that is sent, as the
+// intruder must be assumed to know it.
+#[tracing::instrument(name = "Report unrecognized login", skip_all)]
+pub async fn report_unrecognized_login(
+    State(state): State<AppState>,
+    Json(request): Json<ReportUnrecognizedLoginRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let claims =
+        validate_one_time_token(&request.token, TokenPurpose::UnrecognizedLogin).map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let email = state
+        .one_time_token_store
+        .write()
+        .await
+        .consume_token(TokenPurpose::UnrecognizedLogin, &claims.jti)
+        .await
+        .map_err(|e| match e {
+            OneTimeTokenStoreError::TokenNotFound => AuthAPIError::InvalidToken,
+            e => AuthAPIError::UnexpectedError(eyre!(e)),
+        })?;
+
+    if email.0.expose_secret() != claims.sub {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    state
+        .user_store
+        .write()
+        .await
+        .clear_password(&email)
+        .await
+        .map_err(|e| match e {
+            UserStoreError::UserNotFound => AuthAPIError::InvalidToken,
+            e => AuthAPIError::UnexpectedError(eyre!(e)),
+        })?;
+
+    revoke_sessions(&state, &email).await?;
+
+    // The intruder's device must be reported again should they get back in
+    state
+        .device_history_store
+        .write()
+        .await
+        .remove_devices(&email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    send_password_reset_link(&state, &email).await?;
+
+    let response = Json(UnrecognizedLoginResponse {
+        message: "Your account was signed out everywhere, follow the link we emailed you to set a new password".into(),
+    });
+
+    Ok((StatusCode::OK, response))
+}
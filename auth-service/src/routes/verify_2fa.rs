@@ -1,15 +1,24 @@
 use crate::app_state::AppState;
+use crate::domain::audit::AuditEventType;
 use crate::domain::data_stores::{LoginAttemptId, TwoFACode};
 use crate::domain::email::Email;
 use crate::domain::error::AuthAPIError;
+use crate::domain::trusted_device::TrustedDevice;
 use crate::routes::{handle_no_2fa, LoginResponse};
+use crate::utils::audit::{record_audit_event, ClientInfo};
+use crate::utils::auth::generate_trusted_device_cookie;
+use crate::utils::constants::TRUSTED_DEVICE_TTL_SECONDS;
 use axum::extract::State;
-use axum::http::StatusCode;
+use axum::http::header::USER_AGENT;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::Response;
 use axum::Json;
 use axum_extra::extract::CookieJar;
+use chrono::{Duration, Utc};
 use color_eyre::eyre::eyre;
 use secrecy::SecretString;
 use serde::Deserialize;
+use uuid::Uuid;
 
 #[derive(Deserialize)]
 pub struct Verify2FARequest {
@@ -18,13 +27,34 @@ pub struct Verify2FARequest {
     login_attempt_id: String,
     #[serde(rename = "2FACode")]
     two_fa_code: String,
+    // Skip 2FA on this device for the next logins
+    #[serde(rename = "trustDevice", default)]
+    trust_device: bool,
 }
 
 #[tracing::instrument(name = "Verify 2FA Code", skip_all)]
 pub async fn verify_2fa(
     State(state): State<AppState>,
+    client: ClientInfo,
+    headers: HeaderMap,
     jar: CookieJar,
     Json(request): Json<Verify2FARequest>,
+) -> (CookieJar, Response) {
+    let actor = Email::parse(SecretString::from(request.email.clone())).ok();
+    let (jar, result) = verify_code(&state, &client, &headers, jar, request).await;
+
+    (
+        jar,
+        record_audit_event(&state, client, AuditEventType::Verify2FA, actor, result).await,
+    )
+}
+
+async fn verify_code(
+    state: &AppState,
+    client: &ClientInfo,
+    headers: &HeaderMap,
+    jar: CookieJar,
+    request: Verify2FARequest,
 ) -> (CookieJar, Result<(StatusCode, Json<LoginResponse>), AuthAPIError>) {
     let email = match Email::parse(SecretString::from(request.email)) {
         Ok(email) => email,
@@ -41,7 +71,7 @@ pub async fn verify_2fa(
         Err(_) => return (jar, Err(AuthAPIError::InvalidCredentials)),
     };
 
-    let (_login_attempt_id, _two_fa_code) = match state.two_fa_code_store.read().await.get_code(&email).await {
+    let (_login_attempt_id, _two_fa_code_hash) = match state.two_fa_code_store.read().await.get_code(&email).await {
         Ok(result) => result,
         Err(_) => return (jar, Err(AuthAPIError::IncorrectCredentials)),
     };
@@ -50,7 +80,7 @@ pub async fn verify_2fa(
         return (jar, Err(AuthAPIError::IncorrectCredentials));
     }
 
-    if _two_fa_code != two_fa_code {
+    if !_two_fa_code_hash.verify(&login_attempt_id, &two_fa_code) {
         return (jar, Err(AuthAPIError::IncorrectCredentials));
     }
 
@@ -58,5 +88,57 @@ pub async fn verify_2fa(
         return (jar, Err(AuthAPIError::UnexpectedError(eyre!(e))));
     }
 
-    handle_no_2fa(&email, jar).await
+    let jar = match request.trust_device {
+        true => match trust_device(&email, state, headers, jar).await {
+            (jar, Ok(())) => jar,
+            (jar, Err(e)) => return (jar, Err(e)),
+        },
+        false => jar,
+    };
+
+    handle_no_2fa(&email, state, client, jar).await
+}
+
+#[tracing::instrument(name = "Trust device", skip_all)]
+async fn trust_device(
+    email: &Email,
+    state: &AppState,
+    headers: &HeaderMap,
+    jar: CookieJar,
+) -> (CookieJar, Result<(), AuthAPIError>) {
+    let user_agent = headers
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_owned();
+
+    let created_at = Utc::now();
+    let expires_at = match created_at.checked_add_signed(Duration::seconds(*TRUSTED_DEVICE_TTL_SECONDS)) {
+        Some(expires_at) => expires_at,
+        None => {
+            return (
+                jar,
+                Err(AuthAPIError::UnexpectedError(eyre!("Failed to compute device expiration"))),
+            )
+        }
+    };
+
+    let device = TrustedDevice::new(Uuid::new_v4().to_string(), user_agent, created_at, expires_at);
+
+    let cookie = match generate_trusted_device_cookie(email, &device.id) {
+        Ok(cookie) => cookie,
+        Err(e) => return (jar, Err(AuthAPIError::UnexpectedError(e))),
+    };
+
+    if let Err(e) = state
+        .trusted_device_store
+        .write()
+        .await
+        .add_device(email.clone(), device)
+        .await
+    {
+        return (jar, Err(AuthAPIError::UnexpectedError(eyre!(e))));
+    }
+
+    (jar.add(cookie), Ok(()))
 }
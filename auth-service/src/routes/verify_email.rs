@@ -0,0 +1,137 @@
+use crate::app_state::AppState;
+use crate::domain::data_stores::{OneTimeTokenStoreError, TokenPurpose, UserStoreError};
+use crate::domain::email::Email;
+use crate::domain::error::AuthAPIError;
+use crate::routes::send_password_reset_link;
+use crate::utils::auth::{forget_unproven_password, generate_one_time_token, validate_one_time_token};
+use crate::utils::constants::AUTH_SERVICE_URL;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::Json;
+use color_eyre::eyre::eyre;
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct VerifyEmailQuery {
+    token: String,
+}
+
+#[derive(Deserialize)]
+pub struct ResendVerificationEmailRequest {
+    email: SecretString,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct VerifyEmailResponse {
+    pub message: String,
+}
+
+#[tracing::instrument(name = "Verify email", skip_all)]
+pub async fn verify_email(
+    State(state): State<AppState>,
+    Query(query): Query<VerifyEmailQuery>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let claims =
+        validate_one_time_token(&query.token, TokenPurpose::EmailVerification).map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let email = state
+        .one_time_token_store
+        .write()
+        .await
+        .consume_token(TokenPurpose::EmailVerification, &claims.jti)
+        .await
+        .map_err(|e| match e {
+            OneTimeTokenStoreError::TokenNotFound => AuthAPIError::InvalidToken,
+            e => AuthAPIError::UnexpectedError(eyre!(e)),
+        })?;
+
+    if email.0.expose_secret() != claims.sub {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    let user = state.user_store.read().await.get_user(&email).await.map_err(|e| match e {
+        UserStoreError::UserNotFound => AuthAPIError::InvalidToken,
+        e => AuthAPIError::UnexpectedError(eyre!(e)),
+    })?;
+
+    state
+        .user_store
+        .write()
+        .await
+        .mark_email_verified(&email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    // The password was set before anyone proved they own the address, so the owner chooses it again
+    if !user.email_verified && user.password.is_some() {
+        forget_unproven_password(&state, &email).await?;
+        send_password_reset_link(&state, &email).await?;
+
+        let response = Json(VerifyEmailResponse {
+            message: "Email verified successfully, follow the link we emailed you to choose your password".into(),
+        });
+
+        return Ok((StatusCode::OK, response));
+    }
+
+    let response = Json(VerifyEmailResponse {
+        message: "Email verified successfully".into(),
+    });
+
+    Ok((StatusCode::OK, response))
+}
+
+#[tracing::instrument(name = "Resend verification email", skip_all)]
+pub async fn resend_verification_email(
+    State(state): State<AppState>,
+    Json(request): Json<ResendVerificationEmailRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = Email::parse(request.email.expose_secret().into()).map_err(|_| AuthAPIError::InvalidCredentials)?;
+
+    // Respond the same way whatever the account state, so the route can't be used to enumerate users
+    let response = Json(VerifyEmailResponse {
+        message: "If an unverified account exists for this email, a confirmation link has been sent".into(),
+    });
+
+    let user = match state.user_store.read().await.get_user(&email).await {
+        Ok(user) => user,
+        Err(UserStoreError::UserNotFound) => return Ok((StatusCode::OK, response)),
+        Err(e) => return Err(AuthAPIError::UnexpectedError(eyre!(e))),
+    };
+
+    if !user.email_verified {
+        send_verification_email(&state, &email).await?;
+    }
+
+    Ok((StatusCode::OK, response))
+}
+
+#[tracing::instrument(name = "Send verification email", skip_all)]
+pub async fn send_verification_email(state: &AppState, email: &Email) -> Result<(), AuthAPIError> {
+    let (token, token_id) =
+        generate_one_time_token(email, TokenPurpose::EmailVerification).map_err(AuthAPIError::UnexpectedError)?;
+
+    state
+        .one_time_token_store
+        .write()
+        .await
+        .add_token(TokenPurpose::EmailVerification, &token_id, email.clone())
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let link = format!("{}/verify-email?token={}", AUTH_SERVICE_URL.as_str(), token);
+
+    state
+        .email_client
+        .read()
+        .await
+        .send_email(
+            email,
+            "Confirm your email address",
+            format!("Confirm your email address by following this link: {}", link).as_str(),
+        )
+        .await
+        .map_err(AuthAPIError::UnexpectedError)
+}
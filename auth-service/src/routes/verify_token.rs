@@ -1,13 +1,17 @@
 use crate::app_state::AppState;
+use crate::domain::audit::AuditEventType;
+use crate::domain::data_stores::UserStoreError;
+use crate::domain::email::Email;
 use crate::domain::error::AuthAPIError;
-use crate::utils::auth::validate_token;
-use crate::utils::constants::env::JWT_COOKIE_NAME;
+use crate::domain::organization::OrganizationId;
+use crate::utils::audit::{record_audit_event, token_actor, ClientInfo};
+use crate::utils::auth::{ensure_account_active, is_token_banned, validate_token};
 use axum::extract::State;
 use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::response::{IntoResponse, Response};
 use axum::Json;
-use axum_extra::extract::cookie::Cookie;
 use color_eyre::eyre::eyre;
+use secrecy::SecretString;
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize)]
@@ -15,22 +19,38 @@ pub struct VerifyTokenRequest {
     token: String,
 }
 
+// Lets other services authorize the user with the roles and permissions carried by the token,
+// and scope their data to the organization the user is signed into
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct VerifyTokenResponse {
+    pub email: String,
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+    #[serde(rename = "organizationId")]
+    pub organization_id: Option<OrganizationId>,
+}
+
 #[tracing::instrument(name = "Verify JWT Token", skip_all)]
 pub async fn verify_token(
     State(state): State<AppState>,
+    client: ClientInfo,
     Json(request): Json<VerifyTokenRequest>,
-) -> Result<impl IntoResponse, AuthAPIError> {
-    if validate_token(&request.token).await.is_err() {
-        return Err(AuthAPIError::IncorrectCredentials);
-    }
+) -> Response {
+    let actor = token_actor(&request.token).await;
+    let result = check_token(&state, request).await;
 
-    let jwt = Cookie::new(JWT_COOKIE_NAME, request.token);
+    record_audit_event(&state, client, AuditEventType::VerifyToken, actor, result).await
+}
 
-    let is_token_banned = state
-        .banned_token_store
-        .read()
-        .await
-        .contains_token(jwt.value().as_ref())
+async fn check_token(state: &AppState, request: VerifyTokenRequest) -> Result<Response, AuthAPIError> {
+    let claims = match validate_token(&request.token).await {
+        Ok(claims) => claims,
+        Err(_) => return Err(AuthAPIError::IncorrectCredentials),
+    };
+
+    let email = Email::parse(SecretString::from(claims.sub.clone())).map_err(|_| AuthAPIError::IncorrectCredentials)?;
+
+    let is_token_banned = is_token_banned(&state.banned_token_store, &request.token, &email, &claims)
         .await
         .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
 
@@ -38,5 +58,20 @@ pub async fn verify_token(
         return Ok(StatusCode::UNAUTHORIZED.into_response());
     }
 
-    Ok(StatusCode::OK.into_response())
+    // Tokens stop being accepted as soon as the account is blocked, even before they are revoked
+    let user = state.user_store.read().await.get_user(&email).await.map_err(|e| match e {
+        UserStoreError::UserNotFound => AuthAPIError::IncorrectCredentials,
+        e => AuthAPIError::UnexpectedError(eyre!(e)),
+    })?;
+
+    ensure_account_active(&user)?;
+
+    let response = Json(VerifyTokenResponse {
+        email: claims.sub,
+        roles: claims.grants.roles,
+        permissions: claims.grants.permissions,
+        organization_id: claims.grants.organization_id,
+    });
+
+    Ok((StatusCode::OK, response).into_response())
 }
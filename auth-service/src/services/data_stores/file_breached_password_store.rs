@@ -0,0 +1,164 @@
+use crate::domain::data_stores::{breached_password_hash, BreachedPasswordStore, BreachedPasswordStoreError};
+use color_eyre::eyre::eyre;
+use secrecy::SecretString;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+// Looks passwords up in a Have I Been Pwned dump of SHA-1 hashes ordered by hash, one `HASH:COUNT` per line.
+// The file is binary searched on every lookup, so it is never loaded into memory.
+pub struct FileBreachedPasswordStore {
+    path: PathBuf,
+}
+
+impl FileBreachedPasswordStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait::async_trait]
+impl BreachedPasswordStore for FileBreachedPasswordStore {
+    #[tracing::instrument(name = "Looking up breached password in file", skip_all)]
+    async fn breach_count(&self, password: &SecretString) -> Result<u64, BreachedPasswordStoreError> {
+        let current_span = tracing::Span::current();
+
+        let hash = breached_password_hash(password);
+        let path = self.path.clone();
+
+        tokio::task::spawn_blocking(move || current_span.in_scope(|| find_breach_count(&path, &hash)))
+            .await
+            .map_err(|e| BreachedPasswordStoreError::UnexpectedError(eyre!(e)))?
+            .map_err(|e| BreachedPasswordStoreError::UnexpectedError(eyre!(e)))
+    }
+}
+
+fn find_breach_count(path: &Path, hash: &str) -> io::Result<u64> {
+    let file = File::open(path)?;
+    let length = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    // Looks for the smallest offset followed by a line whose hash is not below the one looked up
+    let mut low = 0;
+    let mut high = length;
+    while low < high {
+        let middle = low.saturating_add(high.saturating_sub(low).checked_div(2).unwrap_or_default());
+
+        match next_line(&mut reader, middle)? {
+            Some(line) if line_hash(&line).as_str() < hash => low = middle.saturating_add(1),
+            _ => high = middle,
+        }
+    }
+
+    match next_line(&mut reader, low)? {
+        Some(line) if line_hash(&line) == hash => Ok(line_count(&line)),
+        _ => Ok(0),
+    }
+}
+
+// The first line starting at or after `offset`
+fn next_line(reader: &mut BufReader<File>, offset: u64) -> io::Result<Option<String>> {
+    let mut line = Vec::new();
+
+    match offset.checked_sub(1) {
+        None => {
+            reader.seek(SeekFrom::Start(0))?;
+        }
+        // Skips the rest of the line `offset` falls into, unless it is right after a line break
+        Some(previous) => {
+            reader.seek(SeekFrom::Start(previous))?;
+            reader.read_until(b'\n', &mut line)?;
+            line.clear();
+        }
+    }
+
+    match reader.read_until(b'\n', &mut line)? {
+        0 => Ok(None),
+        _ => Ok(Some(String::from_utf8_lossy(&line).trim_end().to_owned())),
+    }
+}
+
+fn line_hash(line: &str) -> String {
+    line.split(':').next().unwrap_or_default().to_ascii_uppercase()
+}
+
+fn line_count(line: &str) -> u64 {
+    // Some dumps leave the count out, the password was still breached
+    line.split(':')
+        .nth(1)
+        .and_then(|count| count.trim().parse().ok())
+        .unwrap_or(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    // Writes a dump of the passwords, ordered by hash like the real ones
+    fn write_dump(passwords: &[(&str, u64)], line_break: &str) -> PathBuf {
+        let mut lines: Vec<String> = passwords
+            .iter()
+            .map(|(password, count)| format!("{}:{}", breached_password_hash(&(*password).into()), count))
+            .collect();
+        lines.sort();
+
+        let path = std::env::temp_dir().join(format!("breached-passwords-{}.txt", Uuid::new_v4()));
+        std::fs::write(&path, lines.join(line_break) + line_break).unwrap();
+
+        path
+    }
+
+    const PASSWORDS: [(&str, u64); 6] = [
+        ("password", 9_545_824),
+        ("123456", 37_359_195),
+        ("qwerty", 3_912_816),
+        ("letmein", 1_219_586),
+        ("dragon", 1_045_278),
+        ("P@ssw0rd", 82_035),
+    ];
+
+    #[tokio::test]
+    async fn test_finds_every_breached_password() {
+        for line_break in ["\n", "\r\n"] {
+            let path = write_dump(&PASSWORDS, line_break);
+            let store = FileBreachedPasswordStore::new(&path);
+
+            for (password, count) in PASSWORDS {
+                assert_eq!(store.breach_count(&password.into()).await.unwrap(), count, "{}", password);
+            }
+
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_returns_zero_for_unknown_password() {
+        let path = write_dump(&PASSWORDS, "\n");
+        let store = FileBreachedPasswordStore::new(&path);
+
+        for password in ["", "Password", "correct horse battery staple", "g7#Kq9!vLm2@"] {
+            assert_eq!(store.breach_count(&password.into()).await.unwrap(), 0, "{}", password);
+        }
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_handles_single_line_dump() {
+        let path = write_dump(&[("password", 3)], "\n");
+        let store = FileBreachedPasswordStore::new(&path);
+
+        assert_eq!(store.breach_count(&"password".into()).await.unwrap(), 3);
+        assert_eq!(store.breach_count(&"dragon".into()).await.unwrap(), 0);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fails_if_file_is_missing() {
+        let store = FileBreachedPasswordStore::new(std::env::temp_dir().join(Uuid::new_v4().to_string()));
+
+        assert!(store.breach_count(&"password".into()).await.is_err());
+    }
+}
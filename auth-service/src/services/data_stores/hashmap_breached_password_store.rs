@@ -0,0 +1,40 @@
+use crate::domain::data_stores::{breached_password_hash, BreachedPasswordStore, BreachedPasswordStoreError};
+use secrecy::SecretString;
+use std::collections::HashMap;
+
+// Stays empty unless passwords are added, which turns the breach check off
+#[derive(Default)]
+pub struct HashmapBreachedPasswordStore {
+    breach_counts: HashMap<String, u64>,
+}
+
+impl HashmapBreachedPasswordStore {
+    pub fn add_password(&mut self, password: &SecretString, count: u64) {
+        self.breach_counts.insert(breached_password_hash(password), count);
+    }
+}
+
+#[async_trait::async_trait]
+impl BreachedPasswordStore for HashmapBreachedPasswordStore {
+    async fn breach_count(&self, password: &SecretString) -> Result<u64, BreachedPasswordStoreError> {
+        Ok(self
+            .breach_counts
+            .get(&breached_password_hash(password))
+            .copied()
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_breach_count() {
+        let mut store = HashmapBreachedPasswordStore::default();
+        store.add_password(&"password123".into(), 42);
+
+        assert_eq!(store.breach_count(&"password123".into()).await.unwrap(), 42);
+        assert_eq!(store.breach_count(&"Password123".into()).await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::domain::data_stores::{DeviceHistoryStore, DeviceHistoryStoreError};
+use crate::domain::device_history::{DeviceFingerprint, DeviceRecord, DeviceSighting};
+use crate::domain::email::Email;
+use chrono::{DateTime, Utc};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+#[derive(Default)]
+pub struct HashmapDeviceHistoryStore {
+    devices: HashMap<Email, HashMap<DeviceFingerprint, DeviceRecord>>,
+}
+
+#[async_trait::async_trait]
+impl DeviceHistoryStore for HashmapDeviceHistoryStore {
+    async fn record_device(
+        &mut self,
+        email: &Email,
+        fingerprint: &DeviceFingerprint,
+        seen_at: DateTime<Utc>,
+    ) -> Result<DeviceSighting, DeviceHistoryStoreError> {
+        let devices = self.devices.entry(email.clone()).or_default();
+        let first = devices.is_empty();
+
+        let sighting = match devices.get_mut(fingerprint) {
+            Some(device) => {
+                device.last_seen_at = seen_at;
+                DeviceSighting::Known
+            }
+            None => {
+                devices.insert(
+                    fingerprint.clone(),
+                    DeviceRecord {
+                        fingerprint: fingerprint.clone(),
+                        first_seen_at: seen_at,
+                        last_seen_at: seen_at,
+                    },
+                );
+
+                match first {
+                    true => DeviceSighting::First,
+                    false => DeviceSighting::New,
+                }
+            }
+        };
+
+        Ok(sighting)
+    }
+
+    async fn get_devices(&self, email: &Email) -> Result<Vec<DeviceRecord>, DeviceHistoryStoreError> {
+        let mut devices: Vec<DeviceRecord> = self
+            .devices
+            .get(email)
+            .map(|devices| devices.values().cloned().collect())
+            .unwrap_or_default();
+        devices.sort_by_key(|device| Reverse(device.last_seen_at));
+
+        Ok(devices)
+    }
+
+    async fn move_devices(&mut self, old_email: &Email, new_email: &Email) -> Result<(), DeviceHistoryStoreError> {
+        if let Some(devices) = self.devices.remove(old_email) {
+            self.devices.insert(new_email.clone(), devices);
+        }
+
+        Ok(())
+    }
+
+    async fn remove_devices(&mut self, email: &Email) -> Result<(), DeviceHistoryStoreError> {
+        self.devices.remove(email);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn test_tells_new_devices_apart() {
+        let mut store = HashmapDeviceHistoryStore::default();
+        let email: Email = "test@test.pl".try_into().unwrap();
+        let laptop = DeviceFingerprint::new(Some("Firefox"), Some("203.0.113.7".parse().unwrap()));
+        let phone = DeviceFingerprint::new(Some("Safari"), Some("198.51.100.7".parse().unwrap()));
+
+        assert_eq!(
+            store.record_device(&email, &laptop, Utc::now()).await.unwrap(),
+            DeviceSighting::First
+        );
+        assert_eq!(
+            store.record_device(&email, &laptop, Utc::now()).await.unwrap(),
+            DeviceSighting::Known
+        );
+        assert_eq!(
+            store.record_device(&email, &phone, Utc::now()).await.unwrap(),
+            DeviceSighting::New
+        );
+
+        // Devices are per user
+        let other_email: Email = "other@test.pl".try_into().unwrap();
+        assert_eq!(
+            store.record_device(&other_email, &laptop, Utc::now()).await.unwrap(),
+            DeviceSighting::First
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lists_most_recently_seen_devices_first() {
+        let mut store = HashmapDeviceHistoryStore::default();
+        let email: Email = "test@test.pl".try_into().unwrap();
+        let laptop = DeviceFingerprint::new(Some("Firefox"), None);
+        let phone = DeviceFingerprint::new(Some("Safari"), None);
+        let start = Utc::now();
+
+        store.record_device(&email, &laptop, start).await.unwrap();
+        store.record_device(&email, &phone, start + Duration::hours(1)).await.unwrap();
+        store
+            .record_device(&email, &laptop, start + Duration::hours(2))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.get_devices(&email).await.unwrap(),
+            vec![
+                DeviceRecord {
+                    fingerprint: laptop,
+                    first_seen_at: start,
+                    last_seen_at: start + Duration::hours(2),
+                },
+                DeviceRecord {
+                    fingerprint: phone,
+                    first_seen_at: start + Duration::hours(1),
+                    last_seen_at: start + Duration::hours(1),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_devices_follow_email_change() {
+        let mut store = HashmapDeviceHistoryStore::default();
+        let old_email: Email = "old@test.pl".try_into().unwrap();
+        let new_email: Email = "new@test.pl".try_into().unwrap();
+        let laptop = DeviceFingerprint::new(Some("Firefox"), None);
+
+        store.record_device(&old_email, &laptop, Utc::now()).await.unwrap();
+        store.move_devices(&old_email, &new_email).await.unwrap();
+
+        assert_eq!(
+            store.record_device(&new_email, &laptop, Utc::now()).await.unwrap(),
+            DeviceSighting::Known
+        );
+        assert_eq!(
+            store.record_device(&old_email, &laptop, Utc::now()).await.unwrap(),
+            DeviceSighting::First
+        );
+    }
+
+    #[tokio::test]
+    async fn test_removed_devices_are_forgotten() {
+        let mut store = HashmapDeviceHistoryStore::default();
+        let email: Email = "test@test.pl".try_into().unwrap();
+        let laptop = DeviceFingerprint::new(Some("Firefox"), None);
+
+        store.record_device(&email, &laptop, Utc::now()).await.unwrap();
+        store.remove_devices(&email).await.unwrap();
+
+        assert_eq!(
+            store.record_device(&email, &laptop, Utc::now()).await.unwrap(),
+            DeviceSighting::First
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use crate::domain::data_stores::{EmailChangeStore, EmailChangeStoreError};
+use crate::domain::email::Email;
+use crate::domain::email_change::EmailChange;
+use std::collections::HashMap;
+
+// Expiration is left to the confirmation tokens, which carry their own
+#[derive(Default)]
+pub struct HashmapEmailChangeStore {
+    changes: HashMap<Email, EmailChange>,
+}
+
+#[async_trait::async_trait]
+impl EmailChangeStore for HashmapEmailChangeStore {
+    async fn add_change(&mut self, change: EmailChange) -> Result<(), EmailChangeStoreError> {
+        self.changes.insert(change.old_email.clone(), change);
+
+        Ok(())
+    }
+
+    async fn confirm_change(&mut self, old_email: &Email, token_id: &str) -> Result<EmailChange, EmailChangeStoreError> {
+        let change = self.changes.get_mut(old_email).ok_or(EmailChangeStoreError::ChangeNotFound)?;
+
+        if !change.confirm(token_id) {
+            return Err(EmailChangeStoreError::ChangeNotFound);
+        }
+
+        Ok(change.clone())
+    }
+
+    async fn get_change(&self, old_email: &Email) -> Result<EmailChange, EmailChangeStoreError> {
+        self.changes
+            .get(old_email)
+            .cloned()
+            .ok_or(EmailChangeStoreError::ChangeNotFound)
+    }
+
+    async fn remove_change(&mut self, old_email: &Email) -> Result<(), EmailChangeStoreError> {
+        self.changes
+            .remove(old_email)
+            .map(|_| ())
+            .ok_or(EmailChangeStoreError::ChangeNotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email_change(new_email: &str) -> EmailChange {
+        EmailChange::new(
+            Email::parse("old@example.com".into()).unwrap(),
+            Email::parse(new_email.into()).unwrap(),
+            format!("old-token-{}", new_email),
+            format!("new-token-{}", new_email),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_confirm_change() {
+        let mut store = HashmapEmailChangeStore::default();
+        let change = email_change("new@example.com");
+
+        store.add_change(change.clone()).await.unwrap();
+
+        let confirmed = store
+            .confirm_change(&change.old_email, &change.new_email_token_id)
+            .await
+            .unwrap();
+        assert!(confirmed.new_email_confirmed);
+        assert!(!confirmed.is_confirmed());
+
+        let confirmed = store
+            .confirm_change(&change.old_email, &change.old_email_token_id)
+            .await
+            .unwrap();
+        assert!(confirmed.is_confirmed());
+    }
+
+    #[tokio::test]
+    async fn test_new_change_replaces_pending_one() {
+        let mut store = HashmapEmailChangeStore::default();
+        let first_change = email_change("first@example.com");
+        let second_change = email_change("second@example.com");
+
+        store.add_change(first_change.clone()).await.unwrap();
+        store.add_change(second_change.clone()).await.unwrap();
+
+        assert_eq!(
+            store
+                .confirm_change(&first_change.old_email, &first_change.old_email_token_id)
+                .await
+                .unwrap_err(),
+            EmailChangeStoreError::ChangeNotFound
+        );
+        assert!(store
+            .confirm_change(&second_change.old_email, &second_change.old_email_token_id)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_remove_change() {
+        let mut store = HashmapEmailChangeStore::default();
+        let change = email_change("new@example.com");
+
+        store.add_change(change.clone()).await.unwrap();
+        store.remove_change(&change.old_email).await.unwrap();
+
+        assert_eq!(
+            store
+                .confirm_change(&change.old_email, &change.old_email_token_id)
+                .await
+                .unwrap_err(),
+            EmailChangeStoreError::ChangeNotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_change() {
+        let mut store = HashmapEmailChangeStore::default();
+        let change = email_change("new@example.com");
+
+        assert_eq!(
+            store.get_change(&change.old_email).await.unwrap_err(),
+            EmailChangeStoreError::ChangeNotFound
+        );
+
+        store.add_change(change.clone()).await.unwrap();
+
+        assert_eq!(store.get_change(&change.old_email).await.unwrap(), change);
+    }
+}
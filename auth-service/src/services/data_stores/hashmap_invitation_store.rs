@@ -0,0 +1,129 @@
+use crate::domain::data_stores::{InvitationStore, InvitationStoreError};
+use crate::domain::email::Email;
+use crate::domain::invitation::Invitation;
+use std::collections::HashMap;
+
+#[derive(Default)]
+pub struct HashmapInvitationStore {
+    invitations: HashMap<String, Invitation>,
+}
+
+#[async_trait::async_trait]
+impl InvitationStore for HashmapInvitationStore {
+    async fn add_invitation(&mut self, invitation: Invitation) -> Result<(), InvitationStoreError> {
+        self.invitations.retain(|_, existing| !existing.is_expired());
+        self.invitations.insert(invitation.id.clone(), invitation);
+
+        Ok(())
+    }
+
+    async fn get_invitation(&self, id: &str) -> Result<Invitation, InvitationStoreError> {
+        self.invitations
+            .get(id)
+            .filter(|invitation| !invitation.is_expired())
+            .cloned()
+            .ok_or(InvitationStoreError::InvitationNotFound)
+    }
+
+    async fn list_invitations(&self) -> Result<Vec<Invitation>, InvitationStoreError> {
+        let mut invitations: Vec<Invitation> = self
+            .invitations
+            .values()
+            .filter(|invitation| !invitation.is_expired())
+            .cloned()
+            .collect();
+        invitations.sort_by_key(|invitation| invitation.created_at);
+
+        Ok(invitations)
+    }
+
+    async fn remove_invitation(&mut self, id: &str) -> Result<(), InvitationStoreError> {
+        self.invitations
+            .remove(id)
+            .map(|_| ())
+            .ok_or(InvitationStoreError::InvitationNotFound)
+    }
+
+    async fn remove_invitations(&mut self, email: &Email) -> Result<(), InvitationStoreError> {
+        self.invitations.retain(|_, invitation| &invitation.email != email);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn invitation(id: &str, email: &str, expires_in: Duration) -> Invitation {
+        let now = Utc::now();
+
+        Invitation {
+            id: id.to_owned(),
+            email: Email::parse(email.to_owned().into()).unwrap(),
+            role: None,
+            organization_id: None,
+            invited_by: Email::parse("admin@test.pl".into()).unwrap(),
+            created_at: now,
+            expires_at: now + expires_in,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_and_remove_invitation() {
+        let mut store = HashmapInvitationStore::default();
+        let pending = invitation("invitation-id", "test@test.pl", Duration::days(1));
+
+        store.add_invitation(pending.clone()).await.unwrap();
+        assert_eq!(store.get_invitation("invitation-id").await.unwrap(), pending);
+        assert_eq!(store.list_invitations().await.unwrap(), vec![pending]);
+
+        store.remove_invitation("invitation-id").await.unwrap();
+        assert_eq!(
+            store.get_invitation("invitation-id").await,
+            Err(InvitationStoreError::InvitationNotFound)
+        );
+        assert_eq!(
+            store.remove_invitation("invitation-id").await,
+            Err(InvitationStoreError::InvitationNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expired_invitation_is_not_returned() {
+        let mut store = HashmapInvitationStore::default();
+        let expired = invitation("invitation-id", "test@test.pl", Duration::seconds(-1));
+
+        store.add_invitation(expired).await.unwrap();
+
+        assert_eq!(
+            store.get_invitation("invitation-id").await,
+            Err(InvitationStoreError::InvitationNotFound)
+        );
+        assert!(store.list_invitations().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_remove_invitations_of_user() {
+        let mut store = HashmapInvitationStore::default();
+        let other = invitation("other-id", "other@test.pl", Duration::days(1));
+
+        store
+            .add_invitation(invitation("first-id", "test@test.pl", Duration::days(1)))
+            .await
+            .unwrap();
+        store
+            .add_invitation(invitation("second-id", "test@test.pl", Duration::days(1)))
+            .await
+            .unwrap();
+        store.add_invitation(other.clone()).await.unwrap();
+
+        store
+            .remove_invitations(&Email::parse("test@test.pl".into()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(store.list_invitations().await.unwrap(), vec![other]);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::domain::data_stores::{LoginFailureStore, LoginFailureStoreError};
+use crate::domain::email::Email;
+use crate::domain::login_failures::LoginFailures;
+use std::collections::HashMap;
+
+#[derive(Default)]
+pub struct HashmapLoginFailureStore {
+    failures: HashMap<Email, LoginFailures>,
+}
+
+#[async_trait::async_trait]
+impl LoginFailureStore for HashmapLoginFailureStore {
+    async fn get_failures(&self, email: &Email) -> Result<LoginFailures, LoginFailureStoreError> {
+        Ok(self.failures.get(email).cloned().unwrap_or_default())
+    }
+
+    async fn set_failures(&mut self, email: &Email, failures: LoginFailures) -> Result<(), LoginFailureStoreError> {
+        self.failures.insert(email.clone(), failures);
+
+        Ok(())
+    }
+
+    async fn clear_failures(&mut self, email: &Email) -> Result<(), LoginFailureStoreError> {
+        self.failures.remove(email);
+
+        Ok(())
+    }
+
+    async fn move_failures(&mut self, old_email: &Email, new_email: &Email) -> Result<(), LoginFailureStoreError> {
+        if let Some(failures) = self.failures.remove(old_email) {
+            self.failures.insert(new_email.clone(), failures);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[tokio::test]
+    async fn test_get_failures_of_unknown_user() {
+        let store = HashmapLoginFailureStore::default();
+        let email = Email::parse("test@example.com".into()).unwrap();
+
+        assert_eq!(store.get_failures(&email).await.unwrap(), LoginFailures::default());
+    }
+
+    #[tokio::test]
+    async fn test_set_and_clear_failures() {
+        let mut store = HashmapLoginFailureStore::default();
+        let email = Email::parse("test@example.com".into()).unwrap();
+
+        let failures = LoginFailures {
+            count: 5,
+            last_failed_at: Some(Utc::now()),
+            locked_until: Some(Utc::now()),
+        };
+        store.set_failures(&email, failures.clone()).await.unwrap();
+        assert_eq!(store.get_failures(&email).await.unwrap(), failures);
+
+        store.clear_failures(&email).await.unwrap();
+        assert_eq!(store.get_failures(&email).await.unwrap(), LoginFailures::default());
+    }
+
+    #[tokio::test]
+    async fn test_move_failures() {
+        let mut store = HashmapLoginFailureStore::default();
+        let old_email = Email::parse("old@example.com".into()).unwrap();
+        let new_email = Email::parse("new@example.com".into()).unwrap();
+
+        let failures = LoginFailures {
+            count: 5,
+            last_failed_at: Some(Utc::now()),
+            locked_until: Some(Utc::now()),
+        };
+        store.set_failures(&old_email, failures.clone()).await.unwrap();
+        store.move_failures(&old_email, &new_email).await.unwrap();
+
+        assert_eq!(store.get_failures(&new_email).await.unwrap(), failures);
+        assert_eq!(store.get_failures(&old_email).await.unwrap(), LoginFailures::default());
+
+        // Moving again, as a retried email change does, keeps them in place
+        store.move_failures(&old_email, &new_email).await.unwrap();
+        assert_eq!(store.get_failures(&new_email).await.unwrap(), failures);
+    }
+}
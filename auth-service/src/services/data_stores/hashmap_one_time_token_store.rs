@@ -0,0 +1,99 @@
+use crate::domain::data_stores::{OneTimeTokenStore, OneTimeTokenStoreError, TokenPurpose};
+use crate::domain::email::Email;
+use chrono::{DateTime, Duration, Utc};
+use color_eyre::eyre::eyre;
+use std::collections::HashMap;
+
+#[derive(Default)]
+pub struct HashmapOneTimeTokenStore {
+    tokens: HashMap<(TokenPurpose, String), (Email, DateTime<Utc>)>,
+}
+
+#[async_trait::async_trait]
+impl OneTimeTokenStore for HashmapOneTimeTokenStore {
+    async fn add_token(&mut self, purpose: TokenPurpose, token_id: &str, email: Email) -> Result<(), OneTimeTokenStoreError> {
+        let expires_at = Duration::try_seconds(purpose.ttl_seconds())
+            .and_then(|ttl| Utc::now().checked_add_signed(ttl))
+            .ok_or_else(|| OneTimeTokenStoreError::UnexpectedError(eyre!("Failed to compute token expiration time")))?;
+
+        self.tokens.insert((purpose, token_id.to_owned()), (email, expires_at));
+
+        Ok(())
+    }
+
+    async fn consume_token(&mut self, purpose: TokenPurpose, token_id: &str) -> Result<Email, OneTimeTokenStoreError> {
+        match self.tokens.remove(&(purpose, token_id.to_owned())) {
+            Some((email, expires_at)) if expires_at > Utc::now() => Ok(email),
+            _ => Err(OneTimeTokenStoreError::TokenNotFound),
+        }
+    }
+
+    async fn remove_tokens(&mut self, email: &Email) -> Result<(), OneTimeTokenStoreError> {
+        self.tokens.retain(|_, (token_email, _)| token_email != email);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_consume_token() {
+        let mut store = HashmapOneTimeTokenStore::default();
+        let email = Email::parse("test@example.com".into()).unwrap();
+
+        store
+            .add_token(TokenPurpose::MagicLink, "token-id", email.clone())
+            .await
+            .unwrap();
+
+        let result = store.consume_token(TokenPurpose::MagicLink, "token-id").await;
+        assert_eq!(result.unwrap(), email);
+    }
+
+    #[tokio::test]
+    async fn test_token_can_only_be_consumed_once() {
+        let mut store = HashmapOneTimeTokenStore::default();
+        let email = Email::parse("test@example.com".into()).unwrap();
+
+        store.add_token(TokenPurpose::MagicLink, "token-id", email).await.unwrap();
+        store.consume_token(TokenPurpose::MagicLink, "token-id").await.unwrap();
+
+        let result = store.consume_token(TokenPurpose::MagicLink, "token-id").await;
+        assert_eq!(result.unwrap_err(), OneTimeTokenStoreError::TokenNotFound);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_token_is_rejected() {
+        let mut store = HashmapOneTimeTokenStore::default();
+
+        let result = store.consume_token(TokenPurpose::MagicLink, "unknown").await;
+        assert_eq!(result.unwrap_err(), OneTimeTokenStoreError::TokenNotFound);
+    }
+
+    #[tokio::test]
+    async fn test_remove_tokens() {
+        let mut store = HashmapOneTimeTokenStore::default();
+        let email = Email::parse("test@example.com".into()).unwrap();
+        let other_email = Email::parse("other@example.com".into()).unwrap();
+
+        store
+            .add_token(TokenPurpose::MagicLink, "token-id", email.clone())
+            .await
+            .unwrap();
+        store
+            .add_token(TokenPurpose::PasswordReset, "other-token-id", other_email.clone())
+            .await
+            .unwrap();
+
+        store.remove_tokens(&email).await.unwrap();
+
+        let result = store.consume_token(TokenPurpose::MagicLink, "token-id").await;
+        assert_eq!(result.unwrap_err(), OneTimeTokenStoreError::TokenNotFound);
+
+        let result = store.consume_token(TokenPurpose::PasswordReset, "other-token-id").await;
+        assert_eq!(result.unwrap(), other_email);
+    }
+}
@@ -0,0 +1,298 @@
+use crate::domain::data_stores::{OrganizationStore, OrganizationStoreError};
+use crate::domain::email::Email;
+use crate::domain::organization::{Member, MemberInvitation, Membership, Organization, OrganizationId};
+use crate::domain::rbac::RoleName;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeSet, HashMap};
+
+struct MemberEntry {
+    roles: BTreeSet<RoleName>,
+    joined_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+pub struct HashmapOrganizationStore {
+    organizations: HashMap<OrganizationId, Organization>,
+    members: HashMap<OrganizationId, HashMap<Email, MemberEntry>>,
+    invitations: HashMap<String, MemberInvitation>,
+}
+
+impl HashmapOrganizationStore {
+    fn membership(&self, id: &OrganizationId, entry: &MemberEntry) -> Result<Membership, OrganizationStoreError> {
+        let organization = self
+            .organizations
+            .get(id)
+            .cloned()
+            .ok_or(OrganizationStoreError::OrganizationNotFound)?;
+
+        Ok(Membership {
+            organization,
+            roles: entry.roles.iter().cloned().collect(),
+            joined_at: entry.joined_at,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl OrganizationStore for HashmapOrganizationStore {
+    async fn add_organization(&mut self, organization: Organization) -> Result<(), OrganizationStoreError> {
+        self.organizations.insert(organization.id, organization);
+
+        Ok(())
+    }
+
+    async fn get_organization(&self, id: &OrganizationId) -> Result<Organization, OrganizationStoreError> {
+        self.organizations
+            .get(id)
+            .cloned()
+            .ok_or(OrganizationStoreError::OrganizationNotFound)
+    }
+
+    async fn set_member(&mut self, id: &OrganizationId, email: &Email, roles: &[RoleName]) -> Result<(), OrganizationStoreError> {
+        if !self.organizations.contains_key(id) {
+            return Err(OrganizationStoreError::OrganizationNotFound);
+        }
+
+        let entry = self
+            .members
+            .entry(*id)
+            .or_default()
+            .entry(email.clone())
+            .or_insert_with(|| MemberEntry {
+                roles: BTreeSet::new(),
+                joined_at: Utc::now(),
+            });
+        entry.roles = roles.iter().cloned().collect();
+
+        Ok(())
+    }
+
+    async fn remove_member(&mut self, id: &OrganizationId, email: &Email) -> Result<(), OrganizationStoreError> {
+        self.members
+            .get_mut(id)
+            .and_then(|members| members.remove(email))
+            .map(|_| ())
+            .ok_or(OrganizationStoreError::MemberNotFound)
+    }
+
+    async fn get_membership(&self, id: &OrganizationId, email: &Email) -> Result<Membership, OrganizationStoreError> {
+        let entry = self
+            .members
+            .get(id)
+            .and_then(|members| members.get(email))
+            .ok_or(OrganizationStoreError::MemberNotFound)?;
+
+        self.membership(id, entry)
+    }
+
+    async fn list_memberships(&self, email: &Email) -> Result<Vec<Membership>, OrganizationStoreError> {
+        let mut memberships = self
+            .members
+            .iter()
+            .filter_map(|(id, members)| members.get(email).map(|entry| self.membership(id, entry)))
+            .collect::<Result<Vec<_>, _>>()?;
+        memberships.sort_by_key(|membership| membership.joined_at);
+
+        Ok(memberships)
+    }
+
+    async fn list_members(&self, id: &OrganizationId) -> Result<Vec<Member>, OrganizationStoreError> {
+        if !self.organizations.contains_key(id) {
+            return Err(OrganizationStoreError::OrganizationNotFound);
+        }
+
+        let mut members: Vec<Member> = self
+            .members
+            .get(id)
+            .map(|members| {
+                members
+                    .iter()
+                    .map(|(email, entry)| Member {
+                        email: email.clone(),
+                        roles: entry.roles.iter().cloned().collect(),
+                        joined_at: entry.joined_at,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        members.sort_by_key(|member| member.joined_at);
+
+        Ok(members)
+    }
+
+    async fn add_member_invitation(&mut self, invitation: MemberInvitation) -> Result<(), OrganizationStoreError> {
+        if !self.organizations.contains_key(&invitation.organization_id) {
+            return Err(OrganizationStoreError::OrganizationNotFound);
+        }
+
+        self.invitations
+            .retain(|_, existing| existing.organization_id != invitation.organization_id || existing.email != invitation.email);
+        self.invitations.insert(invitation.id.clone(), invitation);
+
+        Ok(())
+    }
+
+    async fn take_member_invitation(&mut self, id: &str) -> Result<MemberInvitation, OrganizationStoreError> {
+        self.invitations.remove(id).ok_or(OrganizationStoreError::InvitationNotFound)
+    }
+
+    async fn move_memberships(&mut self, old_email: &Email, new_email: &Email) -> Result<(), OrganizationStoreError> {
+        for members in self.members.values_mut() {
+            if let Some(entry) = members.remove(old_email) {
+                members.insert(new_email.clone(), entry);
+            }
+        }
+
+        self.invitations
+            .values_mut()
+            .filter(|invitation| &invitation.email == old_email)
+            .for_each(|invitation| invitation.email = new_email.clone());
+
+        Ok(())
+    }
+
+    async fn remove_memberships(&mut self, email: &Email) -> Result<(), OrganizationStoreError> {
+        for members in self.members.values_mut() {
+            members.remove(email);
+        }
+
+        self.invitations.retain(|_, invitation| &invitation.email != email);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::organization::OrganizationName;
+
+    fn organization(name: &str) -> Organization {
+        Organization::new(OrganizationName::parse(name).unwrap())
+    }
+
+    fn role(name: &str) -> RoleName {
+        RoleName::parse(name).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_add_and_get_organization() {
+        let mut store = HashmapOrganizationStore::default();
+        let acme = organization("Acme");
+
+        assert_eq!(
+            store.get_organization(&acme.id).await,
+            Err(OrganizationStoreError::OrganizationNotFound)
+        );
+
+        store.add_organization(acme.clone()).await.unwrap();
+        assert_eq!(store.get_organization(&acme.id).await.unwrap(), acme);
+    }
+
+    #[tokio::test]
+    async fn test_set_and_remove_member() {
+        let mut store = HashmapOrganizationStore::default();
+        let email = Email::parse("test@test.pl".into()).unwrap();
+        let acme = organization("Acme");
+
+        assert_eq!(
+            store.set_member(&acme.id, &email, &[]).await,
+            Err(OrganizationStoreError::OrganizationNotFound)
+        );
+
+        store.add_organization(acme.clone()).await.unwrap();
+        store.set_member(&acme.id, &email, &[role("org-admin")]).await.unwrap();
+        let joined_at = store.get_membership(&acme.id, &email).await.unwrap().joined_at;
+
+        store.set_member(&acme.id, &email, &[role("support")]).await.unwrap();
+        let membership = store.get_membership(&acme.id, &email).await.unwrap();
+        assert_eq!(membership.roles, vec![role("support")]);
+        assert_eq!(membership.joined_at, joined_at);
+        assert_eq!(store.list_members(&acme.id).await.unwrap().len(), 1);
+
+        store.remove_member(&acme.id, &email).await.unwrap();
+        assert_eq!(
+            store.get_membership(&acme.id, &email).await,
+            Err(OrganizationStoreError::MemberNotFound)
+        );
+        assert_eq!(
+            store.remove_member(&acme.id, &email).await,
+            Err(OrganizationStoreError::MemberNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_memberships_in_joining_order() {
+        let mut store = HashmapOrganizationStore::default();
+        let email = Email::parse("test@test.pl".into()).unwrap();
+        let acme = organization("Acme");
+        let globex = organization("Globex");
+
+        for organization in [&acme, &globex] {
+            store.add_organization(organization.clone()).await.unwrap();
+            store.set_member(&organization.id, &email, &[]).await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        }
+
+        let ids: Vec<OrganizationId> = store
+            .list_memberships(&email)
+            .await
+            .unwrap()
+            .iter()
+            .map(|membership| membership.organization.id)
+            .collect();
+        assert_eq!(ids, vec![acme.id, globex.id]);
+    }
+
+    #[tokio::test]
+    async fn test_move_and_remove_memberships() {
+        let mut store = HashmapOrganizationStore::default();
+        let old_email = Email::parse("old@test.pl".into()).unwrap();
+        let new_email = Email::parse("new@test.pl".into()).unwrap();
+        let acme = organization("Acme");
+
+        store.add_organization(acme.clone()).await.unwrap();
+        store.set_member(&acme.id, &old_email, &[]).await.unwrap();
+        store.move_memberships(&old_email, &new_email).await.unwrap();
+
+        assert!(store.list_memberships(&old_email).await.unwrap().is_empty());
+        assert_eq!(store.list_memberships(&new_email).await.unwrap().len(), 1);
+
+        store.remove_memberships(&new_email).await.unwrap();
+        assert!(store.list_memberships(&new_email).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_replace_and_take_member_invitation() {
+        let mut store = HashmapOrganizationStore::default();
+        let email = Email::parse("test@test.pl".into()).unwrap();
+        let acme = organization("Acme");
+        let expires_at = Utc::now();
+        let invitation = |id: &str| MemberInvitation {
+            id: id.to_owned(),
+            organization_id: acme.id,
+            email: email.clone(),
+            roles: vec![role("support")],
+            expires_at,
+        };
+
+        assert_eq!(
+            store.add_member_invitation(invitation("first")).await,
+            Err(OrganizationStoreError::OrganizationNotFound)
+        );
+
+        store.add_organization(acme.clone()).await.unwrap();
+        store.add_member_invitation(invitation("first")).await.unwrap();
+        store.add_member_invitation(invitation("second")).await.unwrap();
+
+        assert_eq!(
+            store.take_member_invitation("first").await,
+            Err(OrganizationStoreError::InvitationNotFound)
+        );
+        assert_eq!(store.take_member_invitation("second").await.unwrap(), invitation("second"));
+        assert_eq!(
+            store.take_member_invitation("second").await,
+            Err(OrganizationStoreError::InvitationNotFound)
+        );
+    }
+}
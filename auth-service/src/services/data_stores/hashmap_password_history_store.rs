@@ -0,0 +1,94 @@
+use crate::domain::data_stores::{PasswordHistoryStore, PasswordHistoryStoreError};
+use crate::domain::email::Email;
+use crate::domain::hashed_password::HashedPassword;
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Default)]
+pub struct HashmapPasswordHistoryStore {
+    // Most recent first
+    passwords: HashMap<Email, VecDeque<HashedPassword>>,
+}
+
+#[async_trait::async_trait]
+impl PasswordHistoryStore for HashmapPasswordHistoryStore {
+    async fn get_passwords(&self, email: &Email, limit: usize) -> Result<Vec<HashedPassword>, PasswordHistoryStoreError> {
+        Ok(self
+            .passwords
+            .get(email)
+            .map(|passwords| passwords.iter().take(limit).cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn add_password(
+        &mut self,
+        email: &Email,
+        password: HashedPassword,
+        keep: usize,
+    ) -> Result<(), PasswordHistoryStoreError> {
+        let passwords = self.passwords.entry(email.clone()).or_default();
+        passwords.push_front(password);
+        passwords.truncate(keep);
+
+        Ok(())
+    }
+
+    async fn move_passwords(&mut self, old_email: &Email, new_email: &Email) -> Result<(), PasswordHistoryStoreError> {
+        if let Some(passwords) = self.passwords.remove(old_email) {
+            self.passwords.insert(new_email.clone(), passwords);
+        }
+
+        Ok(())
+    }
+
+    async fn remove_passwords(&mut self, email: &Email) -> Result<(), PasswordHistoryStoreError> {
+        self.passwords.remove(email);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_keeps_most_recent_passwords() {
+        let mut store = HashmapPasswordHistoryStore::default();
+        let email: Email = "test@test.pl".try_into().unwrap();
+
+        let mut passwords = Vec::new();
+        for password in ["firstPassword1", "secondPassword2", "thirdPassword3"] {
+            let password = HashedPassword::parse(password.into()).await.unwrap();
+            store.add_password(&email, password.clone(), 2).await.unwrap();
+            passwords.push(password);
+        }
+
+        assert_eq!(
+            store.get_passwords(&email, 5).await.unwrap(),
+            vec![passwords[2].clone(), passwords[1].clone()]
+        );
+        assert_eq!(store.get_passwords(&email, 1).await.unwrap(), vec![passwords[2].clone()]);
+        assert!(store
+            .get_passwords(&"noone@example.com".try_into().unwrap(), 5)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_move_and_remove_passwords() {
+        let mut store = HashmapPasswordHistoryStore::default();
+        let old_email: Email = "old@test.pl".try_into().unwrap();
+        let new_email: Email = "new@test.pl".try_into().unwrap();
+
+        let password = HashedPassword::parse("firstPassword1".into()).await.unwrap();
+        store.add_password(&old_email, password.clone(), 2).await.unwrap();
+        store.move_passwords(&old_email, &new_email).await.unwrap();
+
+        assert_eq!(store.get_passwords(&new_email, 5).await.unwrap(), vec![password]);
+        assert!(store.get_passwords(&old_email, 5).await.unwrap().is_empty());
+
+        store.remove_passwords(&new_email).await.unwrap();
+        assert!(store.get_passwords(&new_email, 5).await.unwrap().is_empty());
+    }
+}
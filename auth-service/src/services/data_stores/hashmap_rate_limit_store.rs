@@ -0,0 +1,165 @@
+use crate::domain::data_stores::{RateLimitStore, RateLimitStoreError};
+use crate::domain::rate_limit::{RateLimit, RateLimitDecision};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, VecDeque};
+
+// How often keys are checked for windows that emptied since they were last requested
+const SWEEP_INTERVAL_SECONDS: i64 = 60;
+
+// Only sees the requests made to this instance, see `RedisRateLimitStore` for deployments with several
+#[derive(Default)]
+pub struct HashmapRateLimitStore {
+    windows: HashMap<String, Window>,
+    last_sweep: Option<DateTime<Utc>>,
+}
+
+struct Window {
+    length: Duration,
+    // Oldest first
+    requests: VecDeque<DateTime<Utc>>,
+}
+
+impl Window {
+    fn prune(&mut self, now: DateTime<Utc>) {
+        if let Some(window_start) = now.checked_sub_signed(self.length) {
+            while self.requests.front().is_some_and(|at| *at <= window_start) {
+                self.requests.pop_front();
+            }
+        }
+    }
+}
+
+impl HashmapRateLimitStore {
+    // Keys that are no longer requested would otherwise be kept forever
+    fn sweep(&mut self, now: DateTime<Utc>) {
+        let due = self
+            .last_sweep
+            .and_then(|at| at.checked_add_signed(Duration::seconds(SWEEP_INTERVAL_SECONDS)))
+            .is_none_or(|next| next <= now);
+        if !due {
+            return;
+        }
+
+        self.windows.retain(|_, window| {
+            window.prune(now);
+            !window.requests.is_empty()
+        });
+        self.last_sweep = Some(now);
+    }
+}
+
+#[async_trait::async_trait]
+impl RateLimitStore for HashmapRateLimitStore {
+    async fn record_request(
+        &mut self,
+        key: &str,
+        limit: &RateLimit,
+        now: DateTime<Utc>,
+    ) -> Result<RateLimitDecision, RateLimitStoreError> {
+        self.sweep(now);
+
+        let window = self.windows.entry(key.to_owned()).or_insert_with(|| Window {
+            length: limit.window(),
+            requests: VecDeque::new(),
+        });
+        window.length = limit.window();
+        window.prune(now);
+
+        let decision = limit.decide(window.requests.make_contiguous(), now);
+        if decision == RateLimitDecision::Allowed {
+            window.requests.push_back(now);
+        } else if window.requests.is_empty() {
+            self.windows.remove(key);
+        }
+
+        Ok(decision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn limit() -> RateLimit {
+        RateLimit {
+            max_requests: 2,
+            window_seconds: 60,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejects_requests_over_the_limit() {
+        let mut store = HashmapRateLimitStore::default();
+        let now = Utc::now();
+
+        for _ in 0..2 {
+            assert_eq!(
+                store.record_request("login:ip:127.0.0.1", &limit(), now).await.unwrap(),
+                RateLimitDecision::Allowed
+            );
+        }
+
+        assert_eq!(
+            store.record_request("login:ip:127.0.0.1", &limit(), now).await.unwrap(),
+            RateLimitDecision::Limited { retry_after_seconds: 60 }
+        );
+        // Other keys have their own window
+        assert_eq!(
+            store.record_request("login:ip:127.0.0.2", &limit(), now).await.unwrap(),
+            RateLimitDecision::Allowed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_forgets_keys_whose_window_emptied() {
+        let mut store = HashmapRateLimitStore::default();
+        let start = Utc::now();
+
+        store.record_request("login:ip:127.0.0.1", &limit(), start).await.unwrap();
+        store
+            .record_request("login:ip:127.0.0.2", &limit(), start + Duration::seconds(30))
+            .await
+            .unwrap();
+        assert_eq!(store.windows.len(), 2);
+
+        store
+            .record_request("login:ip:127.0.0.3", &limit(), start + Duration::seconds(61))
+            .await
+            .unwrap();
+        assert!(!store.windows.contains_key("login:ip:127.0.0.1"));
+        assert!(store.windows.contains_key("login:ip:127.0.0.2"));
+        assert!(store.windows.contains_key("login:ip:127.0.0.3"));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_requests_are_not_recorded() {
+        let mut store = HashmapRateLimitStore::default();
+        let start = Utc::now();
+
+        store.record_request("key", &limit(), start).await.unwrap();
+        store
+            .record_request("key", &limit(), start + Duration::seconds(30))
+            .await
+            .unwrap();
+
+        // Hammering the route doesn't push back the moment it opens again
+        for seconds in [31, 45, 59] {
+            assert!(matches!(
+                store
+                    .record_request("key", &limit(), start + Duration::seconds(seconds))
+                    .await
+                    .unwrap(),
+                RateLimitDecision::Limited { .. }
+            ));
+        }
+
+        assert_eq!(
+            store
+                .record_request("key", &limit(), start + Duration::seconds(60))
+                .await
+                .unwrap(),
+            RateLimitDecision::Allowed
+        );
+    }
+}
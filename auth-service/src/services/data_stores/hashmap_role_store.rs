@@ -0,0 +1,157 @@
+use crate::domain::data_stores::{RoleStore, RoleStoreError};
+use crate::domain::email::Email;
+use crate::domain::rbac::{Role, RoleName};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+#[derive(Default)]
+pub struct HashmapRoleStore {
+    roles: BTreeMap<RoleName, Role>,
+    assignments: HashMap<Email, BTreeSet<RoleName>>,
+}
+
+#[async_trait::async_trait]
+impl RoleStore for HashmapRoleStore {
+    async fn list_roles(&self) -> Result<Vec<Role>, RoleStoreError> {
+        Ok(self.roles.values().cloned().collect())
+    }
+
+    async fn set_role(&mut self, role: Role) -> Result<(), RoleStoreError> {
+        self.roles.insert(role.name.clone(), role);
+
+        Ok(())
+    }
+
+    async fn delete_role(&mut self, name: &RoleName) -> Result<(), RoleStoreError> {
+        self.roles.remove(name).ok_or(RoleStoreError::RoleNotFound)?;
+
+        for roles in self.assignments.values_mut() {
+            roles.remove(name);
+        }
+
+        Ok(())
+    }
+
+    async fn assign_role(&mut self, email: &Email, name: &RoleName) -> Result<(), RoleStoreError> {
+        if !self.roles.contains_key(name) {
+            return Err(RoleStoreError::RoleNotFound);
+        }
+
+        self.assignments.entry(email.clone()).or_default().insert(name.clone());
+
+        Ok(())
+    }
+
+    async fn unassign_role(&mut self, email: &Email, name: &RoleName) -> Result<(), RoleStoreError> {
+        let is_removed = self.assignments.get_mut(email).is_some_and(|roles| roles.remove(name));
+
+        match is_removed {
+            true => Ok(()),
+            false => Err(RoleStoreError::RoleNotAssigned),
+        }
+    }
+
+    async fn get_user_roles(&self, email: &Email) -> Result<Vec<RoleName>, RoleStoreError> {
+        Ok(self
+            .assignments
+            .get(email)
+            .map(|roles| roles.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn move_user_roles(&mut self, old_email: &Email, new_email: &Email) -> Result<(), RoleStoreError> {
+        if let Some(roles) = self.assignments.remove(old_email) {
+            self.assignments.entry(new_email.clone()).or_default().extend(roles);
+        }
+
+        Ok(())
+    }
+
+    async fn remove_user_roles(&mut self, email: &Email) -> Result<(), RoleStoreError> {
+        self.assignments.remove(email);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::rbac::Permission;
+
+    fn role(name: &str) -> Role {
+        Role {
+            name: RoleName::parse(name).unwrap(),
+            permissions: vec![Permission::parse("users:read").unwrap()],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_set_and_delete_role() {
+        let mut store = HashmapRoleStore::default();
+        let support = role("support");
+        store.set_role(support.clone()).await.unwrap();
+
+        let mut updated = support.clone();
+        updated.permissions.push(Permission::parse("users:write").unwrap());
+        store.set_role(updated.clone()).await.unwrap();
+        assert_eq!(store.list_roles().await.unwrap(), vec![updated]);
+
+        store.delete_role(&support.name).await.unwrap();
+        assert!(store.list_roles().await.unwrap().is_empty());
+        assert_eq!(store.delete_role(&support.name).await, Err(RoleStoreError::RoleNotFound));
+    }
+
+    #[tokio::test]
+    async fn test_assign_role() {
+        let mut store = HashmapRoleStore::default();
+        let email = Email::parse("test@test.pl".into()).unwrap();
+        let support = role("support");
+
+        assert_eq!(
+            store.assign_role(&email, &support.name).await,
+            Err(RoleStoreError::RoleNotFound)
+        );
+
+        store.set_role(support.clone()).await.unwrap();
+        store.assign_role(&email, &support.name).await.unwrap();
+        assert_eq!(store.get_user_roles(&email).await.unwrap(), vec![support.name.clone()]);
+
+        store.unassign_role(&email, &support.name).await.unwrap();
+        assert!(store.get_user_roles(&email).await.unwrap().is_empty());
+        assert_eq!(
+            store.unassign_role(&email, &support.name).await,
+            Err(RoleStoreError::RoleNotAssigned)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deleted_role_is_unassigned() {
+        let mut store = HashmapRoleStore::default();
+        let email = Email::parse("test@test.pl".into()).unwrap();
+        let support = role("support");
+
+        store.set_role(support.clone()).await.unwrap();
+        store.assign_role(&email, &support.name).await.unwrap();
+        store.delete_role(&support.name).await.unwrap();
+
+        assert!(store.get_user_roles(&email).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_move_and_remove_user_roles() {
+        let mut store = HashmapRoleStore::default();
+        let old_email = Email::parse("old@test.pl".into()).unwrap();
+        let new_email = Email::parse("new@test.pl".into()).unwrap();
+        let support = role("support");
+
+        store.set_role(support.clone()).await.unwrap();
+        store.assign_role(&old_email, &support.name).await.unwrap();
+        store.move_user_roles(&old_email, &new_email).await.unwrap();
+
+        assert!(store.get_user_roles(&old_email).await.unwrap().is_empty());
+        assert_eq!(store.get_user_roles(&new_email).await.unwrap(), vec![support.name]);
+
+        store.remove_user_roles(&new_email).await.unwrap();
+        assert!(store.get_user_roles(&new_email).await.unwrap().is_empty());
+    }
+}
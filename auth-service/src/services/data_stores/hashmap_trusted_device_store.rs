@@ -0,0 +1,138 @@
+use crate::domain::data_stores::{TrustedDeviceStore, TrustedDeviceStoreError};
+use crate::domain::email::Email;
+use crate::domain::trusted_device::TrustedDevice;
+use std::collections::HashMap;
+
+#[derive(Default)]
+pub struct HashmapTrustedDeviceStore {
+    devices: HashMap<Email, Vec<TrustedDevice>>,
+}
+
+#[async_trait::async_trait]
+impl TrustedDeviceStore for HashmapTrustedDeviceStore {
+    async fn add_device(&mut self, email: Email, device: TrustedDevice) -> Result<(), TrustedDeviceStoreError> {
+        let devices = self.devices.entry(email).or_default();
+        devices.retain(|existing| !existing.is_expired() && existing.id != device.id);
+        devices.push(device);
+
+        Ok(())
+    }
+
+    async fn get_device(&self, email: &Email, device_id: &str) -> Result<TrustedDevice, TrustedDeviceStoreError> {
+        self.devices
+            .get(email)
+            .and_then(|devices| devices.iter().find(|device| device.id == device_id && !device.is_expired()))
+            .cloned()
+            .ok_or(TrustedDeviceStoreError::DeviceNotFound)
+    }
+
+    async fn get_devices(&self, email: &Email) -> Result<Vec<TrustedDevice>, TrustedDeviceStoreError> {
+        Ok(self
+            .devices
+            .get(email)
+            .map(|devices| devices.iter().filter(|device| !device.is_expired()).cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn remove_device(&mut self, email: &Email, device_id: &str) -> Result<(), TrustedDeviceStoreError> {
+        let devices = self.devices.get_mut(email).ok_or(TrustedDeviceStoreError::DeviceNotFound)?;
+
+        let count = devices.len();
+        devices.retain(|device| device.id != device_id);
+
+        if devices.len() == count {
+            return Err(TrustedDeviceStoreError::DeviceNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn move_devices(&mut self, old_email: &Email, new_email: &Email) -> Result<(), TrustedDeviceStoreError> {
+        if let Some(devices) = self.devices.remove(old_email) {
+            self.devices.entry(new_email.clone()).or_default().extend(devices);
+        }
+
+        Ok(())
+    }
+
+    async fn remove_devices(&mut self, email: &Email) -> Result<(), TrustedDeviceStoreError> {
+        self.devices.remove(email);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn device(id: &str, expires_in: Duration) -> TrustedDevice {
+        let now = Utc::now();
+        TrustedDevice::new(id.to_owned(), "test-agent".to_owned(), now, now + expires_in)
+    }
+
+    #[tokio::test]
+    async fn test_add_and_get_device() {
+        let mut store = HashmapTrustedDeviceStore::default();
+        let email = Email::parse("test@example.com".into()).unwrap();
+        let device = device("device-1", Duration::days(1));
+
+        store.add_device(email.clone(), device.clone()).await.unwrap();
+
+        assert_eq!(store.get_device(&email, "device-1").await.unwrap(), device);
+        assert_eq!(store.get_devices(&email).await.unwrap(), vec![device]);
+    }
+
+    #[tokio::test]
+    async fn test_expired_device_is_not_returned() {
+        let mut store = HashmapTrustedDeviceStore::default();
+        let email = Email::parse("test@example.com".into()).unwrap();
+
+        store
+            .add_device(email.clone(), device("device-1", Duration::seconds(-1)))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.get_device(&email, "device-1").await.unwrap_err(),
+            TrustedDeviceStoreError::DeviceNotFound
+        );
+        assert!(store.get_devices(&email).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_remove_device() {
+        let mut store = HashmapTrustedDeviceStore::default();
+        let email = Email::parse("test@example.com".into()).unwrap();
+
+        store
+            .add_device(email.clone(), device("device-1", Duration::days(1)))
+            .await
+            .unwrap();
+        store.remove_device(&email, "device-1").await.unwrap();
+
+        assert_eq!(
+            store.get_device(&email, "device-1").await.unwrap_err(),
+            TrustedDeviceStoreError::DeviceNotFound
+        );
+        assert_eq!(
+            store.remove_device(&email, "device-1").await.unwrap_err(),
+            TrustedDeviceStoreError::DeviceNotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_move_devices() {
+        let mut store = HashmapTrustedDeviceStore::default();
+        let old_email = Email::parse("old@example.com".into()).unwrap();
+        let new_email = Email::parse("new@example.com".into()).unwrap();
+        let device = device("device-1", Duration::days(1));
+
+        store.add_device(old_email.clone(), device.clone()).await.unwrap();
+        store.move_devices(&old_email, &new_email).await.unwrap();
+
+        assert!(store.get_devices(&old_email).await.unwrap().is_empty());
+        assert_eq!(store.get_device(&new_email, "device-1").await.unwrap(), device);
+    }
+}
@@ -1,11 +1,11 @@
-use crate::domain::data_stores::{LoginAttemptId, TwoFACode, TwoFACodeStore, TwoFACodeStoreError};
+use crate::domain::data_stores::{LoginAttemptId, TwoFACodeHash, TwoFACodeStore, TwoFACodeStoreError};
 use crate::domain::email::Email;
 use color_eyre::eyre::eyre;
 use std::collections::HashMap;
 
 #[derive(Default)]
 pub struct HashmapTwoFACodeStore {
-    two_fa_store: HashMap<Email, (TwoFACode, LoginAttemptId)>,
+    two_fa_store: HashMap<Email, (TwoFACodeHash, LoginAttemptId)>,
 }
 
 #[async_trait::async_trait]
@@ -14,9 +14,9 @@ impl TwoFACodeStore for HashmapTwoFACodeStore {
         &mut self,
         email: Email,
         login_attempt_id: LoginAttemptId,
-        code: TwoFACode,
+        code_hash: TwoFACodeHash,
     ) -> Result<(), TwoFACodeStoreError> {
-        self.two_fa_store.insert(email, (code, login_attempt_id));
+        self.two_fa_store.insert(email, (code_hash, login_attempt_id));
 
         Ok(())
     }
@@ -31,10 +31,10 @@ impl TwoFACodeStore for HashmapTwoFACodeStore {
         Ok(())
     }
 
-    async fn get_code(&self, email: &Email) -> Result<(LoginAttemptId, TwoFACode), TwoFACodeStoreError> {
+    async fn get_code(&self, email: &Email) -> Result<(LoginAttemptId, TwoFACodeHash), TwoFACodeStoreError> {
         match self.two_fa_store.get(email) {
             None => Err(TwoFACodeStoreError::LoginAttemptIdNotFound),
-            Some((code, login_attempt_id)) => Ok((login_attempt_id.clone(), code.clone())),
+            Some((code_hash, login_attempt_id)) => Ok((login_attempt_id.clone(), code_hash.clone())),
         }
     }
 }
@@ -53,8 +53,9 @@ mod tests {
         let random_email = Email::parse(SafeEmail().fake::<String>().into()).unwrap();
         let code = TwoFACode::parse("123123".to_string()).unwrap();
         let login_attempt_id = LoginAttemptId::default();
+        let code_hash = code.hash(&login_attempt_id).unwrap();
 
-        let result = store.add_code(random_email, login_attempt_id, code).await;
+        let result = store.add_code(random_email, login_attempt_id, code_hash).await;
         assert!(result.is_ok());
 
         let failed_code = TwoFACode::parse("123".to_string());
@@ -70,7 +71,11 @@ mod tests {
         let login_attempt_id = LoginAttemptId::default();
 
         store
-            .add_code(random_email.clone(), login_attempt_id.clone(), code.clone())
+            .add_code(
+                random_email.clone(),
+                login_attempt_id.clone(),
+                code.hash(&login_attempt_id).unwrap(),
+            )
             .await
             .expect("Adding code failed for some reason!");
 
@@ -78,9 +83,9 @@ mod tests {
 
         assert!(result.is_ok());
 
-        let (_login_attempt_id, _code) = result.expect("Failed to receive code and login_attempt_id");
+        let (_login_attempt_id, _code_hash) = result.expect("Failed to receive code and login_attempt_id");
 
-        assert_eq!(code, _code);
+        assert!(_code_hash.verify(&login_attempt_id, &code));
         assert_eq!(login_attempt_id, _login_attempt_id);
     }
 
@@ -93,7 +98,7 @@ mod tests {
         let code = TwoFACode::parse("123456".to_string()).unwrap();
 
         store
-            .add_code(email.clone(), login_attempt_id.clone(), code.clone())
+            .add_code(email.clone(), login_attempt_id.clone(), code.hash(&login_attempt_id).unwrap())
             .await
             .unwrap();
 
@@ -1,6 +1,13 @@
+use crate::domain::account_status::AccountState;
 use crate::domain::data_stores::{UserStore, UserStoreError};
 use crate::domain::email::Email;
-use crate::domain::user::User;
+use crate::domain::hashed_password::HashedPassword;
+use crate::domain::profile::Profile;
+use crate::domain::user::{User, UserPage, UserSearch};
+use crate::utils::hashing_executor::HashingOverloaded;
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::eyre;
+use secrecy::ExposeSecret;
 use std::collections::HashMap;
 // TODO: Create a new struct called `HashmapUserStore` containing a `users` field
 // which stores a `HashMap`` of email `String`s mapped to `User` objects.
@@ -44,19 +51,142 @@ impl UserStore for HashmapUserStore {
             .map(|user| user.to_owned())
     }
 
-    // TODO: Implement a public method called `validate_user`, which takes an
-    // immutable reference to self, an email string slice, and a password string slice
-    // as arguments. `validate_user` should return a `Result` type containing either a
-    // unit type `()` if the email/password passed in match an existing user, or a `UserStoreError`.
-    // Return `UserStoreError::UserNotFound` if the user can not be found.
-    // Return `UserStoreError::InvalidCredentials` if the password is incorrect.
+    async fn list_users(&self, search: &UserSearch) -> Result<UserPage, UserStoreError> {
+        let query = search.query.as_deref().map(str::to_lowercase);
+
+        let mut users: Vec<&User> = self
+            .users
+            .values()
+            .filter(|user| match &query {
+                None => true,
+                Some(query) => {
+                    user.email.0.expose_secret().to_lowercase().contains(query)
+                        || user
+                            .profile
+                            .display_name
+                            .as_ref()
+                            .is_some_and(|name| name.as_ref().to_lowercase().contains(query))
+                }
+            })
+            .collect();
+
+        users.sort_by_key(|user| (user.created_at, user.email.0.expose_secret().to_owned()));
+
+        let total = u64::try_from(users.len()).map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?;
+        let offset = usize::try_from(search.offset).map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?;
+        let limit = usize::try_from(search.limit).map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?;
+
+        let users = users.into_iter().skip(offset).take(limit).cloned().collect();
+
+        Ok(UserPage { users, total })
+    }
+
+    // Unknown users and users without a password are checked against a dummy hash, see `verify_dummy_password`
     async fn validate_user(&self, email: &Email, raw_password: &str) -> Result<(), UserStoreError> {
-        let user = self.users.get(email).ok_or(UserStoreError::UserNotFound)?;
+        let Some(user) = self.users.get(email) else {
+            HashedPassword::verify_dummy_password(raw_password)
+                .await
+                .map_err(UserStoreError::UnexpectedError)?;
+            return Err(UserStoreError::UserNotFound);
+        };
+        let Some(password) = user.password.as_ref() else {
+            HashedPassword::verify_dummy_password(raw_password)
+                .await
+                .map_err(UserStoreError::UnexpectedError)?;
+            return Err(UserStoreError::InvalidCredentials);
+        };
 
-        user.password
+        password
             .verify_raw_password(raw_password)
             .await
-            .map_err(|_| UserStoreError::InvalidCredentials)
+            .map_err(|e| match HashingOverloaded::caused(&e) {
+                true => UserStoreError::UnexpectedError(e),
+                false => UserStoreError::InvalidCredentials,
+            })
+    }
+
+    async fn mark_email_verified(&mut self, email: &Email) -> Result<(), UserStoreError> {
+        let user = self.users.get_mut(email).ok_or(UserStoreError::UserNotFound)?;
+        user.email_verified = true;
+
+        Ok(())
+    }
+
+    async fn update_password(&mut self, email: &Email, password: HashedPassword) -> Result<(), UserStoreError> {
+        let user = self.users.get_mut(email).ok_or(UserStoreError::UserNotFound)?;
+        user.password = Some(password);
+
+        Ok(())
+    }
+
+    async fn upgrade_password_hash(
+        &mut self,
+        email: &Email,
+        old_password: &HashedPassword,
+        new_password: HashedPassword,
+    ) -> Result<(), UserStoreError> {
+        let user = self.users.get_mut(email).ok_or(UserStoreError::UserNotFound)?;
+
+        if user.password.as_ref() == Some(old_password) {
+            user.password = Some(new_password);
+        }
+
+        Ok(())
+    }
+
+    async fn update_email(&mut self, old_email: &Email, new_email: &Email) -> Result<(), UserStoreError> {
+        if !self.users.contains_key(old_email) {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        if self.users.contains_key(new_email) {
+            return Err(UserStoreError::UserAlreadyExists);
+        }
+
+        let mut user = self.users.remove(old_email).ok_or(UserStoreError::UserNotFound)?;
+        user.email = new_email.clone();
+        self.users.insert(new_email.clone(), user);
+
+        Ok(())
+    }
+
+    async fn delete_user(&mut self, email: &Email) -> Result<(), UserStoreError> {
+        self.users.remove(email).map(|_| ()).ok_or(UserStoreError::UserNotFound)
+    }
+
+    async fn update_profile(&mut self, email: &Email, profile: Profile) -> Result<(), UserStoreError> {
+        let user = self.users.get_mut(email).ok_or(UserStoreError::UserNotFound)?;
+        user.profile = profile;
+
+        Ok(())
+    }
+
+    async fn clear_password(&mut self, email: &Email) -> Result<(), UserStoreError> {
+        let user = self.users.get_mut(email).ok_or(UserStoreError::UserNotFound)?;
+        user.password = None;
+
+        Ok(())
+    }
+
+    async fn set_account_state(&mut self, email: &Email, account_state: AccountState) -> Result<(), UserStoreError> {
+        let user = self.users.get_mut(email).ok_or(UserStoreError::UserNotFound)?;
+        user.account_state = account_state;
+
+        Ok(())
+    }
+
+    async fn set_requires_2fa(&mut self, email: &Email, requires_2fa: bool) -> Result<(), UserStoreError> {
+        let user = self.users.get_mut(email).ok_or(UserStoreError::UserNotFound)?;
+        user.requires_2fa = requires_2fa;
+
+        Ok(())
+    }
+
+    async fn record_login(&mut self, email: &Email, logged_in_at: DateTime<Utc>) -> Result<(), UserStoreError> {
+        let user = self.users.get_mut(email).ok_or(UserStoreError::UserNotFound)?;
+        user.last_login_at = Some(logged_in_at);
+
+        Ok(())
     }
 }
 
@@ -64,7 +194,7 @@ impl UserStore for HashmapUserStore {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::hashed_password::HashedPassword;
+    use crate::domain::account_status::AccountStatus;
 
     #[tokio::test]
     async fn test_add_user() {
@@ -72,7 +202,7 @@ mod tests {
 
         let user = User::new(
             "test@test.pl".try_into().unwrap(),
-            HashedPassword::parse("testPassword123".into()).await.unwrap(),
+            Some(HashedPassword::parse("testPassword123".into()).await.unwrap()),
             false,
         );
         let result = store.add_user(user.clone()).await;
@@ -92,7 +222,7 @@ mod tests {
         let mut store = HashmapUserStore::default();
         let user = User::new(
             "test@test.pl".try_into().unwrap(),
-            HashedPassword::parse("testPassword123".into()).await.unwrap(),
+            Some(HashedPassword::parse("testPassword123".into()).await.unwrap()),
             false,
         );
         store.add_user(user.clone()).await.unwrap();
@@ -111,7 +241,7 @@ mod tests {
         let mut store = HashmapUserStore::default();
         let user = User::new(
             "test@test.pl".try_into().unwrap(),
-            HashedPassword::parse("testPassword123".into()).await.unwrap(),
+            Some(HashedPassword::parse("testPassword123".into()).await.unwrap()),
             false,
         );
         store.add_user(user.clone()).await.unwrap();
@@ -139,4 +269,248 @@ mod tests {
             "Unknown email should return UserNotFound"
         );
     }
+
+    #[tokio::test]
+    async fn test_upgrade_password_hash() {
+        let mut store = HashmapUserStore::default();
+        let old_password = HashedPassword::parse("testPassword123".into()).await.unwrap();
+        let user = User::new("test@test.pl".try_into().unwrap(), Some(old_password.clone()), false);
+        store.add_user(user.clone()).await.unwrap();
+
+        let new_password = HashedPassword::parse("testPassword123".into()).await.unwrap();
+        store
+            .upgrade_password_hash(&user.email, &old_password, new_password.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_user(&user.email).await.unwrap().password,
+            Some(new_password.clone())
+        );
+
+        // The password changed since the old hash was read, so it is kept
+        store
+            .upgrade_password_hash(
+                &user.email,
+                &old_password,
+                HashedPassword::parse("testPassword123".into()).await.unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(store.get_user(&user.email).await.unwrap().password, Some(new_password));
+    }
+
+    #[tokio::test]
+    async fn test_validate_passwordless_user() {
+        let mut store = HashmapUserStore::default();
+        let user = User::new("test@test.pl".try_into().unwrap(), None, false);
+        store.add_user(user.clone()).await.unwrap();
+
+        let res = store.validate_user(&user.email, "").await;
+        assert_eq!(
+            res.expect_err("Result should be error"),
+            UserStoreError::InvalidCredentials,
+            "Passwordless user should never pass password validation"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mark_email_verified() {
+        let mut store = HashmapUserStore::default();
+        let user = User::new("test@test.pl".try_into().unwrap(), None, false);
+        store.add_user(user.clone()).await.unwrap();
+
+        assert!(!store.get_user(&user.email).await.unwrap().email_verified);
+
+        store.mark_email_verified(&user.email).await.unwrap();
+
+        assert!(store.get_user(&user.email).await.unwrap().email_verified);
+
+        let res = store.mark_email_verified(&"noone@example.com".try_into().unwrap()).await;
+        assert_eq!(
+            res.expect_err("Result should be error"),
+            UserStoreError::UserNotFound,
+            "Unknown email should return UserNotFound"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_password() {
+        let mut store = HashmapUserStore::default();
+        let user = User::new("test@test.pl".try_into().unwrap(), None, false);
+        store.add_user(user.clone()).await.unwrap();
+
+        store
+            .update_password(&user.email, HashedPassword::parse("newPassword123".into()).await.unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(store.validate_user(&user.email, "newPassword123").await.unwrap(), ());
+
+        let res = store
+            .update_password(
+                &"noone@example.com".try_into().unwrap(),
+                HashedPassword::parse("newPassword123".into()).await.unwrap(),
+            )
+            .await;
+        assert_eq!(
+            res.expect_err("Result should be error"),
+            UserStoreError::UserNotFound,
+            "Unknown email should return UserNotFound"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_email() {
+        let mut store = HashmapUserStore::default();
+        let user = User::new("test@test.pl".try_into().unwrap(), None, false);
+        let other_user = User::new("other@test.pl".try_into().unwrap(), None, false);
+        store.add_user(user.clone()).await.unwrap();
+        store.add_user(other_user.clone()).await.unwrap();
+
+        assert_eq!(
+            store.update_email(&user.email, &other_user.email).await.unwrap_err(),
+            UserStoreError::UserAlreadyExists,
+            "Taken email should return UserAlreadyExists"
+        );
+
+        let new_email: Email = "new@test.pl".try_into().unwrap();
+        store.update_email(&user.email, &new_email).await.unwrap();
+
+        assert_eq!(store.get_user(&user.email).await.unwrap_err(), UserStoreError::UserNotFound);
+        assert_eq!(store.get_user(&new_email).await.unwrap().email, new_email);
+    }
+
+    #[tokio::test]
+    async fn test_delete_user() {
+        let mut store = HashmapUserStore::default();
+        let user = User::new("test@test.pl".try_into().unwrap(), None, false);
+        store.add_user(user.clone()).await.unwrap();
+
+        store.delete_user(&user.email).await.unwrap();
+
+        assert_eq!(store.get_user(&user.email).await.unwrap_err(), UserStoreError::UserNotFound);
+        assert_eq!(
+            store.delete_user(&user.email).await.unwrap_err(),
+            UserStoreError::UserNotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_profile() {
+        use crate::domain::profile::{DisplayName, Locale, Timezone};
+
+        let mut store = HashmapUserStore::default();
+        let user = User::new("test@test.pl".try_into().unwrap(), None, false);
+        store.add_user(user.clone()).await.unwrap();
+
+        let profile = Profile {
+            display_name: Some(DisplayName::parse("Jane Doe").unwrap()),
+            locale: Some(Locale::parse("pl-PL").unwrap()),
+            timezone: Some(Timezone::parse("Europe/Warsaw").unwrap()),
+        };
+        store.update_profile(&user.email, profile.clone()).await.unwrap();
+
+        assert_eq!(store.get_user(&user.email).await.unwrap().profile, profile);
+    }
+
+    #[tokio::test]
+    async fn test_record_login() {
+        let mut store = HashmapUserStore::default();
+        let user = User::new("test@test.pl".try_into().unwrap(), None, false);
+        store.add_user(user.clone()).await.unwrap();
+        assert_eq!(store.get_user(&user.email).await.unwrap().last_login_at, None);
+
+        let logged_in_at = Utc::now();
+        store.record_login(&user.email, logged_in_at).await.unwrap();
+
+        assert_eq!(store.get_user(&user.email).await.unwrap().last_login_at, Some(logged_in_at));
+        assert_eq!(
+            store
+                .record_login(&"other@test.pl".try_into().unwrap(), logged_in_at)
+                .await
+                .unwrap_err(),
+            UserStoreError::UserNotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_users() {
+        use crate::domain::profile::DisplayName;
+        use chrono::Duration;
+
+        let mut store = HashmapUserStore::default();
+        let now = Utc::now();
+
+        for (i, email) in ["alice@test.pl", "bob@test.pl", "carol@example.com"].into_iter().enumerate() {
+            let mut user = User::new(email.try_into().unwrap(), None, false);
+            user.created_at = now + Duration::seconds(i.try_into().unwrap());
+            store.add_user(user).await.unwrap();
+        }
+        store
+            .update_profile(
+                &"carol@example.com".try_into().unwrap(),
+                Profile {
+                    display_name: Some(DisplayName::parse("Carol Test").unwrap()),
+                    ..Profile::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let emails = |page: &UserPage| -> Vec<String> {
+            page.users
+                .iter()
+                .map(|user| user.email.0.expose_secret().to_owned())
+                .collect()
+        };
+
+        let page = store
+            .list_users(&UserSearch {
+                query: None,
+                offset: 1,
+                limit: 1,
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(emails(&page), vec!["bob@test.pl"]);
+
+        let page = store
+            .list_users(&UserSearch {
+                query: Some("TEST".to_owned()),
+                offset: 0,
+                limit: 10,
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.total, 3);
+
+        let page = store
+            .list_users(&UserSearch {
+                query: Some("carol test".to_owned()),
+                offset: 0,
+                limit: 10,
+            })
+            .await
+            .unwrap();
+        assert_eq!(emails(&page), vec!["carol@example.com"]);
+    }
+
+    #[tokio::test]
+    async fn test_admin_updates() {
+        let mut store = HashmapUserStore::default();
+        let password = HashedPassword::parse("testPassword123".into()).await.unwrap();
+        let user = User::new("test@test.pl".try_into().unwrap(), Some(password), false);
+        store.add_user(user.clone()).await.unwrap();
+
+        let account_state = AccountState::new(AccountStatus::Disabled, None);
+        store.set_account_state(&user.email, account_state.clone()).await.unwrap();
+        store.set_requires_2fa(&user.email, true).await.unwrap();
+        store.clear_password(&user.email).await.unwrap();
+
+        let updated_user = store.get_user(&user.email).await.unwrap();
+        assert_eq!(updated_user.account_state, account_state);
+        assert!(updated_user.requires_2fa);
+        assert_eq!(updated_user.password, None);
+    }
 }
@@ -1,10 +1,12 @@
-use crate::domain::data_stores::{BannedTokenStore, BannedTokenStoreError};
+use crate::domain::data_stores::{BannedTokenStore, BannedTokenStoreError, TokenRevocation};
+use crate::domain::email::Email;
 use secrecy::{ExposeSecret, SecretString};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Default)]
 pub struct HashsetBannedTokenStore {
     banned_tokens: HashSet<String>,
+    revoked_users: HashMap<Email, TokenRevocation>,
 }
 
 #[async_trait::async_trait]
@@ -18,6 +20,24 @@ impl BannedTokenStore for HashsetBannedTokenStore {
     async fn contains_token(&self, token: &str) -> Result<bool, BannedTokenStoreError> {
         Ok(self.banned_tokens.contains(token))
     }
+
+    async fn revoke_user_tokens(&mut self, email: &Email, keep_token: Option<&str>) -> Result<(), BannedTokenStoreError> {
+        self.revoked_users.insert(email.clone(), TokenRevocation::new(keep_token));
+
+        Ok(())
+    }
+
+    async fn is_user_token_revoked(
+        &self,
+        email: &Email,
+        token: &str,
+        issued_at_micros: i64,
+    ) -> Result<bool, BannedTokenStoreError> {
+        Ok(self
+            .revoked_users
+            .get(email)
+            .is_some_and(|revocation| revocation.revokes(token, issued_at_micros)))
+    }
 }
 
 // TODO: Add unit tests for your `HashmapUserStore` implementation
@@ -25,16 +45,38 @@ impl BannedTokenStore for HashsetBannedTokenStore {
 mod tests {
     use super::*;
     use crate::domain::email::Email;
+    use crate::domain::rbac::Grants;
     use crate::utils::auth::generate_auth_cookie;
 
     #[tokio::test]
     async fn test_ban_token() {
         let mut store = HashsetBannedTokenStore::default();
-        let jwt = generate_auth_cookie(&Email::parse("test@test.pl".into()).unwrap()).unwrap();
+        let jwt = generate_auth_cookie(&Email::parse("test@test.pl".into()).unwrap(), Grants::default()).unwrap();
         store.add_token(jwt.value().into()).await.expect("Failed to s add token.");
 
         let is_banned = store.contains_token(jwt.value().as_ref()).await.unwrap();
 
         assert!(is_banned);
     }
+
+    #[tokio::test]
+    async fn test_revoke_user_tokens() {
+        let mut store = HashsetBannedTokenStore::default();
+        let email = Email::parse("test@test.pl".into()).unwrap();
+        let now = chrono::Utc::now().timestamp_micros();
+
+        assert!(!store.is_user_token_revoked(&email, "token", now).await.unwrap());
+
+        store.revoke_user_tokens(&email, Some("kept")).await.unwrap();
+
+        assert!(store.is_user_token_revoked(&email, "token", now).await.unwrap());
+        assert!(!store.is_user_token_revoked(&email, "kept", now).await.unwrap());
+        // Issued right after the revocation, such as by logging in again
+        let later = chrono::Utc::now().timestamp_micros() + 1;
+        assert!(!store.is_user_token_revoked(&email, "token", later).await.unwrap());
+        assert!(!store
+            .is_user_token_revoked(&Email::parse("other@test.pl".into()).unwrap(), "token", now)
+            .await
+            .unwrap());
+    }
 }
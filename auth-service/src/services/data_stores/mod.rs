@@ -1,6 +1,30 @@
+pub mod file_breached_password_store;
+pub mod hashmap_breached_password_store;
+pub mod hashmap_device_history_store;
+pub mod hashmap_email_change_store;
+pub mod hashmap_invitation_store;
+pub mod hashmap_login_failure_store;
+pub mod hashmap_one_time_token_store;
+pub mod hashmap_organization_store;
+pub mod hashmap_password_history_store;
+pub mod hashmap_rate_limit_store;
+pub mod hashmap_role_store;
+pub mod hashmap_trusted_device_store;
 pub mod hashmap_two_fa_code_store;
 pub mod hashmap_user_store;
 pub mod hashset_banned_token_store;
+pub mod postgres_audit_log;
+pub mod postgres_device_history_store;
+pub mod postgres_invitation_store;
+pub mod postgres_organization_store;
+pub mod postgres_password_history_store;
+pub mod postgres_role_store;
 pub mod postgres_user_store;
 pub mod redis_banned_token_store;
+pub mod redis_email_change_store;
+pub mod redis_login_failure_store;
+pub mod redis_one_time_token_store;
+pub mod redis_rate_limit_store;
+pub mod redis_trusted_device_store;
 pub mod redis_two_fa_code_store;
+pub mod vec_audit_log;
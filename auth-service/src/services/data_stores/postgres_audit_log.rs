@@ -0,0 +1,169 @@
+use crate::domain::audit::{AuditEvent, AuditEventType, AuditOutcome, AuditPage, AuditSearch};
+use crate::domain::data_stores::{AuditLog, AuditLogError};
+use crate::domain::email::Email;
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::eyre;
+use secrecy::{ExposeSecret, SecretString};
+use sqlx::PgPool;
+
+pub struct PostgresAuditLog {
+    pool: PgPool,
+}
+
+impl PostgresAuditLog {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditLog for PostgresAuditLog {
+    #[tracing::instrument(name = "Recording audit event in PostgreSQL", skip_all)]
+    async fn record_event(&mut self, event: AuditEvent) -> Result<(), AuditLogError> {
+        sqlx::query!(
+            r#"
+                INSERT INTO audit_events (occurred_at, actor, ip, user_agent, event_type, outcome, reason)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            event.occurred_at,
+            event.actor.as_ref().map(|actor| actor.0.expose_secret().to_owned()),
+            event.ip.map(|ip| ip.to_string()),
+            event.user_agent,
+            event.event_type.as_ref(),
+            event.outcome.as_ref(),
+            event.reason
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AuditLogError::UnexpectedError(eyre!(e)))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Listing audit events from PostgreSQL", skip_all)]
+    async fn list_events(&self, search: &AuditSearch) -> Result<AuditPage, AuditLogError> {
+        let actor = search.actor.as_ref().map(|actor| actor.0.expose_secret().to_owned());
+        let event_types: Vec<String> = search
+            .event_types
+            .iter()
+            .map(|event_type| event_type.as_ref().to_owned())
+            .collect();
+        let limit: i64 = search
+            .limit
+            .try_into()
+            .map_err(|e| AuditLogError::UnexpectedError(eyre!("Failed to cast limit into i64: {}", e)))?;
+        let offset: i64 = search
+            .offset
+            .try_into()
+            .map_err(|e| AuditLogError::UnexpectedError(eyre!("Failed to cast offset into i64: {}", e)))?;
+
+        let events = sqlx::query_as!(
+            AuditEventRow,
+            r#"
+                SELECT occurred_at, actor, ip, user_agent, event_type, outcome, reason
+                FROM audit_events
+                WHERE ($1::TEXT IS NULL OR actor = $1)
+                  AND (CARDINALITY($2::TEXT[]) = 0 OR event_type = ANY($2))
+                ORDER BY occurred_at DESC, id DESC
+                LIMIT $3 OFFSET $4
+            "#,
+            actor,
+            &event_types,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AuditLogError::UnexpectedError(eyre!(e)))?
+        .into_iter()
+        .map(AuditEvent::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+        let total = sqlx::query_scalar!(
+            r#"
+                SELECT COUNT(*) AS "count!"
+                FROM audit_events
+                WHERE ($1::TEXT IS NULL OR actor = $1)
+                  AND (CARDINALITY($2::TEXT[]) = 0 OR event_type = ANY($2))
+            "#,
+            actor,
+            &event_types
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| AuditLogError::UnexpectedError(eyre!(e)))?
+        .try_into()
+        .map_err(|e| AuditLogError::UnexpectedError(eyre!("Failed to cast count into u64: {}", e)))?;
+
+        Ok(AuditPage { events, total })
+    }
+
+    #[tracing::instrument(name = "Moving audit events to new actor in PostgreSQL", skip_all)]
+    async fn move_actor(&mut self, old_email: &Email, new_email: &Email) -> Result<(), AuditLogError> {
+        sqlx::query!(
+            r#"
+                UPDATE audit_events
+                SET actor = $2
+                WHERE actor = $1
+            "#,
+            old_email.0.expose_secret(),
+            new_email.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AuditLogError::UnexpectedError(eyre!(e)))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Forgetting audit event actor in PostgreSQL", skip_all)]
+    async fn forget_actor(&mut self, email: &Email) -> Result<(), AuditLogError> {
+        sqlx::query!(
+            r#"
+                UPDATE audit_events
+                SET actor = NULL, ip = NULL, user_agent = NULL
+                WHERE actor = $1
+            "#,
+            email.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AuditLogError::UnexpectedError(eyre!(e)))?;
+
+        Ok(())
+    }
+}
+
+struct AuditEventRow {
+    occurred_at: DateTime<Utc>,
+    actor: Option<String>,
+    ip: Option<String>,
+    user_agent: Option<String>,
+    event_type: String,
+    outcome: String,
+    reason: Option<String>,
+}
+
+impl TryFrom<AuditEventRow> for AuditEvent {
+    type Error = AuditLogError;
+
+    fn try_from(row: AuditEventRow) -> Result<Self, Self::Error> {
+        Ok(AuditEvent {
+            occurred_at: row.occurred_at,
+            actor: row
+                .actor
+                .map(|actor| Email::parse(SecretString::from(actor)))
+                .transpose()
+                .map_err(|e| AuditLogError::UnexpectedError(e.into()))?,
+            ip: row
+                .ip
+                .map(|ip| ip.parse())
+                .transpose()
+                .map_err(|e| AuditLogError::UnexpectedError(eyre!("Invalid IP address: {}", e)))?,
+            user_agent: row.user_agent,
+            event_type: AuditEventType::parse(&row.event_type).map_err(|e| AuditLogError::UnexpectedError(e.into()))?,
+            outcome: AuditOutcome::parse(&row.outcome).map_err(|e| AuditLogError::UnexpectedError(e.into()))?,
+            reason: row.reason,
+        })
+    }
+}
@@ -0,0 +1,133 @@
+use crate::domain::data_stores::{DeviceHistoryStore, DeviceHistoryStoreError};
+use crate::domain::device_history::{DeviceFingerprint, DeviceRecord, DeviceSighting};
+use crate::domain::email::Email;
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::eyre;
+use secrecy::ExposeSecret;
+use sqlx::PgPool;
+
+pub struct PostgresDeviceHistoryStore {
+    pool: PgPool,
+}
+
+impl PostgresDeviceHistoryStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl DeviceHistoryStore for PostgresDeviceHistoryStore {
+    #[tracing::instrument(name = "Recording login device in PostgreSQL", skip_all)]
+    async fn record_device(
+        &mut self,
+        email: &Email,
+        fingerprint: &DeviceFingerprint,
+        seen_at: DateTime<Utc>,
+    ) -> Result<DeviceSighting, DeviceHistoryStoreError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| DeviceHistoryStoreError::UnexpectedError(eyre!(e)))?;
+
+        let has_devices = sqlx::query_scalar!(
+            r#"
+                SELECT EXISTS(SELECT 1 FROM device_history WHERE user_email = $1) AS "exists!"
+            "#,
+            email.0.expose_secret()
+        )
+        .fetch_one(&mut *transaction)
+        .await
+        .map_err(|e| DeviceHistoryStoreError::UnexpectedError(eyre!(e)))?;
+
+        // `xmax` is only set on rows that were already there and got updated
+        let inserted = sqlx::query_scalar!(
+            r#"
+                INSERT INTO device_history (user_email, fingerprint, first_seen_at, last_seen_at)
+                VALUES ($1, $2, $3, $3)
+                ON CONFLICT (user_email, fingerprint) DO UPDATE SET last_seen_at = EXCLUDED.last_seen_at
+                RETURNING (xmax = 0) AS "inserted!"
+            "#,
+            email.0.expose_secret(),
+            fingerprint.as_ref(),
+            seen_at
+        )
+        .fetch_one(&mut *transaction)
+        .await
+        .map_err(|e| DeviceHistoryStoreError::UnexpectedError(eyre!(e)))?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| DeviceHistoryStoreError::UnexpectedError(eyre!(e)))?;
+
+        Ok(match (has_devices, inserted) {
+            (false, _) => DeviceSighting::First,
+            (true, false) => DeviceSighting::Known,
+            (true, true) => DeviceSighting::New,
+        })
+    }
+
+    #[tracing::instrument(name = "Retrieving login devices from PostgreSQL", skip_all)]
+    async fn get_devices(&self, email: &Email) -> Result<Vec<DeviceRecord>, DeviceHistoryStoreError> {
+        let rows = sqlx::query!(
+            r#"
+                SELECT fingerprint, first_seen_at, last_seen_at
+                FROM device_history
+                WHERE user_email = $1
+                ORDER BY last_seen_at DESC
+            "#,
+            email.0.expose_secret()
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DeviceHistoryStoreError::UnexpectedError(eyre!(e)))?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(DeviceRecord {
+                    fingerprint: DeviceFingerprint::parse(row.fingerprint)
+                        .map_err(|e| DeviceHistoryStoreError::UnexpectedError(e.into()))?,
+                    first_seen_at: row.first_seen_at,
+                    last_seen_at: row.last_seen_at,
+                })
+            })
+            .collect()
+    }
+
+    // The rows already follow the user through their foreign key, this covers a history kept without it
+    #[tracing::instrument(name = "Moving login devices in PostgreSQL", skip_all)]
+    async fn move_devices(&mut self, old_email: &Email, new_email: &Email) -> Result<(), DeviceHistoryStoreError> {
+        sqlx::query!(
+            r#"
+                UPDATE device_history
+                SET user_email = $2
+                WHERE user_email = $1
+            "#,
+            old_email.0.expose_secret(),
+            new_email.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DeviceHistoryStoreError::UnexpectedError(eyre!(e)))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Removing login devices from PostgreSQL", skip_all)]
+    async fn remove_devices(&mut self, email: &Email) -> Result<(), DeviceHistoryStoreError> {
+        sqlx::query!(
+            r#"
+                DELETE FROM device_history
+                WHERE user_email = $1
+            "#,
+            email.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DeviceHistoryStoreError::UnexpectedError(eyre!(e)))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use crate::domain::data_stores::{InvitationStore, InvitationStoreError};
+use crate::domain::email::Email;
+use crate::domain::invitation::Invitation;
+use crate::domain::organization::OrganizationId;
+use crate::domain::rbac::RoleName;
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::eyre;
+use secrecy::{ExposeSecret, SecretString};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct PostgresInvitationStore {
+    pool: PgPool,
+}
+
+impl PostgresInvitationStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl InvitationStore for PostgresInvitationStore {
+    #[tracing::instrument(name = "Adding invitation to PostgreSQL", skip_all)]
+    async fn add_invitation(&mut self, invitation: Invitation) -> Result<(), InvitationStoreError> {
+        sqlx::query!(
+            r#"
+                INSERT INTO invitations (id, email, role_name, organization_id, invited_by, created_at, expires_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            invitation.id,
+            invitation.email.0.expose_secret(),
+            invitation.role.as_ref().map(|role| role.as_ref()),
+            invitation.organization_id.as_ref().map(|id| *id.as_uuid()),
+            invitation.invited_by.0.expose_secret(),
+            invitation.created_at,
+            invitation.expires_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| InvitationStoreError::UnexpectedError(eyre!(e)))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Retrieving invitation from PostgreSQL", skip_all)]
+    async fn get_invitation(&self, id: &str) -> Result<Invitation, InvitationStoreError> {
+        sqlx::query_as!(
+            InvitationRow,
+            r#"
+                SELECT id, email, role_name, organization_id, invited_by, created_at, expires_at
+                FROM invitations
+                WHERE id = $1 AND expires_at > NOW()
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| InvitationStoreError::UnexpectedError(eyre!(e)))?
+        .ok_or(InvitationStoreError::InvitationNotFound)?
+        .try_into()
+    }
+
+    #[tracing::instrument(name = "Retrieving invitations from PostgreSQL", skip_all)]
+    async fn list_invitations(&self) -> Result<Vec<Invitation>, InvitationStoreError> {
+        sqlx::query_as!(
+            InvitationRow,
+            r#"
+                SELECT id, email, role_name, organization_id, invited_by, created_at, expires_at
+                FROM invitations
+                WHERE expires_at > NOW()
+                ORDER BY created_at, id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| InvitationStoreError::UnexpectedError(eyre!(e)))?
+        .into_iter()
+        .map(Invitation::try_from)
+        .collect()
+    }
+
+    #[tracing::instrument(name = "Removing invitation from PostgreSQL", skip_all)]
+    async fn remove_invitation(&mut self, id: &str) -> Result<(), InvitationStoreError> {
+        let result = sqlx::query!(
+            r#"
+                DELETE FROM invitations
+                WHERE id = $1
+            "#,
+            id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| InvitationStoreError::UnexpectedError(eyre!(e)))?;
+
+        match result.rows_affected() {
+            0 => Err(InvitationStoreError::InvitationNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    #[tracing::instrument(name = "Removing user invitations from PostgreSQL", skip_all)]
+    async fn remove_invitations(&mut self, email: &Email) -> Result<(), InvitationStoreError> {
+        sqlx::query!(
+            r#"
+                DELETE FROM invitations
+                WHERE email = $1
+            "#,
+            email.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| InvitationStoreError::UnexpectedError(eyre!(e)))?;
+
+        Ok(())
+    }
+}
+
+struct InvitationRow {
+    id: String,
+    email: String,
+    role_name: Option<String>,
+    organization_id: Option<Uuid>,
+    invited_by: String,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+}
+
+impl TryFrom<InvitationRow> for Invitation {
+    type Error = InvitationStoreError;
+
+    fn try_from(row: InvitationRow) -> Result<Self, Self::Error> {
+        let parse_email =
+            |email: String| Email::parse(SecretString::from(email)).map_err(|e| InvitationStoreError::UnexpectedError(e.into()));
+
+        Ok(Invitation {
+            id: row.id,
+            email: parse_email(row.email)?,
+            role: row
+                .role_name
+                .as_deref()
+                .map(RoleName::parse)
+                .transpose()
+                .map_err(|e| InvitationStoreError::UnexpectedError(e.into()))?,
+            organization_id: row.organization_id.map(OrganizationId::from),
+            invited_by: parse_email(row.invited_by)?,
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+        })
+    }
+}
@@ -0,0 +1,366 @@
+use crate::domain::data_stores::{OrganizationStore, OrganizationStoreError};
+use crate::domain::email::Email;
+use crate::domain::organization::{Member, MemberInvitation, Membership, Organization, OrganizationId, OrganizationName};
+use crate::domain::rbac::RoleName;
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::eyre;
+use secrecy::ExposeSecret;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct PostgresOrganizationStore {
+    pool: PgPool,
+}
+
+impl PostgresOrganizationStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl OrganizationStore for PostgresOrganizationStore {
+    #[tracing::instrument(name = "Adding organization to PostgreSQL", skip_all)]
+    async fn add_organization(&mut self, organization: Organization) -> Result<(), OrganizationStoreError> {
+        sqlx::query!(
+            r#"
+                INSERT INTO organizations (id, name, created_at)
+                VALUES ($1, $2, $3)
+            "#,
+            organization.id.as_uuid(),
+            organization.name.as_ref(),
+            organization.created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| OrganizationStoreError::UnexpectedError(eyre!(e)))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Retrieving organization from PostgreSQL", skip_all)]
+    async fn get_organization(&self, id: &OrganizationId) -> Result<Organization, OrganizationStoreError> {
+        let row = sqlx::query!(
+            r#"
+                SELECT id, name, created_at
+                FROM organizations
+                WHERE id = $1
+            "#,
+            id.as_uuid()
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| OrganizationStoreError::UnexpectedError(eyre!(e)))?
+        .ok_or(OrganizationStoreError::OrganizationNotFound)?;
+
+        organization(row.id, &row.name, row.created_at)
+    }
+
+    #[tracing::instrument(name = "Saving organization member to PostgreSQL", skip_all)]
+    async fn set_member(&mut self, id: &OrganizationId, email: &Email, roles: &[RoleName]) -> Result<(), OrganizationStoreError> {
+        let roles: Vec<String> = roles.iter().map(|role| role.as_ref().to_owned()).collect();
+
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| OrganizationStoreError::UnexpectedError(eyre!(e)))?;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO organization_members (organization_id, user_email)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING
+            "#,
+            id.as_uuid(),
+            email.0.expose_secret()
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_error) if db_error.constraint() == Some("organization_members_organization_id_fkey") => {
+                OrganizationStoreError::OrganizationNotFound
+            }
+            e => OrganizationStoreError::UnexpectedError(eyre!(e)),
+        })?;
+
+        sqlx::query!(
+            r#"
+                DELETE FROM organization_member_roles
+                WHERE organization_id = $1 AND user_email = $2
+            "#,
+            id.as_uuid(),
+            email.0.expose_secret()
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(|e| OrganizationStoreError::UnexpectedError(eyre!(e)))?;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO organization_member_roles (organization_id, user_email, role_name)
+                SELECT $1, $2, role_name
+                FROM UNNEST($3::TEXT[]) AS role_name
+                ON CONFLICT DO NOTHING
+            "#,
+            id.as_uuid(),
+            email.0.expose_secret(),
+            &roles
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_error) if db_error.constraint() == Some("organization_member_roles_role_name_fkey") => {
+                OrganizationStoreError::RoleNotFound
+            }
+            e => OrganizationStoreError::UnexpectedError(eyre!(e)),
+        })?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| OrganizationStoreError::UnexpectedError(eyre!(e)))
+    }
+
+    #[tracing::instrument(name = "Removing organization member from PostgreSQL", skip_all)]
+    async fn remove_member(&mut self, id: &OrganizationId, email: &Email) -> Result<(), OrganizationStoreError> {
+        let result = sqlx::query!(
+            r#"
+                DELETE FROM organization_members
+                WHERE organization_id = $1 AND user_email = $2
+            "#,
+            id.as_uuid(),
+            email.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| OrganizationStoreError::UnexpectedError(eyre!(e)))?;
+
+        match result.rows_affected() {
+            0 => Err(OrganizationStoreError::MemberNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    #[tracing::instrument(name = "Retrieving organization membership from PostgreSQL", skip_all)]
+    async fn get_membership(&self, id: &OrganizationId, email: &Email) -> Result<Membership, OrganizationStoreError> {
+        let row = sqlx::query!(
+            r#"
+                SELECT organizations.id, organizations.name, organizations.created_at, organization_members.joined_at,
+                    COALESCE(
+                        ARRAY_AGG(organization_member_roles.role_name ORDER BY organization_member_roles.role_name)
+                            FILTER (WHERE organization_member_roles.role_name IS NOT NULL),
+                        '{}'
+                    ) AS "roles!"
+                FROM organization_members
+                JOIN organizations ON organizations.id = organization_members.organization_id
+                LEFT JOIN organization_member_roles
+                    ON organization_member_roles.organization_id = organization_members.organization_id
+                    AND organization_member_roles.user_email = organization_members.user_email
+                WHERE organization_members.organization_id = $1 AND organization_members.user_email = $2
+                GROUP BY organizations.id, organization_members.organization_id, organization_members.user_email
+            "#,
+            id.as_uuid(),
+            email.0.expose_secret()
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| OrganizationStoreError::UnexpectedError(eyre!(e)))?
+        .ok_or(OrganizationStoreError::MemberNotFound)?;
+
+        Ok(Membership {
+            organization: organization(row.id, &row.name, row.created_at)?,
+            roles: role_names(&row.roles)?,
+            joined_at: row.joined_at,
+        })
+    }
+
+    #[tracing::instrument(name = "Retrieving organization memberships from PostgreSQL", skip_all)]
+    async fn list_memberships(&self, email: &Email) -> Result<Vec<Membership>, OrganizationStoreError> {
+        let rows = sqlx::query!(
+            r#"
+                SELECT organizations.id, organizations.name, organizations.created_at, organization_members.joined_at,
+                    COALESCE(
+                        ARRAY_AGG(organization_member_roles.role_name ORDER BY organization_member_roles.role_name)
+                            FILTER (WHERE organization_member_roles.role_name IS NOT NULL),
+                        '{}'
+                    ) AS "roles!"
+                FROM organization_members
+                JOIN organizations ON organizations.id = organization_members.organization_id
+                LEFT JOIN organization_member_roles
+                    ON organization_member_roles.organization_id = organization_members.organization_id
+                    AND organization_member_roles.user_email = organization_members.user_email
+                WHERE organization_members.user_email = $1
+                GROUP BY organizations.id, organization_members.organization_id, organization_members.user_email
+                ORDER BY organization_members.joined_at, organizations.id
+            "#,
+            email.0.expose_secret()
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| OrganizationStoreError::UnexpectedError(eyre!(e)))?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(Membership {
+                    organization: organization(row.id, &row.name, row.created_at)?,
+                    roles: role_names(&row.roles)?,
+                    joined_at: row.joined_at,
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Retrieving organization members from PostgreSQL", skip_all)]
+    async fn list_members(&self, id: &OrganizationId) -> Result<Vec<Member>, OrganizationStoreError> {
+        self.get_organization(id).await?;
+
+        let rows = sqlx::query!(
+            r#"
+                SELECT organization_members.user_email, organization_members.joined_at,
+                    COALESCE(
+                        ARRAY_AGG(organization_member_roles.role_name ORDER BY organization_member_roles.role_name)
+                            FILTER (WHERE organization_member_roles.role_name IS NOT NULL),
+                        '{}'
+                    ) AS "roles!"
+                FROM organization_members
+                LEFT JOIN organization_member_roles
+                    ON organization_member_roles.organization_id = organization_members.organization_id
+                    AND organization_member_roles.user_email = organization_members.user_email
+                WHERE organization_members.organization_id = $1
+                GROUP BY organization_members.organization_id, organization_members.user_email
+                ORDER BY organization_members.joined_at, organization_members.user_email
+            "#,
+            id.as_uuid()
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| OrganizationStoreError::UnexpectedError(eyre!(e)))?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(Member {
+                    email: Email::parse(row.user_email.into()).map_err(|e| OrganizationStoreError::UnexpectedError(e.into()))?,
+                    roles: role_names(&row.roles)?,
+                    joined_at: row.joined_at,
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Adding organization invitation to PostgreSQL", skip_all)]
+    async fn add_member_invitation(&mut self, invitation: MemberInvitation) -> Result<(), OrganizationStoreError> {
+        let roles: Vec<String> = invitation.roles.iter().map(|role| role.as_ref().to_owned()).collect();
+
+        sqlx::query!(
+            r#"
+                INSERT INTO organization_invitations (id, organization_id, user_email, roles, expires_at)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (organization_id, user_email)
+                DO UPDATE SET id = EXCLUDED.id, roles = EXCLUDED.roles, expires_at = EXCLUDED.expires_at
+            "#,
+            invitation.id,
+            invitation.organization_id.as_uuid(),
+            invitation.email.0.expose_secret(),
+            &roles,
+            invitation.expires_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_error)
+                if db_error.constraint() == Some("organization_invitations_organization_id_fkey") =>
+            {
+                OrganizationStoreError::OrganizationNotFound
+            }
+            e => OrganizationStoreError::UnexpectedError(eyre!(e)),
+        })?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Taking organization invitation from PostgreSQL", skip_all)]
+    async fn take_member_invitation(&mut self, id: &str) -> Result<MemberInvitation, OrganizationStoreError> {
+        let row = sqlx::query!(
+            r#"
+                DELETE FROM organization_invitations
+                WHERE id = $1
+                RETURNING id, organization_id, user_email, roles, expires_at
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| OrganizationStoreError::UnexpectedError(eyre!(e)))?
+        .ok_or(OrganizationStoreError::InvitationNotFound)?;
+
+        Ok(MemberInvitation {
+            id: row.id,
+            organization_id: OrganizationId::from(row.organization_id),
+            email: Email::parse(row.user_email.into()).map_err(|e| OrganizationStoreError::UnexpectedError(e.into()))?,
+            roles: role_names(&row.roles)?,
+            expires_at: row.expires_at,
+        })
+    }
+
+    // Usually a no-op, the foreign key already follows the email change of the user
+    #[tracing::instrument(name = "Moving organization memberships in PostgreSQL", skip_all)]
+    async fn move_memberships(&mut self, old_email: &Email, new_email: &Email) -> Result<(), OrganizationStoreError> {
+        sqlx::query!(
+            r#"
+                UPDATE organization_members
+                SET user_email = $2
+                WHERE user_email = $1
+            "#,
+            old_email.0.expose_secret(),
+            new_email.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| OrganizationStoreError::UnexpectedError(eyre!(e)))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Removing organization memberships from PostgreSQL", skip_all)]
+    async fn remove_memberships(&mut self, email: &Email) -> Result<(), OrganizationStoreError> {
+        sqlx::query!(
+            r#"
+                DELETE FROM organization_members
+                WHERE user_email = $1
+            "#,
+            email.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| OrganizationStoreError::UnexpectedError(eyre!(e)))?;
+
+        sqlx::query!(
+            r#"
+                DELETE FROM organization_invitations
+                WHERE user_email = $1
+            "#,
+            email.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| OrganizationStoreError::UnexpectedError(eyre!(e)))?;
+
+        Ok(())
+    }
+}
+
+fn organization(id: Uuid, name: &str, created_at: DateTime<Utc>) -> Result<Organization, OrganizationStoreError> {
+    Ok(Organization {
+        id: OrganizationId::from(id),
+        name: OrganizationName::parse(name).map_err(|e| OrganizationStoreError::UnexpectedError(e.into()))?,
+        created_at,
+    })
+}
+
+fn role_names(names: &[String]) -> Result<Vec<RoleName>, OrganizationStoreError> {
+    names
+        .iter()
+        .map(|name| RoleName::parse(name).map_err(|e| OrganizationStoreError::UnexpectedError(e.into())))
+        .collect()
+}
@@ -0,0 +1,129 @@
+use crate::domain::data_stores::{PasswordHistoryStore, PasswordHistoryStoreError};
+use crate::domain::email::Email;
+use crate::domain::hashed_password::HashedPassword;
+use color_eyre::eyre::eyre;
+use secrecy::ExposeSecret;
+use sqlx::PgPool;
+
+pub struct PostgresPasswordHistoryStore {
+    pool: PgPool,
+}
+
+impl PostgresPasswordHistoryStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl PasswordHistoryStore for PostgresPasswordHistoryStore {
+    #[tracing::instrument(name = "Retrieving password history from PostgreSQL", skip_all)]
+    async fn get_passwords(&self, email: &Email, limit: usize) -> Result<Vec<HashedPassword>, PasswordHistoryStoreError> {
+        let rows = sqlx::query!(
+            r#"
+                SELECT password_hash
+                FROM password_history
+                WHERE user_email = $1
+                ORDER BY id DESC
+                LIMIT $2
+            "#,
+            email.0.expose_secret(),
+            i64::try_from(limit).unwrap_or(i64::MAX)
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| PasswordHistoryStoreError::UnexpectedError(eyre!(e)))?;
+
+        rows.into_iter()
+            .map(|row| {
+                HashedPassword::parse_password_hash(row.password_hash.into())
+                    .map_err(|e| PasswordHistoryStoreError::UnexpectedError(eyre!(e)))
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Adding password to history in PostgreSQL", skip_all)]
+    async fn add_password(
+        &mut self,
+        email: &Email,
+        password: HashedPassword,
+        keep: usize,
+    ) -> Result<(), PasswordHistoryStoreError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| PasswordHistoryStoreError::UnexpectedError(eyre!(e)))?;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO password_history (user_email, password_hash)
+                VALUES ($1, $2)
+            "#,
+            email.0.expose_secret(),
+            password.0.expose_secret()
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(|e| PasswordHistoryStoreError::UnexpectedError(eyre!(e)))?;
+
+        sqlx::query!(
+            r#"
+                DELETE FROM password_history
+                WHERE user_email = $1
+                  AND id NOT IN (
+                      SELECT id
+                      FROM password_history
+                      WHERE user_email = $1
+                      ORDER BY id DESC
+                      LIMIT $2
+                  )
+            "#,
+            email.0.expose_secret(),
+            i64::try_from(keep).unwrap_or(i64::MAX)
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(|e| PasswordHistoryStoreError::UnexpectedError(eyre!(e)))?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| PasswordHistoryStoreError::UnexpectedError(eyre!(e)))
+    }
+
+    // The rows already follow the user through their foreign key, this covers a history kept without it
+    #[tracing::instrument(name = "Moving password history in PostgreSQL", skip_all)]
+    async fn move_passwords(&mut self, old_email: &Email, new_email: &Email) -> Result<(), PasswordHistoryStoreError> {
+        sqlx::query!(
+            r#"
+                UPDATE password_history
+                SET user_email = $2
+                WHERE user_email = $1
+            "#,
+            old_email.0.expose_secret(),
+            new_email.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| PasswordHistoryStoreError::UnexpectedError(eyre!(e)))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Removing password history from PostgreSQL", skip_all)]
+    async fn remove_passwords(&mut self, email: &Email) -> Result<(), PasswordHistoryStoreError> {
+        sqlx::query!(
+            r#"
+                DELETE FROM password_history
+                WHERE user_email = $1
+            "#,
+            email.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| PasswordHistoryStoreError::UnexpectedError(eyre!(e)))?;
+
+        Ok(())
+    }
+}
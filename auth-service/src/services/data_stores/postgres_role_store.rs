@@ -0,0 +1,230 @@
+use crate::domain::data_stores::{RoleStore, RoleStoreError};
+use crate::domain::email::Email;
+use crate::domain::rbac::{Permission, Role, RoleName};
+use color_eyre::eyre::eyre;
+use secrecy::ExposeSecret;
+use sqlx::PgPool;
+
+pub struct PostgresRoleStore {
+    pool: PgPool,
+}
+
+impl PostgresRoleStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl RoleStore for PostgresRoleStore {
+    #[tracing::instrument(name = "Retrieving roles from PostgreSQL", skip_all)]
+    async fn list_roles(&self) -> Result<Vec<Role>, RoleStoreError> {
+        let rows = sqlx::query!(
+            r#"
+                SELECT roles.name, role_permissions.permission AS "permission?"
+                FROM roles
+                LEFT JOIN role_permissions ON role_permissions.role_name = roles.name
+                ORDER BY roles.name, role_permissions.permission
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RoleStoreError::UnexpectedError(eyre!(e)))?;
+
+        let mut roles: Vec<Role> = Vec::new();
+
+        for row in rows {
+            let permission = row
+                .permission
+                .as_deref()
+                .map(Permission::parse)
+                .transpose()
+                .map_err(|e| RoleStoreError::UnexpectedError(e.into()))?;
+
+            match roles.last_mut() {
+                Some(role) if role.name.as_ref() == row.name => role.permissions.extend(permission),
+                _ => roles.push(Role {
+                    name: RoleName::parse(&row.name).map_err(|e| RoleStoreError::UnexpectedError(e.into()))?,
+                    permissions: permission.into_iter().collect(),
+                }),
+            }
+        }
+
+        Ok(roles)
+    }
+
+    #[tracing::instrument(name = "Saving role to PostgreSQL", skip_all)]
+    async fn set_role(&mut self, role: Role) -> Result<(), RoleStoreError> {
+        let permissions: Vec<String> = role
+            .permissions
+            .iter()
+            .map(|permission| permission.as_ref().to_owned())
+            .collect();
+
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| RoleStoreError::UnexpectedError(eyre!(e)))?;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO roles (name)
+                VALUES ($1)
+                ON CONFLICT DO NOTHING
+            "#,
+            role.name.as_ref()
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(|e| RoleStoreError::UnexpectedError(eyre!(e)))?;
+
+        sqlx::query!(
+            r#"
+                DELETE FROM role_permissions
+                WHERE role_name = $1
+            "#,
+            role.name.as_ref()
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(|e| RoleStoreError::UnexpectedError(eyre!(e)))?;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO role_permissions (role_name, permission)
+                SELECT $1, permission
+                FROM UNNEST($2::TEXT[]) AS permission
+                ON CONFLICT DO NOTHING
+            "#,
+            role.name.as_ref(),
+            &permissions
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(|e| RoleStoreError::UnexpectedError(eyre!(e)))?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| RoleStoreError::UnexpectedError(eyre!(e)))
+    }
+
+    #[tracing::instrument(name = "Deleting role from PostgreSQL", skip_all)]
+    async fn delete_role(&mut self, name: &RoleName) -> Result<(), RoleStoreError> {
+        let result = sqlx::query!(
+            r#"
+                DELETE FROM roles
+                WHERE name = $1
+            "#,
+            name.as_ref()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| RoleStoreError::UnexpectedError(eyre!(e)))?;
+
+        match result.rows_affected() {
+            0 => Err(RoleStoreError::RoleNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    #[tracing::instrument(name = "Assigning role in PostgreSQL", skip_all)]
+    async fn assign_role(&mut self, email: &Email, name: &RoleName) -> Result<(), RoleStoreError> {
+        sqlx::query!(
+            r#"
+                INSERT INTO user_roles (user_email, role_name)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING
+            "#,
+            email.0.expose_secret(),
+            name.as_ref()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_error) if db_error.constraint() == Some("user_roles_role_name_fkey") => {
+                RoleStoreError::RoleNotFound
+            }
+            e => RoleStoreError::UnexpectedError(eyre!(e)),
+        })?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Unassigning role in PostgreSQL", skip_all)]
+    async fn unassign_role(&mut self, email: &Email, name: &RoleName) -> Result<(), RoleStoreError> {
+        let result = sqlx::query!(
+            r#"
+                DELETE FROM user_roles
+                WHERE user_email = $1 AND role_name = $2
+            "#,
+            email.0.expose_secret(),
+            name.as_ref()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| RoleStoreError::UnexpectedError(eyre!(e)))?;
+
+        match result.rows_affected() {
+            0 => Err(RoleStoreError::RoleNotAssigned),
+            _ => Ok(()),
+        }
+    }
+
+    #[tracing::instrument(name = "Retrieving user roles from PostgreSQL", skip_all)]
+    async fn get_user_roles(&self, email: &Email) -> Result<Vec<RoleName>, RoleStoreError> {
+        let names = sqlx::query_scalar!(
+            r#"
+                SELECT role_name
+                FROM user_roles
+                WHERE user_email = $1
+                ORDER BY role_name
+            "#,
+            email.0.expose_secret()
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| RoleStoreError::UnexpectedError(eyre!(e)))?;
+
+        names
+            .iter()
+            .map(|name| RoleName::parse(name).map_err(|e| RoleStoreError::UnexpectedError(e.into())))
+            .collect()
+    }
+
+    // Usually a no-op, the foreign key already follows the email change of the user
+    #[tracing::instrument(name = "Moving user roles in PostgreSQL", skip_all)]
+    async fn move_user_roles(&mut self, old_email: &Email, new_email: &Email) -> Result<(), RoleStoreError> {
+        sqlx::query!(
+            r#"
+                UPDATE user_roles
+                SET user_email = $2
+                WHERE user_email = $1
+            "#,
+            old_email.0.expose_secret(),
+            new_email.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| RoleStoreError::UnexpectedError(eyre!(e)))?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Removing user roles from PostgreSQL", skip_all)]
+    async fn remove_user_roles(&mut self, email: &Email) -> Result<(), RoleStoreError> {
+        sqlx::query!(
+            r#"
+                DELETE FROM user_roles
+                WHERE user_email = $1
+            "#,
+            email.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| RoleStoreError::UnexpectedError(eyre!(e)))?;
+
+        Ok(())
+    }
+}
@@ -1,7 +1,11 @@
+use crate::domain::account_status::{AccountState, AccountStatus, StatusReason};
 use crate::domain::data_stores::{UserStore, UserStoreError};
 use crate::domain::email::Email;
 use crate::domain::hashed_password::HashedPassword;
-use crate::domain::user::User;
+use crate::domain::profile::{DisplayName, Locale, Profile, Timezone};
+use crate::domain::user::{User, UserPage, UserSearch};
+use crate::utils::hashing_executor::HashingOverloaded;
+use chrono::{DateTime, Utc};
 use color_eyre::eyre::eyre;
 use secrecy::{ExposeSecret, SecretString};
 use sqlx::PgPool;
@@ -22,25 +26,42 @@ impl UserStore for PostgresUserStore {
     async fn add_user(&mut self, user: User) -> Result<(), UserStoreError> {
         sqlx::query!(
             r#"
-            INSERT INTO users (email, password_hash, requires_2fa)
-            VALUES ($1, $2, $3)
+            INSERT INTO users (
+                email, password_hash, requires_2fa, email_verified, status, status_reason, status_changed_at, suspended_until,
+                display_name, locale, timezone, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
             "#,
             user.email.0.expose_secret(),
-            &user.password.0.expose_secret(),
-            user.requires_2fa
+            user.password.as_ref().map(|password| password.0.expose_secret()),
+            user.requires_2fa,
+            user.email_verified,
+            user.account_state.status.as_ref(),
+            user.account_state.reason.as_ref().map(AsRef::as_ref),
+            user.account_state.changed_at,
+            user.account_state.suspended_until,
+            user.profile.display_name.as_ref().map(AsRef::as_ref),
+            user.profile.locale.as_ref().map(AsRef::as_ref),
+            user.profile.timezone.as_ref().map(AsRef::as_ref),
+            user.created_at
         )
         .execute(&self.pool)
         .await
-        .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?;
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_error) if db_error.is_unique_violation() => UserStoreError::UserAlreadyExists,
+            e => UserStoreError::UnexpectedError(eyre!(e)),
+        })?;
 
         Ok(())
     }
 
     #[tracing::instrument(name = "Retrieving user from PostgreSQL", skip_all)]
     async fn get_user(&self, email: &Email) -> Result<User, UserStoreError> {
-        sqlx::query!(
+        sqlx::query_as!(
+            UserRow,
             r#"
-                 SELECT email, password_hash, requires_2fa
+                SELECT email, password_hash, requires_2fa, email_verified, status, status_reason, status_changed_at, suspended_until,
+                    display_name, locale, timezone, created_at, last_login_at
                 FROM users
                 WHERE email = $1
             "#,
@@ -49,23 +70,404 @@ impl UserStore for PostgresUserStore {
         .fetch_optional(&self.pool)
         .await
         .map_err(|_| UserStoreError::InvalidCredentials)?
-        .map(|row| {
-            Ok(User::new(
-                Email::parse(SecretString::from(row.email)).map_err(|e| UserStoreError::UnexpectedError(e.into()))?,
-                HashedPassword::parse_password_hash(row.password_hash.into())
-                    .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?,
-                row.requires_2fa,
-            ))
-        })
+        .map(User::try_from)
         .ok_or(UserStoreError::UserNotFound)?
     }
 
+    // The search is a case-insensitive substring match on the email or the display name
+    #[tracing::instrument(name = "Listing users from PostgreSQL", skip_all)]
+    async fn list_users(&self, search: &UserSearch) -> Result<UserPage, UserStoreError> {
+        let pattern = search.query.as_deref().map(like_pattern);
+        let limit: i64 = search
+            .limit
+            .try_into()
+            .map_err(|e| UserStoreError::UnexpectedError(eyre!("Failed to cast limit into i64: {}", e)))?;
+        let offset: i64 = search
+            .offset
+            .try_into()
+            .map_err(|e| UserStoreError::UnexpectedError(eyre!("Failed to cast offset into i64: {}", e)))?;
+
+        let users = sqlx::query_as!(
+            UserRow,
+            r#"
+                SELECT email, password_hash, requires_2fa, email_verified, status, status_reason, status_changed_at, suspended_until,
+                    display_name, locale, timezone, created_at, last_login_at
+                FROM users
+                WHERE $1::TEXT IS NULL OR email ILIKE $1 OR display_name ILIKE $1
+                ORDER BY created_at, email
+                LIMIT $2 OFFSET $3
+            "#,
+            pattern,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?
+        .into_iter()
+        .map(User::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+        let total = sqlx::query_scalar!(
+            r#"
+                SELECT COUNT(*) AS "count!"
+                FROM users
+                WHERE $1::TEXT IS NULL OR email ILIKE $1 OR display_name ILIKE $1
+            "#,
+            pattern
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?
+        .try_into()
+        .map_err(|e| UserStoreError::UnexpectedError(eyre!("Failed to cast count into u64: {}", e)))?;
+
+        Ok(UserPage { users, total })
+    }
+
     #[tracing::instrument(name = "Validating user credentials in PostgreSQL", skip_all)]
     async fn validate_user(&self, email: &Email, raw_password: &str) -> Result<(), UserStoreError> {
-        let user: User = self.get_user(email).await?;
-        user.password
+        // Unknown users and users without a password go through a verification too, so that the time it takes doesn't
+        // tell them apart from a wrong password
+        let user = match self.get_user(email).await {
+            Ok(user) => user,
+            Err(UserStoreError::UserNotFound) => {
+                HashedPassword::verify_dummy_password(raw_password)
+                    .await
+                    .map_err(UserStoreError::UnexpectedError)?;
+                return Err(UserStoreError::UserNotFound);
+            }
+            Err(e) => return Err(e),
+        };
+        let Some(password) = user.password else {
+            HashedPassword::verify_dummy_password(raw_password)
+                .await
+                .map_err(UserStoreError::UnexpectedError)?;
+            return Err(UserStoreError::InvalidCredentials);
+        };
+
+        password
             .verify_raw_password(raw_password)
             .await
-            .map_err(|_| UserStoreError::InvalidCredentials)
+            .map_err(|e| match HashingOverloaded::caused(&e) {
+                true => UserStoreError::UnexpectedError(e),
+                false => UserStoreError::InvalidCredentials,
+            })
+    }
+
+    #[tracing::instrument(name = "Marking user email as verified in PostgreSQL", skip_all)]
+    async fn mark_email_verified(&mut self, email: &Email) -> Result<(), UserStoreError> {
+        let result = sqlx::query!(
+            r#"
+                UPDATE users
+                SET email_verified = TRUE
+                WHERE email = $1
+            "#,
+            email.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?;
+
+        match result.rows_affected() {
+            0 => Err(UserStoreError::UserNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    #[tracing::instrument(name = "Updating user password in PostgreSQL", skip_all)]
+    async fn update_password(&mut self, email: &Email, password: HashedPassword) -> Result<(), UserStoreError> {
+        let result = sqlx::query!(
+            r#"
+                UPDATE users
+                SET password_hash = $2
+                WHERE email = $1
+            "#,
+            email.0.expose_secret(),
+            password.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?;
+
+        match result.rows_affected() {
+            0 => Err(UserStoreError::UserNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    // Comparing the hash in the same UPDATE keeps a password changed in the meantime
+    #[tracing::instrument(name = "Upgrading user password hash in PostgreSQL", skip_all)]
+    async fn upgrade_password_hash(
+        &mut self,
+        email: &Email,
+        old_password: &HashedPassword,
+        new_password: HashedPassword,
+    ) -> Result<(), UserStoreError> {
+        sqlx::query!(
+            r#"
+                UPDATE users
+                SET password_hash = $3
+                WHERE email = $1 AND password_hash = $2
+            "#,
+            email.0.expose_secret(),
+            old_password.0.expose_secret(),
+            new_password.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?;
+
+        Ok(())
+    }
+
+    // Roles, memberships and histories follow the primary key through ON UPDATE CASCADE, audit events
+    // have no foreign key and move in the same transaction
+    #[tracing::instrument(name = "Updating user email in PostgreSQL", skip_all)]
+    async fn update_email(&mut self, old_email: &Email, new_email: &Email) -> Result<(), UserStoreError> {
+        let mut transaction = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?;
+
+        let result = sqlx::query!(
+            r#"
+                UPDATE users
+                SET email = $2
+                WHERE email = $1
+            "#,
+            old_email.0.expose_secret(),
+            new_email.0.expose_secret()
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_error) if db_error.is_unique_violation() => UserStoreError::UserAlreadyExists,
+            e => UserStoreError::UnexpectedError(eyre!(e)),
+        })?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        sqlx::query!(
+            r#"
+                UPDATE audit_events
+                SET actor = $2
+                WHERE actor = $1
+            "#,
+            old_email.0.expose_secret(),
+            new_email.0.expose_secret()
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?;
+
+        transaction
+            .commit()
+            .await
+            .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))
+    }
+
+    #[tracing::instrument(name = "Deleting user from PostgreSQL", skip_all)]
+    async fn delete_user(&mut self, email: &Email) -> Result<(), UserStoreError> {
+        let result = sqlx::query!(
+            r#"
+                DELETE FROM users
+                WHERE email = $1
+            "#,
+            email.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?;
+
+        match result.rows_affected() {
+            0 => Err(UserStoreError::UserNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    #[tracing::instrument(name = "Updating user profile in PostgreSQL", skip_all)]
+    async fn update_profile(&mut self, email: &Email, profile: Profile) -> Result<(), UserStoreError> {
+        let result = sqlx::query!(
+            r#"
+                UPDATE users
+                SET display_name = $2, locale = $3, timezone = $4
+                WHERE email = $1
+            "#,
+            email.0.expose_secret(),
+            profile.display_name.as_ref().map(AsRef::as_ref),
+            profile.locale.as_ref().map(AsRef::as_ref),
+            profile.timezone.as_ref().map(AsRef::as_ref)
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?;
+
+        match result.rows_affected() {
+            0 => Err(UserStoreError::UserNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    #[tracing::instrument(name = "Clearing user password in PostgreSQL", skip_all)]
+    async fn clear_password(&mut self, email: &Email) -> Result<(), UserStoreError> {
+        let result = sqlx::query!(
+            r#"
+                UPDATE users
+                SET password_hash = NULL
+                WHERE email = $1
+            "#,
+            email.0.expose_secret()
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?;
+
+        match result.rows_affected() {
+            0 => Err(UserStoreError::UserNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    #[tracing::instrument(name = "Setting user account state in PostgreSQL", skip_all)]
+    async fn set_account_state(&mut self, email: &Email, account_state: AccountState) -> Result<(), UserStoreError> {
+        let result = sqlx::query!(
+            r#"
+                UPDATE users
+                SET status = $2, status_reason = $3, status_changed_at = $4, suspended_until = $5
+                WHERE email = $1
+            "#,
+            email.0.expose_secret(),
+            account_state.status.as_ref(),
+            account_state.reason.as_ref().map(AsRef::as_ref),
+            account_state.changed_at,
+            account_state.suspended_until
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?;
+
+        match result.rows_affected() {
+            0 => Err(UserStoreError::UserNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    #[tracing::instrument(name = "Setting user 2FA requirement in PostgreSQL", skip_all)]
+    async fn set_requires_2fa(&mut self, email: &Email, requires_2fa: bool) -> Result<(), UserStoreError> {
+        let result = sqlx::query!(
+            r#"
+                UPDATE users
+                SET requires_2fa = $2
+                WHERE email = $1
+            "#,
+            email.0.expose_secret(),
+            requires_2fa
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?;
+
+        match result.rows_affected() {
+            0 => Err(UserStoreError::UserNotFound),
+            _ => Ok(()),
+        }
     }
+
+    #[tracing::instrument(name = "Recording user login in PostgreSQL", skip_all)]
+    async fn record_login(&mut self, email: &Email, logged_in_at: DateTime<Utc>) -> Result<(), UserStoreError> {
+        let result = sqlx::query!(
+            r#"
+                UPDATE users
+                SET last_login_at = $2
+                WHERE email = $1
+            "#,
+            email.0.expose_secret(),
+            logged_in_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?;
+
+        match result.rows_affected() {
+            0 => Err(UserStoreError::UserNotFound),
+            _ => Ok(()),
+        }
+    }
+}
+
+struct UserRow {
+    email: String,
+    password_hash: Option<String>,
+    requires_2fa: bool,
+    email_verified: bool,
+    status: String,
+    status_reason: Option<String>,
+    status_changed_at: DateTime<Utc>,
+    suspended_until: Option<DateTime<Utc>>,
+    display_name: Option<String>,
+    locale: Option<String>,
+    timezone: Option<String>,
+    created_at: DateTime<Utc>,
+    last_login_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<UserRow> for User {
+    type Error = UserStoreError;
+
+    fn try_from(row: UserRow) -> Result<Self, Self::Error> {
+        let mut user = User::new(
+            Email::parse(SecretString::from(row.email)).map_err(|e| UserStoreError::UnexpectedError(e.into()))?,
+            row.password_hash
+                .map(|hash| HashedPassword::parse_password_hash(hash.into()))
+                .transpose()
+                .map_err(|e| UserStoreError::UnexpectedError(eyre!(e)))?,
+            row.requires_2fa,
+        );
+        user.email_verified = row.email_verified;
+        user.account_state = AccountState {
+            status: AccountStatus::parse(&row.status).map_err(|e| UserStoreError::UnexpectedError(e.into()))?,
+            reason: row
+                .status_reason
+                .as_deref()
+                .map(StatusReason::parse)
+                .transpose()
+                .map_err(|e| UserStoreError::UnexpectedError(e.into()))?,
+            changed_at: row.status_changed_at,
+            suspended_until: row.suspended_until,
+        };
+        user.profile = Profile {
+            display_name: row
+                .display_name
+                .as_deref()
+                .map(DisplayName::parse)
+                .transpose()
+                .map_err(|e| UserStoreError::UnexpectedError(e.into()))?,
+            locale: row
+                .locale
+                .as_deref()
+                .map(Locale::parse)
+                .transpose()
+                .map_err(|e| UserStoreError::UnexpectedError(e.into()))?,
+            timezone: row
+                .timezone
+                .as_deref()
+                .map(Timezone::parse)
+                .transpose()
+                .map_err(|e| UserStoreError::UnexpectedError(e.into()))?,
+        };
+        user.created_at = row.created_at;
+        user.last_login_at = row.last_login_at;
+
+        Ok(user)
+    }
+}
+
+// Matches the query anywhere in the value, with the wildcards of the query itself escaped
+fn like_pattern(query: &str) -> String {
+    let escaped = query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
 }
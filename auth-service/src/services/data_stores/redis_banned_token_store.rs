@@ -1,5 +1,6 @@
 use crate::{
-    domain::data_stores::{BannedTokenStore, BannedTokenStoreError},
+    domain::data_stores::{BannedTokenStore, BannedTokenStoreError, TokenRevocation},
+    domain::email::Email,
     utils::auth::TOKEN_TTL_SECONDS,
 };
 use color_eyre::eyre::Context;
@@ -54,10 +55,66 @@ impl BannedTokenStore for RedisBannedTokenStore {
             .wrap_err("Failed read from the Store to check if value for key exists")
             .map_err(BannedTokenStoreError::UnexpectedError)
     }
+
+    #[tracing::instrument(name = "Revoke user tokens in Redis Store", skip_all)]
+    async fn revoke_user_tokens(&mut self, email: &Email, keep_token: Option<&str>) -> Result<(), BannedTokenStoreError> {
+        let key = get_revoked_user_key(email);
+
+        let revocation = serde_json::to_string(&TokenRevocation::new(keep_token))
+            .wrap_err("Failed to serialize token revocation")
+            .map_err(BannedTokenStoreError::UnexpectedError)?;
+
+        // Older tokens have expired by the time the entry does
+        let ttl: u64 = TOKEN_TTL_SECONDS
+            .try_into()
+            .wrap_err("Failed to cast i64 into u64")
+            .map_err(BannedTokenStoreError::UnexpectedError)?;
+
+        let _: () = self
+            .conn
+            .write()
+            .await
+            .set_ex(key, revocation, ttl)
+            .wrap_err("Failed to set key in Store")
+            .map_err(BannedTokenStoreError::UnexpectedError)?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Check if user token is revoked", skip_all)]
+    async fn is_user_token_revoked(
+        &self,
+        email: &Email,
+        token: &str,
+        issued_at_micros: i64,
+    ) -> Result<bool, BannedTokenStoreError> {
+        let revocation: Option<String> = self
+            .conn
+            .write()
+            .await
+            .get(get_revoked_user_key(email))
+            .wrap_err("Failed to read token revocation from the Store")
+            .map_err(BannedTokenStoreError::UnexpectedError)?;
+
+        let Some(revocation) = revocation else {
+            return Ok(false);
+        };
+
+        let revocation: TokenRevocation = serde_json::from_str(&revocation)
+            .wrap_err("Failed to deserialize token revocation")
+            .map_err(BannedTokenStoreError::UnexpectedError)?;
+
+        Ok(revocation.revokes(token, issued_at_micros))
+    }
 }
 
 // We are using a key prefix to prevent collisions and organize data!
 const BANNED_TOKEN_KEY_PREFIX: &str = "banned_token:";
+const REVOKED_USER_KEY_PREFIX: &str = "revoked_user:";
+
+fn get_revoked_user_key(email: &Email) -> String {
+    format!("{}{}", REVOKED_USER_KEY_PREFIX, email.0.expose_secret())
+}
 
 fn get_key(token: &str) -> String {
     format!("{}{}", BANNED_TOKEN_KEY_PREFIX, token)
@@ -0,0 +1,149 @@
+use crate::domain::data_stores::{EmailChangeStore, EmailChangeStoreError, TokenPurpose};
+use crate::domain::email::Email;
+use crate::domain::email_change::EmailChange;
+use color_eyre::eyre::Context;
+use redis::{Commands, Connection};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub struct RedisEmailChangeStore {
+    conn: Arc<RwLock<Connection>>,
+}
+
+impl RedisEmailChangeStore {
+    pub fn new(conn: Connection) -> Self {
+        Self {
+            conn: Arc::new(RwLock::new(conn)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EmailChangeStore for RedisEmailChangeStore {
+    #[tracing::instrument(name = "Add email change into Redis Store", skip_all)]
+    async fn add_change(&mut self, change: EmailChange) -> Result<(), EmailChangeStoreError> {
+        let ttl: u64 = TokenPurpose::EmailChange
+            .ttl_seconds()
+            .try_into()
+            .wrap_err("Failed to cast i64 into u64")
+            .map_err(EmailChangeStoreError::UnexpectedError)?;
+
+        let value = serde_json::to_string(&StoredEmailChange::from(&change))
+            .wrap_err("Failed to serialize email change")
+            .map_err(EmailChangeStoreError::UnexpectedError)?;
+
+        self.conn
+            .write()
+            .await
+            .set_ex(get_key(&change.old_email), value, ttl)
+            .wrap_err("Failed to set email change in Redis")
+            .map_err(EmailChangeStoreError::UnexpectedError)
+    }
+
+    #[tracing::instrument(name = "Confirm email change in Redis Store", skip_all)]
+    async fn confirm_change(&mut self, old_email: &Email, token_id: &str) -> Result<EmailChange, EmailChangeStoreError> {
+        let key = get_key(old_email);
+        let mut conn = self.conn.write().await;
+
+        let mut change = read_change(&mut conn, old_email)?;
+
+        if !change.confirm(token_id) {
+            return Err(EmailChangeStoreError::ChangeNotFound);
+        }
+
+        let value = serde_json::to_string(&StoredEmailChange::from(&change))
+            .wrap_err("Failed to serialize email change")
+            .map_err(EmailChangeStoreError::UnexpectedError)?;
+
+        // Keep the expiration set when the change was requested
+        let _: () = redis::cmd("SET")
+            .arg(&key)
+            .arg(value)
+            .arg("KEEPTTL")
+            .query(&mut *conn)
+            .wrap_err("Failed to update email change in Redis")
+            .map_err(EmailChangeStoreError::UnexpectedError)?;
+
+        Ok(change)
+    }
+
+    #[tracing::instrument(name = "Get email change from Redis Store", skip_all)]
+    async fn get_change(&self, old_email: &Email) -> Result<EmailChange, EmailChangeStoreError> {
+        read_change(&mut *self.conn.write().await, old_email)
+    }
+
+    #[tracing::instrument(name = "Remove email change from Redis Store", skip_all)]
+    async fn remove_change(&mut self, old_email: &Email) -> Result<(), EmailChangeStoreError> {
+        let removed: usize = self
+            .conn
+            .write()
+            .await
+            .del(get_key(old_email))
+            .wrap_err("Failed to delete email change from Redis")
+            .map_err(EmailChangeStoreError::UnexpectedError)?;
+
+        match removed {
+            0 => Err(EmailChangeStoreError::ChangeNotFound),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn read_change(conn: &mut Connection, old_email: &Email) -> Result<EmailChange, EmailChangeStoreError> {
+    let value: Option<String> = conn
+        .get(get_key(old_email))
+        .wrap_err("Failed to get email change from Redis")
+        .map_err(EmailChangeStoreError::UnexpectedError)?;
+
+    let value = value.ok_or(EmailChangeStoreError::ChangeNotFound)?;
+
+    serde_json::from_str::<StoredEmailChange>(&value)
+        .wrap_err("Failed to deserialize email change")
+        .map_err(EmailChangeStoreError::UnexpectedError)?
+        .into_email_change(old_email.clone())
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredEmailChange {
+    new_email: String,
+    old_email_token_id: String,
+    new_email_token_id: String,
+    old_email_confirmed: bool,
+    new_email_confirmed: bool,
+}
+
+impl From<&EmailChange> for StoredEmailChange {
+    fn from(change: &EmailChange) -> Self {
+        Self {
+            new_email: change.new_email.0.expose_secret().to_owned(),
+            old_email_token_id: change.old_email_token_id.clone(),
+            new_email_token_id: change.new_email_token_id.clone(),
+            old_email_confirmed: change.old_email_confirmed,
+            new_email_confirmed: change.new_email_confirmed,
+        }
+    }
+}
+
+impl StoredEmailChange {
+    fn into_email_change(self, old_email: Email) -> Result<EmailChange, EmailChangeStoreError> {
+        let new_email =
+            Email::parse(SecretString::from(self.new_email)).map_err(|e| EmailChangeStoreError::UnexpectedError(e.into()))?;
+
+        Ok(EmailChange {
+            old_email,
+            new_email,
+            old_email_token_id: self.old_email_token_id,
+            new_email_token_id: self.new_email_token_id,
+            old_email_confirmed: self.old_email_confirmed,
+            new_email_confirmed: self.new_email_confirmed,
+        })
+    }
+}
+
+const EMAIL_CHANGE_PREFIX: &str = "email_change:";
+
+fn get_key(email: &Email) -> String {
+    format!("{}{}", EMAIL_CHANGE_PREFIX, email.0.expose_secret())
+}
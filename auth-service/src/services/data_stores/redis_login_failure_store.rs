@@ -0,0 +1,92 @@
+use crate::domain::data_stores::{LoginFailureStore, LoginFailureStoreError};
+use crate::domain::email::Email;
+use crate::domain::login_failures::LoginFailures;
+use crate::utils::constants::{LOGIN_FAILURE_WINDOW_SECONDS, LOGIN_LOCKOUT_MAX_SECONDS};
+use color_eyre::eyre::Context;
+use redis::{Commands, Connection};
+use secrecy::ExposeSecret;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub struct RedisLoginFailureStore {
+    conn: Arc<RwLock<Connection>>,
+}
+
+impl RedisLoginFailureStore {
+    pub fn new(conn: Connection) -> Self {
+        Self {
+            conn: Arc::new(RwLock::new(conn)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LoginFailureStore for RedisLoginFailureStore {
+    #[tracing::instrument(name = "Get login failures from Redis Store", skip_all)]
+    async fn get_failures(&self, email: &Email) -> Result<LoginFailures, LoginFailureStoreError> {
+        let value: Option<String> = self
+            .conn
+            .write()
+            .await
+            .get(get_key(email))
+            .wrap_err("Failed to get login failures from Redis")
+            .map_err(LoginFailureStoreError::UnexpectedError)?;
+
+        match value {
+            None => Ok(LoginFailures::default()),
+            Some(value) => serde_json::from_str(&value)
+                .wrap_err("Failed to deserialize login failures")
+                .map_err(LoginFailureStoreError::UnexpectedError),
+        }
+    }
+
+    #[tracing::instrument(name = "Set login failures in Redis Store", skip_all)]
+    async fn set_failures(&mut self, email: &Email, failures: LoginFailures) -> Result<(), LoginFailureStoreError> {
+        // Outlives both the failure window and the longest lockout
+        let ttl: u64 = LOGIN_FAILURE_WINDOW_SECONDS
+            .saturating_add(*LOGIN_LOCKOUT_MAX_SECONDS)
+            .try_into()
+            .wrap_err("Failed to cast i64 into u64")
+            .map_err(LoginFailureStoreError::UnexpectedError)?;
+
+        let value = serde_json::to_string(&failures)
+            .wrap_err("Failed to serialize login failures")
+            .map_err(LoginFailureStoreError::UnexpectedError)?;
+
+        self.conn
+            .write()
+            .await
+            .set_ex(get_key(email), value, ttl)
+            .wrap_err("Failed to set login failures in Redis")
+            .map_err(LoginFailureStoreError::UnexpectedError)
+    }
+
+    #[tracing::instrument(name = "Clear login failures from Redis Store", skip_all)]
+    async fn clear_failures(&mut self, email: &Email) -> Result<(), LoginFailureStoreError> {
+        self.conn
+            .write()
+            .await
+            .del(get_key(email))
+            .wrap_err("Failed to delete login failures from Redis")
+            .map_err(LoginFailureStoreError::UnexpectedError)
+    }
+
+    #[tracing::instrument(name = "Move login failures in Redis Store", skip_all)]
+    async fn move_failures(&mut self, old_email: &Email, new_email: &Email) -> Result<(), LoginFailureStoreError> {
+        let failures = self.get_failures(old_email).await?;
+
+        // Nothing left under the old address means an earlier attempt already moved them
+        if failures == LoginFailures::default() {
+            return Ok(());
+        }
+
+        self.set_failures(new_email, failures).await?;
+        self.clear_failures(old_email).await
+    }
+}
+
+const LOGIN_FAILURES_PREFIX: &str = "login_failures:";
+
+fn get_key(email: &Email) -> String {
+    format!("{}{}", LOGIN_FAILURES_PREFIX, email.0.expose_secret())
+}
@@ -0,0 +1,105 @@
+use crate::domain::data_stores::{OneTimeTokenStore, OneTimeTokenStoreError, TokenPurpose};
+use crate::domain::email::Email;
+use color_eyre::eyre::Context;
+use redis::{Commands, Connection};
+use secrecy::{ExposeSecret, SecretString};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub struct RedisOneTimeTokenStore {
+    conn: Arc<RwLock<Connection>>,
+}
+
+impl RedisOneTimeTokenStore {
+    pub fn new(conn: Connection) -> Self {
+        Self {
+            conn: Arc::new(RwLock::new(conn)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl OneTimeTokenStore for RedisOneTimeTokenStore {
+    #[tracing::instrument(name = "Add one-time token into Redis Store", skip_all)]
+    async fn add_token(&mut self, purpose: TokenPurpose, token_id: &str, email: Email) -> Result<(), OneTimeTokenStoreError> {
+        let ttl: u64 = purpose
+            .ttl_seconds()
+            .try_into()
+            .wrap_err("Failed to cast i64 into u64")
+            .map_err(OneTimeTokenStoreError::UnexpectedError)?;
+
+        let key = get_key(purpose, token_id);
+        let user_key = get_user_key(&email);
+        let mut conn = self.conn.write().await;
+
+        let _: () = conn
+            .set_ex(&key, email.0.expose_secret(), ttl)
+            .wrap_err("Failed to set one-time token in Redis")
+            .map_err(OneTimeTokenStoreError::UnexpectedError)?;
+
+        // Index the token by user, so that every token of a user can be removed at once
+        let _: () = conn
+            .sadd(&user_key, &key)
+            .wrap_err("Failed to index one-time token in Redis")
+            .map_err(OneTimeTokenStoreError::UnexpectedError)?;
+
+        let user_key_ttl: i64 = conn
+            .ttl(&user_key)
+            .wrap_err("Failed to get expiration of one-time token index in Redis")
+            .map_err(OneTimeTokenStoreError::UnexpectedError)?;
+
+        if user_key_ttl < purpose.ttl_seconds() {
+            let _: () = conn
+                .expire(&user_key, purpose.ttl_seconds())
+                .wrap_err("Failed to set expiration of one-time token index in Redis")
+                .map_err(OneTimeTokenStoreError::UnexpectedError)?;
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Consume one-time token from Redis Store", skip_all)]
+    async fn consume_token(&mut self, purpose: TokenPurpose, token_id: &str) -> Result<Email, OneTimeTokenStoreError> {
+        // GETDEL makes reading and invalidating the token a single atomic step
+        let email: Option<String> = self
+            .conn
+            .write()
+            .await
+            .get_del(get_key(purpose, token_id))
+            .wrap_err("Failed to consume one-time token from Redis")
+            .map_err(OneTimeTokenStoreError::UnexpectedError)?;
+
+        let email = email.ok_or(OneTimeTokenStoreError::TokenNotFound)?;
+
+        Email::parse(SecretString::from(email)).map_err(|e| OneTimeTokenStoreError::UnexpectedError(e.into()))
+    }
+
+    #[tracing::instrument(name = "Remove one-time tokens of user from Redis Store", skip_all)]
+    async fn remove_tokens(&mut self, email: &Email) -> Result<(), OneTimeTokenStoreError> {
+        let user_key = get_user_key(email);
+        let mut conn = self.conn.write().await;
+
+        let keys: Vec<String> = conn
+            .smembers(&user_key)
+            .wrap_err("Failed to get one-time tokens of user from Redis")
+            .map_err(OneTimeTokenStoreError::UnexpectedError)?;
+
+        let _: () = conn
+            .del(keys.iter().chain(std::iter::once(&user_key)).collect::<Vec<_>>())
+            .wrap_err("Failed to delete one-time tokens of user from Redis")
+            .map_err(OneTimeTokenStoreError::UnexpectedError)?;
+
+        Ok(())
+    }
+}
+
+const ONE_TIME_TOKEN_PREFIX: &str = "one_time_token:";
+const USER_ONE_TIME_TOKENS_PREFIX: &str = "one_time_tokens:";
+
+fn get_key(purpose: TokenPurpose, token_id: &str) -> String {
+    format!("{}{}:{}", ONE_TIME_TOKEN_PREFIX, purpose.as_str(), token_id)
+}
+
+fn get_user_key(email: &Email) -> String {
+    format!("{}{}", USER_ONE_TIME_TOKENS_PREFIX, email.0.expose_secret())
+}
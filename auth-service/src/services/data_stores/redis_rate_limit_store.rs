@@ -0,0 +1,77 @@
+use crate::domain::data_stores::{RateLimitStore, RateLimitStoreError};
+use crate::domain::rate_limit::{RateLimit, RateLimitDecision};
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::Context;
+use redis::{Commands, Connection};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+// Keeps the requests of each key in a sorted set scored by their time in milliseconds, shared by every instance
+pub struct RedisRateLimitStore {
+    conn: Arc<RwLock<Connection>>,
+}
+
+impl RedisRateLimitStore {
+    pub fn new(conn: Connection) -> Self {
+        Self {
+            conn: Arc::new(RwLock::new(conn)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    #[tracing::instrument(name = "Record request in Redis rate limit store", skip_all)]
+    async fn record_request(
+        &mut self,
+        key: &str,
+        limit: &RateLimit,
+        now: DateTime<Utc>,
+    ) -> Result<RateLimitDecision, RateLimitStoreError> {
+        let key = get_key(key);
+        let window_start = now
+            .checked_sub_signed(limit.window())
+            .map_or(0, |window_start| window_start.timestamp_millis());
+        let member = format!("{}:{}", now.timestamp_millis(), Uuid::new_v4());
+
+        let mut conn = self.conn.write().await;
+
+        // The request is added in the same transaction it is counted in, so that concurrent requests from several
+        // instances can't all slip through. It is taken back out when it turns out to be over the limit.
+        let (requests,): (Vec<(String, i64)>,) = redis::pipe()
+            .atomic()
+            .zrembyscore(&key, "-inf", window_start)
+            .ignore()
+            .zadd(&key, &member, now.timestamp_millis())
+            .ignore()
+            .zrange_withscores(&key, 0, -1)
+            .expire(&key, i64::from(limit.window_seconds))
+            .ignore()
+            .query(&mut *conn)
+            .wrap_err("Failed to record request in Redis")
+            .map_err(RateLimitStoreError::UnexpectedError)?;
+
+        let earlier_requests: Vec<DateTime<Utc>> = requests
+            .into_iter()
+            .filter(|(request, _)| *request != member)
+            .filter_map(|(_, milliseconds)| DateTime::from_timestamp_millis(milliseconds))
+            .collect();
+
+        let decision = limit.decide(&earlier_requests, now);
+        if decision != RateLimitDecision::Allowed {
+            let _: () = conn
+                .zrem(&key, &member)
+                .wrap_err("Failed to remove rejected request from Redis")
+                .map_err(RateLimitStoreError::UnexpectedError)?;
+        }
+
+        Ok(decision)
+    }
+}
+
+const RATE_LIMIT_PREFIX: &str = "rate_limit:";
+
+fn get_key(key: &str) -> String {
+    format!("{}{}", RATE_LIMIT_PREFIX, key)
+}
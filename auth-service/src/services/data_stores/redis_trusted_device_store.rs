@@ -0,0 +1,126 @@
+use crate::domain::data_stores::{TrustedDeviceStore, TrustedDeviceStoreError};
+use crate::domain::email::Email;
+use crate::domain::trusted_device::TrustedDevice;
+use crate::utils::constants::TRUSTED_DEVICE_TTL_SECONDS;
+use color_eyre::eyre::Context;
+use redis::{Commands, Connection};
+use secrecy::ExposeSecret;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// Devices of a user are kept in a single Redis hash, keyed by device id
+pub struct RedisTrustedDeviceStore {
+    conn: Arc<RwLock<Connection>>,
+}
+
+impl RedisTrustedDeviceStore {
+    pub fn new(conn: Connection) -> Self {
+        Self {
+            conn: Arc::new(RwLock::new(conn)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TrustedDeviceStore for RedisTrustedDeviceStore {
+    #[tracing::instrument(name = "Add trusted device into Redis Store", skip_all)]
+    async fn add_device(&mut self, email: Email, device: TrustedDevice) -> Result<(), TrustedDeviceStoreError> {
+        let key = get_key(&email);
+
+        let device_str = serde_json::to_string(&device)
+            .wrap_err("Failed to serialize trusted device")
+            .map_err(TrustedDeviceStoreError::UnexpectedError)?;
+
+        let mut conn = self.conn.write().await;
+
+        let _: () = conn
+            .hset(&key, &device.id, device_str)
+            .wrap_err("Failed to set trusted device in Redis")
+            .map_err(TrustedDeviceStoreError::UnexpectedError)?;
+
+        // Every device expires at the latest one TTL from now, so the whole hash can expire with the newest one
+        let _: () = conn
+            .expire(&key, *TRUSTED_DEVICE_TTL_SECONDS)
+            .wrap_err("Failed to set expiration of trusted devices in Redis")
+            .map_err(TrustedDeviceStoreError::UnexpectedError)?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Get trusted device from Redis Store", skip_all)]
+    async fn get_device(&self, email: &Email, device_id: &str) -> Result<TrustedDevice, TrustedDeviceStoreError> {
+        self.get_devices(email)
+            .await?
+            .into_iter()
+            .find(|device| device.id == device_id)
+            .ok_or(TrustedDeviceStoreError::DeviceNotFound)
+    }
+
+    #[tracing::instrument(name = "Get trusted devices from Redis Store", skip_all)]
+    async fn get_devices(&self, email: &Email) -> Result<Vec<TrustedDevice>, TrustedDeviceStoreError> {
+        let devices: HashMap<String, String> = self
+            .conn
+            .write()
+            .await
+            .hgetall(get_key(email))
+            .wrap_err("Failed to get trusted devices from Redis")
+            .map_err(TrustedDeviceStoreError::UnexpectedError)?;
+
+        let mut devices = devices
+            .values()
+            .map(|device| serde_json::from_str::<TrustedDevice>(device))
+            .collect::<Result<Vec<_>, _>>()
+            .wrap_err("Failed to deserialize trusted device")
+            .map_err(TrustedDeviceStoreError::UnexpectedError)?;
+
+        devices.retain(|device| !device.is_expired());
+        devices.sort_by_key(|device| device.created_at);
+
+        Ok(devices)
+    }
+
+    #[tracing::instrument(name = "Remove trusted device from Redis Store", skip_all)]
+    async fn remove_device(&mut self, email: &Email, device_id: &str) -> Result<(), TrustedDeviceStoreError> {
+        let removed: usize = self
+            .conn
+            .write()
+            .await
+            .hdel(get_key(email), device_id)
+            .wrap_err("Failed to delete trusted device from Redis")
+            .map_err(TrustedDeviceStoreError::UnexpectedError)?;
+
+        match removed {
+            0 => Err(TrustedDeviceStoreError::DeviceNotFound),
+            _ => Ok(()),
+        }
+    }
+
+    #[tracing::instrument(name = "Move trusted devices in Redis Store", skip_all)]
+    async fn move_devices(&mut self, old_email: &Email, new_email: &Email) -> Result<(), TrustedDeviceStoreError> {
+        for device in self.get_devices(old_email).await? {
+            self.add_device(new_email.clone(), device).await?;
+        }
+
+        self.remove_devices(old_email).await
+    }
+
+    #[tracing::instrument(name = "Remove trusted devices from Redis Store", skip_all)]
+    async fn remove_devices(&mut self, email: &Email) -> Result<(), TrustedDeviceStoreError> {
+        let _: () = self
+            .conn
+            .write()
+            .await
+            .del(get_key(email))
+            .wrap_err("Failed to delete trusted devices from Redis")
+            .map_err(TrustedDeviceStoreError::UnexpectedError)?;
+
+        Ok(())
+    }
+}
+
+const TRUSTED_DEVICES_PREFIX: &str = "trusted_devices:";
+
+fn get_key(email: &Email) -> String {
+    format!("{}{}", TRUSTED_DEVICES_PREFIX, email.0.expose_secret())
+}
@@ -6,7 +6,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use crate::domain::{
-    data_stores::{LoginAttemptId, TwoFACode, TwoFACodeStore, TwoFACodeStoreError},
+    data_stores::{LoginAttemptId, TwoFACodeHash, TwoFACodeStore, TwoFACodeStoreError},
     email::Email,
 };
 
@@ -29,13 +29,13 @@ impl TwoFACodeStore for RedisTwoFACodeStore {
         &mut self,
         email: Email,
         login_attempt_id: LoginAttemptId,
-        code: TwoFACode,
+        code_hash: TwoFACodeHash,
     ) -> Result<(), TwoFACodeStoreError> {
         // TODO:
         // 1. Create a new key using the get_key helper function.
         // 2. Create a TwoFATuple instance.
         let two_fa_tuple = TwoFATuple(
-            code.0.expose_secret().to_owned(),
+            code_hash.as_ref().expose_secret().to_owned(),
             login_attempt_id.0.expose_secret().to_owned(),
         );
         // 3. Use serde_json::to_string to serialize the TwoFATuple instance into a JSON string.
@@ -71,7 +71,7 @@ impl TwoFACodeStore for RedisTwoFACodeStore {
     }
 
     #[tracing::instrument(name = "Get code from Redis 2FA Store", skip_all)]
-    async fn get_code(&self, email: &Email) -> Result<(LoginAttemptId, TwoFACode), TwoFACodeStoreError> {
+    async fn get_code(&self, email: &Email) -> Result<(LoginAttemptId, TwoFACodeHash), TwoFACodeStoreError> {
         // TODO:
         // 1. Create a new key using the get_key helper function.
         // 2. Call the get command on the Redis connection to get the value stored for the key.
@@ -87,16 +87,17 @@ impl TwoFACodeStore for RedisTwoFACodeStore {
             .wrap_err("Failed to deserialize 2FA tuple") // New!
             .map_err(TwoFACodeStoreError::UnexpectedError)?;
 
-        let two_fa_code = TwoFACode::parse(two_fa_tuple.0).map_err(TwoFACodeStoreError::UnexpectedError)?;
+        let two_fa_code_hash = TwoFACodeHash::parse(two_fa_tuple.0).map_err(TwoFACodeStoreError::UnexpectedError)?;
         let login_attempt_id = LoginAttemptId::parse(two_fa_tuple.1).map_err(TwoFACodeStoreError::UnexpectedError)?;
 
-        // Then, parse the login attempt ID string and 2FA code string into a LoginAttemptId and TwoFACode type respectively.
+        // Then, parse the login attempt ID string and 2FA code hash string into a LoginAttemptId and TwoFACodeHash type respectively.
         // Return TwoFACodeStoreError::UnexpectedError if parsing fails.
 
-        Ok((login_attempt_id, two_fa_code))
+        Ok((login_attempt_id, two_fa_code_hash))
     }
 }
 
+// Holds the hex-encoded code hash and the login attempt id
 #[derive(Serialize, Deserialize)]
 struct TwoFATuple(pub String, pub String);
 
@@ -0,0 +1,166 @@
+use crate::domain::audit::{AuditEvent, AuditPage, AuditSearch};
+use crate::domain::data_stores::{AuditLog, AuditLogError};
+use crate::domain::email::Email;
+use std::cmp::Reverse;
+
+#[derive(Default)]
+pub struct VecAuditLog {
+    // In the order they were recorded
+    events: Vec<AuditEvent>,
+}
+
+#[async_trait::async_trait]
+impl AuditLog for VecAuditLog {
+    async fn record_event(&mut self, event: AuditEvent) -> Result<(), AuditLogError> {
+        self.events.push(event);
+
+        Ok(())
+    }
+
+    async fn list_events(&self, search: &AuditSearch) -> Result<AuditPage, AuditLogError> {
+        let mut matching: Vec<&AuditEvent> = self.events.iter().filter(|event| matches(event, search)).collect();
+        // Stable, so events recorded at the same time stay most recent first
+        matching.reverse();
+        matching.sort_by_key(|event| Reverse(event.occurred_at));
+
+        let total = u64::try_from(matching.len()).unwrap_or(u64::MAX);
+        let events = matching
+            .into_iter()
+            .skip(usize::try_from(search.offset).unwrap_or(usize::MAX))
+            .take(usize::try_from(search.limit).unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+
+        Ok(AuditPage { events, total })
+    }
+
+    async fn move_actor(&mut self, old_email: &Email, new_email: &Email) -> Result<(), AuditLogError> {
+        self.events
+            .iter_mut()
+            .filter(|event| event.actor.as_ref() == Some(old_email))
+            .for_each(|event| event.actor = Some(new_email.clone()));
+
+        Ok(())
+    }
+
+    async fn forget_actor(&mut self, email: &Email) -> Result<(), AuditLogError> {
+        self.events
+            .iter_mut()
+            .filter(|event| event.actor.as_ref() == Some(email))
+            .for_each(|event| {
+                event.actor = None;
+                event.ip = None;
+                event.user_agent = None;
+            });
+
+        Ok(())
+    }
+}
+
+fn matches(event: &AuditEvent, search: &AuditSearch) -> bool {
+    (search.actor.is_none() || event.actor == search.actor)
+        && (search.event_types.is_empty() || search.event_types.contains(&event.event_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::audit::{AuditEventType, AuditOutcome};
+    use chrono::{Duration, Utc};
+
+    fn event(actor: &Email, event_type: AuditEventType, minutes_ago: i64) -> AuditEvent {
+        AuditEvent {
+            occurred_at: Utc::now() - Duration::minutes(minutes_ago),
+            actor: Some(actor.clone()),
+            ip: Some("203.0.113.7".parse().unwrap()),
+            user_agent: Some("test".to_owned()),
+            event_type,
+            outcome: AuditOutcome::Success,
+            reason: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lists_matching_events_most_recent_first() {
+        let mut log = VecAuditLog::default();
+        let alice: Email = "alice@example.com".try_into().unwrap();
+        let bob: Email = "bob@example.com".try_into().unwrap();
+
+        let signup = event(&alice, AuditEventType::Signup, 3);
+        let first_login = event(&alice, AuditEventType::Login, 2);
+        let other_login = event(&bob, AuditEventType::Login, 2);
+        let second_login = event(&alice, AuditEventType::Login, 1);
+        for event in [&signup, &first_login, &other_login, &second_login] {
+            log.record_event(event.clone()).await.unwrap();
+        }
+
+        let page = log
+            .list_events(&AuditSearch {
+                actor: Some(alice.clone()),
+                event_types: vec![AuditEventType::Login],
+                offset: 0,
+                limit: 10,
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.events, vec![second_login.clone(), first_login]);
+        assert_eq!(page.total, 2);
+
+        let page = log
+            .list_events(&AuditSearch {
+                actor: None,
+                event_types: Vec::new(),
+                offset: 1,
+                limit: 2,
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.events.len(), 2);
+        assert_eq!(page.total, 4);
+    }
+
+    #[tokio::test]
+    async fn test_moves_events_to_new_actor() {
+        let mut log = VecAuditLog::default();
+        let alice: Email = "alice@example.com".try_into().unwrap();
+        let alice_new: Email = "alice@example.org".try_into().unwrap();
+
+        log.record_event(event(&alice, AuditEventType::Login, 1)).await.unwrap();
+        log.move_actor(&alice, &alice_new).await.unwrap();
+
+        let search = |actor: &Email| AuditSearch {
+            actor: Some(actor.clone()),
+            event_types: Vec::new(),
+            offset: 0,
+            limit: 10,
+        };
+        assert_eq!(log.list_events(&search(&alice_new)).await.unwrap().total, 1);
+        assert_eq!(log.list_events(&search(&alice)).await.unwrap().total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_forgets_actor() {
+        let mut log = VecAuditLog::default();
+        let alice: Email = "alice@example.com".try_into().unwrap();
+        let bob: Email = "bob@example.com".try_into().unwrap();
+
+        log.record_event(event(&alice, AuditEventType::Login, 2)).await.unwrap();
+        log.record_event(event(&bob, AuditEventType::Login, 1)).await.unwrap();
+        log.forget_actor(&alice).await.unwrap();
+
+        let page = log
+            .list_events(&AuditSearch {
+                actor: None,
+                event_types: Vec::new(),
+                offset: 0,
+                limit: 10,
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.events[0].actor, Some(bob));
+        assert_eq!(
+            (&page.events[1].actor, page.events[1].ip, &page.events[1].user_agent),
+            (&None, None, &None)
+        );
+    }
+}
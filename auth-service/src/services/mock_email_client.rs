@@ -2,9 +2,26 @@ use crate::domain::email::Email;
 use crate::domain::email_client::EmailClient;
 use color_eyre::Result;
 use secrecy::ExposeSecret;
+use tokio::sync::RwLock;
+
+#[derive(Debug, Clone)]
+pub struct SentEmail {
+    pub recipient: Email,
+    pub subject: String,
+    pub content: String,
+}
 
 #[derive(Default)]
-pub struct MockEmailClient;
+pub struct MockEmailClient {
+    sent_emails: RwLock<Vec<SentEmail>>,
+}
+
+impl MockEmailClient {
+    // Lets tests inspect the emails (and the codes or links inside them) that would have been sent
+    pub async fn sent_emails(&self) -> Vec<SentEmail> {
+        self.sent_emails.read().await.clone()
+    }
+}
 
 #[async_trait::async_trait]
 impl EmailClient for MockEmailClient {
@@ -17,6 +34,12 @@ impl EmailClient for MockEmailClient {
             content
         );
 
+        self.sent_emails.write().await.push(SentEmail {
+            recipient: recipient.clone(),
+            subject: subject.to_owned(),
+            content: content.to_owned(),
+        });
+
         Ok(())
     }
 }
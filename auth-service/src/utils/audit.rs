@@ -0,0 +1,88 @@
+use crate::app_state::AppState;
+use crate::domain::audit::{AuditEvent, AuditEventType, AuditOutcome};
+use crate::domain::email::Email;
+use crate::domain::error::AuthAPIError;
+use crate::utils::auth::validate_token;
+use crate::utils::rate_limit::client_ip;
+use axum::extract::FromRequestParts;
+use axum::http::header::USER_AGENT;
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use secrecy::SecretString;
+use std::convert::Infallible;
+use std::net::IpAddr;
+
+// Longer user agents are cut, they are only shown to people reviewing the log
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+// Where a request came from, as recorded in the audit log
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientInfo {
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientInfo {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let user_agent = parts
+            .headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|user_agent| user_agent.trim().chars().take(MAX_USER_AGENT_LENGTH).collect::<String>())
+            .filter(|user_agent| !user_agent.is_empty());
+
+        Ok(ClientInfo {
+            ip: client_ip(&parts.extensions),
+            user_agent,
+        })
+    }
+}
+
+// Records how the request ended in the audit log and turns its result into the response. A failure to record is only
+// logged, so that the audit log being down doesn't lock users out.
+pub async fn record_audit_event<T: IntoResponse>(
+    state: &AppState,
+    client: ClientInfo,
+    event_type: AuditEventType,
+    actor: Option<Email>,
+    result: Result<T, AuthAPIError>,
+) -> Response {
+    let (outcome, reason, response) = match result {
+        Ok(response) => {
+            let response = response.into_response();
+            match response.status() {
+                StatusCode::PARTIAL_CONTENT => (AuditOutcome::Challenged, None, response),
+                status if status.is_success() => (AuditOutcome::Success, None, response),
+                status => (AuditOutcome::Failure, status.canonical_reason().map(str::to_owned), response),
+            }
+        }
+        Err(e) => (AuditOutcome::Failure, Some(e.to_string()), e.into_response()),
+    };
+
+    let event = AuditEvent {
+        occurred_at: Utc::now(),
+        actor,
+        ip: client.ip,
+        user_agent: client.user_agent,
+        event_type,
+        outcome,
+        reason,
+    };
+
+    if let Err(e) = state.audit_log.write().await.record_event(event).await {
+        tracing::error!(error = ?e, event_type = event_type.as_ref(), "Failed to record audit event");
+    }
+
+    response
+}
+
+// The user a token was issued to, when it is valid. Whether it was revoked doesn't matter, the request is still theirs.
+pub async fn token_actor(token: &str) -> Option<Email> {
+    let claims = validate_token(token).await.ok()?;
+
+    Email::parse(SecretString::from(claims.sub)).ok()
+}
@@ -1,17 +1,37 @@
+use crate::app_state::{AppState, BannedTokenStoreType};
+use crate::domain::account_status::AccountStatus;
+use crate::domain::data_stores::{BannedTokenStoreError, OrganizationStoreError, TokenPurpose, TwoFACodeStoreError};
 use crate::domain::email::Email;
-use crate::utils::constants::env::JWT_COOKIE_NAME;
-use crate::utils::constants::JWT_SECRET;
+use crate::domain::error::AuthAPIError;
+use crate::domain::hashed_password::{HashedPassword, HashingParams};
+use crate::domain::organization::OrganizationId;
+use crate::domain::password_policy::PasswordViolation;
+use crate::domain::rbac::Grants;
+use crate::domain::user::User;
+use crate::utils::constants::env::{JWT_COOKIE_NAME, TRUSTED_DEVICE_COOKIE_NAME};
+use crate::utils::constants::{JWT_SECRET, TRUSTED_DEVICE_TTL_SECONDS};
+use crate::utils::hashing_executor::HashingOverloaded;
+use axum::extract::{FromRequestParts, Request, State};
+use axum::http::request::Parts;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
 use axum_extra::extract::cookie::{Cookie, SameSite};
+use axum_extra::extract::CookieJar;
 use chrono::Utc;
-use color_eyre::eyre::{Context, ContextCompat, Result};
+use color_eyre::eyre::{eyre, Context, ContextCompat, Report, Result};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Validation};
-use secrecy::ExposeSecret;
+use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context as TaskContext, Poll};
+use tower::{Layer, Service};
+use tracing::Instrument;
 
 // Create cookie with a new JWT auth token
 #[tracing::instrument(name = "Generate auth Cookie", skip_all)]
-pub fn generate_auth_cookie(email: &Email) -> Result<Cookie<'static>> {
-    let jwt = generate_auth_token(email)?;
+pub fn generate_auth_cookie(email: &Email, grants: Grants) -> Result<Cookie<'static>> {
+    let jwt = generate_auth_token(email, grants)?;
     Ok(create_auth_cookie(jwt))
 }
 
@@ -29,10 +49,117 @@ fn create_auth_cookie(token: String) -> Cookie<'static> {
 // This value determines how long the JWT auth token is valid for
 pub const TOKEN_TTL_SECONDS: i64 = 600; // 10 minutes
 
+// This value determines how long a magic login link is valid for
+pub const MAGIC_LINK_TTL_SECONDS: i64 = 900; // 15 minutes
+
+pub const EMAIL_VERIFICATION_TTL_SECONDS: i64 = 86400; // 24 hours
+
+pub const PASSWORD_RESET_TTL_SECONDS: i64 = 3600; // 1 hour
+
+pub const EMAIL_CHANGE_TTL_SECONDS: i64 = 86400; // 24 hours
+
+pub const INVITATION_TTL_SECONDS: i64 = 7 * 86400; // 7 days
+
+// Users may only read the new sign-in email days later
+pub const UNRECOGNIZED_LOGIN_TTL_SECONDS: i64 = 7 * 86400; // 7 days
+
+const TRUSTED_DEVICE_AUDIENCE: &str = "trusted_device";
+
 // Create JWT auth token
 #[tracing::instrument(name = "Generate JWT Token", skip_all)]
-fn generate_auth_token(email: &Email) -> Result<String> {
-    let delta = chrono::Duration::try_seconds(TOKEN_TTL_SECONDS).wrap_err("Failed to create time delta")?;
+fn generate_auth_token(email: &Email, grants: Grants) -> Result<String> {
+    let exp = expiration_timestamp(TOKEN_TTL_SECONDS)?;
+    let now = Utc::now();
+    let iat = now.timestamp().try_into().wrap_err("Failed to cast iat into usize")?;
+
+    let sub = email.0.expose_secret().to_owned();
+
+    let claims = Claims {
+        sub,
+        exp,
+        iat,
+        iat_micros: Some(now.timestamp_micros()),
+        grants,
+    };
+
+    create_token(&claims)
+}
+
+// Create a signed, short-lived token that is sent by email and can be used exactly once.
+// Returns the token together with its id, which has to be recorded in a `OneTimeTokenStore`.
+#[tracing::instrument(name = "Generate one-time token", skip_all)]
+pub fn generate_one_time_token(email: &Email, purpose: TokenPurpose) -> Result<(String, String)> {
+    generate_scoped_token(email, purpose.as_str(), purpose.ttl_seconds())
+}
+
+// Check the signature, expiration and purpose of a one-time token.
+// Whether the token has already been used is up to the `OneTimeTokenStore`.
+#[tracing::instrument(name = "Validate one-time token", skip_all)]
+pub fn validate_one_time_token(token: &str, purpose: TokenPurpose) -> Result<ScopedClaims> {
+    validate_scoped_token(token, purpose.as_str())
+}
+
+// Create a cookie identifying a device on which the user chose to skip 2FA.
+// The device id in the token has to be recorded in a `TrustedDeviceStore`.
+#[tracing::instrument(name = "Generate trusted device Cookie", skip_all)]
+pub fn generate_trusted_device_cookie(email: &Email, device_id: &str) -> Result<Cookie<'static>> {
+    let ttl_seconds = *TRUSTED_DEVICE_TTL_SECONDS;
+
+    let exp = expiration_timestamp(ttl_seconds)?;
+    let claims = ScopedClaims {
+        sub: email.0.expose_secret().to_owned(),
+        exp,
+        jti: device_id.to_owned(),
+        aud: TRUSTED_DEVICE_AUDIENCE.to_owned(),
+    };
+    let token = create_token(&claims)?;
+
+    let cookie = Cookie::build((TRUSTED_DEVICE_COOKIE_NAME, token))
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .max_age(time::Duration::seconds(ttl_seconds))
+        .build();
+
+    Ok(cookie)
+}
+
+#[tracing::instrument(name = "Validate trusted device token", skip_all)]
+pub fn validate_trusted_device_token(token: &str) -> Result<ScopedClaims> {
+    validate_scoped_token(token, TRUSTED_DEVICE_AUDIENCE)
+}
+
+// The audience keeps scoped tokens from being accepted as auth tokens, or for another scope
+fn generate_scoped_token(email: &Email, audience: &str, ttl_seconds: i64) -> Result<(String, String)> {
+    let exp = expiration_timestamp(ttl_seconds)?;
+
+    let jti = uuid::Uuid::new_v4().to_string();
+
+    let claims = ScopedClaims {
+        sub: email.0.expose_secret().to_owned(),
+        exp,
+        jti: jti.clone(),
+        aud: audience.to_owned(),
+    };
+
+    Ok((create_token(&claims)?, jti))
+}
+
+fn validate_scoped_token(token: &str, audience: &str) -> Result<ScopedClaims> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[audience]);
+
+    decode::<ScopedClaims>(
+        token,
+        &DecodingKey::from_secret(JWT_SECRET.expose_secret().as_bytes()),
+        &validation,
+    )
+    .map(|data| data.claims)
+    .wrap_err("Failed to decode scoped token")
+}
+
+fn expiration_timestamp(ttl_seconds: i64) -> Result<usize> {
+    let delta = chrono::Duration::try_seconds(ttl_seconds).wrap_err("Failed to create time delta")?;
 
     // Create JWT expiration time
     let exp = Utc::now()
@@ -41,13 +168,7 @@ fn generate_auth_token(email: &Email) -> Result<String> {
         .timestamp();
 
     // Cast exp to a usize, which is what Claims expects
-    let exp: usize = exp.try_into().wrap_err("Failed to cast exp into usize")?;
-
-    let sub = email.0.expose_secret().to_owned();
-
-    let claims = Claims { sub, exp };
-
-    create_token(&claims)
+    exp.try_into().wrap_err("Failed to cast exp into usize")
 }
 
 // Check if JWT auth token is valid by decoding it using the JWT secret
@@ -63,7 +184,7 @@ pub async fn validate_token(token: &str) -> Result<Claims, jsonwebtoken::errors:
 
 // Create JWT auth token by encoding claims using the JWT secret
 #[tracing::instrument(name = "Create JWT Token", skip_all)]
-fn create_token(claims: &Claims) -> Result<String> {
+fn create_token<T: Serialize>(claims: &T) -> Result<String> {
     encode(
         &jsonwebtoken::Header::default(),
         &claims,
@@ -72,20 +193,377 @@ fn create_token(claims: &Claims) -> Result<String> {
     .wrap_err("Failed to create token")
 }
 
+// A token is banned when it was logged out, or issued before all sessions of the user were revoked
+#[tracing::instrument(name = "Check if token is banned", skip_all)]
+pub async fn is_token_banned(
+    banned_token_store: &BannedTokenStoreType,
+    token: &str,
+    email: &Email,
+    claims: &Claims,
+) -> Result<bool, BannedTokenStoreError> {
+    let banned_token_store = banned_token_store.read().await;
+
+    if banned_token_store.contains_token(token).await? {
+        return Ok(true);
+    }
+
+    banned_token_store
+        .is_user_token_revoked(email, token, claims.issued_at_micros())
+        .await
+}
+
+// Only active accounts can sign in or have their tokens accepted
+pub fn ensure_account_active(user: &User) -> Result<(), AuthAPIError> {
+    match user.account_state.status_at(Utc::now()) {
+        AccountStatus::Active => Ok(()),
+        AccountStatus::Pending => Err(AuthAPIError::AccountPending),
+        AccountStatus::Suspended => Err(AuthAPIError::AccountSuspended),
+        AccountStatus::Disabled => Err(AuthAPIError::AccountDisabled),
+    }
+}
+
+// Checks a password the user picked against the password policy and known breaches, then hashes it
+#[tracing::instrument(name = "Hash new password", skip_all)]
+pub async fn hash_new_password(state: &AppState, email: &Email, password: SecretString) -> Result<HashedPassword, AuthAPIError> {
+    let mut violations = state.password_policy.check(&password, email);
+
+    // Overly long passwords are not worth hashing to look up
+    if !violations
+        .iter()
+        .any(|violation| matches!(violation, PasswordViolation::TooLong(_)))
+    {
+        let breach_count = state
+            .breached_password_store
+            .read()
+            .await
+            .breach_count(&password)
+            .await
+            .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+        if breach_count > 0 {
+            violations.push(PasswordViolation::Breached);
+        }
+    }
+
+    if !violations.is_empty() {
+        return Err(AuthAPIError::WeakPassword(violations));
+    }
+
+    HashedPassword::parse(password)
+        .await
+        .map_err(|e| hashing_error(e, AuthAPIError::InvalidCredentials))
+}
+
+// A password that couldn't be hashed or verified because hashing is overloaded is neither right nor wrong
+pub fn hashing_error(e: Report, otherwise: AuthAPIError) -> AuthAPIError {
+    match HashingOverloaded::caused(&e) {
+        true => AuthAPIError::ServiceOverloaded,
+        false => otherwise,
+    }
+}
+
+// Only for existing accounts, a signup must not tell whether the email already has a history
+#[tracing::instrument(name = "Check password history", skip_all)]
+pub async fn ensure_password_not_reused(state: &AppState, email: &Email, password: &SecretString) -> Result<(), AuthAPIError> {
+    let history_size = state.password_policy.history_size;
+    if history_size == 0 {
+        return Ok(());
+    }
+
+    let used_passwords = state
+        .password_history_store
+        .read()
+        .await
+        .get_passwords(email, history_size)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    for used_password in used_passwords {
+        match used_password.verify_raw_password(password.expose_secret()).await {
+            Ok(()) => return Err(AuthAPIError::PasswordReused),
+            Err(e) if HashingOverloaded::caused(&e) => return Err(AuthAPIError::ServiceOverloaded),
+            Err(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+// Adds the password the user just set to their history, once it is saved
+#[tracing::instrument(name = "Record password in history", skip_all)]
+pub async fn record_password(state: &AppState, email: &Email, password: HashedPassword) -> Result<(), AuthAPIError> {
+    state
+        .password_history_store
+        .write()
+        .await
+        .add_password(email, password, state.password_policy.history_size)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))
+}
+
+// Signs the user out of every device, including a login waiting for its 2FA code
+pub async fn revoke_sessions(state: &AppState, email: &Email) -> Result<(), AuthAPIError> {
+    state
+        .banned_token_store
+        .write()
+        .await
+        .revoke_user_tokens(email, None)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    match state.two_fa_code_store.write().await.remove_code(email).await {
+        Ok(()) | Err(TwoFACodeStoreError::LoginAttemptIdNotFound) => {}
+        Err(e) => return Err(AuthAPIError::UnexpectedError(eyre!(e))),
+    }
+
+    state
+        .trusted_device_store
+        .write()
+        .await
+        .remove_devices(email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))
+}
+
+// Anyone could have signed up with an address they don't own and chosen its password. Once the owner proves the
+// address through an emailed link, that password, its history and whatever signed in with it are dropped.
+#[tracing::instrument(name = "Forget unproven password", skip_all)]
+pub async fn forget_unproven_password(state: &AppState, email: &Email) -> Result<(), AuthAPIError> {
+    state
+        .user_store
+        .write()
+        .await
+        .clear_password(email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    state
+        .password_history_store
+        .write()
+        .await
+        .remove_passwords(email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    revoke_sessions(state, email).await
+}
+
+// Replaces a hash made with outdated parameters once the password is known to be right.
+// The new hash is computed in the background, so that the login isn't slowed down.
+pub fn upgrade_password_hash(state: &AppState, email: &Email, password: &HashedPassword, raw_password: SecretString) {
+    let params = HashingParams::default();
+    if !password.needs_rehash(&params) {
+        return;
+    }
+
+    let user_store = state.user_store.clone();
+    let email = email.clone();
+    let old_password = password.clone();
+
+    tokio::spawn(
+        async move {
+            let result = match HashedPassword::parse_with_params(raw_password, params).await {
+                Ok(new_password) => user_store
+                    .write()
+                    .await
+                    .upgrade_password_hash(&email, &old_password, new_password)
+                    .await
+                    .map_err(|e| eyre!(e)),
+                Err(e) => Err(e),
+            };
+
+            if let Err(e) = result {
+                tracing::error!("Failed to upgrade password hash: {:?}", e);
+            }
+        }
+        .instrument(tracing::info_span!("Upgrade password hash")),
+    );
+}
+
+// Looks up the roles of the user and the permissions they grant, to be embedded into a new auth token.
+// Changes to the roles only take effect once the user logs in again.
+// Without an organization, the user isn't signed into any and has to pick one explicitly.
+// The roles held in the organization are added to the ones assigned to the user. The permissions aren't scoped
+// to the organization: the /admin user listing and audit log stay service-wide, so roles granting them are only
+// meant for organizations run by the operators of the service.
+#[tracing::instrument(name = "Get user grants", skip_all)]
+pub async fn get_user_grants(
+    state: &AppState,
+    email: &Email,
+    organization_id: Option<OrganizationId>,
+) -> Result<Grants, AuthAPIError> {
+    let membership = match organization_id {
+        Some(id) => match state.organization_store.read().await.get_membership(&id, email).await {
+            Ok(membership) => Some(membership),
+            Err(OrganizationStoreError::MemberNotFound) => return Err(AuthAPIError::OrganizationNotFound),
+            Err(e) => return Err(AuthAPIError::UnexpectedError(eyre!(e))),
+        },
+        None => None,
+    };
+
+    let role_store = state.role_store.read().await;
+
+    let roles = role_store
+        .list_roles()
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+    let mut assigned = role_store
+        .get_user_roles(email)
+        .await
+        .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+    let Some(membership) = membership else {
+        return Ok(Grants::new(&roles, &assigned));
+    };
+
+    assigned.extend(membership.roles);
+
+    Ok(Grants::new(&roles, &assigned).in_organization(membership.organization.id))
+}
+
+// Extracts the user from a valid, non-banned auth cookie
+#[derive(Clone)]
+pub struct AuthenticatedUser {
+    pub email: Email,
+    pub token: String,
+    pub grants: Grants,
+}
+
+impl FromRequestParts<AppState> for AuthenticatedUser {
+    type Rejection = AuthAPIError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let jar = CookieJar::from_headers(&parts.headers);
+
+        let token = jar.get(JWT_COOKIE_NAME).ok_or(AuthAPIError::MissingToken)?.value().to_owned();
+
+        let claims = validate_token(&token).await.map_err(|_| AuthAPIError::InvalidToken)?;
+
+        let email = Email::parse(SecretString::from(claims.sub.clone())).map_err(|_| AuthAPIError::InvalidToken)?;
+
+        let is_token_banned = is_token_banned(&state.banned_token_store, &token, &email, &claims)
+            .await
+            .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)))?;
+
+        if is_token_banned {
+            return Err(AuthAPIError::InvalidToken);
+        }
+
+        Ok(AuthenticatedUser {
+            email,
+            token,
+            grants: claims.grants,
+        })
+    }
+}
+
+// Middleware rejecting requests without a valid auth cookie. The user is kept in the request extensions
+// for the `RequirePermission` guards behind it.
+pub async fn authenticate(State(state): State<AppState>, request: Request, next: Next) -> Result<Response, AuthAPIError> {
+    let (mut parts, body) = request.into_parts();
+
+    let user = AuthenticatedUser::from_request_parts(&mut parts, &state).await?;
+    parts.extensions.insert(user);
+
+    Ok(next.run(Request::from_parts(parts, body)).await)
+}
+
+// Guard layer letting through only users whose auth token grants the permission, such as
+// `RequirePermission("users:write")`. It has to run behind the `authenticate` middleware.
+#[derive(Debug, Clone, Copy)]
+pub struct RequirePermission(pub &'static str);
+
+impl<S> Layer<S> for RequirePermission {
+    type Service = RequirePermissionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequirePermissionService {
+            inner,
+            permission: self.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequirePermissionService<S> {
+    inner: S,
+    permission: &'static str,
+}
+
+impl<S> Service<Request> for RequirePermissionService<S>
+where
+    S: Service<Request, Response = Response>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        // Without an authenticated user the request is denied, rather than let through unchecked
+        let is_granted = request
+            .extensions()
+            .get::<AuthenticatedUser>()
+            .is_some_and(|user| user.grants.has_permission(self.permission));
+
+        if !is_granted {
+            return Box::pin(async { Ok(AuthAPIError::Forbidden.into_response()) });
+        }
+
+        Box::pin(self.inner.call(request))
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
     pub exp: usize,
+    pub iat: usize,
+    // `iat` with a finer precision, compared with session revocations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat_micros: Option<i64>,
+    // Tokens issued before roles existed grant nothing
+    #[serde(flatten)]
+    pub grants: Grants,
+}
+
+impl Claims {
+    // Tokens issued without `iat_micros` are taken as issued at the end of their second, so that a revocation in the
+    // same second still catches them
+    pub fn issued_at_micros(&self) -> i64 {
+        self.iat_micros.unwrap_or_else(|| {
+            i64::try_from(self.iat)
+                .ok()
+                .and_then(|iat| iat.checked_add(1))
+                .and_then(|iat| iat.checked_mul(1_000_000))
+                .and_then(|iat| iat.checked_sub(1))
+                .unwrap_or(i64::MAX)
+        })
+    }
+}
+
+// Claims of tokens that are only valid for a single audience, such as one-time links
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScopedClaims {
+    pub sub: String,
+    pub exp: usize,
+    pub jti: String,
+    pub aud: String,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::data_stores::TokenRevocation;
 
     #[tokio::test]
     async fn test_generate_auth_cookie() {
         let email = Email::parse("test@example.com".into()).unwrap();
-        let jwt = generate_auth_cookie(&email).unwrap();
+        let jwt = generate_auth_cookie(&email, Grants::default()).unwrap();
         assert_eq!(jwt.name(), JWT_COOKIE_NAME);
         assert_eq!(jwt.value().split('.').count(), 3);
         assert_eq!(jwt.path(), Some("/"));
@@ -106,16 +584,17 @@ mod tests {
     #[tokio::test]
     async fn test_generate_auth_token() {
         let email = Email::parse("test@example.com".into()).unwrap();
-        let jwt = generate_auth_token(&email).unwrap();
+        let jwt = generate_auth_token(&email, Grants::default()).unwrap();
         assert_eq!(jwt.split('.').count(), 3);
     }
 
     #[tokio::test]
     async fn test_validate_token_with_valid_token() {
         let email = Email::parse("test@example.com".into()).unwrap();
-        let jwt = generate_auth_token(&email).unwrap();
+        let jwt = generate_auth_token(&email, Grants::default()).unwrap();
         let result = validate_token(&jwt).await.unwrap();
         assert_eq!(result.sub, "test@example.com");
+        assert_eq!(result.grants, Grants::default());
 
         let exp = Utc::now()
             .checked_add_signed(chrono::Duration::try_minutes(9).expect("valid duration"))
@@ -125,6 +604,85 @@ mod tests {
         assert!(result.exp > exp as usize);
     }
 
+    #[tokio::test]
+    async fn test_validate_token_keeps_grants() {
+        let email = Email::parse("test@example.com".into()).unwrap();
+        let grants = Grants {
+            roles: vec!["admin".to_owned()],
+            permissions: vec!["users:write".to_owned()],
+            organization_id: Some(OrganizationId::new()),
+        };
+        let jwt = generate_auth_token(&email, grants.clone()).unwrap();
+
+        assert_eq!(validate_token(&jwt).await.unwrap().grants, grants);
+    }
+
+    #[tokio::test]
+    async fn test_token_issue_time_has_sub_second_precision() {
+        let email = Email::parse("test@example.com".into()).unwrap();
+        let revocation = TokenRevocation::new(None);
+
+        let jwt = generate_auth_token(&email, Grants::default()).unwrap();
+        let claims = validate_token(&jwt).await.unwrap();
+
+        assert!(claims.iat_micros.is_some());
+        assert!(!revocation.revokes(&jwt, claims.issued_at_micros()));
+    }
+
+    #[test]
+    fn test_token_without_sub_second_issue_time_ends_its_second() {
+        let claims = Claims {
+            sub: "test@example.com".to_owned(),
+            exp: 2,
+            iat: 1,
+            iat_micros: None,
+            grants: Grants::default(),
+        };
+
+        assert_eq!(claims.issued_at_micros(), 1_999_999);
+    }
+
+    #[tokio::test]
+    async fn test_validate_one_time_token() {
+        let email = Email::parse("test@example.com".into()).unwrap();
+        let (token, jti) = generate_one_time_token(&email, TokenPurpose::MagicLink).unwrap();
+
+        let claims = validate_one_time_token(&token, TokenPurpose::MagicLink).unwrap();
+        assert_eq!(claims.sub, "test@example.com");
+        assert_eq!(claims.jti, jti);
+    }
+
+    #[tokio::test]
+    async fn test_auth_token_is_not_a_valid_one_time_token() {
+        let email = Email::parse("test@example.com".into()).unwrap();
+        let jwt = generate_auth_token(&email, Grants::default()).unwrap();
+
+        assert!(validate_one_time_token(&jwt, TokenPurpose::MagicLink).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_one_time_token_is_not_a_valid_auth_token() {
+        let email = Email::parse("test@example.com".into()).unwrap();
+        let (token, _) = generate_one_time_token(&email, TokenPurpose::MagicLink).unwrap();
+
+        assert!(validate_token(&token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_validate_trusted_device_token() {
+        let email = Email::parse("test@example.com".into()).unwrap();
+        let cookie = generate_trusted_device_cookie(&email, "device-id").unwrap();
+        assert_eq!(cookie.name(), TRUSTED_DEVICE_COOKIE_NAME);
+        assert_eq!(cookie.http_only(), Some(true));
+
+        let claims = validate_trusted_device_token(cookie.value()).unwrap();
+        assert_eq!(claims.sub, "test@example.com");
+        assert_eq!(claims.jti, "device-id");
+
+        assert!(validate_token(cookie.value()).await.is_err());
+        assert!(validate_one_time_token(cookie.value(), TokenPurpose::MagicLink).is_err());
+    }
+
     #[tokio::test]
     async fn test_validate_token_with_invalid_token() {
         let token = "invalid_token".to_owned();
@@ -1,22 +1,167 @@
+use crate::domain::password_strength;
+use crate::domain::rate_limit::RateLimit;
 use dotenv::dotenv;
 use lazy_static::lazy_static;
 use secrecy::SecretString;
 
 pub const DEFAULT_REDIS_HOSTNAME: &str = "127.0.0.1";
+pub const DEFAULT_AUTH_SERVICE_URL: &str = "http://localhost:3000";
+pub const DEFAULT_TRUSTED_DEVICE_TTL_DAYS: i64 = 30;
+pub const DEFAULT_LOGIN_LOCKOUT_THRESHOLD: u32 = 5;
+pub const DEFAULT_LOGIN_LOCKOUT_BASE_SECONDS: i64 = 60;
+pub const DEFAULT_LOGIN_LOCKOUT_MAX_SECONDS: i64 = 24 * 60 * 60;
+// Failed logins older than this no longer count towards a lockout
+pub const LOGIN_FAILURE_WINDOW_SECONDS: i64 = 24 * 60 * 60;
+// Configured minimum lengths below this are ignored
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+pub const DEFAULT_PASSWORD_MAX_LENGTH: usize = 128;
+pub const DEFAULT_PASSWORD_MIN_STRENGTH: u8 = 3;
+pub const DEFAULT_PASSWORD_HISTORY_SIZE: usize = 5;
+pub const DEFAULT_ARGON2_MEMORY_COST_KIB: u32 = 15000;
+pub const DEFAULT_ARGON2_TIME_COST: u32 = 2;
+pub const DEFAULT_ARGON2_PARALLELISM: u32 = 1;
+pub const DEFAULT_PASSWORD_PEPPER_ID: &str = "1";
+pub const DEFAULT_HASHING_MAX_QUEUE: usize = 64;
+// As `requests/seconds`, the email limits are tighter since an attacker can spread requests over many IPs
+pub const DEFAULT_RATE_LIMIT_LOGIN_PER_IP: &str = "30/60";
+pub const DEFAULT_RATE_LIMIT_LOGIN_PER_EMAIL: &str = "10/60";
+pub const DEFAULT_RATE_LIMIT_SIGNUP_PER_IP: &str = "10/3600";
+pub const DEFAULT_RATE_LIMIT_SIGNUP_PER_EMAIL: &str = "5/3600";
+pub const DEFAULT_RATE_LIMIT_VERIFY_2FA_PER_IP: &str = "30/60";
+pub const DEFAULT_RATE_LIMIT_VERIFY_2FA_PER_EMAIL: &str = "5/60";
+pub const DEFAULT_RATE_LIMIT_MAGIC_LINK_PER_IP: &str = "20/3600";
+pub const DEFAULT_RATE_LIMIT_MAGIC_LINK_PER_EMAIL: &str = "5/3600";
+pub const DEFAULT_RATE_LIMIT_FORGOT_PASSWORD_PER_IP: &str = "20/3600";
+pub const DEFAULT_RATE_LIMIT_FORGOT_PASSWORD_PER_EMAIL: &str = "5/3600";
+pub const DEFAULT_RATE_LIMIT_RESEND_VERIFICATION_PER_IP: &str = "20/3600";
+pub const DEFAULT_RATE_LIMIT_RESEND_VERIFICATION_PER_EMAIL: &str = "5/3600";
 
 lazy_static! {
     pub static ref JWT_SECRET: SecretString = get_jwt_secret_token();
+    // Checked when the service starts, as 2FA codes can't be hashed without it
+    pub static ref TWO_FA_CODE_SECRET: Option<SecretString> = get_two_fa_code_secret();
     pub static ref REDIS_HOST_NAME: String = set_redis_host();
     pub static ref DATABASE_URL: SecretString = set_db_url();
     pub static ref POSTMARK_AUTH_TOKEN: SecretString = set_postmark_auth_token();
+    pub static ref AUTH_SERVICE_URL: String = set_auth_service_url();
+    pub static ref TRUSTED_DEVICE_TTL_SECONDS: i64 = set_trusted_device_ttl_seconds();
+    pub static ref LOGIN_LOCKOUT_THRESHOLD: u32 = set_login_lockout_threshold();
+    pub static ref LOGIN_LOCKOUT_BASE_SECONDS: i64 = set_login_lockout_base_seconds();
+    pub static ref LOGIN_LOCKOUT_MAX_SECONDS: i64 = set_login_lockout_max_seconds();
+    pub static ref INVITE_ONLY_SIGNUP: bool = set_invite_only_signup();
+    pub static ref UNIFORM_SIGNUP_RESPONSE: bool = set_uniform_signup_response();
+    pub static ref PASSWORD_MIN_LENGTH: usize = set_password_min_length();
+    pub static ref PASSWORD_MAX_LENGTH: usize = set_password_max_length();
+    pub static ref PASSWORD_MIN_STRENGTH: u8 = set_password_min_strength();
+    pub static ref PASSWORD_HISTORY_SIZE: usize = set_password_history_size();
+    pub static ref BREACHED_PASSWORDS_FILE: Option<String> = set_breached_passwords_file();
+    pub static ref ARGON2_MEMORY_COST_KIB: u32 = set_argon2_memory_cost_kib();
+    pub static ref ARGON2_TIME_COST: u32 = set_argon2_time_cost();
+    pub static ref ARGON2_PARALLELISM: u32 = set_argon2_parallelism();
+    pub static ref PASSWORD_PEPPER: Option<SecretString> = set_password_pepper();
+    pub static ref PASSWORD_PEPPER_ID: String = set_password_pepper_id();
+    pub static ref PASSWORD_RETIRED_PEPPERS: Vec<(String, SecretString)> = set_password_retired_peppers();
+    pub static ref HASHING_MAX_CONCURRENCY: usize = set_hashing_max_concurrency();
+    pub static ref HASHING_MAX_QUEUE: usize = set_hashing_max_queue();
+    pub static ref RATE_LIMIT_LOGIN_PER_IP: Option<RateLimit> =
+        set_rate_limit(env::RATE_LIMIT_LOGIN_PER_IP_ENV_VAR, DEFAULT_RATE_LIMIT_LOGIN_PER_IP);
+    pub static ref RATE_LIMIT_LOGIN_PER_EMAIL: Option<RateLimit> =
+        set_rate_limit(env::RATE_LIMIT_LOGIN_PER_EMAIL_ENV_VAR, DEFAULT_RATE_LIMIT_LOGIN_PER_EMAIL);
+    pub static ref RATE_LIMIT_SIGNUP_PER_IP: Option<RateLimit> =
+        set_rate_limit(env::RATE_LIMIT_SIGNUP_PER_IP_ENV_VAR, DEFAULT_RATE_LIMIT_SIGNUP_PER_IP);
+    pub static ref RATE_LIMIT_SIGNUP_PER_EMAIL: Option<RateLimit> =
+        set_rate_limit(env::RATE_LIMIT_SIGNUP_PER_EMAIL_ENV_VAR, DEFAULT_RATE_LIMIT_SIGNUP_PER_EMAIL);
+    pub static ref RATE_LIMIT_VERIFY_2FA_PER_IP: Option<RateLimit> = set_rate_limit(
+        env::RATE_LIMIT_VERIFY_2FA_PER_IP_ENV_VAR,
+        DEFAULT_RATE_LIMIT_VERIFY_2FA_PER_IP
+    );
+    pub static ref RATE_LIMIT_VERIFY_2FA_PER_EMAIL: Option<RateLimit> = set_rate_limit(
+        env::RATE_LIMIT_VERIFY_2FA_PER_EMAIL_ENV_VAR,
+        DEFAULT_RATE_LIMIT_VERIFY_2FA_PER_EMAIL
+    );
+    pub static ref RATE_LIMIT_MAGIC_LINK_PER_IP: Option<RateLimit> = set_rate_limit(
+        env::RATE_LIMIT_MAGIC_LINK_PER_IP_ENV_VAR,
+        DEFAULT_RATE_LIMIT_MAGIC_LINK_PER_IP
+    );
+    pub static ref RATE_LIMIT_MAGIC_LINK_PER_EMAIL: Option<RateLimit> = set_rate_limit(
+        env::RATE_LIMIT_MAGIC_LINK_PER_EMAIL_ENV_VAR,
+        DEFAULT_RATE_LIMIT_MAGIC_LINK_PER_EMAIL
+    );
+    pub static ref RATE_LIMIT_FORGOT_PASSWORD_PER_IP: Option<RateLimit> = set_rate_limit(
+        env::RATE_LIMIT_FORGOT_PASSWORD_PER_IP_ENV_VAR,
+        DEFAULT_RATE_LIMIT_FORGOT_PASSWORD_PER_IP
+    );
+    pub static ref RATE_LIMIT_FORGOT_PASSWORD_PER_EMAIL: Option<RateLimit> = set_rate_limit(
+        env::RATE_LIMIT_FORGOT_PASSWORD_PER_EMAIL_ENV_VAR,
+        DEFAULT_RATE_LIMIT_FORGOT_PASSWORD_PER_EMAIL
+    );
+    pub static ref RATE_LIMIT_RESEND_VERIFICATION_PER_IP: Option<RateLimit> = set_rate_limit(
+        env::RATE_LIMIT_RESEND_VERIFICATION_PER_IP_ENV_VAR,
+        DEFAULT_RATE_LIMIT_RESEND_VERIFICATION_PER_IP
+    );
+    pub static ref RATE_LIMIT_RESEND_VERIFICATION_PER_EMAIL: Option<RateLimit> = set_rate_limit(
+        env::RATE_LIMIT_RESEND_VERIFICATION_PER_EMAIL_ENV_VAR,
+        DEFAULT_RATE_LIMIT_RESEND_VERIFICATION_PER_EMAIL
+    );
 }
 
 pub mod env {
     pub const JWT_SECRET_NAME: &str = "JWT_SECRET";
+    pub const TWO_FA_CODE_SECRET_NAME: &str = "TWO_FA_CODE_SECRET";
     pub const DATABASE_URL_NAME: &str = "DATABASE_URL";
     pub const JWT_COOKIE_NAME: &str = "jwt";
+    pub const TRUSTED_DEVICE_COOKIE_NAME: &str = "trusted_device";
     pub const REDIS_HOST_NAME_ENV_VAR: &str = "REDIS_HOST_NAME";
     pub const POSTMARK_AUTH_TOKEN_ENV_VAR: &str = "POSTMARK_AUTH_TOKEN";
+    // Public base URL of this service, used to build the links sent by email
+    pub const AUTH_SERVICE_URL_ENV_VAR: &str = "AUTH_SERVICE_URL";
+    // How many days a device stays trusted after the user chose to skip 2FA on it
+    pub const TRUSTED_DEVICE_TTL_DAYS_ENV_VAR: &str = "TRUSTED_DEVICE_TTL_DAYS";
+    // Failed logins in a row after which an account gets locked
+    pub const LOGIN_LOCKOUT_THRESHOLD_ENV_VAR: &str = "LOGIN_LOCKOUT_THRESHOLD";
+    // Length of the first lockout, which doubles with every further failure
+    pub const LOGIN_LOCKOUT_BASE_SECONDS_ENV_VAR: &str = "LOGIN_LOCKOUT_BASE_SECONDS";
+    pub const LOGIN_LOCKOUT_MAX_SECONDS_ENV_VAR: &str = "LOGIN_LOCKOUT_MAX_SECONDS";
+    // Set to `true` to only let people sign up with an invitation
+    pub const INVITE_ONLY_SIGNUP_ENV_VAR: &str = "INVITE_ONLY_SIGNUP";
+    // Set to `true` to answer signups with a taken email like any other, emailing the owner of the account instead
+    pub const UNIFORM_SIGNUP_RESPONSE_ENV_VAR: &str = "UNIFORM_SIGNUP_RESPONSE";
+    // Lengths new passwords must have, in characters
+    pub const PASSWORD_MIN_LENGTH_ENV_VAR: &str = "PASSWORD_MIN_LENGTH";
+    pub const PASSWORD_MAX_LENGTH_ENV_VAR: &str = "PASSWORD_MAX_LENGTH";
+    // Strength score from 0 to 4 new passwords must reach, 0 turns the check off
+    pub const PASSWORD_MIN_STRENGTH_ENV_VAR: &str = "PASSWORD_MIN_STRENGTH";
+    // How many of their last passwords users cannot pick again, 0 turns the check off
+    pub const PASSWORD_HISTORY_SIZE_ENV_VAR: &str = "PASSWORD_HISTORY_SIZE";
+    // Path of a Have I Been Pwned SHA-1 dump ordered by hash, new passwords found in it are rejected
+    pub const BREACHED_PASSWORDS_FILE_ENV_VAR: &str = "BREACHED_PASSWORDS_FILE";
+    // Argon2id cost of new password hashes, existing hashes are upgraded when their user logs in
+    pub const ARGON2_MEMORY_COST_KIB_ENV_VAR: &str = "ARGON2_MEMORY_COST_KIB";
+    pub const ARGON2_TIME_COST_ENV_VAR: &str = "ARGON2_TIME_COST";
+    pub const ARGON2_PARALLELISM_ENV_VAR: &str = "ARGON2_PARALLELISM";
+    // Secret mixed into new password hashes, hashes are not peppered without it
+    pub const PASSWORD_PEPPER_ENV_VAR: &str = "PASSWORD_PEPPER";
+    // Up to 8 characters naming the pepper, to be changed along with it
+    pub const PASSWORD_PEPPER_ID_ENV_VAR: &str = "PASSWORD_PEPPER_ID";
+    // Peppers replaced by a rotation, as comma-separated `id:pepper` pairs, to verify hashes not upgraded yet
+    pub const PASSWORD_RETIRED_PEPPERS_ENV_VAR: &str = "PASSWORD_RETIRED_PEPPERS";
+    // Passwords hashed or verified at the same time, defaults to the number of CPUs
+    pub const HASHING_MAX_CONCURRENCY_ENV_VAR: &str = "HASHING_MAX_CONCURRENCY";
+    // Passwords waiting for their turn, beyond which requests fail with a 503
+    pub const HASHING_MAX_QUEUE_ENV_VAR: &str = "HASHING_MAX_QUEUE";
+    // Requests let through per client IP and per email, as `requests/seconds` over a sliding window, or `off`
+    pub const RATE_LIMIT_LOGIN_PER_IP_ENV_VAR: &str = "RATE_LIMIT_LOGIN_PER_IP";
+    pub const RATE_LIMIT_LOGIN_PER_EMAIL_ENV_VAR: &str = "RATE_LIMIT_LOGIN_PER_EMAIL";
+    pub const RATE_LIMIT_SIGNUP_PER_IP_ENV_VAR: &str = "RATE_LIMIT_SIGNUP_PER_IP";
+    pub const RATE_LIMIT_SIGNUP_PER_EMAIL_ENV_VAR: &str = "RATE_LIMIT_SIGNUP_PER_EMAIL";
+    pub const RATE_LIMIT_VERIFY_2FA_PER_IP_ENV_VAR: &str = "RATE_LIMIT_VERIFY_2FA_PER_IP";
+    pub const RATE_LIMIT_VERIFY_2FA_PER_EMAIL_ENV_VAR: &str = "RATE_LIMIT_VERIFY_2FA_PER_EMAIL";
+    pub const RATE_LIMIT_MAGIC_LINK_PER_IP_ENV_VAR: &str = "RATE_LIMIT_MAGIC_LINK_PER_IP";
+    pub const RATE_LIMIT_MAGIC_LINK_PER_EMAIL_ENV_VAR: &str = "RATE_LIMIT_MAGIC_LINK_PER_EMAIL";
+    pub const RATE_LIMIT_FORGOT_PASSWORD_PER_IP_ENV_VAR: &str = "RATE_LIMIT_FORGOT_PASSWORD_PER_IP";
+    pub const RATE_LIMIT_FORGOT_PASSWORD_PER_EMAIL_ENV_VAR: &str = "RATE_LIMIT_FORGOT_PASSWORD_PER_EMAIL";
+    pub const RATE_LIMIT_RESEND_VERIFICATION_PER_IP_ENV_VAR: &str = "RATE_LIMIT_RESEND_VERIFICATION_PER_IP";
+    pub const RATE_LIMIT_RESEND_VERIFICATION_PER_EMAIL_ENV_VAR: &str = "RATE_LIMIT_RESEND_VERIFICATION_PER_EMAIL";
 }
 
 pub mod prod {
@@ -45,6 +190,167 @@ fn set_redis_host() -> String {
     dotenv().ok();
     std::env::var(env::REDIS_HOST_NAME_ENV_VAR).unwrap_or(DEFAULT_REDIS_HOSTNAME.to_owned())
 }
+fn set_auth_service_url() -> String {
+    dotenv().ok();
+    std::env::var(env::AUTH_SERVICE_URL_ENV_VAR).unwrap_or(DEFAULT_AUTH_SERVICE_URL.to_owned())
+}
+fn set_trusted_device_ttl_seconds() -> i64 {
+    dotenv().ok();
+    let days = std::env::var(env::TRUSTED_DEVICE_TTL_DAYS_ENV_VAR)
+        .ok()
+        .and_then(|days| days.parse::<i64>().ok())
+        .filter(|days| *days > 0)
+        .unwrap_or(DEFAULT_TRUSTED_DEVICE_TTL_DAYS);
+
+    days.saturating_mul(24 * 60 * 60)
+}
+fn set_login_lockout_threshold() -> u32 {
+    dotenv().ok();
+    std::env::var(env::LOGIN_LOCKOUT_THRESHOLD_ENV_VAR)
+        .ok()
+        .and_then(|threshold| threshold.parse::<u32>().ok())
+        .filter(|threshold| *threshold > 0)
+        .unwrap_or(DEFAULT_LOGIN_LOCKOUT_THRESHOLD)
+}
+fn set_login_lockout_base_seconds() -> i64 {
+    dotenv().ok();
+    std::env::var(env::LOGIN_LOCKOUT_BASE_SECONDS_ENV_VAR)
+        .ok()
+        .and_then(|seconds| seconds.parse::<i64>().ok())
+        .filter(|seconds| *seconds > 0)
+        .unwrap_or(DEFAULT_LOGIN_LOCKOUT_BASE_SECONDS)
+}
+fn set_login_lockout_max_seconds() -> i64 {
+    dotenv().ok();
+    std::env::var(env::LOGIN_LOCKOUT_MAX_SECONDS_ENV_VAR)
+        .ok()
+        .and_then(|seconds| seconds.parse::<i64>().ok())
+        .filter(|seconds| *seconds > 0)
+        .unwrap_or(DEFAULT_LOGIN_LOCKOUT_MAX_SECONDS)
+}
+fn set_invite_only_signup() -> bool {
+    dotenv().ok();
+    std::env::var(env::INVITE_ONLY_SIGNUP_ENV_VAR).is_ok_and(|value| value.eq_ignore_ascii_case("true"))
+}
+fn set_uniform_signup_response() -> bool {
+    dotenv().ok();
+    std::env::var(env::UNIFORM_SIGNUP_RESPONSE_ENV_VAR).is_ok_and(|value| value.eq_ignore_ascii_case("true"))
+}
+fn set_password_min_length() -> usize {
+    dotenv().ok();
+    std::env::var(env::PASSWORD_MIN_LENGTH_ENV_VAR)
+        .ok()
+        .and_then(|length| length.parse::<usize>().ok())
+        .unwrap_or(MIN_PASSWORD_LENGTH)
+        .max(MIN_PASSWORD_LENGTH)
+}
+fn set_password_max_length() -> usize {
+    dotenv().ok();
+    std::env::var(env::PASSWORD_MAX_LENGTH_ENV_VAR)
+        .ok()
+        .and_then(|length| length.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_PASSWORD_MAX_LENGTH)
+        .max(*PASSWORD_MIN_LENGTH)
+}
+fn set_password_min_strength() -> u8 {
+    dotenv().ok();
+    std::env::var(env::PASSWORD_MIN_STRENGTH_ENV_VAR)
+        .ok()
+        .and_then(|strength| strength.parse::<u8>().ok())
+        .filter(|strength| *strength <= password_strength::MAX_SCORE)
+        .unwrap_or(DEFAULT_PASSWORD_MIN_STRENGTH)
+}
+fn set_password_history_size() -> usize {
+    dotenv().ok();
+    std::env::var(env::PASSWORD_HISTORY_SIZE_ENV_VAR)
+        .ok()
+        .and_then(|size| size.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_PASSWORD_HISTORY_SIZE)
+}
+fn set_argon2_memory_cost_kib() -> u32 {
+    // Argon2 needs at least 8 KiB per lane
+    let min_memory_cost_kib = ARGON2_PARALLELISM.saturating_mul(8);
+
+    dotenv().ok();
+    std::env::var(env::ARGON2_MEMORY_COST_KIB_ENV_VAR)
+        .ok()
+        .and_then(|memory| memory.parse::<u32>().ok())
+        .unwrap_or(DEFAULT_ARGON2_MEMORY_COST_KIB)
+        .max(min_memory_cost_kib)
+}
+fn set_argon2_time_cost() -> u32 {
+    dotenv().ok();
+    std::env::var(env::ARGON2_TIME_COST_ENV_VAR)
+        .ok()
+        .and_then(|iterations| iterations.parse::<u32>().ok())
+        .filter(|iterations| *iterations > 0)
+        .unwrap_or(DEFAULT_ARGON2_TIME_COST)
+}
+fn set_argon2_parallelism() -> u32 {
+    dotenv().ok();
+    std::env::var(env::ARGON2_PARALLELISM_ENV_VAR)
+        .ok()
+        .and_then(|lanes| lanes.parse::<u32>().ok())
+        .filter(|lanes| *lanes > 0 && *lanes <= argon2::Params::MAX_P_COST)
+        .unwrap_or(DEFAULT_ARGON2_PARALLELISM)
+}
+fn set_password_pepper() -> Option<SecretString> {
+    dotenv().ok();
+    std::env::var(env::PASSWORD_PEPPER_ENV_VAR)
+        .ok()
+        .filter(|pepper| !pepper.is_empty())
+        .map(SecretString::from)
+}
+fn set_password_pepper_id() -> String {
+    dotenv().ok();
+    std::env::var(env::PASSWORD_PEPPER_ID_ENV_VAR)
+        .ok()
+        .filter(|id| is_valid_pepper_id(id))
+        .unwrap_or_else(|| DEFAULT_PASSWORD_PEPPER_ID.to_owned())
+}
+fn set_password_retired_peppers() -> Vec<(String, SecretString)> {
+    dotenv().ok();
+    std::env::var(env::PASSWORD_RETIRED_PEPPERS_ENV_VAR)
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|pair| pair.trim().split_once(':'))
+        .filter(|(id, pepper)| is_valid_pepper_id(id) && !pepper.is_empty())
+        .map(|(id, pepper)| (id.to_owned(), SecretString::from(pepper)))
+        .collect()
+}
+// Stored in the hashes as the Argon2 key id, which holds at most 8 bytes
+fn is_valid_pepper_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= argon2::Params::MAX_KEYID_LEN && id.chars().all(|c| c.is_ascii_alphanumeric())
+}
+fn set_hashing_max_concurrency() -> usize {
+    dotenv().ok();
+    std::env::var(env::HASHING_MAX_CONCURRENCY_ENV_VAR)
+        .ok()
+        .and_then(|jobs| jobs.parse::<usize>().ok())
+        .filter(|jobs| *jobs > 0 && *jobs <= tokio::sync::Semaphore::MAX_PERMITS)
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, usize::from))
+}
+fn set_hashing_max_queue() -> usize {
+    dotenv().ok();
+    std::env::var(env::HASHING_MAX_QUEUE_ENV_VAR)
+        .ok()
+        .and_then(|jobs| jobs.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_HASHING_MAX_QUEUE)
+}
+// Invalid values fall back to the default rather than turning the limit off
+fn set_rate_limit(env_var: &str, default: &str) -> Option<RateLimit> {
+    dotenv().ok();
+    std::env::var(env_var)
+        .ok()
+        .and_then(|value| RateLimit::parse(&value).ok())
+        .unwrap_or_else(|| RateLimit::parse(default).ok().flatten())
+}
+fn set_breached_passwords_file() -> Option<String> {
+    dotenv().ok();
+    std::env::var(env::BREACHED_PASSWORDS_FILE_ENV_VAR)
+        .ok()
+        .filter(|path| !path.is_empty())
+}
 fn set_db_url() -> SecretString {
     dotenv().ok();
     SecretString::from(std::env::var(env::DATABASE_URL_NAME).expect("DATABASE_URL must bet set"))
@@ -64,3 +370,11 @@ fn get_jwt_secret_token() -> SecretString {
 
     jwt_secret.into()
 }
+
+fn get_two_fa_code_secret() -> Option<SecretString> {
+    dotenv().ok();
+    std::env::var(env::TWO_FA_CODE_SECRET_NAME)
+        .ok()
+        .filter(|secret| !secret.is_empty())
+        .map(SecretString::from)
+}
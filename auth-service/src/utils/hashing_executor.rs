@@ -0,0 +1,229 @@
+use crate::utils::constants::{HASHING_MAX_CONCURRENCY, HASHING_MAX_QUEUE};
+use color_eyre::eyre::{Report, Result};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+lazy_static! {
+    // Every password hash and verification goes through it
+    pub static ref HASHING_EXECUTOR: HashingExecutor = HashingExecutor::new(*HASHING_MAX_CONCURRENCY, *HASHING_MAX_QUEUE);
+}
+
+#[derive(Debug, Error)]
+#[error("Password hashing is overloaded")]
+pub struct HashingOverloaded;
+
+impl HashingOverloaded {
+    // Whether a password couldn't be hashed or verified because of an overload, rather than being wrong
+    pub fn caused(e: &Report) -> bool {
+        e.chain().any(|cause| cause.is::<HashingOverloaded>())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashingMetrics {
+    pub max_concurrency: usize,
+    pub max_queued: usize,
+    pub running: usize,
+    pub queued: usize,
+    // Since the service started
+    pub completed: u64,
+    pub rejected: u64,
+}
+
+// Runs the slow password hashes on the blocking pool, a few at a time. Jobs wait for their turn in a bounded queue and
+// are rejected right away once it is full, so that a burst of logins can neither take every blocking thread nor pile up
+// requests that would time out anyway.
+pub struct HashingExecutor {
+    permits: Arc<Semaphore>,
+    max_concurrency: usize,
+    max_queued: usize,
+    queued: AtomicUsize,
+    completed: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl HashingExecutor {
+    pub fn new(max_concurrency: usize, max_queued: usize) -> Self {
+        HashingExecutor {
+            permits: Arc::new(Semaphore::new(max_concurrency)),
+            max_concurrency,
+            max_queued,
+            queued: AtomicUsize::new(0),
+            completed: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    // Fails with `HashingOverloaded` without running the job when the queue is full
+    pub async fn run<T, F>(&self, job: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let permit = match self.permits.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => self.wait_for_permit().await?,
+        };
+
+        // The permit goes with the job, so that it is held until the hash is done even if the request is dropped
+        let result = tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            job()
+        })
+        .await;
+
+        self.completed.fetch_add(1, Ordering::Relaxed);
+
+        result?
+    }
+
+    async fn wait_for_permit(&self) -> Result<OwnedSemaphorePermit> {
+        let reserved = self.queued.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |queued| {
+            (queued < self.max_queued).then(|| queued.saturating_add(1))
+        });
+
+        if reserved.is_err() {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(metrics = ?self.metrics(), "Password hashing queue is full");
+
+            return Err(Report::new(HashingOverloaded));
+        }
+
+        let _slot = QueueSlot(&self.queued);
+
+        Ok(self.permits.clone().acquire_owned().await?)
+    }
+
+    pub fn metrics(&self) -> HashingMetrics {
+        HashingMetrics {
+            max_concurrency: self.max_concurrency,
+            max_queued: self.max_queued,
+            running: self.max_concurrency.saturating_sub(self.permits.available_permits()),
+            queued: self.queued.load(Ordering::SeqCst),
+            completed: self.completed.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// Frees the place in the queue once the job gets a permit, or when the request waiting for it is dropped
+struct QueueSlot<'a>(&'a AtomicUsize);
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    // Runs a job that blocks until it is told to finish
+    fn blocked_job(executor: &Arc<HashingExecutor>) -> (tokio::task::JoinHandle<Result<()>>, mpsc::Sender<()>) {
+        let (sender, receiver) = mpsc::channel();
+        let executor = executor.clone();
+
+        let handle = tokio::spawn(async move {
+            executor
+                .run(move || {
+                    receiver.recv()?;
+                    Ok(())
+                })
+                .await
+        });
+
+        (handle, sender)
+    }
+
+    async fn wait_until(executor: &HashingExecutor, condition: impl Fn(HashingMetrics) -> bool) {
+        for _ in 0..100 {
+            if condition(executor.metrics()) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        panic!("Executor never reached the expected state: {:?}", executor.metrics());
+    }
+
+    #[tokio::test]
+    async fn runs_jobs() {
+        let executor = HashingExecutor::new(2, 0);
+
+        assert_eq!(executor.run(|| Ok(42)).await.unwrap(), 42);
+        assert!(executor
+            .run(|| Err::<(), _>(color_eyre::eyre::eyre!("Invalid password")))
+            .await
+            .is_err());
+
+        let metrics = executor.metrics();
+        assert_eq!(metrics.completed, 2);
+        assert_eq!(metrics.running, 0);
+        assert_eq!(metrics.rejected, 0);
+    }
+
+    #[tokio::test]
+    async fn queues_jobs_beyond_concurrency() {
+        let executor = Arc::new(HashingExecutor::new(1, 1));
+
+        let (running, finish_running) = blocked_job(&executor);
+        wait_until(&executor, |metrics| metrics.running == 1).await;
+
+        let (queued, finish_queued) = blocked_job(&executor);
+        wait_until(&executor, |metrics| metrics.queued == 1).await;
+
+        finish_running.send(()).unwrap();
+        finish_queued.send(()).unwrap();
+        running.await.unwrap().unwrap();
+        queued.await.unwrap().unwrap();
+
+        let metrics = executor.metrics();
+        assert_eq!((metrics.running, metrics.queued, metrics.completed), (0, 0, 2));
+    }
+
+    #[tokio::test]
+    async fn rejects_jobs_when_queue_is_full() {
+        let executor = Arc::new(HashingExecutor::new(1, 1));
+
+        let (running, finish_running) = blocked_job(&executor);
+        wait_until(&executor, |metrics| metrics.running == 1).await;
+        let (queued, finish_queued) = blocked_job(&executor);
+        wait_until(&executor, |metrics| metrics.queued == 1).await;
+
+        let e = executor.run(|| Ok(())).await.unwrap_err();
+        assert!(HashingOverloaded::caused(&e));
+        assert_eq!(executor.metrics().rejected, 1);
+
+        finish_running.send(()).unwrap();
+        finish_queued.send(()).unwrap();
+        running.await.unwrap().unwrap();
+        queued.await.unwrap().unwrap();
+
+        // Room is made again once the queue drains
+        executor.run(|| Ok(())).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn dropped_request_leaves_queue() {
+        let executor = Arc::new(HashingExecutor::new(1, 1));
+
+        let (running, finish_running) = blocked_job(&executor);
+        wait_until(&executor, |metrics| metrics.running == 1).await;
+
+        let (queued, _) = blocked_job(&executor);
+        wait_until(&executor, |metrics| metrics.queued == 1).await;
+        queued.abort();
+        wait_until(&executor, |metrics| metrics.queued == 0).await;
+
+        finish_running.send(()).unwrap();
+        running.await.unwrap().unwrap();
+    }
+}
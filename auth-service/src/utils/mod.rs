@@ -1,3 +1,6 @@
+pub mod audit;
 pub mod auth;
 pub mod constants;
+pub mod hashing_executor;
+pub mod rate_limit;
 pub mod tracing;
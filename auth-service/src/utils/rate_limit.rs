@@ -0,0 +1,181 @@
+use crate::app_state::RateLimitStoreType;
+use crate::domain::error::AuthAPIError;
+use crate::domain::rate_limit::{RateLimit, RateLimitDecision, RouteRateLimits};
+use axum::body::{to_bytes, Body};
+use axum::extract::{ConnectInfo, Request};
+use axum::http::{Extensions, StatusCode};
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::eyre;
+use serde::Deserialize;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+// The rate limited routes take small JSON bodies, larger ones aren't worth reading to find the email
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+// Rejects requests to a route with a 429 once the client IP, or the email in the JSON body, has made too many
+// within the sliding window, such as `RateLimitLayer::new(store, "login", policy.login)`. The route name keeps the
+// windows of different routes apart.
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    store: RateLimitStoreType,
+    route: &'static str,
+    limits: RouteRateLimits,
+}
+
+impl RateLimitLayer {
+    pub fn new(store: RateLimitStoreType, route: &'static str, limits: RouteRateLimits) -> Self {
+        RateLimitLayer { store, route, limits }
+    }
+
+    // Gives the request back when it is let through, with the body put back after looking for the email in it
+    async fn check(&self, request: Request) -> Result<Request, Response> {
+        let now = Utc::now();
+
+        if let Some(limit) = &self.limits.per_ip {
+            // Requests whose IP is unknown share a single window
+            let ip = client_ip(request.extensions()).map_or_else(|| "unknown".to_owned(), ip_key);
+            self.record_request(&format!("{}:ip:{}", self.route, ip), limit, now).await?;
+        }
+
+        let Some(limit) = &self.limits.per_email else {
+            return Ok(request);
+        };
+
+        let (parts, body) = request.into_parts();
+        let body = to_bytes(body, MAX_BODY_BYTES)
+            .await
+            .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE.into_response())?;
+
+        // Requests without an email are rejected by the route itself
+        if let Some(email) = request_email(&body) {
+            self.record_request(&format!("{}:email:{}", self.route, email), limit, now)
+                .await?;
+        }
+
+        Ok(Request::from_parts(parts, Body::from(body)))
+    }
+
+    async fn record_request(&self, key: &str, limit: &RateLimit, now: DateTime<Utc>) -> Result<(), Response> {
+        let decision = self
+            .store
+            .write()
+            .await
+            .record_request(key, limit, now)
+            .await
+            .map_err(|e| AuthAPIError::UnexpectedError(eyre!(e)).into_response())?;
+
+        match decision {
+            RateLimitDecision::Allowed => Ok(()),
+            RateLimitDecision::Limited { retry_after_seconds } => {
+                tracing::warn!(key, "Rate limit reached");
+                Err(AuthAPIError::TooManyRequests { retry_after_seconds }.into_response())
+            }
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    inner: S,
+    layer: RateLimitLayer,
+}
+
+impl<S> Service<Request> for RateLimitService<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        // The service that was polled ready is the one to call, a fresh clone is left in its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+
+        Box::pin(async move {
+            match layer.check(request).await {
+                Ok(request) => inner.call(request).await,
+                Err(response) => Ok(response),
+            }
+        })
+    }
+}
+
+// The address the request came from, known when the app is served with `ConnectInfo`
+pub fn client_ip(extensions: &Extensions) -> Option<IpAddr> {
+    extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(address)| address.ip().to_canonical())
+}
+
+// IPv6 clients are usually handed a whole /64, so they are limited by it rather than by a single address
+fn ip_key(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ip) => ip.to_string(),
+        IpAddr::V6(ip) => {
+            let prefix: Vec<String> = ip.segments().iter().take(4).map(|segment| format!("{:x}", segment)).collect();
+            format!("{}::/64", prefix.join(":"))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct EmailField {
+    email: String,
+}
+
+// Lower-cased, so that changing the case of the email doesn't get around the limit
+fn request_email(body: &[u8]) -> Option<String> {
+    serde_json::from_slice::<EmailField>(body)
+        .ok()
+        .map(|field| field.email.trim().to_lowercase())
+        .filter(|email| !email.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ipv6_clients_are_limited_by_their_prefix() {
+        assert_eq!(ip_key("203.0.113.7".parse().unwrap()), "203.0.113.7");
+        assert_eq!(ip_key("2001:db8:0:1:aaaa::1".parse().unwrap()), "2001:db8:0:1::/64");
+        assert_eq!(
+            ip_key("2001:db8:0:1:aaaa::1".parse().unwrap()),
+            ip_key("2001:db8:0:1:bbbb::2".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn email_is_read_from_the_body() {
+        assert_eq!(
+            request_email(br#"{"email": " John@Example.com ", "password": "password123"}"#),
+            Some("john@example.com".to_owned())
+        );
+        assert_eq!(request_email(br#"{"password": "password123"}"#), None);
+        assert_eq!(request_email(b"not json"), None);
+    }
+}
@@ -0,0 +1,303 @@
+use crate::helpers::TestApp;
+use auth_service::domain::audit::AuditSearch;
+use auth_service::domain::email::Email;
+use auth_service::domain::invitation::Invitation;
+use auth_service::domain::login_failures::LoginFailures;
+use auth_service::domain::trusted_device::TrustedDevice;
+use auth_service::routes::AccountExport;
+use auth_service::utils::constants::env::JWT_COOKIE_NAME;
+use chrono::{Duration, Utc};
+use serde_json::json;
+
+async fn signup(app: &TestApp, email: &str) {
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "password123",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.verify_email(email).await;
+}
+
+// Logs in and returns the auth token, which the client also keeps as a cookie
+async fn login(app: &TestApp, email: &str) -> String {
+    let response = app.post_login(&json!({ "email": email, "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+
+    token
+}
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let mut app = TestApp::new().await;
+
+    let response = app.delete_account(&json!({ "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.get_account_export().await;
+    assert_eq!(response.status().as_u16(), 400);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_422_if_malformed_input() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    login(&app, &random_email).await;
+
+    let response = app.delete_account(&json!({})).await;
+
+    assert_eq!(response.status().as_u16(), 422);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_and_keep_account_if_password_is_wrong() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    login(&app, &random_email).await;
+
+    let response = app.delete_account(&json!({ "password": "wrongPassword123" })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    login(&app, &random_email).await;
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_delete_account_and_revoke_sessions() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    let token = login(&app, &random_email).await;
+
+    let response = app.delete_account(&json!({ "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 204);
+    assert!(response
+        .cookies()
+        .any(|cookie| cookie.name() == JWT_COOKIE_NAME && cookie.value().is_empty()));
+
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_leave_nothing_about_the_user_in_any_store() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    app.make_admin(&random_email).await;
+    login(&app, &random_email).await;
+    let email = Email::parse(random_email.clone().into()).unwrap();
+    let state = app.app_state.clone();
+
+    // Fill every store the API doesn't reach easily
+    let response = app.post_organization(&json!({ "name": "Acme" })).await;
+    assert_eq!(response.status().as_u16(), 201);
+    let response = app
+        .post_change_email(&json!({ "newEmail": TestApp::get_random_email() }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "wrongPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+    state
+        .trusted_device_store
+        .write()
+        .await
+        .add_device(
+            email.clone(),
+            TrustedDevice::new(
+                "device".to_owned(),
+                "test".to_owned(),
+                Utc::now(),
+                Utc::now() + Duration::days(1),
+            ),
+        )
+        .await
+        .unwrap();
+    state
+        .invitation_store
+        .write()
+        .await
+        .add_invitation(Invitation {
+            id: "invitation".to_owned(),
+            email: email.clone(),
+            role: None,
+            organization_id: None,
+            invited_by: Email::parse(TestApp::get_random_email().into()).unwrap(),
+            created_at: Utc::now(),
+            expires_at: Utc::now() + Duration::days(1),
+        })
+        .await
+        .unwrap();
+
+    let response = app.delete_account(&json!({ "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 204);
+
+    assert!(state.user_store.read().await.get_user(&email).await.is_err());
+    assert!(state.two_fa_code_store.read().await.get_code(&email).await.is_err());
+    assert!(state
+        .trusted_device_store
+        .read()
+        .await
+        .get_devices(&email)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(state.email_change_store.read().await.get_change(&email).await.is_err());
+    assert!(state.role_store.read().await.get_user_roles(&email).await.unwrap().is_empty());
+    assert!(state
+        .organization_store
+        .read()
+        .await
+        .list_memberships(&email)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(state
+        .invitation_store
+        .read()
+        .await
+        .list_invitations()
+        .await
+        .unwrap()
+        .iter()
+        .all(|invitation| invitation.email != email));
+    assert_eq!(
+        state.login_failure_store.read().await.get_failures(&email).await.unwrap(),
+        LoginFailures::default()
+    );
+    assert!(state
+        .password_history_store
+        .read()
+        .await
+        .get_passwords(&email, 10)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(state
+        .device_history_store
+        .read()
+        .await
+        .get_devices(&email)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let events = state
+        .audit_log
+        .read()
+        .await
+        .list_events(&AuditSearch {
+            actor: None,
+            event_types: Vec::new(),
+            offset: 0,
+            limit: 100,
+        })
+        .await
+        .unwrap()
+        .events;
+    assert!(!events.is_empty());
+    assert!(events
+        .iter()
+        .all(|event| event.actor.is_none() && event.ip.is_none() && event.user_agent.is_none()));
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_invalidate_links_sent_before_deletion() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    login(&app, &random_email).await;
+
+    app.post_magic_link(&json!({ "email": random_email })).await;
+    let email = app.get_last_email(&random_email).await.expect("No magic link email sent");
+    let token = TestApp::get_link_token(&email.content);
+
+    let response = app.delete_account(&json!({ "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 204);
+
+    // Signing up again with the same address must not revive the old link
+    signup(&app, &random_email).await;
+
+    let response = app.post_verify_magic_link(&json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_export_account_data() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    login(&app, &random_email).await;
+
+    let response = app
+        .patch_me(&json!({ "displayName": "Alice", "locale": "en-GB", "timezone": "Europe/Warsaw" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let new_email = TestApp::get_random_email();
+    let response = app.post_change_email(&json!({ "newEmail": new_email })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.get_account_export().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let export = response
+        .json::<AccountExport>()
+        .await
+        .expect("Could not deserialize response body to AccountExport");
+
+    assert_eq!(export.user.email, random_email);
+    assert_eq!(export.user.display_name.as_deref(), Some("Alice"));
+    assert_eq!(export.user.locale.as_deref(), Some("en-GB"));
+    assert_eq!(export.user.timezone.as_deref(), Some("Europe/Warsaw"));
+    assert!(export.user.email_verified);
+    assert!(export.user.has_password);
+    assert!(!export.user.requires_2fa);
+    assert_eq!(export.user.status.status, "active");
+    assert!(export
+        .user
+        .last_login_at
+        .is_some_and(|last_login_at| last_login_at >= export.user.created_at));
+    assert!(export.trusted_devices.is_empty());
+    assert_eq!(export.pending_email_change.map(|change| change.new_email), Some(new_email));
+    assert_eq!(export.known_devices.len(), 1);
+
+    let events: Vec<(&str, &str)> = export
+        .audit_events
+        .iter()
+        .map(|event| (event.event_type.as_str(), event.outcome.as_str()))
+        .collect();
+    assert_eq!(events, vec![("login", "success"), ("signup", "success")]);
+    assert_eq!(export.audit_events[0].ip.as_deref(), Some("127.0.0.1"));
+    app.clean_up().await;
+}
@@ -0,0 +1,494 @@
+use crate::helpers::TestApp;
+use argon2::password_hash::{rand_core::OsRng, PasswordHasher, SaltString};
+use auth_service::domain::email::Email;
+use auth_service::routes::{AdminUserList, AdminUserSummary, ImportFailure, ImportUsersResponse};
+use auth_service::utils::constants::env::JWT_COOKIE_NAME;
+use auth_service::utils::constants::LOGIN_LOCKOUT_THRESHOLD;
+use auth_service::utils::hashing_executor::HashingMetrics;
+use chrono::{Duration, Utc};
+use reqwest::StatusCode;
+use secrecy::ExposeSecret;
+use serde_json::json;
+
+async fn signup(app: &TestApp, email: &str) {
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "password123",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    app.verify_email(email).await;
+}
+
+async fn login(app: &TestApp, email: &str) -> reqwest::Response {
+    app.post_login(&json!({ "email": email, "password": "password123" })).await
+}
+
+// Logs in and returns the auth token, so that it can still be checked once the admin logs in
+async fn login_for_token(app: &TestApp, email: &str) -> String {
+    let response = login(app, email).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+
+    token
+}
+
+async fn login_as_admin(app: &TestApp) {
+    let admin_email = TestApp::get_random_email();
+    signup(app, &admin_email).await;
+    app.make_admin(&admin_email).await;
+
+    assert_eq!(login(app, &admin_email).await.status(), StatusCode::OK);
+}
+
+async fn lock_out(app: &TestApp, email: &str) {
+    for _ in 0..*LOGIN_LOCKOUT_THRESHOLD {
+        app.post_login(&json!({ "email": email, "password": "wrongPassword123" }))
+            .await;
+    }
+
+    assert_eq!(login(app, email).await.status(), StatusCode::LOCKED);
+}
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let mut app = TestApp::new().await;
+
+    let response = app.delete_admin_lockout(&TestApp::get_random_email()).await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_if_not_an_admin() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    assert_eq!(login(&app, &random_email).await.status(), StatusCode::OK);
+
+    let response = app.delete_admin_lockout(&random_email).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app.get_admin_users(&[]).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app.post_admin_user_action(&random_email, "disable").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_clear_lockout() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    lock_out(&app, &random_email).await;
+
+    login_as_admin(&app).await;
+
+    let response = app.delete_admin_lockout(&random_email).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    assert_eq!(login(&app, &random_email).await.status(), StatusCode::OK);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_email() {
+    let mut app = TestApp::new().await;
+
+    login_as_admin(&app).await;
+
+    let response = app.delete_admin_lockout("invalid_email").await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_list_and_search_users() {
+    let mut app = TestApp::new().await;
+
+    // Every user gets the same unique marker, so that other tests' users don't match the search
+    let marker = uuid::Uuid::new_v4().simple().to_string();
+    let emails: Vec<String> = (0..3).map(|i| format!("{marker}{i}@example.com")).collect();
+    for email in emails.iter() {
+        signup(&app, email).await;
+    }
+
+    login_as_admin(&app).await;
+
+    let response = app
+        .get_admin_users(&[("search", &marker.to_uppercase()), ("perPage", "2")])
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let first_page = response
+        .json::<AdminUserList>()
+        .await
+        .expect("Could not deserialize response body to AdminUserList");
+    assert_eq!(first_page.total, 3);
+    assert_eq!(first_page.page, 1);
+    assert_eq!(first_page.users.len(), 2);
+
+    let response = app
+        .get_admin_users(&[("search", &marker), ("perPage", "2"), ("page", "2")])
+        .await;
+    let second_page = response
+        .json::<AdminUserList>()
+        .await
+        .expect("Could not deserialize response body to AdminUserList");
+    assert_eq!(second_page.users.len(), 1);
+
+    let mut listed: Vec<String> = first_page
+        .users
+        .into_iter()
+        .chain(second_page.users)
+        .map(|user| user.email)
+        .collect();
+    listed.sort();
+    assert_eq!(listed, emails);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_show_user() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+
+    login_as_admin(&app).await;
+
+    let response = app.get_admin_user(&random_email).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let user = response
+        .json::<AdminUserSummary>()
+        .await
+        .expect("Could not deserialize response body to AdminUserSummary");
+    assert_eq!(user.email, random_email);
+    assert!(user.email_verified);
+    assert!(user.has_password);
+    assert!(user.roles.is_empty());
+    assert_eq!(user.status.status, "active");
+
+    let response = app.get_admin_user(&TestApp::get_random_email()).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_disable_and_enable_user() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    let token = login_for_token(&app, &random_email).await;
+
+    login_as_admin(&app).await;
+
+    let response = app.post_admin_user_action(&random_email, "disable").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(login(&app, &random_email).await.status(), StatusCode::FORBIDDEN);
+
+    let user = app
+        .get_admin_user(&random_email)
+        .await
+        .json::<AdminUserSummary>()
+        .await
+        .expect("Could not deserialize response body to AdminUserSummary");
+    assert_eq!(user.status.status, "disabled");
+
+    let response = app.post_admin_user_action(&random_email, "enable").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    assert_eq!(login(&app, &random_email).await.status(), StatusCode::OK);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_suspend_user_with_reason() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    let token = login_for_token(&app, &random_email).await;
+
+    login_as_admin(&app).await;
+
+    let suspended_until = Utc::now() + Duration::days(7);
+    let response = app
+        .put_admin_user_status(
+            &random_email,
+            &json!({ "status": "suspended", "reason": " Chargeback ", "suspendedUntil": suspended_until }),
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(login(&app, &random_email).await.status(), StatusCode::FORBIDDEN);
+
+    let user = app
+        .get_admin_user(&random_email)
+        .await
+        .json::<AdminUserSummary>()
+        .await
+        .expect("Could not deserialize response body to AdminUserSummary");
+    assert_eq!(user.status.status, "suspended");
+    assert_eq!(user.status.reason, Some("Chargeback".to_owned()));
+    assert_eq!(
+        user.status.suspended_until.map(|until| until.timestamp_micros()),
+        Some(suspended_until.timestamp_micros())
+    );
+
+    let response = app.put_admin_user_status(&random_email, &json!({ "status": "active" })).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    assert_eq!(login(&app, &random_email).await.status(), StatusCode::OK);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_account_status() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+
+    login_as_admin(&app).await;
+
+    let test_cases = [
+        json!({ "status": "deleted" }),
+        json!({ "status": "disabled", "reason": "  " }),
+        json!({ "status": "disabled", "suspendedUntil": Utc::now() + Duration::days(1) }),
+        json!({ "status": "suspended", "suspendedUntil": Utc::now() - Duration::days(1) }),
+    ];
+
+    for body in test_cases {
+        let response = app.put_admin_user_status(&random_email, &body).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "Failed for input: {:?}", body);
+    }
+
+    let response = app
+        .put_admin_user_status(&TestApp::get_random_email(), &json!({ "status": "pending" }))
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_force_2fa() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+
+    login_as_admin(&app).await;
+
+    let response = app.post_admin_user_action(&random_email, "force-2fa").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    assert_eq!(login(&app, &random_email).await.status(), StatusCode::PARTIAL_CONTENT);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_reset_password_and_send_link() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    let token = login_for_token(&app, &random_email).await;
+
+    login_as_admin(&app).await;
+
+    let response = app.post_admin_user_action(&random_email, "reset-password").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(login(&app, &random_email).await.status(), StatusCode::UNAUTHORIZED);
+
+    let email = app.get_last_email(&random_email).await.expect("No reset email sent");
+    let reset_token = TestApp::get_link_token(&email.content);
+
+    let response = app
+        .post_reset_password(&json!({ "token": reset_token, "password": "newPassword123" }))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "newPassword123" }))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_revoke_sessions() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    let token = login_for_token(&app, &random_email).await;
+
+    login_as_admin(&app).await;
+
+    let response = app.post_admin_user_action(&random_email, "revoke-sessions").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .post_admin_user_action(&TestApp::get_random_email(), "revoke-sessions")
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    app.clean_up().await;
+}
+
+// `password123` hashed by bcrypt, scrypt and PBKDF2-SHA256, with cheap parameters
+fn legacy_hashes() -> Vec<String> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    let scrypt_hash = scrypt::Scrypt
+        .hash_password_customized(b"password123", None, None, scrypt::Params::new(10, 8, 1, 32).unwrap(), &salt)
+        .unwrap();
+
+    let pbkdf2_hash = pbkdf2::Pbkdf2
+        .hash_password_customized(
+            b"password123",
+            Some(pbkdf2::Algorithm::Pbkdf2Sha256.ident()),
+            None,
+            pbkdf2::Params {
+                rounds: 1000,
+                output_length: 32,
+            },
+            &salt,
+        )
+        .unwrap();
+
+    vec![
+        bcrypt::hash("password123", 4).unwrap(),
+        scrypt_hash.to_string(),
+        pbkdf2_hash.to_string(),
+    ]
+}
+
+#[tokio::test]
+async fn should_import_users_and_upgrade_legacy_hashes_on_login() {
+    let mut app = TestApp::new().await;
+
+    let existing_email = TestApp::get_random_email();
+    signup(&app, &existing_email).await;
+
+    login_as_admin(&app).await;
+
+    let legacy_users: Vec<(String, String)> = legacy_hashes()
+        .into_iter()
+        .map(|hash| (TestApp::get_random_email(), hash))
+        .collect();
+
+    let mut users: Vec<serde_json::Value> = legacy_users
+        .iter()
+        .map(|(email, hash)| json!({ "email": email, "passwordHash": hash, "emailVerified": true }))
+        .collect();
+    users.push(json!({ "email": "invalid_email", "passwordHash": legacy_users[0].1 }));
+    users.push(json!({ "email": TestApp::get_random_email(), "passwordHash": "$1$saltsalt$qjXMvbEw8oaL.CzflDugX/" }));
+    users.push(json!({ "email": existing_email, "passwordHash": legacy_users[0].1 }));
+
+    let response = app.post_admin_import_users(&json!({ "users": users })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.json::<ImportUsersResponse>().await.unwrap();
+    assert_eq!(body.imported, 3);
+    let errors: Vec<String> = body.failed.into_iter().map(|ImportFailure { error, .. }| error).collect();
+    assert_eq!(
+        errors,
+        vec!["Invalid email", "Unsupported password hash", "User already exists"]
+    );
+
+    for (email, hash) in legacy_users {
+        assert_eq!(login(&app, &email).await.status(), StatusCode::OK, "{}", hash);
+
+        // The legacy hash is replaced by an Argon2id one in the background
+        let email = Email::parse(email.into()).unwrap();
+        let mut upgraded = false;
+        for _ in 0..50 {
+            let user = app.user_store.read().await.get_user(&email).await.unwrap();
+            if user
+                .password
+                .is_some_and(|password| password.as_ref().expose_secret().starts_with("$argon2id$"))
+            {
+                upgraded = true;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert!(upgraded, "{}", hash);
+
+        assert_eq!(login(&app, email.as_ref().expose_secret()).await.status(), StatusCode::OK);
+    }
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_413_if_too_many_users_to_import() {
+    let mut app = TestApp::new().await;
+
+    login_as_admin(&app).await;
+
+    let users: Vec<serde_json::Value> = (0..1001)
+        .map(|i| json!({ "email": format!("user{}@example.com", i) }))
+        .collect();
+
+    let response = app.post_admin_import_users(&json!({ "users": users })).await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_hashing_metrics() {
+    let mut app = TestApp::new().await;
+
+    login_as_admin(&app).await;
+
+    let response = app.get_admin_hashing_metrics().await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let metrics = response.json::<HashingMetrics>().await.unwrap();
+    assert!(metrics.max_concurrency > 0);
+    // At least the admin's signup and login went through the executor
+    assert!(metrics.completed >= 2);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_if_hashing_metrics_requested_without_permission() {
+    let mut app = TestApp::new().await;
+
+    let email = TestApp::get_random_email();
+    signup(&app, &email).await;
+    assert_eq!(login(&app, &email).await.status(), StatusCode::OK);
+
+    let response = app.get_admin_hashing_metrics().await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    app.clean_up().await;
+}
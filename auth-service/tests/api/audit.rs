@@ -0,0 +1,232 @@
+use crate::helpers::TestApp;
+use auth_service::routes::{AuditEventList, AuditEventResponse, TwoFactorAuthResponse};
+use auth_service::utils::constants::env::JWT_COOKIE_NAME;
+use reqwest::header::USER_AGENT;
+use reqwest::StatusCode;
+use serde_json::json;
+
+async fn signup(app: &TestApp, email: &str, requires_2fa: bool) {
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "password123",
+            "requires2FA": requires_2fa
+        }))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    app.verify_email(email).await;
+}
+
+async fn login(app: &TestApp, email: &str) -> reqwest::Response {
+    app.post_login(&json!({ "email": email, "password": "password123" })).await
+}
+
+async fn login_as_admin(app: &TestApp) {
+    let admin_email = TestApp::get_random_email();
+    signup(app, &admin_email, false).await;
+    app.make_admin(&admin_email).await;
+
+    assert_eq!(login(app, &admin_email).await.status(), StatusCode::OK);
+}
+
+async fn audit_events(app: &TestApp, query: &[(&str, &str)]) -> AuditEventList {
+    let response = app.get_admin_audit_events(query).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    response
+        .json::<AuditEventList>()
+        .await
+        .expect("Could not deserialize response body to AuditEventList")
+}
+
+// Event type and outcome of each event, most recent first
+fn summary(events: &[AuditEventResponse]) -> Vec<(&str, &str)> {
+    events
+        .iter()
+        .map(|event| (event.event_type.as_str(), event.outcome.as_str()))
+        .collect()
+}
+
+#[tokio::test]
+async fn should_record_signup_and_logins_with_client_details() {
+    let mut app = TestApp::new().await;
+
+    let email = TestApp::get_random_email();
+    signup(&app, &email, false).await;
+
+    let response = app
+        .post_login(&json!({ "email": email, "password": "wrongPassword123" }))
+        .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .http_client
+        .post(format!("{}/login", &app.address))
+        .header(USER_AGENT, "Mozilla/5.0 (X11; Linux x86_64) Firefox/128.0")
+        .json(&json!({ "email": email, "password": "password123" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    login_as_admin(&app).await;
+
+    let list = audit_events(&app, &[("actor", &email)]).await;
+    assert_eq!(
+        summary(&list.events),
+        vec![("login", "success"), ("login", "failure"), ("signup", "success")]
+    );
+    assert_eq!(list.total, 3);
+
+    let successful_login = &list.events[0];
+    assert_eq!(successful_login.actor.as_deref(), Some(email.as_str()));
+    assert_eq!(successful_login.ip.as_deref(), Some("127.0.0.1"));
+    assert_eq!(
+        successful_login.user_agent.as_deref(),
+        Some("Mozilla/5.0 (X11; Linux x86_64) Firefox/128.0")
+    );
+    assert_eq!(successful_login.reason, None);
+    assert_eq!(list.events[1].reason.as_deref(), Some("Incorrect credentials"));
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_record_2fa_logout_and_token_verification() {
+    let mut app = TestApp::new().await;
+
+    let email = TestApp::get_random_email();
+    signup(&app, &email, true).await;
+
+    let response = login(&app, &email).await;
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    let login_attempt_id = response
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse")
+        .login_attempt_id;
+
+    let response = app
+        .post_verify_2fa(&json!({
+            "email": email,
+            "loginAttemptId": login_attempt_id,
+            "2FACode": app.get_last_two_fa_code(&email).await
+        }))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+
+    assert_eq!(
+        app.post_verify_token(&json!({ "token": token })).await.status(),
+        StatusCode::OK
+    );
+    assert_eq!(app.post_logout().await.status(), StatusCode::OK);
+    // The token was revoked by the logout
+    assert_eq!(
+        app.post_verify_token(&json!({ "token": token })).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+
+    login_as_admin(&app).await;
+
+    let list = audit_events(&app, &[("actor", &email)]).await;
+    assert_eq!(
+        summary(&list.events),
+        vec![
+            ("verify_token", "failure"),
+            ("logout", "success"),
+            ("verify_token", "success"),
+            ("verify_2fa", "success"),
+            ("login", "challenged"),
+            ("signup", "success"),
+        ]
+    );
+
+    let list = audit_events(&app, &[("actor", &email), ("eventType", "logout")]).await;
+    assert_eq!(summary(&list.events), vec![("logout", "success")]);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_paginate_audit_events() {
+    let mut app = TestApp::new().await;
+
+    let email = TestApp::get_random_email();
+    signup(&app, &email, false).await;
+    for _ in 0..3 {
+        assert_eq!(login(&app, &email).await.status(), StatusCode::OK);
+    }
+
+    login_as_admin(&app).await;
+
+    let list = audit_events(&app, &[("actor", &email), ("page", "2"), ("perPage", "3")]).await;
+    assert_eq!(summary(&list.events), vec![("signup", "success")]);
+    assert_eq!((list.page, list.per_page, list.total), (2, 3, 4));
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_event_type_is_invalid() {
+    let mut app = TestApp::new().await;
+
+    login_as_admin(&app).await;
+
+    let response = app.get_admin_audit_events(&[("eventType", "password_reset")]).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_if_not_allowed_to_read_audit_events() {
+    let mut app = TestApp::new().await;
+
+    let email = TestApp::get_random_email();
+    signup(&app, &email, false).await;
+    assert_eq!(login(&app, &email).await.status(), StatusCode::OK);
+
+    let response = app.get_admin_audit_events(&[]).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_own_login_history() {
+    let mut app = TestApp::new().await;
+
+    let other_email = TestApp::get_random_email();
+    signup(&app, &other_email, false).await;
+    assert_eq!(login(&app, &other_email).await.status(), StatusCode::OK);
+
+    let email = TestApp::get_random_email();
+    signup(&app, &email, false).await;
+    let response = app
+        .post_login(&json!({ "email": email, "password": "wrongPassword123" }))
+        .await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(login(&app, &email).await.status(), StatusCode::OK);
+
+    let response = app.get_login_history(&[]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let list = response
+        .json::<AuditEventList>()
+        .await
+        .expect("Could not deserialize response body to AuditEventList");
+    assert_eq!(summary(&list.events), vec![("login", "success"), ("login", "failure")]);
+    assert!(list.events.iter().all(|event| event.actor.as_deref() == Some(email.as_str())));
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_login_history_requested_without_jwt_cookie() {
+    let mut app = TestApp::new().await;
+
+    let response = app.get_login_history(&[]).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    app.clean_up().await;
+}
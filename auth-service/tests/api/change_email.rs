@@ -0,0 +1,350 @@
+use crate::helpers::TestApp;
+use auth_service::domain::email::Email;
+use auth_service::routes::{AuditEventList, TwoFactorAuthResponse};
+use auth_service::utils::constants::env::JWT_COOKIE_NAME;
+use auth_service::utils::constants::LOGIN_LOCKOUT_THRESHOLD;
+use serde_json::json;
+
+async fn signup_and_login(app: &TestApp, email: &str, requires_2fa: bool) {
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "password123",
+            "requires2FA": requires_2fa
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.verify_email(email).await;
+
+    if requires_2fa {
+        let response = app.post_login(&json!({ "email": email, "password": "password123" })).await;
+        assert_eq!(response.status().as_u16(), 206);
+
+        let login_attempt_id = response
+            .json::<TwoFactorAuthResponse>()
+            .await
+            .expect("Could not deserialize response body to TwoFactorAuthResponse")
+            .login_attempt_id;
+
+        let response = app
+            .post_verify_2fa(&json!({
+                "email": email,
+                "loginAttemptId": login_attempt_id,
+                "2FACode": app.get_last_two_fa_code(email).await,
+                "trustDevice": true,
+            }))
+            .await;
+        assert_eq!(response.status().as_u16(), 200);
+    } else {
+        let response = app.post_login(&json!({ "email": email, "password": "password123" })).await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+}
+
+// Returns the tokens of the links sent to the old and the new address
+async fn request_email_change(app: &TestApp, old_email: &str, new_email: &str) -> (String, String) {
+    let response = app.post_change_email(&json!({ "newEmail": new_email })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let old_email_message = app.get_last_email(old_email).await.expect("No email sent to the old address");
+    let new_email_message = app.get_last_email(new_email).await.expect("No email sent to the new address");
+
+    (
+        TestApp::get_link_token(&old_email_message.content),
+        TestApp::get_link_token(&new_email_message.content),
+    )
+}
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let mut app = TestApp::new().await;
+
+    let response = app
+        .post_change_email(&json!({ "newEmail": TestApp::get_random_email() }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_new_email() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup_and_login(&app, &random_email, false).await;
+
+    for new_email in ["invalid_email", random_email.as_str()] {
+        let response = app.post_change_email(&json!({ "newEmail": new_email })).await;
+
+        assert_eq!(response.status().as_u16(), 400, "Failed for input: {}", new_email);
+    }
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_409_if_new_email_is_used() {
+    let mut app = TestApp::new().await;
+
+    let other_email = TestApp::get_random_email();
+    signup_and_login(&app, &other_email, false).await;
+
+    let random_email = TestApp::get_random_email();
+    signup_and_login(&app, &random_email, false).await;
+
+    let response = app.post_change_email(&json!({ "newEmail": other_email })).await;
+
+    assert_eq!(response.status().as_u16(), 409);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_change_email_once_both_addresses_confirm() {
+    let mut app = TestApp::new().await;
+
+    let old_email = TestApp::get_random_email();
+    let new_email = TestApp::get_random_email();
+    signup_and_login(&app, &old_email, false).await;
+
+    let (old_email_token, new_email_token) = request_email_change(&app, &old_email, &new_email).await;
+
+    let response = app.get_confirm_email_change(&new_email_token).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Nothing changes until both addresses confirmed
+    let response = app
+        .post_login(&json!({ "email": old_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let auth_cookie = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found");
+
+    let response = app.get_confirm_email_change(&old_email_token).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_login(&json!({ "email": old_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_verify_token(&json!({ "token": auth_cookie.value() })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app
+        .post_login(&json!({ "email": new_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_invalid_token() {
+    let mut app = TestApp::new().await;
+
+    let response = app.get_confirm_email_change("invalid_token").await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_change_was_replaced() {
+    let mut app = TestApp::new().await;
+
+    let old_email = TestApp::get_random_email();
+    signup_and_login(&app, &old_email, false).await;
+
+    let (first_token, _) = request_email_change(&app, &old_email, &TestApp::get_random_email()).await;
+    request_email_change(&app, &old_email, &TestApp::get_random_email()).await;
+
+    let response = app.get_confirm_email_change(&first_token).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_409_if_new_email_was_taken_meanwhile() {
+    let mut app = TestApp::new().await;
+
+    let old_email = TestApp::get_random_email();
+    let new_email = TestApp::get_random_email();
+    signup_and_login(&app, &old_email, false).await;
+
+    let (old_email_token, new_email_token) = request_email_change(&app, &old_email, &new_email).await;
+
+    let response = app
+        .post_signup(&json!({
+            "email": new_email,
+            "password": "password123",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.get_confirm_email_change(&old_email_token).await;
+    let response = app.get_confirm_email_change(&new_email_token).await;
+
+    assert_eq!(response.status().as_u16(), 409);
+
+    let response = app
+        .post_login(&json!({ "email": old_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_keep_trusted_devices_after_change() {
+    let mut app = TestApp::new().await;
+
+    let old_email = TestApp::get_random_email();
+    let new_email = TestApp::get_random_email();
+    signup_and_login(&app, &old_email, true).await;
+
+    let (old_email_token, new_email_token) = request_email_change(&app, &old_email, &new_email).await;
+
+    app.get_confirm_email_change(&old_email_token).await;
+    let response = app.get_confirm_email_change(&new_email_token).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_login(&json!({ "email": new_email, "password": "password123" }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_keep_lockout_after_change() {
+    let mut app = TestApp::new().await;
+
+    let old_email = TestApp::get_random_email();
+    let new_email = TestApp::get_random_email();
+    signup_and_login(&app, &old_email, false).await;
+
+    let (old_email_token, new_email_token) = request_email_change(&app, &old_email, &new_email).await;
+
+    let body = json!({ "email": old_email, "password": "wrongPassword123" });
+    for _ in 0..*LOGIN_LOCKOUT_THRESHOLD {
+        app.post_login(&body).await;
+    }
+
+    for token in [new_email_token, old_email_token] {
+        let response = app.get_confirm_email_change(&token).await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    let response = app
+        .post_login(&json!({ "email": new_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 423);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_finish_change_that_failed_halfway_when_link_is_followed_again() {
+    let mut app = TestApp::new().await;
+
+    let old_email = TestApp::get_random_email();
+    let new_email = TestApp::get_random_email();
+    signup_and_login(&app, &old_email, false).await;
+
+    let (old_email_token, new_email_token) = request_email_change(&app, &old_email, &new_email).await;
+
+    let body = json!({ "email": old_email, "password": "wrongPassword123" });
+    for _ in 0..*LOGIN_LOCKOUT_THRESHOLD {
+        app.post_login(&body).await;
+    }
+
+    let response = app.get_confirm_email_change(&new_email_token).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // A first attempt that moved the account and then stopped
+    app.app_state
+        .user_store
+        .write()
+        .await
+        .update_email(
+            &Email::parse(old_email.clone().into()).unwrap(),
+            &Email::parse(new_email.clone().into()).unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let response = app.get_confirm_email_change(&old_email_token).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_login(&json!({ "email": new_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 423);
+
+    // The change is done once every step went through
+    let response = app.get_confirm_email_change(&old_email_token).await;
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_invalidate_links_sent_to_old_address() {
+    let mut app = TestApp::new().await;
+
+    let old_email = TestApp::get_random_email();
+    let new_email = TestApp::get_random_email();
+    signup_and_login(&app, &old_email, false).await;
+
+    let (old_email_token, new_email_token) = request_email_change(&app, &old_email, &new_email).await;
+
+    let response = app.post_forgot_password(&json!({ "email": old_email })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let reset_email = app.get_last_email(&old_email).await.expect("No password reset email sent");
+    let reset_token = TestApp::get_link_token(&reset_email.content);
+
+    for token in [new_email_token, old_email_token] {
+        let response = app.get_confirm_email_change(&token).await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    let response = app
+        .post_reset_password(&json!({ "token": reset_token, "password": "newPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_keep_login_history_after_change() {
+    let mut app = TestApp::new().await;
+
+    let old_email = TestApp::get_random_email();
+    let new_email = TestApp::get_random_email();
+    signup_and_login(&app, &old_email, false).await;
+
+    let (old_email_token, new_email_token) = request_email_change(&app, &old_email, &new_email).await;
+    for token in [new_email_token, old_email_token] {
+        let response = app.get_confirm_email_change(&token).await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    let response = app
+        .post_login(&json!({ "email": new_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.get_login_history(&[]).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let list = response
+        .json::<AuditEventList>()
+        .await
+        .expect("Could not deserialize response body to AuditEventList");
+    assert_eq!(list.total, 2);
+    assert!(list.events.iter().all(|event| event.actor.as_deref() == Some(new_email.as_str())));
+    app.clean_up().await;
+}
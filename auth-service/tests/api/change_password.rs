@@ -0,0 +1,279 @@
+use crate::helpers::TestApp;
+use auth_service::domain::error::ErrorResponse;
+use auth_service::domain::password_policy::PasswordPolicy;
+use auth_service::utils::constants::env::JWT_COOKIE_NAME;
+use serde_json::json;
+use std::time::Duration;
+
+async fn signup(app: &TestApp, email: &str) {
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "password123",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.verify_email(email).await;
+}
+
+// Logs in and returns the auth token, which the client also keeps as a cookie
+async fn login(app: &TestApp, email: &str, password: &str) -> String {
+    let response = app.post_login(&json!({ "email": email, "password": password })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+
+    token
+}
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let mut app = TestApp::new().await;
+
+    let response = app
+        .post_change_password(&json!({
+            "currentPassword": "password123",
+            "newPassword": "newPassword123"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_422_if_malformed_input() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    login(&app, &random_email, "password123").await;
+
+    let response = app.post_change_password(&json!({ "newPassword": "newPassword123" })).await;
+
+    assert_eq!(response.status().as_u16(), 422);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_incorrect_current_password() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    login(&app, &random_email, "password123").await;
+
+    let response = app
+        .post_change_password(&json!({
+            "currentPassword": "wrongPassword",
+            "newPassword": "newPassword123"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_new_password() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    login(&app, &random_email, "password123").await;
+
+    let response = app
+        .post_change_password(&json!({
+            "currentPassword": "password123",
+            "newPassword": "short"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_new_password_was_breached() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    login(&app, &random_email, "password123").await;
+
+    app.breached_password_store
+        .write()
+        .await
+        .add_password(&"breachedPassword1".into(), 1);
+
+    let response = app
+        .post_change_password(&json!({
+            "currentPassword": "password123",
+            "newPassword": "breachedPassword1"
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    let body = response
+        .json::<ErrorResponse>()
+        .await
+        .expect("Could not deserialize response body to ErrorResponse");
+    assert_eq!(body.error, "Weak password");
+    assert_eq!(body.details.first().map(|detail| detail.rule.as_str()), Some("breached"));
+
+    // The old password keeps working
+    login(&app, &random_email, "password123").await;
+    app.clean_up().await;
+}
+
+async fn change_password(app: &TestApp, current_password: &str, new_password: &str) -> reqwest::Response {
+    app.post_change_password(&json!({
+        "currentPassword": current_password,
+        "newPassword": new_password
+    }))
+    .await
+}
+
+#[tokio::test]
+async fn should_return_400_if_new_password_was_used_recently() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    login(&app, &random_email, "password123").await;
+
+    let response = change_password(&app, "password123", "password123").await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = change_password(&app, "password123", "newPassword123").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = change_password(&app, "newPassword123", "password123").await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response
+            .json::<ErrorResponse>()
+            .await
+            .expect("Could not deserialize response body to ErrorResponse")
+            .error,
+        "Password was used recently"
+    );
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_accept_password_older_than_history() {
+    let mut app = TestApp::new_with_password_policy(PasswordPolicy {
+        min_strength: 0,
+        history_size: 2,
+        ..PasswordPolicy::default()
+    })
+    .await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    login(&app, &random_email, "password123").await;
+
+    let response = change_password(&app, "password123", "newPassword123").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = change_password(&app, "newPassword123", "password123").await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = change_password(&app, "newPassword123", "otherPassword123").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Only the last 2 passwords are remembered
+    let response = change_password(&app, "otherPassword123", "password123").await;
+    assert_eq!(response.status().as_u16(), 200);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_change_password_and_notify_user() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    login(&app, &random_email, "password123").await;
+
+    let response = app
+        .post_change_password(&json!({
+            "currentPassword": "password123",
+            "newPassword": "newPassword123"
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let email = app.get_last_email(&random_email).await.expect("No notification email sent");
+    assert_eq!(email.subject, "Your password was changed");
+
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    login(&app, &random_email, "newPassword123").await;
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_keep_other_sessions_by_default() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+
+    let other_session = login(&app, &random_email, "password123").await;
+    // Tokens issued within the same second are identical
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    login(&app, &random_email, "password123").await;
+
+    let response = app
+        .post_change_password(&json!({
+            "currentPassword": "password123",
+            "newPassword": "newPassword123"
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_verify_token(&json!({ "token": other_session })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_revoke_other_sessions_if_requested() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+
+    let other_session = login(&app, &random_email, "password123").await;
+    // Tokens issued within the same second are identical
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let current_session = login(&app, &random_email, "password123").await;
+
+    let response = app
+        .post_change_password(&json!({
+            "currentPassword": "password123",
+            "newPassword": "newPassword123",
+            "revokeOtherSessions": true
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_verify_token(&json!({ "token": other_session })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_verify_token(&json!({ "token": current_session })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    app.clean_up().await;
+}
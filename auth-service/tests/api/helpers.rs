@@ -1,12 +1,32 @@
-use auth_service::app_state::{AppState, BannedTokenStoreType, EmailClientType, TwoFACodeStoreType};
+use auth_service::app_state::{AppState, BannedTokenStoreType, RoleStoreType, TwoFACodeStoreType, UserStoreType};
+use auth_service::domain::account_status::AccountState;
+use auth_service::domain::email::Email;
+use auth_service::domain::hashed_password::HashedPassword;
+use auth_service::domain::password_policy::PasswordPolicy;
+use auth_service::domain::rate_limit::{RateLimitPolicy, RouteRateLimits};
+use auth_service::domain::rbac::{RoleName, ADMIN_ROLE};
+use auth_service::domain::user::User;
+use auth_service::services::data_stores::hashmap_breached_password_store::HashmapBreachedPasswordStore;
+use auth_service::services::data_stores::hashmap_rate_limit_store::HashmapRateLimitStore;
+use auth_service::services::data_stores::postgres_audit_log::PostgresAuditLog;
+use auth_service::services::data_stores::postgres_device_history_store::PostgresDeviceHistoryStore;
+use auth_service::services::data_stores::postgres_invitation_store::PostgresInvitationStore;
+use auth_service::services::data_stores::postgres_organization_store::PostgresOrganizationStore;
+use auth_service::services::data_stores::postgres_password_history_store::PostgresPasswordHistoryStore;
+use auth_service::services::data_stores::postgres_role_store::PostgresRoleStore;
 use auth_service::services::data_stores::postgres_user_store::PostgresUserStore;
 use auth_service::services::data_stores::redis_banned_token_store::RedisBannedTokenStore;
+use auth_service::services::data_stores::redis_email_change_store::RedisEmailChangeStore;
+use auth_service::services::data_stores::redis_login_failure_store::RedisLoginFailureStore;
+use auth_service::services::data_stores::redis_one_time_token_store::RedisOneTimeTokenStore;
+use auth_service::services::data_stores::redis_trusted_device_store::RedisTrustedDeviceStore;
 use auth_service::services::data_stores::redis_two_fa_code_store::RedisTwoFACodeStore;
-use auth_service::services::mock_email_client::MockEmailClient;
-use auth_service::utils::constants::{test, REDIS_HOST_NAME};
+use auth_service::services::mock_email_client::{MockEmailClient, SentEmail};
+use auth_service::utils::constants::{test, AUTH_SERVICE_URL, REDIS_HOST_NAME};
 use auth_service::{get_postgres_pool, get_redis_client, Application};
 use dotenv::dotenv;
 use reqwest::cookie::Jar;
+use secrecy::ExposeSecret;
 use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
 use sqlx::{Connection, Executor, PgConnection, PgPool};
 use std::str::FromStr;
@@ -18,19 +38,109 @@ pub struct TestApp {
     pub address: String,
     pub http_client: reqwest::Client,
     pub cookie_jar: Arc<Jar>,
+    pub user_store: UserStoreType,
+    pub role_store: RoleStoreType,
     pub banned_token_store: BannedTokenStoreType,
     pub two_fa_code_store: TwoFACodeStoreType,
-    #[allow(dead_code)]
-    pub email_client: EmailClientType,
+    pub email_client: Arc<RwLock<MockEmailClient>>,
+    pub breached_password_store: Arc<RwLock<HashmapBreachedPasswordStore>>,
+    // Every store of the app, for the tests checking what it keeps about a user
+    pub app_state: AppState,
     pub db_name: String,
     cleaned_up: bool,
 }
 
+// Keeps the strength check out of the way of tests using simple passwords such as `password123`
+fn lenient_password_policy() -> PasswordPolicy {
+    PasswordPolicy {
+        min_strength: 0,
+        ..PasswordPolicy::default()
+    }
+}
+
+// Tests log in and sign up many times in a row, only the rate limit tests turn limits on
+fn unlimited_rate_limit_policy() -> RateLimitPolicy {
+    RateLimitPolicy {
+        login: RouteRateLimits::default(),
+        signup: RouteRateLimits::default(),
+        verify_2fa: RouteRateLimits::default(),
+        magic_link: RouteRateLimits::default(),
+        forgot_password: RouteRateLimits::default(),
+        resend_verification: RouteRateLimits::default(),
+    }
+}
+
+// Settings the tests change from one app to the other
+struct TestAppOptions {
+    invite_only_signup: bool,
+    uniform_signup_response: bool,
+    password_policy: PasswordPolicy,
+    rate_limit_policy: RateLimitPolicy,
+}
+
+impl Default for TestAppOptions {
+    fn default() -> Self {
+        TestAppOptions {
+            invite_only_signup: false,
+            uniform_signup_response: false,
+            password_policy: lenient_password_policy(),
+            rate_limit_policy: unlimited_rate_limit_policy(),
+        }
+    }
+}
+
 impl TestApp {
     pub async fn new() -> Self {
+        Self::build(TestAppOptions::default()).await
+    }
+
+    // An app rejecting signups without an invitation
+    pub async fn new_invite_only() -> Self {
+        Self::build(TestAppOptions {
+            invite_only_signup: true,
+            ..TestAppOptions::default()
+        })
+        .await
+    }
+
+    // An app answering signups with a taken email like new ones
+    #[allow(dead_code)]
+    pub async fn new_with_uniform_signup_response() -> Self {
+        Self::build(TestAppOptions {
+            uniform_signup_response: true,
+            ..TestAppOptions::default()
+        })
+        .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn new_with_password_policy(password_policy: PasswordPolicy) -> Self {
+        Self::build(TestAppOptions {
+            password_policy,
+            ..TestAppOptions::default()
+        })
+        .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn new_with_rate_limit_policy(rate_limit_policy: RateLimitPolicy) -> Self {
+        Self::build(TestAppOptions {
+            rate_limit_policy,
+            ..TestAppOptions::default()
+        })
+        .await
+    }
+
+    async fn build(options: TestAppOptions) -> Self {
         let (pg_pool, db_name) = configure_postgresql().await;
         // let user_store = Arc::new(RwLock::new(HashmapUserStore::default()));
-        let user_store = Arc::new(RwLock::new(PostgresUserStore::new(pg_pool)));
+        let user_store = Arc::new(RwLock::new(PostgresUserStore::new(pg_pool.clone())));
+        let role_store = Arc::new(RwLock::new(PostgresRoleStore::new(pg_pool.clone())));
+        let organization_store = Arc::new(RwLock::new(PostgresOrganizationStore::new(pg_pool.clone())));
+        let invitation_store = Arc::new(RwLock::new(PostgresInvitationStore::new(pg_pool.clone())));
+        let password_history_store = Arc::new(RwLock::new(PostgresPasswordHistoryStore::new(pg_pool.clone())));
+        let audit_log = Arc::new(RwLock::new(PostgresAuditLog::new(pg_pool.clone())));
+        let device_history_store = Arc::new(RwLock::new(PostgresDeviceHistoryStore::new(pg_pool)));
 
         let redis_connection = get_redis_client(REDIS_HOST_NAME.to_owned()).expect("Couldn't get Redis connection");
         let banned_token_store = Arc::new(RwLock::new(RedisBannedTokenStore::new(
@@ -42,17 +152,50 @@ impl TestApp {
             redis_connection.get_connection().unwrap(),
         )));
         let email_client = Arc::new(RwLock::new(MockEmailClient::default()));
+        let one_time_token_store = Arc::new(RwLock::new(RedisOneTimeTokenStore::new(
+            redis_connection.get_connection().unwrap(),
+        )));
 
-        let app_state = AppState::new(
-            user_store,
-            banned_token_store.clone(),
-            two_fa_code_store.clone(),
-            email_client.clone(),
-        );
+        let trusted_device_store = Arc::new(RwLock::new(RedisTrustedDeviceStore::new(
+            redis_connection.get_connection().unwrap(),
+        )));
+
+        let email_change_store = Arc::new(RwLock::new(RedisEmailChangeStore::new(
+            redis_connection.get_connection().unwrap(),
+        )));
+
+        let login_failure_store = Arc::new(RwLock::new(RedisLoginFailureStore::new(
+            redis_connection.get_connection().unwrap(),
+        )));
+
+        let breached_password_store = Arc::new(RwLock::new(HashmapBreachedPasswordStore::default()));
+
+        let app_state = AppState {
+            user_store: user_store.clone(),
+            banned_token_store: banned_token_store.clone(),
+            two_fa_code_store: two_fa_code_store.clone(),
+            email_client: email_client.clone(),
+            one_time_token_store,
+            trusted_device_store,
+            email_change_store,
+            login_failure_store,
+            role_store: role_store.clone(),
+            organization_store,
+            invitation_store,
+            invite_only_signup: options.invite_only_signup,
+            uniform_signup_response: options.uniform_signup_response,
+            breached_password_store: breached_password_store.clone(),
+            password_history_store,
+            password_policy: options.password_policy,
+            rate_limit_store: Arc::new(RwLock::new(HashmapRateLimitStore::default())),
+            rate_limit_policy: options.rate_limit_policy,
+            audit_log,
+            device_history_store,
+        };
 
         let cookie_jar = Arc::new(Jar::default());
 
-        let app = Application::build(app_state, test::APP_ADDRESS)
+        let app = Application::build(app_state.clone(), test::APP_ADDRESS)
             .await
             .expect("Failed to build app");
 
@@ -69,9 +212,13 @@ impl TestApp {
             address,
             http_client,
             cookie_jar,
+            user_store,
+            role_store,
             banned_token_store: banned_token_store.clone(),
             two_fa_code_store: two_fa_code_store.clone(),
             email_client: email_client.clone(),
+            breached_password_store,
+            app_state,
             db_name,
             cleaned_up: false,
         }
@@ -110,6 +257,19 @@ impl TestApp {
             .expect("Failed to execute login request.")
     }
 
+    pub async fn post_login_with_user_agent<Body>(&self, body: &Body, user_agent: &str) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/login", &self.address))
+            .header(reqwest::header::USER_AGENT, user_agent)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute login request.")
+    }
+
     pub async fn post_logout(&self) -> reqwest::Response {
         self.http_client
             .post(&format!("{}/logout", &self.address))
@@ -142,6 +302,537 @@ impl TestApp {
             .expect("Failed to execute request.")
     }
 
+    pub async fn get_trusted_devices(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/trusted-devices", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn delete_trusted_device(&self, device_id: &str) -> reqwest::Response {
+        self.http_client
+            .delete(format!("{}/trusted-devices/{}", &self.address, device_id))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn delete_account<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .delete(format!("{}/account", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_account_export(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/account/export", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_me(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/me", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_login_history(&self, query: &[(&str, &str)]) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/me/login-history", &self.address))
+            .query(query)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn patch_me<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .patch(format!("{}/me", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn delete_admin_lockout(&self, email: &str) -> reqwest::Response {
+        self.http_client
+            .delete(format!("{}/admin/lockouts/{}", &self.address, email))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_users(&self, query: &[(&str, &str)]) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/users", &self.address))
+            .query(query)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_audit_events(&self, query: &[(&str, &str)]) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/audit-events", &self.address))
+            .query(query)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_hashing_metrics(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/metrics/hashing", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_user(&self, email: &str) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/users/{}", &self.address, email))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    // Runs one of the `/admin/users/{email}/...` actions, such as `disable`
+    pub async fn post_admin_user_action(&self, email: &str, action: &str) -> reqwest::Response {
+        self.http_client
+            .post(format!("{}/admin/users/{}/{}", &self.address, email, action))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_admin_import_users<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/users/import", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn put_admin_user_status<Body>(&self, email: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .put(format!("{}/admin/users/{}/status", &self.address, email))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_roles(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/roles", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn put_admin_role<Body>(&self, role: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .put(format!("{}/admin/roles/{}", &self.address, role))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn delete_admin_role(&self, role: &str) -> reqwest::Response {
+        self.http_client
+            .delete(format!("{}/admin/roles/{}", &self.address, role))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_invitations(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/invitations", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_admin_invitation<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/invitations", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn delete_admin_invitation(&self, id: &str) -> reqwest::Response {
+        self.http_client
+            .delete(format!("{}/admin/invitations/{}", &self.address, id))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn put_admin_user_role(&self, email: &str, role: &str) -> reqwest::Response {
+        self.http_client
+            .put(format!("{}/admin/users/{}/roles/{}", &self.address, email, role))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn delete_admin_user_role(&self, email: &str, role: &str) -> reqwest::Response {
+        self.http_client
+            .delete(format!("{}/admin/users/{}/roles/{}", &self.address, email, role))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_organizations(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/organizations", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_organization<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/organizations", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_switch_organization<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/organizations/switch", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_organization_members(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/organization/members", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn put_organization_member<Body>(&self, email: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .put(format!("{}/organization/members/{}", &self.address, email))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn delete_organization_member(&self, email: &str) -> reqwest::Response {
+        self.http_client
+            .delete(format!("{}/organization/members/{}", &self.address, email))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+    pub async fn post_join_organization<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/organizations/join", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    // Assigns the admin role directly in the store, assigning it through the API already requires an admin
+    pub async fn make_admin(&self, email: &str) {
+        let email = Email::parse(email.to_owned().into()).expect("Invalid email");
+        let admin_role = RoleName::parse(ADMIN_ROLE).expect("Invalid role name");
+
+        self.role_store
+            .write()
+            .await
+            .assign_role(&email, &admin_role)
+            .await
+            .expect("Failed to make user an admin");
+    }
+
+    // Creates a verified user directly in the store, for apps where signing up requires an invitation
+    pub async fn add_verified_user(&self, email: &str, password: &str) {
+        let email = Email::parse(email.to_owned().into()).expect("Invalid email");
+        let password = HashedPassword::parse(password.to_owned().into())
+            .await
+            .expect("Invalid password");
+
+        let mut user = User::new(email, Some(password), false);
+        user.email_verified = true;
+
+        self.user_store
+            .write()
+            .await
+            .add_user(user)
+            .await
+            .expect("Failed to add user");
+    }
+
+    // Changes the account state directly in the store, without signing the user out like the API does
+    pub async fn set_account_state(&self, email: &str, account_state: AccountState) {
+        let email = Email::parse(email.to_owned().into()).expect("Invalid email");
+
+        self.user_store
+            .write()
+            .await
+            .set_account_state(&email, account_state)
+            .await
+            .expect("Failed to set account state");
+    }
+
+    pub async fn get_verify_email(&self, token: &str) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/verify-email", &self.address))
+            .query(&[("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_resend_verification_email<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/verify-email/resend", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    // Follows the confirmation link of the most recent email sent to `recipient`. The password chosen on signup is
+    // dropped by the confirmation, so it is set back to `password123` through the reset link that comes with it.
+    pub async fn verify_email(&self, recipient: &str) {
+        let email = self.get_last_email(recipient).await.expect("No confirmation email sent");
+        let token = TestApp::get_link_token(&email.content);
+
+        let response = self.get_verify_email(&token).await;
+        assert_eq!(response.status().as_u16(), 200, "Failed to verify email");
+
+        let email = self.get_last_email(recipient).await.expect("No email sent");
+        if email.subject == "Reset your password" {
+            let token = TestApp::get_link_token(&email.content);
+            let response = self
+                .post_reset_password(&serde_json::json!({ "token": token, "password": "password123" }))
+                .await;
+            assert_eq!(response.status().as_u16(), 200, "Failed to set the password again");
+        }
+    }
+
+    pub async fn post_change_email<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/email/change", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_confirm_email_change(&self, token: &str) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/email/change/confirm", &self.address))
+            .query(&[("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_change_password<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/password/change", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_forgot_password<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/password/forgot", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_reset_password<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/password/reset", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_magic_link<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/login/magic-link", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_verify_magic_link(&self, token: &str) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/login/magic-link/verify", &self.address))
+            .query(&[("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_verify_magic_link<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/login/magic-link/verify", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_report_unrecognized_login(&self, token: &str) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/login/not-me", &self.address))
+            .query(&[("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_report_unrecognized_login<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/login/not-me", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    // Returns the most recent email sent to `recipient` through the mock email client
+    pub async fn get_last_email(&self, recipient: &str) -> Option<SentEmail> {
+        self.email_client
+            .read()
+            .await
+            .sent_emails()
+            .await
+            .into_iter()
+            .filter(|email| email.recipient.as_ref().expose_secret() == recipient)
+            .last()
+    }
+
+    // Returns the 2FA code from the most recent email sent to `recipient`
+    pub async fn get_last_two_fa_code(&self, recipient: &str) -> String {
+        let email = self.get_last_email(recipient).await.expect("No 2FA email sent");
+
+        email
+            .content
+            .split("Your 2FA code: ")
+            .nth(1)
+            .expect("No 2FA code found in email")
+            .trim()
+            .to_owned()
+    }
+
+    // Extracts the `token` query parameter from a link inside an email
+    pub fn get_link_token(content: &str) -> String {
+        content
+            .split("token=")
+            .nth(1)
+            .and_then(|rest| rest.split(|c: char| c.is_whitespace() || c == '&').next())
+            .expect("No token found in email")
+            .to_owned()
+    }
+
+    // Opens the link to `path` inside an email on the test server, the way a browser would
+    pub async fn open_email_link(&self, content: &str, path: &str) -> reqwest::Response {
+        let prefix = format!("{}{}", AUTH_SERVICE_URL.as_str(), path);
+        let link = content
+            .split_whitespace()
+            .map(|word| word.trim_end_matches([',', '.']))
+            .find(|word| {
+                word.strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('?'))
+            })
+            .expect("No link found in email");
+
+        self.http_client
+            .get(link.replacen(AUTH_SERVICE_URL.as_str(), &self.address, 1))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
     pub fn get_random_email() -> String {
         format!("{}@example.com", Uuid::new_v4())
     }
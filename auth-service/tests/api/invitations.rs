@@ -0,0 +1,298 @@
+use crate::helpers::TestApp;
+use auth_service::domain::error::ErrorResponse;
+use auth_service::routes::{
+    InvitationListResponse, InvitationResponse, MembershipListResponse, OrganizationResponse, VerifyTokenResponse,
+};
+use auth_service::utils::constants::env::JWT_COOKIE_NAME;
+use reqwest::StatusCode;
+use serde_json::json;
+
+async fn signup(app: &TestApp, email: &str) {
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "password123",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    app.verify_email(email).await;
+}
+
+// Logs in and returns the auth token
+async fn login(app: &TestApp, email: &str) -> String {
+    let response = app.post_login(&json!({ "email": email, "password": "password123" })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    auth_token(&response)
+}
+
+fn auth_token(response: &reqwest::Response) -> String {
+    response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned()
+}
+
+async fn login_as_admin(app: &TestApp) -> String {
+    let admin_email = TestApp::get_random_email();
+    signup(app, &admin_email).await;
+    app.make_admin(&admin_email).await;
+
+    login(app, &admin_email).await;
+
+    admin_email
+}
+
+async fn invite<Body>(app: &TestApp, body: &Body) -> InvitationResponse
+where
+    Body: serde::Serialize,
+{
+    let response = app.post_admin_invitation(body).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    response
+        .json::<InvitationResponse>()
+        .await
+        .expect("Could not deserialize response body to InvitationResponse")
+}
+
+// Extracts the invite token from the link in the last invitation sent to `email`
+async fn get_invite_token(app: &TestApp, email: &str) -> String {
+    let invitation = app.get_last_email(email).await.expect("No invitation email sent");
+    assert_eq!(invitation.subject, "You have been invited");
+
+    invitation
+        .content
+        .split("invite=")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .expect("No invite token found in email")
+        .to_owned()
+}
+
+async fn signup_with_invite(app: &TestApp, email: &str, invite: &str) -> reqwest::Response {
+    app.post_signup(&json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": false,
+        "invite": invite
+    }))
+    .await
+}
+
+async fn list_invitation_emails(app: &TestApp) -> Vec<String> {
+    let response = app.get_admin_invitations().await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    response
+        .json::<InvitationListResponse>()
+        .await
+        .expect("Could not deserialize response body to InvitationListResponse")
+        .invitations
+        .into_iter()
+        .map(|invitation| invitation.email)
+        .collect()
+}
+
+#[tokio::test]
+async fn should_return_403_if_signing_up_without_invitation_when_invite_only() {
+    let mut app = TestApp::new_invite_only().await;
+
+    let response = app
+        .post_signup(&json!({
+            "email": TestApp::get_random_email(),
+            "password": "password123",
+            "requires2FA": false
+        }))
+        .await;
+
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(
+        response
+            .json::<ErrorResponse>()
+            .await
+            .expect("Could not deserialize response body to ErrorResponse")
+            .error,
+        "Invitation required".to_owned()
+    );
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_sign_up_invited_user_with_verified_email() {
+    let mut app = TestApp::new_invite_only().await;
+
+    let admin_email = TestApp::get_random_email();
+    app.add_verified_user(&admin_email, "password123").await;
+    app.make_admin(&admin_email).await;
+    login(&app, &admin_email).await;
+
+    let invited_email = TestApp::get_random_email();
+    let invitation = invite(&app, &json!({ "email": invited_email })).await;
+    assert_eq!(invitation.email, invited_email);
+    assert_eq!(invitation.invited_by, admin_email);
+    assert_eq!(list_invitation_emails(&app).await, vec![invited_email.clone()]);
+
+    // The link opens the sign up form, which sends the token along
+    let invitation_email = app.get_last_email(&invited_email).await.expect("No invitation email sent");
+    let response = app.open_email_link(&invitation_email.content, "/signup").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.text().await.unwrap().contains("signup-form"));
+
+    let token = get_invite_token(&app, &invited_email).await;
+    let response = signup_with_invite(&app, &invited_email, &token).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    // No verification email is needed before logging in
+    login(&app, &invited_email).await;
+
+    login(&app, &admin_email).await;
+    assert!(list_invitation_emails(&app).await.is_empty());
+
+    let response = signup_with_invite(&app, &invited_email, &token).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_give_invited_user_the_role_they_were_invited_with() {
+    let mut app = TestApp::new().await;
+
+    login_as_admin(&app).await;
+
+    let invited_email = TestApp::get_random_email();
+    invite(&app, &json!({ "email": invited_email, "role": "admin" })).await;
+
+    let token = get_invite_token(&app, &invited_email).await;
+    signup_with_invite(&app, &invited_email, &token).await;
+
+    let token = login(&app, &invited_email).await;
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+    let grants = response
+        .json::<VerifyTokenResponse>()
+        .await
+        .expect("Could not deserialize response body to VerifyTokenResponse");
+    assert_eq!(grants.roles, vec!["admin", "user"]);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_add_invited_user_to_the_organization() {
+    let mut app = TestApp::new().await;
+
+    login_as_admin(&app).await;
+    let response = app.post_organization(&json!({ "name": "Acme" })).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let organization = response
+        .json::<OrganizationResponse>()
+        .await
+        .expect("Could not deserialize response body to OrganizationResponse");
+
+    let invited_email = TestApp::get_random_email();
+    let invitation = invite(
+        &app,
+        &json!({ "email": invited_email, "role": "org-admin", "organizationId": organization.id }),
+    )
+    .await;
+    assert_eq!(invitation.role, Some("org-admin".to_owned()));
+
+    let token = get_invite_token(&app, &invited_email).await;
+    signup_with_invite(&app, &invited_email, &token).await;
+    login(&app, &invited_email).await;
+
+    let organizations = app
+        .get_organizations()
+        .await
+        .json::<MembershipListResponse>()
+        .await
+        .expect("Could not deserialize response body to MembershipListResponse")
+        .organizations;
+    assert_eq!(organizations.len(), 1);
+    assert_eq!(organizations[0].name, "Acme");
+    assert_eq!(organizations[0].roles, vec!["org-admin"]);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_invite_is_invalid() {
+    let mut app = TestApp::new().await;
+
+    login_as_admin(&app).await;
+
+    let invited_email = TestApp::get_random_email();
+    let invitation = invite(&app, &json!({ "email": invited_email })).await;
+    let token = get_invite_token(&app, &invited_email).await;
+
+    // Issued for someone else
+    let response = signup_with_invite(&app, &TestApp::get_random_email(), &token).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = signup_with_invite(&app, &invited_email, "invalid").await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app.delete_admin_invitation(&invitation.id).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app.delete_admin_invitation(&invitation.id).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = signup_with_invite(&app, &invited_email, &token).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_invite_existing_users_or_unknown_roles() {
+    let mut app = TestApp::new().await;
+
+    let admin_email = login_as_admin(&app).await;
+
+    let response = app.post_admin_invitation(&json!({ "email": admin_email })).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let test_cases = [
+        (json!({ "email": "invalid" }), StatusCode::BAD_REQUEST),
+        (
+            json!({ "email": TestApp::get_random_email(), "role": "Not A Role" }),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            json!({ "email": TestApp::get_random_email(), "role": "missing" }),
+            StatusCode::NOT_FOUND,
+        ),
+        (
+            json!({ "email": TestApp::get_random_email(), "organizationId": uuid::Uuid::new_v4() }),
+            StatusCode::NOT_FOUND,
+        ),
+    ];
+
+    for (body, status) in test_cases {
+        let response = app.post_admin_invitation(&body).await;
+        assert_eq!(response.status(), status, "Failed for input: {:?}", body);
+    }
+
+    assert!(list_invitation_emails(&app).await.is_empty());
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_if_not_allowed_to_invite() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    login(&app, &random_email).await;
+
+    let response = app
+        .post_admin_invitation(&json!({ "email": TestApp::get_random_email() }))
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app.get_admin_invitations().await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    app.clean_up().await;
+}
@@ -1,11 +1,21 @@
 use crate::helpers::TestApp;
+use argon2::password_hash::{rand_core::OsRng, SaltString};
+use argon2::{Algorithm, Argon2, Params, PasswordHasher, Version};
+use auth_service::domain::account_status::{AccountState, AccountStatus};
 use auth_service::domain::data_stores::LoginAttemptId;
 use auth_service::domain::email::Email;
+use auth_service::domain::error::ErrorResponse;
+use auth_service::domain::hashed_password::{HashedPassword, HashingParams};
+use auth_service::domain::user::User;
 use auth_service::routes::TwoFactorAuthResponse;
 use auth_service::utils::constants::env::JWT_COOKIE_NAME;
+use auth_service::utils::constants::LOGIN_LOCKOUT_THRESHOLD;
+use chrono::{Duration, Utc};
 use fake::faker::internet::en::SafeEmail;
 use fake::Fake;
 use reqwest::StatusCode;
+use secrecy::ExposeSecret;
+use std::time::{Duration as StdDuration, Instant};
 
 #[tokio::test]
 async fn should_return_422_if_malformed_credentials() {
@@ -40,8 +50,10 @@ async fn should_return_400_if_bad_request() {
 #[tokio::test]
 async fn should_return_401_if_incorrect_credentials() {
     let mut app = TestApp::new().await;
+    // Failed logins are counted per email in a store shared by every test, so the address must be unique
+    let random_email = TestApp::get_random_email();
     let body = serde_json::json!({
-          "email": "user@example.com",
+          "email": random_email,
           "password": "zaq1@WSX",
           "requires2FA": false,
     });
@@ -50,7 +62,7 @@ async fn should_return_401_if_incorrect_credentials() {
     assert_eq!(response.status(), StatusCode::CREATED);
 
     let body = serde_json::json!({
-          "email": "user@example.com",
+          "email": random_email,
           "password": "zaq2@WSX",
     });
 
@@ -75,6 +87,8 @@ async fn should_return_200_if_valid_credentials_and_2fa_disabled() {
 
     assert_eq!(response.status().as_u16(), 201);
 
+    app.verify_email(&random_email).await;
+
     let login_body = serde_json::json!({
         "email": random_email,
         "password": "password123",
@@ -109,11 +123,19 @@ async fn should_return_206_if_valid_credentials_and_2fa_enabled() {
 
     assert_eq!(response.status().as_u16(), 201);
 
+    app.verify_email(&random_email).await;
+
     let mut login_body = signup_body.clone();
     login_body.as_object_mut().unwrap().remove("requires2FA");
 
     let response = app.post_login(&login_body).await;
 
+    assert_eq!(response.status().as_u16(), 206);
+    assert!(
+        !response.cookies().any(|cookie| cookie.name() == JWT_COOKIE_NAME),
+        "The auth cookie must not be set before 2FA is verified"
+    );
+
     let json_body = response
         .json::<TwoFactorAuthResponse>()
         .await
@@ -131,3 +153,324 @@ async fn should_return_206_if_valid_credentials_and_2fa_enabled() {
     );
     app.clean_up().await;
 }
+
+async fn signup_verified(app: &TestApp, email: &str) {
+    let response = app
+        .post_signup(&serde_json::json!({
+            "email": email,
+            "password": "password123",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    app.verify_email(email).await;
+}
+
+async fn fail_logins(app: &TestApp, email: &str, attempts: u32) -> reqwest::Response {
+    let body = serde_json::json!({ "email": email, "password": "wrongPassword123" });
+
+    for _ in 1..attempts {
+        let response = app.post_login(&body).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    app.post_login(&body).await
+}
+
+#[tokio::test]
+async fn should_return_423_once_too_many_logins_failed() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup_verified(&app, &random_email).await;
+
+    let response = fail_logins(&app, &random_email, *LOGIN_LOCKOUT_THRESHOLD).await;
+    assert_eq!(response.status(), StatusCode::LOCKED);
+    assert_eq!(
+        response
+            .json::<ErrorResponse>()
+            .await
+            .expect("Could not deserialize response body to ErrorResponse")
+            .error,
+        "Account locked".to_owned()
+    );
+
+    // The correct password doesn't help while the account is locked
+    let response = app
+        .post_login(&serde_json::json!({ "email": random_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status(), StatusCode::LOCKED);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_notify_user_when_account_gets_locked() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup_verified(&app, &random_email).await;
+
+    fail_logins(&app, &random_email, *LOGIN_LOCKOUT_THRESHOLD).await;
+
+    let email = app.get_last_email(&random_email).await.expect("No lockout email sent");
+    assert_eq!(email.subject, "Your account was locked");
+
+    let response = app.open_email_link(&email.content, "/password/forgot").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.text().await.unwrap().contains("forgot-form"));
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_reset_failed_logins_after_successful_login() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup_verified(&app, &random_email).await;
+
+    let response = fail_logins(&app, &random_email, *LOGIN_LOCKOUT_THRESHOLD - 1).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .post_login(&serde_json::json!({ "email": random_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = fail_logins(&app, &random_email, *LOGIN_LOCKOUT_THRESHOLD - 1).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_lock_unknown_emails_the_same_way() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+
+    let response = fail_logins(&app, &random_email, *LOGIN_LOCKOUT_THRESHOLD).await;
+
+    assert_eq!(response.status(), StatusCode::LOCKED);
+    assert!(app.get_last_email(&random_email).await.is_none());
+    app.clean_up().await;
+}
+
+// Median time of failed logins with a wrong password, one after the other and fewer per email than a lockout takes
+async fn median_failed_login_time(app: &TestApp, emails: &[String]) -> StdDuration {
+    let mut times = Vec::new();
+
+    for email in emails {
+        for _ in 1..*LOGIN_LOCKOUT_THRESHOLD {
+            let started = Instant::now();
+            let response = app
+                .post_login(&serde_json::json!({ "email": email, "password": "wrongPassword123" }))
+                .await;
+            times.push(started.elapsed());
+
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+    }
+
+    times.sort();
+    times[times.len() / 2]
+}
+
+// Without a password verification, a login for an unknown email returns in a fraction of the time a wrong password
+// takes. Only a gap that large is checked, so that noise doesn't make the test flaky.
+#[tokio::test]
+async fn should_take_as_long_for_unknown_emails_as_for_wrong_passwords() {
+    let mut app = TestApp::new().await;
+
+    let existing_emails = [TestApp::get_random_email(), TestApp::get_random_email()];
+    for email in &existing_emails {
+        signup_verified(&app, email).await;
+    }
+    let unknown_emails = [TestApp::get_random_email(), TestApp::get_random_email()];
+
+    // Warms up the dummy hash, made on the first login for an unknown email
+    median_failed_login_time(&app, &[TestApp::get_random_email()]).await;
+
+    let existing = median_failed_login_time(&app, &existing_emails).await;
+    let unknown = median_failed_login_time(&app, &unknown_emails).await;
+
+    assert!(
+        unknown * 2 >= existing,
+        "Unknown emails took {:?}, wrong passwords {:?}",
+        unknown,
+        existing
+    );
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_take_as_long_for_users_without_password_as_for_wrong_passwords() {
+    let mut app = TestApp::new().await;
+
+    let with_password = TestApp::get_random_email();
+    signup_verified(&app, &with_password).await;
+
+    let without_password = TestApp::get_random_email();
+    let response = app
+        .post_signup(&serde_json::json!({ "email": without_password, "requires2FA": false }))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    median_failed_login_time(&app, &[TestApp::get_random_email()]).await;
+
+    let existing = median_failed_login_time(&app, &[with_password]).await;
+    let passwordless = median_failed_login_time(&app, &[without_password]).await;
+
+    assert!(
+        passwordless * 2 >= existing,
+        "Users without password took {:?}, wrong passwords {:?}",
+        passwordless,
+        existing
+    );
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_if_account_is_not_active() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup_verified(&app, &random_email).await;
+
+    let test_cases = [
+        (AccountStatus::Pending, "Account pending"),
+        (AccountStatus::Suspended, "Account suspended"),
+        (AccountStatus::Disabled, "Account disabled"),
+    ];
+
+    for (status, error) in test_cases {
+        app.set_account_state(&random_email, AccountState::new(status, None)).await;
+
+        let response = app
+            .post_login(&serde_json::json!({ "email": random_email, "password": "password123" }))
+            .await;
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(
+            response
+                .json::<ErrorResponse>()
+                .await
+                .expect("Could not deserialize response body to ErrorResponse")
+                .error,
+            error.to_owned()
+        );
+    }
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_200_once_suspension_has_ended() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup_verified(&app, &random_email).await;
+
+    let account_state = AccountState {
+        suspended_until: Some(Utc::now() - Duration::seconds(1)),
+        ..AccountState::new(AccountStatus::Suspended, None)
+    };
+    app.set_account_state(&random_email, account_state).await;
+
+    let response = app
+        .post_login(&serde_json::json!({ "email": random_email, "password": "password123" }))
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_upgrade_password_hash_made_with_outdated_params() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    let email = Email::parse(random_email.clone().into()).unwrap();
+    let outdated_params = HashingParams {
+        memory_cost_kib: 8192,
+        time_cost: 1,
+        parallelism: 1,
+    };
+    let old_password = HashedPassword::parse_with_params("password123".into(), outdated_params)
+        .await
+        .unwrap();
+
+    assert_password_hash_upgraded_on_login(&app, &email, old_password).await;
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_upgrade_password_hash_made_without_pepper() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    let email = Email::parse(random_email.clone().into()).unwrap();
+
+    // Hashed the way passwords were before peppering, with the current parameters
+    let params = HashingParams::default();
+    let argon2 = Argon2::new(
+        Algorithm::Argon2id,
+        Version::V0x13,
+        Params::new(params.memory_cost_kib, params.time_cost, params.parallelism, None).unwrap(),
+    );
+    let hash = argon2
+        .hash_password(b"password123", &SaltString::generate(&mut OsRng))
+        .unwrap()
+        .to_string();
+    let old_password = HashedPassword::parse_password_hash(hash.into()).unwrap();
+    assert!(old_password.needs_rehash(&params), "PASSWORD_PEPPER must be set");
+
+    assert_password_hash_upgraded_on_login(&app, &email, old_password).await;
+    app.clean_up().await;
+}
+
+// Logs in as a user with the given hash, which must then be replaced by one made the current way
+async fn assert_password_hash_upgraded_on_login(app: &TestApp, email: &Email, old_password: HashedPassword) {
+    let mut user = User::new(email.clone(), Some(old_password.clone()), false);
+    user.email_verified = true;
+    app.user_store.write().await.add_user(user).await.unwrap();
+
+    let body = serde_json::json!({ "email": email.as_ref().expose_secret(), "password": "password123" });
+    let response = app.post_login(&body).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The hash is replaced in the background, after the response was sent
+    let mut new_password = None;
+    for _ in 0..50 {
+        let password = app.user_store.read().await.get_user(email).await.unwrap().password;
+        if password.as_ref() != Some(&old_password) {
+            new_password = password;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+
+    let new_password = new_password.expect("Password hash was not upgraded");
+    assert!(!new_password.needs_rehash(&HashingParams::default()));
+
+    let response = app.post_login(&body).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn should_keep_password_hash_made_with_current_params() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup_verified(&app, &random_email).await;
+
+    let email = Email::parse(random_email.clone().into()).unwrap();
+    let password = app.user_store.read().await.get_user(&email).await.unwrap().password;
+
+    let response = app
+        .post_login(&serde_json::json!({ "email": random_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    assert_eq!(app.user_store.read().await.get_user(&email).await.unwrap().password, password);
+    app.clean_up().await;
+}
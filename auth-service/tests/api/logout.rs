@@ -1,5 +1,6 @@
 use crate::helpers::TestApp;
 use auth_service::domain::email::Email;
+use auth_service::domain::rbac::Grants;
 use auth_service::utils::auth::generate_auth_cookie;
 use auth_service::utils::constants::env::JWT_COOKIE_NAME;
 use reqwest::Url;
@@ -36,7 +37,7 @@ async fn should_return_200_if_valid_jwt_cookie() {
 
     let fake_email = Email::parse(TestApp::get_random_email().into()).unwrap();
 
-    let jwt = generate_auth_cookie(&fake_email).expect("Failed to generate auth cookie");
+    let jwt = generate_auth_cookie(&fake_email, Grants::default()).expect("Failed to generate auth cookie");
 
     // add invalid cookie
     app.cookie_jar.add_cookie_str(
@@ -61,7 +62,7 @@ async fn should_return_400_if_logout_called_twice_in_a_row() {
     let mut app = TestApp::new().await;
     let fake_email = Email::parse(TestApp::get_random_email().into()).unwrap();
 
-    let jwt = generate_auth_cookie(&fake_email).expect("Failed to generate auth cookie");
+    let jwt = generate_auth_cookie(&fake_email, Grants::default()).expect("Failed to generate auth cookie");
 
     // add invalid cookie
     app.cookie_jar.add_cookie_str(
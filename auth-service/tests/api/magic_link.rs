@@ -0,0 +1,273 @@
+use crate::helpers::TestApp;
+use auth_service::domain::audit::{AuditEventType, AuditOutcome, AuditSearch};
+use auth_service::domain::email::Email;
+use auth_service::domain::error::ErrorResponse;
+use auth_service::domain::rate_limit::{RateLimit, RateLimitPolicy, RouteRateLimits};
+use auth_service::routes::{AuditEventList, MagicLinkResponse};
+use auth_service::utils::constants::env::JWT_COOKIE_NAME;
+use serde_json::json;
+
+#[tokio::test]
+async fn should_return_422_if_malformed_input() {
+    let mut app = TestApp::new().await;
+
+    let response = app.post_magic_link(&json!({ "_email": "user@example.com" })).await;
+
+    assert_eq!(response.status().as_u16(), 422);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_429_once_email_requested_too_many_links() {
+    let per_email = RouteRateLimits {
+        per_ip: None,
+        per_email: Some(RateLimit {
+            max_requests: 2,
+            window_seconds: 60,
+        }),
+    };
+    let mut app = TestApp::new_with_rate_limit_policy(RateLimitPolicy {
+        login: RouteRateLimits::default(),
+        signup: RouteRateLimits::default(),
+        verify_2fa: RouteRateLimits::default(),
+        magic_link: per_email,
+        forgot_password: RouteRateLimits::default(),
+        resend_verification: RouteRateLimits::default(),
+    })
+    .await;
+
+    let random_email = TestApp::get_random_email();
+    for _ in 0..2 {
+        let response = app.post_magic_link(&json!({ "email": random_email })).await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    let response = app.post_magic_link(&json!({ "email": random_email })).await;
+    assert_eq!(response.status().as_u16(), 429);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_email() {
+    let mut app = TestApp::new().await;
+
+    let response = app.post_magic_link(&json!({ "email": "invalid_email" })).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_200_without_sending_email_if_user_does_not_exist() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+
+    let response = app.post_magic_link(&json!({ "email": random_email })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(app.get_last_email(&random_email).await.is_none());
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_set_auth_cookie_if_valid_magic_link() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+
+    let response = app
+        .post_signup(&json!({
+            "email": random_email,
+            "password": "password123",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.post_magic_link(&json!({ "email": random_email })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let email = app.get_last_email(&random_email).await.expect("No magic link email sent");
+    let token = TestApp::get_link_token(&email.content);
+
+    // Opening the link only asks for confirmation, so a mail scanner can't use it up
+    let response = app.get_verify_magic_link(&token).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.cookies().all(|cookie| cookie.name() != JWT_COOKIE_NAME));
+    response
+        .json::<MagicLinkResponse>()
+        .await
+        .expect("Could not deserialize response body to MagicLinkResponse");
+
+    let response = app.post_verify_magic_link(&json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let auth_cookie = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found");
+
+    assert!(!auth_cookie.value().is_empty());
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_work_for_users_without_password() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+
+    let response = app
+        .post_signup(&json!({
+            "email": random_email,
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    // A passwordless account must never be accessible through the password login
+    let response = app.post_login(&json!({ "email": random_email, "password": "" })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.post_magic_link(&json!({ "email": random_email })).await;
+
+    let email = app.get_last_email(&random_email).await.expect("No magic link email sent");
+    let token = TestApp::get_link_token(&email.content);
+
+    let response = app.post_verify_magic_link(&json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_magic_link_is_used_twice() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+
+    app.post_signup(&json!({
+        "email": random_email,
+        "password": "password123",
+        "requires2FA": false
+    }))
+    .await;
+
+    app.post_magic_link(&json!({ "email": random_email })).await;
+
+    let email = app.get_last_email(&random_email).await.expect("No magic link email sent");
+    let token = TestApp::get_link_token(&email.content);
+
+    let response = app.post_verify_magic_link(&json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_verify_magic_link(&json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    assert_eq!(
+        response
+            .json::<ErrorResponse>()
+            .await
+            .expect("Could not deserialize response body to ErrorResponse")
+            .error,
+        "Invalid token".to_owned()
+    );
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_invalid_token() {
+    let mut app = TestApp::new().await;
+
+    let response = app.get_verify_magic_link("invalid_token").await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_verify_magic_link(&json!({ "token": "invalid_token" })).await;
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_record_magic_link_login_in_audit_log() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+
+    let response = app
+        .post_signup(&json!({
+            "email": random_email,
+            "password": "password123",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.post_magic_link(&json!({ "email": random_email })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let email = app.get_last_email(&random_email).await.expect("No magic link email sent");
+    let token = TestApp::get_link_token(&email.content);
+
+    let response = app.post_verify_magic_link(&json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.get_login_history(&[]).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let list = response
+        .json::<AuditEventList>()
+        .await
+        .expect("Could not deserialize response body to AuditEventList");
+    assert_eq!(list.events.len(), 1);
+    assert_eq!(list.events[0].event_type, "login");
+    assert_eq!(list.events[0].outcome, "success");
+    assert_eq!(list.events[0].actor.as_deref(), Some(random_email.as_str()));
+
+    let requests = app
+        .app_state
+        .audit_log
+        .read()
+        .await
+        .list_events(&AuditSearch {
+            actor: Some(Email::parse(random_email.clone().into()).unwrap()),
+            event_types: vec![AuditEventType::MagicLink],
+            offset: 0,
+            limit: 10,
+        })
+        .await
+        .unwrap();
+    assert_eq!(requests.total, 1);
+    assert_eq!(requests.events[0].outcome, AuditOutcome::Success);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_drop_password_chosen_before_the_address_was_proven() {
+    let mut app = TestApp::new().await;
+
+    // Someone signs up with an address they don't own
+    let random_email = TestApp::get_random_email();
+    let response = app
+        .post_signup(&json!({
+            "email": random_email,
+            "password": "attackerPassword123",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    // The owner signs in with a magic link instead of the confirmation link
+    let response = app.post_magic_link(&json!({ "email": random_email })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let email = app.get_last_email(&random_email).await.expect("No magic link email sent");
+    let response = app
+        .post_verify_magic_link(&json!({ "token": TestApp::get_link_token(&email.content) }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "attackerPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
@@ -1,6 +1,21 @@
 mod helpers;
 mod login;
 mod logout;
+mod magic_link;
 mod verify_token;
 mod verify_2fa;
 mod signup;
+mod trusted_devices;
+mod verify_email;
+mod password_reset;
+mod change_password;
+mod change_email;
+mod account;
+mod me;
+mod admin;
+mod roles;
+mod organizations;
+mod invitations;
+mod rate_limit;
+mod audit;
+mod unrecognized_login;
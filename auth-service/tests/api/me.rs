@@ -0,0 +1,131 @@
+use crate::helpers::TestApp;
+use auth_service::domain::error::ErrorResponse;
+use auth_service::routes::MeResponse;
+use serde_json::json;
+
+async fn signup_and_login(app: &TestApp, email: &str) {
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "password123",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.verify_email(email).await;
+
+    let response = app.post_login(&json!({ "email": email, "password": "password123" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+async fn read_me(response: reqwest::Response) -> MeResponse {
+    assert_eq!(response.status().as_u16(), 200);
+
+    response
+        .json::<MeResponse>()
+        .await
+        .expect("Could not deserialize response body to MeResponse")
+}
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let mut app = TestApp::new().await;
+
+    let response = app.get_me().await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.patch_me(&json!({ "displayName": "Jane Doe" })).await;
+    assert_eq!(response.status().as_u16(), 400);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_identity_of_logged_in_user() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup_and_login(&app, &random_email).await;
+
+    let me = read_me(app.get_me().await).await;
+
+    assert_eq!(me.email, random_email);
+    assert_eq!(me.display_name, None);
+    assert!(me.email_verified);
+    assert!(!me.requires_2fa);
+
+    let last_login_at = me.last_login_at.expect("Last login not recorded");
+    assert!(last_login_at >= me.created_at);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_update_only_given_fields() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup_and_login(&app, &random_email).await;
+
+    let me = read_me(
+        app.patch_me(&json!({
+            "displayName": "Jane Doe",
+            "locale": "pl-PL",
+            "timezone": "Europe/Warsaw"
+        }))
+        .await,
+    )
+    .await;
+    assert_eq!(me.display_name.as_deref(), Some("Jane Doe"));
+
+    let me = read_me(app.patch_me(&json!({ "locale": "en-GB", "timezone": null })).await).await;
+
+    assert_eq!(me.display_name.as_deref(), Some("Jane Doe"));
+    assert_eq!(me.locale.as_deref(), Some("en-GB"));
+    assert_eq!(me.timezone, None);
+    assert_eq!(read_me(app.get_me().await).await, me);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_profile() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup_and_login(&app, &random_email).await;
+
+    let test_cases = [
+        json!({ "displayName": "   " }),
+        json!({ "locale": "english" }),
+        json!({ "timezone": "Mars/Olympus Mons" }),
+    ];
+
+    for test_case in test_cases.iter() {
+        let response = app.patch_me(test_case).await;
+
+        assert_eq!(response.status().as_u16(), 400, "Failed for input: {:?}", test_case);
+        assert_eq!(
+            response
+                .json::<ErrorResponse>()
+                .await
+                .expect("Could not deserialize response body to ErrorResponse")
+                .error,
+            "Invalid profile".to_owned()
+        );
+    }
+
+    assert_eq!(read_me(app.get_me().await).await.locale, None);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_422_if_malformed_input() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup_and_login(&app, &random_email).await;
+
+    let response = app.patch_me(&json!({ "displayName": 42 })).await;
+
+    assert_eq!(response.status().as_u16(), 422);
+    app.clean_up().await;
+}
@@ -0,0 +1,359 @@
+use crate::helpers::TestApp;
+use auth_service::domain::error::ErrorResponse;
+use auth_service::routes::{MeResponse, MemberListResponse, MembershipListResponse, OrganizationResponse, VerifyTokenResponse};
+use auth_service::utils::constants::env::JWT_COOKIE_NAME;
+use reqwest::StatusCode;
+use serde_json::json;
+
+async fn signup(app: &TestApp, email: &str) {
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "password123",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    app.verify_email(email).await;
+}
+
+// Logs in and returns the auth token, so that it can still be checked once someone else logs in
+async fn login(app: &TestApp, email: &str) -> String {
+    let response = app.post_login(&json!({ "email": email, "password": "password123" })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    auth_token(&response)
+}
+
+fn auth_token(response: &reqwest::Response) -> String {
+    response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned()
+}
+
+async fn login_as_admin(app: &TestApp) -> String {
+    let admin_email = TestApp::get_random_email();
+    signup(app, &admin_email).await;
+    app.make_admin(&admin_email).await;
+
+    login(app, &admin_email).await;
+
+    admin_email
+}
+
+async fn verify_token(app: &TestApp, token: &str) -> VerifyTokenResponse {
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    response
+        .json::<VerifyTokenResponse>()
+        .await
+        .expect("Could not deserialize response body to VerifyTokenResponse")
+}
+
+async fn create_organization(app: &TestApp, name: &str) -> OrganizationResponse {
+    let response = app.post_organization(&json!({ "name": name })).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    response
+        .json::<OrganizationResponse>()
+        .await
+        .expect("Could not deserialize response body to OrganizationResponse")
+}
+
+// Returns the new auth token
+async fn switch_organization(app: &TestApp, organization: &OrganizationResponse) -> String {
+    let response = app
+        .post_switch_organization(&json!({ "organizationId": organization.id }))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    auth_token(&response)
+}
+
+async fn list_organizations(app: &TestApp) -> MembershipListResponse {
+    let response = app.get_organizations().await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    response
+        .json::<MembershipListResponse>()
+        .await
+        .expect("Could not deserialize response body to MembershipListResponse")
+}
+
+// Follows the invitation emailed to the user
+async fn join_organization(app: &TestApp, email: &str) {
+    let message = app.get_last_email(email).await.expect("No invitation sent");
+    let token = TestApp::get_link_token(&message.content);
+
+    let response = app.post_join_organization(&json!({ "token": token })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+async fn list_member_emails(app: &TestApp) -> Vec<String> {
+    let response = app.get_organization_members().await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    response
+        .json::<MemberListResponse>()
+        .await
+        .expect("Could not deserialize response body to MemberListResponse")
+        .members
+        .into_iter()
+        .map(|member| member.email)
+        .collect()
+}
+
+#[tokio::test]
+async fn should_return_403_if_not_allowed_to_create_organization() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    login(&app, &random_email).await;
+
+    let response = app.post_organization(&json!({ "name": "Acme" })).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let organizations = list_organizations(&app).await;
+    assert!(organizations.organizations.is_empty());
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_organization() {
+    let mut app = TestApp::new().await;
+
+    login_as_admin(&app).await;
+
+    let test_cases = [
+        app.post_organization(&json!({ "name": "   " })).await,
+        app.post_switch_organization(&json!({ "organizationId": "acme" })).await,
+    ];
+
+    for response in test_cases {
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response
+                .json::<ErrorResponse>()
+                .await
+                .expect("Could not deserialize response body to ErrorResponse")
+                .error,
+            "Invalid organization".to_owned()
+        );
+    }
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_create_and_switch_organization() {
+    let mut app = TestApp::new().await;
+
+    let admin_email = TestApp::get_random_email();
+    signup(&app, &admin_email).await;
+    app.make_admin(&admin_email).await;
+    let old_token = login(&app, &admin_email).await;
+
+    let acme = create_organization(&app, " Acme ").await;
+    assert_eq!(acme.name, "Acme");
+
+    let organizations = list_organizations(&app).await.organizations;
+    assert_eq!(organizations.len(), 1);
+    assert_eq!(organizations[0].id, acme.id);
+    assert_eq!(organizations[0].roles, vec!["org-admin"]);
+    assert!(!organizations[0].active);
+
+    let token = switch_organization(&app, &acme).await;
+
+    let grants = verify_token(&app, &token).await;
+    assert_eq!(grants.organization_id, Some(acme.id));
+    assert_eq!(grants.roles, vec!["admin", "org-admin", "user"]);
+
+    let response = app.post_verify_token(&json!({ "token": old_token })).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let organizations = list_organizations(&app).await.organizations;
+    assert!(organizations[0].active);
+
+    let me = app
+        .get_me()
+        .await
+        .json::<MeResponse>()
+        .await
+        .expect("Could not deserialize response body to MeResponse");
+    assert_eq!(me.organization_id, Some(acme.id));
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_404_when_switching_to_organization_of_others() {
+    let mut app = TestApp::new().await;
+
+    login_as_admin(&app).await;
+    let acme = create_organization(&app, "Acme").await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    login(&app, &random_email).await;
+
+    let response = app.post_switch_organization(&json!({ "organizationId": acme.id })).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_without_active_organization() {
+    let mut app = TestApp::new().await;
+
+    login_as_admin(&app).await;
+
+    let response = app.get_organization_members().await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response
+            .json::<ErrorResponse>()
+            .await
+            .expect("Could not deserialize response body to ErrorResponse")
+            .error,
+        "No active organization".to_owned()
+    );
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_add_users_once_they_accept_the_invitation() {
+    let mut app = TestApp::new().await;
+
+    let admin_email = login_as_admin(&app).await;
+    let acme = create_organization(&app, "Acme").await;
+    switch_organization(&app, &acme).await;
+
+    let member_email = TestApp::get_random_email();
+    signup(&app, &member_email).await;
+
+    let response = app
+        .put_organization_member(&member_email, &json!({ "roles": ["org-admin"] }))
+        .await;
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    assert_eq!(list_member_emails(&app).await, vec![admin_email.clone()]);
+
+    let message = app.get_last_email(&member_email).await.expect("No invitation sent");
+    assert!(message.content.contains("Acme"));
+
+    let response = app.open_email_link(&message.content, "/organizations/join").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.text().await.unwrap().contains("join-form"));
+    assert_eq!(list_member_emails(&app).await, vec![admin_email.clone()]);
+
+    let token = TestApp::get_link_token(&message.content);
+    let response = app.post_join_organization(&json!({ "token": token })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(list_member_emails(&app).await, vec![admin_email, member_email.clone()]);
+
+    let response = app.post_join_organization(&json!({ "token": token })).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // Signed into the organization only once they pick it
+    let token = login(&app, &member_email).await;
+    assert_eq!(verify_token(&app, &token).await.organization_id, None);
+
+    let token = switch_organization(&app, &acme).await;
+    let grants = verify_token(&app, &token).await;
+    assert_eq!(grants.organization_id, Some(acme.id));
+    assert_eq!(grants.roles, vec!["org-admin", "user"]);
+    assert_eq!(grants.permissions, vec!["content:read", "members:read", "members:write"]);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_tell_whether_invited_email_has_an_account() {
+    let mut app = TestApp::new().await;
+
+    login_as_admin(&app).await;
+    let acme = create_organization(&app, "Acme").await;
+    switch_organization(&app, &acme).await;
+
+    let member_email = TestApp::get_random_email();
+    let unknown_email = TestApp::get_random_email();
+    signup(&app, &member_email).await;
+
+    for email in [&member_email, &unknown_email] {
+        let response = app.put_organization_member(email, &json!({ "roles": [] })).await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+
+    assert!(app.get_last_email(&unknown_email).await.is_none());
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_give_roles_with_permissions_the_caller_lacks() {
+    let mut app = TestApp::new().await;
+
+    login_as_admin(&app).await;
+    let acme = create_organization(&app, "Acme").await;
+    switch_organization(&app, &acme).await;
+
+    let member_email = TestApp::get_random_email();
+    let other_email = TestApp::get_random_email();
+    signup(&app, &member_email).await;
+    signup(&app, &other_email).await;
+
+    app.put_organization_member(&member_email, &json!({ "roles": ["org-admin"] }))
+        .await;
+    join_organization(&app, &member_email).await;
+    login(&app, &member_email).await;
+    switch_organization(&app, &acme).await;
+
+    let response = app
+        .put_organization_member(&other_email, &json!({ "roles": ["admin"] }))
+        .await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app
+        .put_organization_member(&other_email, &json!({ "roles": ["missing"] }))
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = app.put_organization_member(&other_email, &json!({ "roles": ["user"] })).await;
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_revoke_sessions_when_member_is_removed() {
+    let mut app = TestApp::new().await;
+
+    let admin_email = login_as_admin(&app).await;
+    let acme = create_organization(&app, "Acme").await;
+    switch_organization(&app, &acme).await;
+
+    let member_email = TestApp::get_random_email();
+    signup(&app, &member_email).await;
+    app.put_organization_member(&member_email, &json!({ "roles": [] })).await;
+    join_organization(&app, &member_email).await;
+
+    let token = login(&app, &member_email).await;
+
+    let response = app.delete_organization_member(&member_email).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    login(&app, &admin_email).await;
+    switch_organization(&app, &acme).await;
+
+    let response = app.delete_organization_member(&member_email).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    assert_eq!(list_member_emails(&app).await, vec![admin_email]);
+
+    let response = app.delete_organization_member(&member_email).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    app.clean_up().await;
+}
@@ -0,0 +1,410 @@
+use crate::helpers::TestApp;
+use auth_service::domain::error::ErrorResponse;
+use auth_service::domain::rate_limit::{RateLimit, RateLimitPolicy, RouteRateLimits};
+use auth_service::routes::TwoFactorAuthResponse;
+use auth_service::utils::constants::env::JWT_COOKIE_NAME;
+use serde_json::json;
+use std::time::Duration;
+
+async fn signup(app: &TestApp, email: &str, requires_2fa: bool) {
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "password123",
+            "requires2FA": requires_2fa
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.verify_email(email).await;
+}
+
+async fn request_reset_token(app: &TestApp, email: &str) -> String {
+    let response = app.post_forgot_password(&json!({ "email": email })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let email = app.get_last_email(email).await.expect("No password reset email sent");
+    assert_eq!(email.subject, "Reset your password");
+
+    TestApp::get_link_token(&email.content)
+}
+
+#[tokio::test]
+async fn should_return_422_if_malformed_input() {
+    let mut app = TestApp::new().await;
+
+    let response = app.post_forgot_password(&json!({ "_email": "user@example.com" })).await;
+    assert_eq!(response.status().as_u16(), 422);
+
+    let response = app.post_reset_password(&json!({ "token": "token" })).await;
+    assert_eq!(response.status().as_u16(), 422);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_429_once_email_requested_too_many_resets() {
+    let per_email = RouteRateLimits {
+        per_ip: None,
+        per_email: Some(RateLimit {
+            max_requests: 2,
+            window_seconds: 60,
+        }),
+    };
+    let mut app = TestApp::new_with_rate_limit_policy(RateLimitPolicy {
+        login: RouteRateLimits::default(),
+        signup: RouteRateLimits::default(),
+        verify_2fa: RouteRateLimits::default(),
+        magic_link: RouteRateLimits::default(),
+        forgot_password: per_email,
+        resend_verification: RouteRateLimits::default(),
+    })
+    .await;
+
+    let random_email = TestApp::get_random_email();
+    for _ in 0..2 {
+        let response = app.post_forgot_password(&json!({ "email": random_email })).await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    let response = app.post_forgot_password(&json!({ "email": random_email })).await;
+    assert_eq!(response.status().as_u16(), 429);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_email() {
+    let mut app = TestApp::new().await;
+
+    let response = app.post_forgot_password(&json!({ "email": "invalid_email" })).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_200_without_sending_email_if_user_does_not_exist() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+
+    let response = app.post_forgot_password(&json!({ "email": random_email })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(app.get_last_email(&random_email).await.is_none());
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_replace_password() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email, false).await;
+
+    let token = request_reset_token(&app, &random_email).await;
+
+    let response = app
+        .post_reset_password(&json!({ "token": token, "password": "newPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    // Tokens issued during the second of the reset are revoked with the old sessions
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "newPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_open_reset_form_from_emailed_link() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email, false).await;
+
+    let response = app.post_forgot_password(&json!({ "email": random_email })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let email = app.get_last_email(&random_email).await.expect("No password reset email sent");
+
+    let response = app.open_email_link(&email.content, "/password/reset").await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.text().await.unwrap().contains("reset-form"));
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_token_is_used_twice() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email, false).await;
+
+    let token = request_reset_token(&app, &random_email).await;
+
+    let response = app
+        .post_reset_password(&json!({ "token": token, "password": "newPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_reset_password(&json!({ "token": token, "password": "otherPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_invalid_token() {
+    let mut app = TestApp::new().await;
+
+    let response = app
+        .post_reset_password(&json!({ "token": "invalid_token", "password": "newPassword123" }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_magic_link_token_is_used() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email, false).await;
+
+    app.post_magic_link(&json!({ "email": random_email })).await;
+
+    let email = app.get_last_email(&random_email).await.expect("No magic link email sent");
+    let token = TestApp::get_link_token(&email.content);
+
+    let response = app
+        .post_reset_password(&json!({ "token": token, "password": "newPassword123" }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_keep_token_usable_if_invalid_password() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email, false).await;
+
+    let token = request_reset_token(&app, &random_email).await;
+
+    let response = app.post_reset_password(&json!({ "token": token, "password": "short" })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app
+        .post_reset_password(&json!({ "token": token, "password": "newPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_password_contains_email() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email, false).await;
+
+    let token = request_reset_token(&app, &random_email).await;
+
+    let response = app
+        .post_reset_password(&json!({ "token": token, "password": format!("my {}", random_email) }))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let rules: Vec<String> = response
+        .json::<ErrorResponse>()
+        .await
+        .expect("Could not deserialize response body to ErrorResponse")
+        .details
+        .into_iter()
+        .map(|detail| detail.rule)
+        .collect();
+    assert_eq!(rules, vec!["containsEmail"]);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_password_is_the_current_one() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email, false).await;
+
+    let token = request_reset_token(&app, &random_email).await;
+
+    let response = app
+        .post_reset_password(&json!({ "token": token, "password": "password123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response
+            .json::<ErrorResponse>()
+            .await
+            .expect("Could not deserialize response body to ErrorResponse")
+            .error,
+        "Password was used recently"
+    );
+
+    let response = app
+        .post_reset_password(&json!({ "token": token, "password": "newPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_invalidate_existing_sessions() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email, false).await;
+
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let auth_cookie = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found");
+
+    let response = app.post_verify_token(&json!({ "token": auth_cookie.value() })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let token = request_reset_token(&app, &random_email).await;
+
+    let response = app
+        .post_reset_password(&json!({ "token": token, "password": "newPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_verify_token(&json!({ "token": auth_cookie.value() })).await;
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_invalidate_pending_2fa_codes() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email, true).await;
+
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 206);
+
+    let login_attempt_id = response
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse")
+        .login_attempt_id;
+    let two_fa_code = app.get_last_two_fa_code(&random_email).await;
+
+    let token = request_reset_token(&app, &random_email).await;
+
+    let response = app
+        .post_reset_password(&json!({ "token": token, "password": "newPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_verify_2fa(&json!({
+            "email": random_email,
+            "loginAttemptId": login_attempt_id,
+            "2FACode": two_fa_code,
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_accept_session_started_right_after_reset() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email, false).await;
+
+    let token = request_reset_token(&app, &random_email).await;
+
+    let response = app
+        .post_reset_password(&json!({ "token": token, "password": "newPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Within the second of the revocation
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "newPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let auth_cookie = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found");
+
+    let response = app.post_verify_token(&json!({ "token": auth_cookie.value() })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_forget_trusted_devices() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email, true).await;
+
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 206);
+
+    let login_attempt_id = response
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse")
+        .login_attempt_id;
+
+    let response = app
+        .post_verify_2fa(&json!({
+            "email": random_email,
+            "loginAttemptId": login_attempt_id,
+            "2FACode": app.get_last_two_fa_code(&random_email).await,
+            "trustDevice": true,
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let token = request_reset_token(&app, &random_email).await;
+
+    let response = app
+        .post_reset_password(&json!({ "token": token, "password": "newPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // The device still sends its cookie but 2FA is asked again
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "newPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 206);
+    app.clean_up().await;
+}
@@ -0,0 +1,148 @@
+use crate::helpers::TestApp;
+use auth_service::domain::rate_limit::{RateLimit, RateLimitPolicy, RouteRateLimits};
+use reqwest::header::RETRY_AFTER;
+use reqwest::StatusCode;
+use serde_json::json;
+
+fn limit(max_requests: u32) -> Option<RateLimit> {
+    Some(RateLimit {
+        max_requests,
+        window_seconds: 60,
+    })
+}
+
+fn policy(login: RouteRateLimits, signup: RouteRateLimits, verify_2fa: RouteRateLimits) -> RateLimitPolicy {
+    RateLimitPolicy {
+        login,
+        signup,
+        verify_2fa,
+        magic_link: RouteRateLimits::default(),
+        forgot_password: RouteRateLimits::default(),
+        resend_verification: RouteRateLimits::default(),
+    }
+}
+
+fn retry_after(response: &reqwest::Response) -> u64 {
+    response
+        .headers()
+        .get(RETRY_AFTER)
+        .expect("Missing Retry-After header")
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+async fn post_login(app: &TestApp, email: &str) -> reqwest::Response {
+    app.post_login(&json!({ "email": email, "password": "wrongPassword123" }))
+        .await
+}
+
+#[tokio::test]
+async fn should_return_429_once_email_made_too_many_logins() {
+    let per_email = RouteRateLimits {
+        per_ip: None,
+        per_email: limit(2),
+    };
+    let mut app = TestApp::new_with_rate_limit_policy(policy(per_email, Default::default(), Default::default())).await;
+
+    let email = TestApp::get_random_email();
+    for _ in 0..2 {
+        assert_ne!(post_login(&app, &email).await.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    // Changing the case of the email doesn't get around the limit
+    let response = post_login(&app, &email.to_uppercase()).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!((1..=60).contains(&retry_after(&response)));
+
+    // Other emails have their own window
+    let response = post_login(&app, &TestApp::get_random_email()).await;
+    assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_429_once_ip_made_too_many_logins() {
+    let per_ip = RouteRateLimits {
+        per_ip: limit(3),
+        per_email: None,
+    };
+    let mut app = TestApp::new_with_rate_limit_policy(policy(per_ip, Default::default(), Default::default())).await;
+
+    for _ in 0..3 {
+        let response = post_login(&app, &TestApp::get_random_email()).await;
+        assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    let response = post_login(&app, &TestApp::get_random_email()).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!((1..=60).contains(&retry_after(&response)));
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_limit_routes_separately() {
+    let per_ip = RouteRateLimits {
+        per_ip: limit(1),
+        per_email: None,
+    };
+    let mut app = TestApp::new_with_rate_limit_policy(policy(per_ip, Default::default(), Default::default())).await;
+
+    post_login(&app, &TestApp::get_random_email()).await;
+    let response = post_login(&app, &TestApp::get_random_email()).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let response = app
+        .post_signup(&json!({
+            "email": TestApp::get_random_email(),
+            "password": "password123",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_429_once_email_made_too_many_signups() {
+    let per_email = RouteRateLimits {
+        per_ip: None,
+        per_email: limit(1),
+    };
+    let mut app = TestApp::new_with_rate_limit_policy(policy(Default::default(), per_email, Default::default())).await;
+
+    let body = json!({
+        "email": TestApp::get_random_email(),
+        "password": "password123",
+        "requires2FA": false
+    });
+
+    assert_eq!(app.post_signup(&body).await.status(), StatusCode::CREATED);
+    assert_eq!(app.post_signup(&body).await.status(), StatusCode::TOO_MANY_REQUESTS);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_429_once_email_made_too_many_2fa_attempts() {
+    let per_email = RouteRateLimits {
+        per_ip: None,
+        per_email: limit(2),
+    };
+    let mut app = TestApp::new_with_rate_limit_policy(policy(Default::default(), Default::default(), per_email)).await;
+
+    let body = json!({
+        "email": TestApp::get_random_email(),
+        "loginAttemptId": "invalid",
+        "2FACode": "123456"
+    });
+
+    for _ in 0..2 {
+        assert_ne!(app.post_verify_2fa(&body).await.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    let response = app.post_verify_2fa(&body).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!((1..=60).contains(&retry_after(&response)));
+    app.clean_up().await;
+}
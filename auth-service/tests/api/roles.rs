@@ -0,0 +1,246 @@
+use crate::helpers::TestApp;
+use auth_service::domain::error::ErrorResponse;
+use auth_service::routes::{AdminUserSummary, RoleListResponse, RoleResponse, VerifyTokenResponse};
+use auth_service::utils::constants::env::JWT_COOKIE_NAME;
+use reqwest::StatusCode;
+use serde_json::json;
+
+async fn signup(app: &TestApp, email: &str) {
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "password123",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    app.verify_email(email).await;
+}
+
+// Logs in and returns the auth token, so that it can still be checked once someone else logs in
+async fn login(app: &TestApp, email: &str) -> String {
+    let response = app.post_login(&json!({ "email": email, "password": "password123" })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+
+    token
+}
+
+async fn login_as_admin(app: &TestApp) {
+    let admin_email = TestApp::get_random_email();
+    signup(app, &admin_email).await;
+    app.make_admin(&admin_email).await;
+
+    login(app, &admin_email).await;
+}
+
+async fn verify_token(app: &TestApp, token: &str) -> VerifyTokenResponse {
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    response
+        .json::<VerifyTokenResponse>()
+        .await
+        .expect("Could not deserialize response body to VerifyTokenResponse")
+}
+
+async fn create_support_role(app: &TestApp) {
+    let response = app
+        .put_admin_role("support", &json!({ "permissions": ["users:read", "content:read"] }))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn should_return_403_if_permission_missing() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    login(&app, &random_email).await;
+
+    let response = app.get_admin_roles().await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app.put_admin_user_role(&random_email, "admin").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_list_seeded_roles() {
+    let mut app = TestApp::new().await;
+
+    login_as_admin(&app).await;
+
+    let response = app.get_admin_roles().await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let roles = response
+        .json::<RoleListResponse>()
+        .await
+        .expect("Could not deserialize response body to RoleListResponse")
+        .roles;
+
+    let names: Vec<&str> = roles.iter().map(|role| role.name.as_str()).collect();
+    assert_eq!(names, vec!["admin", "org-admin", "user"]);
+    assert!(roles
+        .iter()
+        .filter(|role| role.name != "org-admin")
+        .all(|role| role.permissions.contains(&"content:read".to_owned())));
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_embed_roles_and_permissions_into_token() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    let token = login(&app, &random_email).await;
+
+    let grants = verify_token(&app, &token).await;
+    assert_eq!(grants.email, random_email);
+    assert_eq!(grants.roles, vec!["user"]);
+    assert_eq!(grants.permissions, vec!["content:read"]);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_authorize_with_assigned_role() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+
+    login_as_admin(&app).await;
+    create_support_role(&app).await;
+
+    let response = app.put_admin_user_role(&random_email, "support").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app.get_admin_user(&random_email).await;
+    let user = response
+        .json::<AdminUserSummary>()
+        .await
+        .expect("Could not deserialize response body to AdminUserSummary");
+    assert_eq!(user.roles, vec!["support"]);
+
+    let token = login(&app, &random_email).await;
+    let grants = verify_token(&app, &token).await;
+    assert_eq!(grants.roles, vec!["support", "user"]);
+    assert_eq!(grants.permissions, vec!["content:read", "users:read"]);
+
+    let response = app.get_admin_users(&[]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.post_admin_user_action(&random_email, "disable").await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_revoke_sessions_when_role_is_removed() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+
+    login_as_admin(&app).await;
+    create_support_role(&app).await;
+    app.put_admin_user_role(&random_email, "support").await;
+
+    let token = login(&app, &random_email).await;
+
+    login_as_admin(&app).await;
+
+    let response = app.delete_admin_user_role(&random_email, "support").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app.post_verify_token(&json!({ "token": token })).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app.delete_admin_user_role(&random_email, "support").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_update_and_delete_role() {
+    let mut app = TestApp::new().await;
+
+    login_as_admin(&app).await;
+    create_support_role(&app).await;
+
+    let response = app
+        .put_admin_role("support", &json!({ "permissions": ["users:write", "users:write"] }))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response
+            .json::<RoleResponse>()
+            .await
+            .expect("Could not deserialize response body to RoleResponse"),
+        RoleResponse {
+            name: "support".to_owned(),
+            permissions: vec!["users:write".to_owned()],
+        }
+    );
+
+    let response = app.delete_admin_role("support").await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app.delete_admin_role("support").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_role() {
+    let mut app = TestApp::new().await;
+
+    login_as_admin(&app).await;
+
+    let test_cases = [
+        app.put_admin_role("Support", &json!({ "permissions": [] })).await,
+        app.put_admin_role("support", &json!({ "permissions": ["users"] })).await,
+        app.delete_admin_role("user").await,
+    ];
+
+    for response in test_cases {
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response
+                .json::<ErrorResponse>()
+                .await
+                .expect("Could not deserialize response body to ErrorResponse")
+                .error,
+            "Invalid role".to_owned()
+        );
+    }
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_404_if_role_or_user_not_found() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+
+    login_as_admin(&app).await;
+
+    let response = app.put_admin_user_role(&random_email, "missing").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = app.put_admin_user_role(&TestApp::get_random_email(), "admin").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    app.clean_up().await;
+}
@@ -1,5 +1,6 @@
 use crate::helpers::TestApp;
 use auth_service::domain::error::ErrorResponse;
+use auth_service::domain::password_policy::PasswordPolicy;
 use auth_service::routes::SignupResponse;
 
 #[tokio::test]
@@ -10,7 +11,7 @@ async fn should_return_422_if_malformed_input() {
 
     let test_cases = [
         serde_json::json!({
-            "_password":"password123",
+            "password": 123,
             "requires2FA":true,
             "email": random_email
         }),
@@ -67,6 +68,20 @@ async fn should_return_201_if_valid_input() {
     app.clean_up().await;
 }
 
+#[tokio::test]
+async fn should_return_201_if_password_is_omitted() {
+    let mut app = TestApp::new().await;
+
+    let test_case = serde_json::json!({
+        "requires2FA": false,
+        "email": TestApp::get_random_email()
+    });
+
+    let response = app.post_signup(&test_case).await;
+    assert_eq!(response.status().as_u16(), 201);
+    app.clean_up().await;
+}
+
 // #[tokio::test]
 // async fn should_return_400_if_invalid_input() {
 //     let mut app = TestApp::new().await;
@@ -140,3 +155,161 @@ async fn should_return_409_if_email_is_used() {
     );
     app.clean_up().await;
 }
+
+#[tokio::test]
+async fn should_return_201_if_email_is_used_with_uniform_signup_response() {
+    let mut app = TestApp::new_with_uniform_signup_response().await;
+
+    let email = TestApp::get_random_email();
+    let test_case = serde_json::json!({
+        "password": "password123",
+        "requires2FA": false,
+        "email": email
+    });
+
+    let first = app.post_signup(&test_case).await;
+    assert_eq!(first.status().as_u16(), 201);
+
+    let second = app
+        .post_signup(&serde_json::json!({
+            "password": "otherPassword123",
+            "requires2FA": false,
+            "email": email
+        }))
+        .await;
+    assert_eq!(second.status().as_u16(), 201);
+
+    assert_eq!(
+        first.json::<SignupResponse>().await.unwrap(),
+        second.json::<SignupResponse>().await.unwrap()
+    );
+
+    let sent = app.get_last_email(&email).await.expect("No email sent");
+    assert_eq!(sent.subject, "You already have an account");
+
+    // Both links open a page
+    for (path, form) in [("/login", "login-form"), ("/password/forgot", "forgot-form")] {
+        let response = app.open_email_link(&sent.content, path).await;
+        assert_eq!(response.status().as_u16(), 200);
+        assert!(response.text().await.unwrap().contains(form));
+    }
+
+    // The account keeps its password
+    let response = app
+        .post_login(&serde_json::json!({ "email": email, "password": "password123" }))
+        .await;
+    assert_ne!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+// Names of the password rules the response says were broken
+async fn broken_rules(response: reqwest::Response) -> Vec<String> {
+    response
+        .json::<ErrorResponse>()
+        .await
+        .expect("Could not deserialize response body to ErrorResponse")
+        .details
+        .into_iter()
+        .map(|detail| detail.rule)
+        .collect()
+}
+
+#[tokio::test]
+async fn should_return_400_with_every_broken_password_rule() {
+    let mut app = TestApp::new().await;
+
+    let test_cases = [
+        ("short", vec!["minLength"]),
+        ("kowalski-password", vec!["containsEmail"]),
+        ("Jan.Kowalski@example.com", vec!["containsEmail"]),
+    ];
+
+    for (password, expected_rules) in test_cases {
+        let response = app
+            .post_signup(&serde_json::json!({
+                "email": "jan.kowalski@example.com",
+                "password": password,
+                "requires2FA": false
+            }))
+            .await;
+
+        assert_eq!(response.status().as_u16(), 400, "Failed for password: {}", password);
+        assert_eq!(
+            broken_rules(response).await,
+            expected_rules,
+            "Failed for password: {}",
+            password
+        );
+    }
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_password_is_too_long() {
+    let mut app = TestApp::new().await;
+
+    let response = app
+        .post_signup(&serde_json::json!({
+            "email": TestApp::get_random_email(),
+            "password": "a".repeat(PasswordPolicy::default().max_length + 1),
+            "requires2FA": false
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(broken_rules(response).await, vec!["maxLength"]);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_password_is_easy_to_guess() {
+    let mut app = TestApp::new_with_password_policy(PasswordPolicy {
+        min_strength: 3,
+        ..PasswordPolicy::default()
+    })
+    .await;
+
+    let response = app
+        .post_signup(&serde_json::json!({
+            "email": TestApp::get_random_email(),
+            "password": "password123",
+            "requires2FA": false
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(broken_rules(response).await, vec!["strength"]);
+
+    let response = app
+        .post_signup(&serde_json::json!({
+            "email": TestApp::get_random_email(),
+            "password": "g7#Kq9!vLm2@",
+            "requires2FA": false
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 201);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_password_was_breached() {
+    let mut app = TestApp::new().await;
+
+    app.breached_password_store
+        .write()
+        .await
+        .add_password(&"breachedPassword1".into(), 42);
+
+    let response = app
+        .post_signup(&serde_json::json!({
+            "email": TestApp::get_random_email(),
+            "password": "breachedPassword1",
+            "requires2FA": false
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(broken_rules(response).await, vec!["breached"]);
+    app.clean_up().await;
+}
@@ -0,0 +1,183 @@
+use crate::helpers::TestApp;
+use auth_service::domain::error::ErrorResponse;
+use auth_service::domain::trusted_device::TrustedDevice;
+use auth_service::routes::TwoFactorAuthResponse;
+use auth_service::utils::constants::env::{JWT_COOKIE_NAME, TRUSTED_DEVICE_COOKIE_NAME};
+use reqwest::StatusCode;
+
+async fn signup_with_2fa(app: &TestApp, email: &str) -> serde_json::Value {
+    let signup_body = serde_json::json!({
+        "email": email,
+        "password": "password123",
+        "requires2FA": true
+    });
+
+    let response = app.post_signup(&signup_body).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    app.verify_email(email).await;
+
+    serde_json::json!({
+        "email": email,
+        "password": "password123",
+    })
+}
+
+async fn login_with_2fa(app: &TestApp, login_body: &serde_json::Value, trust_device: bool) -> reqwest::Response {
+    let email = login_body["email"].as_str().unwrap();
+
+    let response = app.post_login(login_body).await;
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+
+    let login_attempt_id = response
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse")
+        .login_attempt_id;
+
+    let two_fa_code = app.get_last_two_fa_code(email).await;
+
+    let response = app
+        .post_verify_2fa(&serde_json::json!({
+            "email": email,
+            "loginAttemptId": login_attempt_id,
+            "2FACode": two_fa_code,
+            "trustDevice": trust_device,
+        }))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    response
+}
+
+#[tokio::test]
+async fn should_skip_2fa_on_trusted_device() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    let login_body = signup_with_2fa(&app, &random_email).await;
+
+    let response = login_with_2fa(&app, &login_body, true).await;
+    assert!(response
+        .cookies()
+        .any(|cookie| cookie.name() == TRUSTED_DEVICE_COOKIE_NAME && !cookie.value().is_empty()));
+
+    let response = app.post_login(&login_body).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response
+        .cookies()
+        .any(|cookie| cookie.name() == JWT_COOKIE_NAME && !cookie.value().is_empty()));
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_require_2fa_if_device_not_trusted() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    let login_body = signup_with_2fa(&app, &random_email).await;
+
+    let response = login_with_2fa(&app, &login_body, false).await;
+    assert!(!response.cookies().any(|cookie| cookie.name() == TRUSTED_DEVICE_COOKIE_NAME));
+
+    let response = app.post_login(&login_body).await;
+
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_require_2fa_if_device_trusted_by_another_user() {
+    let mut app = TestApp::new().await;
+
+    let first_login_body = signup_with_2fa(&app, &TestApp::get_random_email()).await;
+    let second_login_body = signup_with_2fa(&app, &TestApp::get_random_email()).await;
+
+    login_with_2fa(&app, &first_login_body, true).await;
+
+    let response = app.post_login(&second_login_body).await;
+
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_list_trusted_devices() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    let login_body = signup_with_2fa(&app, &random_email).await;
+
+    login_with_2fa(&app, &login_body, true).await;
+
+    let response = app.get_trusted_devices().await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let devices = response
+        .json::<Vec<TrustedDevice>>()
+        .await
+        .expect("Could not deserialize response body to a list of TrustedDevice");
+
+    assert_eq!(devices.len(), 1);
+    assert!(devices[0].expires_at > devices[0].created_at);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_require_2fa_after_device_revoked() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    let login_body = signup_with_2fa(&app, &random_email).await;
+
+    login_with_2fa(&app, &login_body, true).await;
+
+    let devices = app
+        .get_trusted_devices()
+        .await
+        .json::<Vec<TrustedDevice>>()
+        .await
+        .expect("Could not deserialize response body to a list of TrustedDevice");
+
+    let response = app.delete_trusted_device(&devices[0].id).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    let response = app.post_login(&login_body).await;
+
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_404_if_device_not_found() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    let login_body = signup_with_2fa(&app, &random_email).await;
+
+    login_with_2fa(&app, &login_body, false).await;
+
+    let response = app.delete_trusted_device("unknown-device").await;
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        response
+            .json::<ErrorResponse>()
+            .await
+            .expect("Could not deserialize response body to ErrorResponse")
+            .error,
+        "Device not found".to_owned()
+    );
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let mut app = TestApp::new().await;
+
+    let response = app.get_trusted_devices().await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    app.clean_up().await;
+}
@@ -0,0 +1,201 @@
+use crate::helpers::TestApp;
+use auth_service::routes::{TwoFactorAuthResponse, UnrecognizedLoginResponse};
+use auth_service::utils::constants::env::JWT_COOKIE_NAME;
+use reqwest::header::USER_AGENT;
+use reqwest::StatusCode;
+use serde_json::json;
+
+const LAPTOP: &str = "Mozilla/5.0 (X11; Linux x86_64) Firefox/128.0";
+const PHONE: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_5 like Mac OS X) Safari/604.1";
+
+async fn signup(app: &TestApp, email: &str, requires_2fa: bool) {
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "password123",
+            "requires2FA": requires_2fa
+        }))
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    app.verify_email(email).await;
+}
+
+async fn login_from(app: &TestApp, email: &str, user_agent: &str) -> reqwest::Response {
+    app.post_login_with_user_agent(&json!({ "email": email, "password": "password123" }), user_agent)
+        .await
+}
+
+async fn last_subject(app: &TestApp, email: &str) -> String {
+    app.get_last_email(email).await.expect("No email sent").subject
+}
+
+fn auth_token(response: &reqwest::Response) -> String {
+    response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned()
+}
+
+#[tokio::test]
+async fn should_not_notify_first_or_known_device() {
+    let mut app = TestApp::new().await;
+
+    let email = TestApp::get_random_email();
+    signup(&app, &email, false).await;
+    let subject = last_subject(&app, &email).await;
+
+    assert_eq!(login_from(&app, &email, LAPTOP).await.status(), StatusCode::OK);
+    assert_eq!(login_from(&app, &email, LAPTOP).await.status(), StatusCode::OK);
+
+    assert_eq!(last_subject(&app, &email).await, subject);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_notify_login_from_new_device() {
+    let mut app = TestApp::new().await;
+
+    let email = TestApp::get_random_email();
+    signup(&app, &email, false).await;
+
+    assert_eq!(login_from(&app, &email, LAPTOP).await.status(), StatusCode::OK);
+    assert_eq!(login_from(&app, &email, PHONE).await.status(), StatusCode::OK);
+
+    let sent = app.get_last_email(&email).await.expect("No email sent");
+    assert_eq!(sent.subject, "New sign-in to your account");
+    assert!(sent.content.contains(PHONE));
+    assert!(sent.content.contains("127.0.0.1"));
+    assert!(sent.content.contains("/login/not-me?token="));
+
+    // Only once, the device is known from now on
+    let emails_before = app.email_client.read().await.sent_emails().await.len();
+    assert_eq!(login_from(&app, &email, PHONE).await.status(), StatusCode::OK);
+    assert_eq!(app.email_client.read().await.sent_emails().await.len(), emails_before);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_notify_new_device_once_2fa_is_verified() {
+    let mut app = TestApp::new().await;
+
+    let email = TestApp::get_random_email();
+    signup(&app, &email, true).await;
+
+    for user_agent in [LAPTOP, PHONE] {
+        let response = login_from(&app, &email, user_agent).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        let login_attempt_id = response
+            .json::<TwoFactorAuthResponse>()
+            .await
+            .expect("Could not deserialize response body to TwoFactorAuthResponse")
+            .login_attempt_id;
+        assert_eq!(last_subject(&app, &email).await, "Login attempt");
+
+        let response = app
+            .http_client
+            .post(format!("{}/verify-2fa", &app.address))
+            .header(USER_AGENT, user_agent)
+            .json(&json!({
+                "email": email,
+                "loginAttemptId": login_attempt_id,
+                "2FACode": app.get_last_two_fa_code(&email).await
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    assert_eq!(last_subject(&app, &email).await, "New sign-in to your account");
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_notify_new_device_after_magic_link() {
+    let mut app = TestApp::new().await;
+
+    let email = TestApp::get_random_email();
+    signup(&app, &email, false).await;
+    assert_eq!(login_from(&app, &email, LAPTOP).await.status(), StatusCode::OK);
+
+    let response = app.post_magic_link(&json!({ "email": email })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let sent = app.get_last_email(&email).await.expect("No email sent");
+
+    let response = app
+        .http_client
+        .post(format!("{}/login/magic-link/verify", &app.address))
+        .header(USER_AGENT, PHONE)
+        .json(&json!({ "token": TestApp::get_link_token(&sent.content) }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let sent = app.get_last_email(&email).await.expect("No email sent");
+    assert_eq!(sent.subject, "New sign-in to your account");
+    assert!(sent.content.contains(PHONE));
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_sign_out_and_reset_password_when_login_reported() {
+    let mut app = TestApp::new().await;
+
+    let email = TestApp::get_random_email();
+    signup(&app, &email, false).await;
+    assert_eq!(login_from(&app, &email, LAPTOP).await.status(), StatusCode::OK);
+
+    let response = login_from(&app, &email, PHONE).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let intruder_token = auth_token(&response);
+
+    let sent = app.get_last_email(&email).await.expect("No email sent");
+    let token = TestApp::get_link_token(&sent.content);
+
+    // Opening the link only asks for confirmation
+    let response = app.get_report_unrecognized_login(&token).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    response
+        .json::<UnrecognizedLoginResponse>()
+        .await
+        .expect("Could not deserialize response body to UnrecognizedLoginResponse");
+    assert_eq!(
+        app.post_verify_token(&json!({ "token": intruder_token })).await.status(),
+        StatusCode::OK
+    );
+
+    let response = app.post_report_unrecognized_login(&json!({ "token": token })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    response
+        .json::<UnrecognizedLoginResponse>()
+        .await
+        .expect("Could not deserialize response body to UnrecognizedLoginResponse");
+
+    assert_eq!(
+        app.post_verify_token(&json!({ "token": intruder_token })).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(login_from(&app, &email, PHONE).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(last_subject(&app, &email).await, "Reset your password");
+
+    // The link works only once
+    let response = app.post_report_unrecognized_login(&json!({ "token": token })).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_report_token_is_invalid() {
+    let mut app = TestApp::new().await;
+
+    let response = app.get_report_unrecognized_login("invalid").await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app.post_report_unrecognized_login(&json!({ "token": "invalid" })).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    app.clean_up().await;
+}
@@ -1,4 +1,5 @@
 use crate::helpers::TestApp;
+use auth_service::domain::account_status::{AccountState, AccountStatus};
 use auth_service::domain::data_stores::{LoginAttemptId, TwoFACode};
 use auth_service::domain::email::Email;
 use auth_service::domain::error::ErrorResponse;
@@ -88,6 +89,8 @@ async fn should_return_401_if_incorrect_credentials() {
 
     assert_eq!(response.status(), 201);
 
+    app.verify_email(&random_email).await;
+
     let mut login_body = signup_body.clone();
     login_body.as_object_mut().unwrap().remove("requires2FA");
 
@@ -138,24 +141,27 @@ async fn should_return_200_if_correct_code() {
 
     assert_eq!(response.status(), 201);
 
+    app.verify_email(&random_email).await;
+
     let mut login_body = signup_body.clone();
     login_body.as_object_mut().unwrap().remove("requires2FA");
 
     let response = app.post_login(&login_body).await;
     assert_eq!(response.status(), 206);
 
-    let (login_attempt_id, two_fa_code) = app
+    let (login_attempt_id, _) = app
         .two_fa_code_store
         .read()
         .await
         .get_code(&Email::parse(random_email.clone().into()).unwrap())
         .await
         .unwrap();
+    let two_fa_code = app.get_last_two_fa_code(&random_email).await;
 
     let request_body = serde_json::json!({
         "email": random_email,
         "loginAttemptId": login_attempt_id.0.expose_secret(),
-        "2FACode": two_fa_code.0.expose_secret()
+        "2FACode": two_fa_code
     });
 
     let response = app.post_verify_2fa(&request_body).await;
@@ -180,24 +186,27 @@ async fn should_return_401_if_same_code_twice() {
 
     assert_eq!(response.status(), 201);
 
+    app.verify_email(&random_email).await;
+
     let mut login_body = signup_body.clone();
     login_body.as_object_mut().unwrap().remove("requires2FA");
 
     let response = app.post_login(&login_body).await;
     assert_eq!(response.status(), 206);
 
-    let (login_attempt_id, two_fa_code) = app
+    let (login_attempt_id, _) = app
         .two_fa_code_store
         .read()
         .await
         .get_code(&Email::parse(random_email.clone().into()).unwrap())
         .await
         .unwrap();
+    let two_fa_code = app.get_last_two_fa_code(&random_email).await;
 
     let request_body = serde_json::json!({
         "email": random_email,
         "loginAttemptId": login_attempt_id.0.expose_secret(),
-        "2FACode": two_fa_code.0.expose_secret()
+        "2FACode": two_fa_code
     });
 
     let response = app.post_verify_2fa(&request_body).await;
@@ -206,7 +215,7 @@ async fn should_return_401_if_same_code_twice() {
     let request_body = serde_json::json!({
         "email": random_email,
         "loginAttemptId": login_attempt_id.0.expose_secret(),
-        "2FACode": two_fa_code.0.expose_secret()
+        "2FACode": two_fa_code
     });
 
     let response = app.post_verify_2fa(&request_body).await;
@@ -231,6 +240,8 @@ async fn should_return_401_if_old_code() {
 
     assert_eq!(response.status().as_u16(), 201);
 
+    app.verify_email(&random_email).await;
+
     // First login call
 
     let login_body = serde_json::json!({
@@ -252,15 +263,7 @@ async fn should_return_401_if_old_code() {
 
     let login_attempt_id = response_body.login_attempt_id;
 
-    let code_tuple = app
-        .two_fa_code_store
-        .read()
-        .await
-        .get_code(&Email::parse(random_email.clone().into()).unwrap())
-        .await
-        .unwrap();
-
-    let code = code_tuple.1.as_ref();
+    let code = app.get_last_two_fa_code(&random_email).await;
 
     // Second login call
 
@@ -273,7 +276,7 @@ async fn should_return_401_if_old_code() {
     let request_body = serde_json::json!({
         "email": random_email,
         "loginAttemptId": login_attempt_id,
-        "2FACode": code.expose_secret()
+        "2FACode": code
     });
 
     let response = app.post_verify_2fa(&request_body).await;
@@ -281,3 +284,94 @@ async fn should_return_401_if_old_code() {
     assert_eq!(response.status().as_u16(), 401);
     app.clean_up().await;
 }
+
+#[tokio::test]
+async fn should_not_store_raw_code() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+
+    let signup_body = serde_json::json!({
+        "email": random_email,
+        "password": "password123",
+        "requires2FA": true
+    });
+
+    let response = app.post_signup(&signup_body).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.verify_email(&random_email).await;
+
+    let login_body = serde_json::json!({
+        "email": random_email,
+        "password": "password123"
+    });
+
+    let response = app.post_login(&login_body).await;
+    assert_eq!(response.status().as_u16(), 206);
+
+    let code = app.get_last_two_fa_code(&random_email).await;
+
+    let (_, code_hash) = app
+        .two_fa_code_store
+        .read()
+        .await
+        .get_code(&Email::parse(random_email.clone().into()).unwrap())
+        .await
+        .unwrap();
+
+    assert!(!code_hash.as_ref().expose_secret().contains(&code));
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_if_account_suspended_before_code_is_entered() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+
+    let signup_body = serde_json::json!({
+        "password": "password123",
+        "requires2FA": true,
+        "email": random_email
+    });
+
+    let response = app.post_signup(&signup_body).await;
+    assert_eq!(response.status(), 201);
+
+    app.verify_email(&random_email).await;
+
+    let response = app
+        .post_login(&serde_json::json!({ "email": random_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status(), 206);
+
+    let login_attempt_id = response
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse")
+        .login_attempt_id;
+    let two_fa_code = app.get_last_two_fa_code(&random_email).await;
+
+    app.set_account_state(&random_email, AccountState::new(AccountStatus::Suspended, None))
+        .await;
+
+    let response = app
+        .post_verify_2fa(&serde_json::json!({
+            "email": random_email,
+            "loginAttemptId": login_attempt_id,
+            "2FACode": two_fa_code
+        }))
+        .await;
+
+    assert_eq!(response.status(), 403);
+    assert_eq!(
+        response
+            .json::<ErrorResponse>()
+            .await
+            .expect("Could not deserialize response body to ErrorResponse")
+            .error,
+        "Account suspended".to_owned()
+    );
+    app.clean_up().await;
+}
@@ -0,0 +1,252 @@
+use crate::helpers::TestApp;
+use auth_service::domain::error::ErrorResponse;
+use auth_service::domain::rate_limit::{RateLimit, RateLimitPolicy, RouteRateLimits};
+use serde_json::json;
+
+async fn signup(app: &TestApp, email: &str) {
+    let response = app
+        .post_signup(&json!({
+            "email": email,
+            "password": "password123",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+}
+
+#[tokio::test]
+async fn should_send_confirmation_email_on_signup() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+
+    let email = app.get_last_email(&random_email).await.expect("No confirmation email sent");
+
+    assert_eq!(email.subject, "Confirm your email address");
+    assert!(email.content.contains("/verify-email?token="));
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_on_login_if_email_not_verified() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "password123" }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(
+        response
+            .json::<ErrorResponse>()
+            .await
+            .expect("Could not deserialize response body to ErrorResponse")
+            .error,
+        "Email not verified".to_owned()
+    );
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_allow_login_once_email_verified() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+
+    app.verify_email(&random_email).await;
+
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "password123" }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_drop_password_chosen_before_the_address_was_proven() {
+    let mut app = TestApp::new().await;
+
+    // Someone signs up with an address they don't own
+    let random_email = TestApp::get_random_email();
+    let response = app
+        .post_signup(&json!({
+            "email": random_email,
+            "password": "attackerPassword123",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    // The owner follows the confirmation link they received
+    let email = app.get_last_email(&random_email).await.expect("No confirmation email sent");
+    let response = app.get_verify_email(&TestApp::get_link_token(&email.content)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "attackerPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    // The owner is asked to choose their own
+    let email = app.get_last_email(&random_email).await.expect("No password reset email sent");
+    assert_eq!(email.subject, "Reset your password");
+    let response = app
+        .post_reset_password(&json!({
+            "token": TestApp::get_link_token(&email.content),
+            "password": "ownerPassword123"
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "ownerPassword123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_confirmation_link_is_used_twice() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+
+    let email = app.get_last_email(&random_email).await.expect("No confirmation email sent");
+    let token = TestApp::get_link_token(&email.content);
+
+    let response = app.get_verify_email(&token).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.get_verify_email(&token).await;
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_invalid_token() {
+    let mut app = TestApp::new().await;
+
+    let response = app.get_verify_email("invalid_token").await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_magic_link_token_is_used() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+
+    app.post_magic_link(&json!({ "email": random_email })).await;
+
+    let email = app.get_last_email(&random_email).await.expect("No magic link email sent");
+    let token = TestApp::get_link_token(&email.content);
+
+    let response = app.get_verify_email(&token).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_resend_confirmation_email() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+
+    let first_email = app.get_last_email(&random_email).await.expect("No confirmation email sent");
+
+    let response = app.post_resend_verification_email(&json!({ "email": random_email })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let second_email = app.get_last_email(&random_email).await.expect("No confirmation email sent");
+    assert_ne!(first_email.content, second_email.content);
+
+    app.verify_email(&random_email).await;
+
+    let response = app
+        .post_login(&json!({ "email": random_email, "password": "password123" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_resend_confirmation_email_if_already_verified() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    signup(&app, &random_email).await;
+    app.verify_email(&random_email).await;
+
+    let sent_before = app.email_client.read().await.sent_emails().await.len();
+
+    let response = app.post_resend_verification_email(&json!({ "email": random_email })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(app.email_client.read().await.sent_emails().await.len(), sent_before);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_200_without_sending_email_if_user_does_not_exist() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+
+    let response = app.post_resend_verification_email(&json!({ "email": random_email })).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(app.get_last_email(&random_email).await.is_none());
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_email() {
+    let mut app = TestApp::new().await;
+
+    let response = app.post_resend_verification_email(&json!({ "email": "invalid_email" })).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_429_once_email_requested_too_many_confirmation_emails() {
+    let per_email = RouteRateLimits {
+        per_ip: None,
+        per_email: Some(RateLimit {
+            max_requests: 2,
+            window_seconds: 60,
+        }),
+    };
+    let mut app = TestApp::new_with_rate_limit_policy(RateLimitPolicy {
+        login: RouteRateLimits::default(),
+        signup: RouteRateLimits::default(),
+        verify_2fa: RouteRateLimits::default(),
+        magic_link: RouteRateLimits::default(),
+        forgot_password: RouteRateLimits::default(),
+        resend_verification: per_email,
+    })
+    .await;
+
+    let random_email = TestApp::get_random_email();
+    for _ in 0..2 {
+        let response = app.post_resend_verification_email(&json!({ "email": random_email })).await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    let response = app.post_resend_verification_email(&json!({ "email": random_email })).await;
+    assert_eq!(response.status().as_u16(), 429);
+    app.clean_up().await;
+}
@@ -1,5 +1,8 @@
 use crate::helpers::TestApp;
+use auth_service::domain::account_status::{AccountState, AccountStatus};
 use auth_service::domain::email::Email;
+use auth_service::domain::error::ErrorResponse;
+use auth_service::domain::rbac::Grants;
 use auth_service::utils::auth::generate_auth_cookie;
 use serde_json::json;
 
@@ -22,8 +25,11 @@ async fn should_return_422_if_malformed_input() {
 async fn should_return_200_valid_token() {
     let mut app = TestApp::new().await;
 
-    let jwt =
-        generate_auth_cookie(&Email::parse(TestApp::get_random_email().into()).unwrap()).expect("Failed to generate auth cookie");
+    let random_email = TestApp::get_random_email();
+    app.add_verified_user(&random_email, "password123").await;
+
+    let jwt = generate_auth_cookie(&Email::parse(random_email.into()).unwrap(), Grants::default())
+        .expect("Failed to generate auth cookie");
 
     let response = app
         .post_verify_token(&json!({
@@ -53,8 +59,11 @@ async fn should_return_401_if_invalid_token() {
 async fn should_return_401_if_banned_token() {
     let mut app = TestApp::new().await;
 
-    let jwt =
-        generate_auth_cookie(&Email::parse(TestApp::get_random_email().into()).unwrap()).expect("Failed to generate auth cookie");
+    let random_email = TestApp::get_random_email();
+    app.add_verified_user(&random_email, "password123").await;
+
+    let jwt = generate_auth_cookie(&Email::parse(random_email.into()).unwrap(), Grants::default())
+        .expect("Failed to generate auth cookie");
 
     let response = app
         .post_verify_token(&json!({
@@ -88,3 +97,68 @@ async fn should_return_401_if_banned_token() {
     assert_eq!(response.status().as_u16(), 401);
     app.clean_up().await;
 }
+
+#[tokio::test]
+async fn should_return_401_if_user_does_not_exist() {
+    let mut app = TestApp::new().await;
+
+    let jwt = generate_auth_cookie(&Email::parse(TestApp::get_random_email().into()).unwrap(), Grants::default())
+        .expect("Failed to generate auth cookie");
+
+    let response = app
+        .post_verify_token(&json!({
+            "token": jwt.value()
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_if_account_is_not_active() {
+    let mut app = TestApp::new().await;
+
+    let random_email = TestApp::get_random_email();
+    app.add_verified_user(&random_email, "password123").await;
+
+    let jwt = generate_auth_cookie(&Email::parse(random_email.clone().into()).unwrap(), Grants::default())
+        .expect("Failed to generate auth cookie");
+
+    let test_cases = [
+        (AccountStatus::Pending, "Account pending"),
+        (AccountStatus::Suspended, "Account suspended"),
+        (AccountStatus::Disabled, "Account disabled"),
+    ];
+
+    for (status, error) in test_cases {
+        app.set_account_state(&random_email, AccountState::new(status, None)).await;
+
+        let response = app
+            .post_verify_token(&json!({
+                "token": jwt.value()
+            }))
+            .await;
+
+        assert_eq!(response.status().as_u16(), 403);
+        assert_eq!(
+            response
+                .json::<ErrorResponse>()
+                .await
+                .expect("Could not deserialize response body to ErrorResponse")
+                .error,
+            error.to_owned()
+        );
+    }
+
+    app.set_account_state(&random_email, AccountState::default()).await;
+
+    let response = app
+        .post_verify_token(&json!({
+            "token": jwt.value()
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    app.clean_up().await;
+}